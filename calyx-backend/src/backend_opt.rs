@@ -12,8 +12,12 @@ pub enum BackendOpt {
     Mlir,
     Resources,
     Sexp,
+    Timing,
+    VerilatorHarness,
     Firrtl,
     PrimitiveUses,
+    Yxi,
+    Json,
     None,
 }
 
@@ -28,8 +32,12 @@ fn backends() -> Vec<(&'static str, BackendOpt)> {
         ("mlir", BackendOpt::Mlir),
         ("resources", BackendOpt::Resources),
         ("sexp", BackendOpt::Sexp),
+        ("timing", BackendOpt::Timing),
+        ("verilator-harness", BackendOpt::VerilatorHarness),
         ("firrtl", BackendOpt::Firrtl),
         ("primitive-uses", BackendOpt::PrimitiveUses),
+        ("yxi", BackendOpt::Yxi),
+        ("json", BackendOpt::Json),
         ("none", BackendOpt::None),
     ]
 }
@@ -68,12 +76,16 @@ impl ToString for BackendOpt {
             Self::Mlir => "mlir",
             Self::Resources => "resources",
             Self::Sexp => "sexp",
+            Self::Timing => "timing",
+            Self::VerilatorHarness => "verilator-harness",
             Self::Verilog => "verilog",
             Self::Xilinx => "xilinx",
             Self::XilinxXml => "xilinx-xml",
             Self::Calyx => "calyx",
             Self::Firrtl => "firrtl",
             Self::PrimitiveUses => "primitive-uses",
+            Self::Yxi => "yxi",
+            Self::Json => "json",
             Self::None => "none",
         }
         .to_string()
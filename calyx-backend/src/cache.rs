@@ -0,0 +1,463 @@
+//! Content-addressed cache for incremental compilation with the Verilog backend.
+//!
+//! Every component in the input program is assigned a key that digests:
+//! 1. The configuration of the pass pipeline and the backend, and the
+//!    contents of the library files the program imports,
+//! 2. The source of the component,
+//! 3. The signatures of the components and primitives it instantiates.
+//!
+//! The Verilog generated for a component is stored under its key. On the next
+//! invocation, only the components whose keys are missing from the cache (and
+//! the components they instantiate, which the passes need to see) are kept in
+//! the context given to the pass pipeline. Everything else is emitted from the
+//! cache.
+//!
+//! Passes like `static-inference` propagate information from the body of a
+//! sub-component into its parents. To account for this, the cache also records
+//! the interface of each component *after* compilation. If recompiling a
+//! component changes this interface, the parents that were compiled against
+//! the old interface are invalidated and another round of compilation is
+//! required (see [ComponentCache::validate]).
+//!
+//! The keys are SHA-256 digests of explicitly serialized data rather than
+//! values computed with [std::hash::Hash], whose output may change between
+//! Rust releases and platforms, so that the cache directory stays valid when
+//! the compiler is rebuilt.
+use crate::traits::Backend;
+use crate::verilog::{emit_component, VerilogBackend};
+use calyx_ir::{self as ir, CellType};
+use calyx_utils::{CalyxResult, Error, OutputFile};
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Hex-encoded SHA-256 digest that identifies a cache entry.
+type Key = String;
+
+/// Cache of the Verilog generated for each component of a program.
+pub struct ComponentCache {
+    /// Directory that contains the cache entries.
+    dir: PathBuf,
+    /// Cache key for every component in the original program.
+    keys: HashMap<ir::Id, Key>,
+    /// Components instantiated by every component in the original program.
+    deps: HashMap<ir::Id, Vec<ir::Id>>,
+    /// Components in the order they were defined in the original program.
+    order: Vec<ir::Id>,
+    /// Components that are missing from the cache and need to be compiled.
+    stale: HashSet<ir::Id>,
+    /// Entrypoint of the original program.
+    entrypoint: ir::Id,
+}
+
+impl ComponentCache {
+    /// Open the cache stored in `dir` for the uncompiled program `ctx`.
+    /// `pipeline` should describe every option that affects the generated
+    /// Verilog, such as the pass plan and the backend configuration.
+    pub fn new(
+        dir: PathBuf,
+        pipeline: &str,
+        ctx: &ir::Context,
+    ) -> CalyxResult<Self> {
+        fs::create_dir_all(&dir).map_err(|err| {
+            Error::invalid_file(format!(
+                "Failed to create cache directory {}: {err}",
+                dir.display()
+            ))
+        })?;
+
+        let mut pipeline_hasher = Sha256::new();
+        update(&mut pipeline_hasher, env!("CARGO_PKG_VERSION"));
+        update(&mut pipeline_hasher, pipeline);
+        update_library(&mut pipeline_hasher, &ctx.lib)?;
+        let pipeline = pipeline_hasher.finalize();
+
+        let comps: HashMap<_, _> = ctx
+            .components
+            .iter()
+            .map(|comp| (comp.name, comp))
+            .collect();
+        let mut keys = HashMap::with_capacity(comps.len());
+        let mut deps = HashMap::with_capacity(comps.len());
+        // Components that instantiate a component missing from the program
+        let mut unresolved = HashSet::new();
+        for comp in &ctx.components {
+            let mut hasher = Sha256::new();
+            update(&mut hasher, pipeline);
+            update(&mut hasher, component_source(comp));
+
+            let mut comp_deps = vec![];
+            let mut prims = vec![];
+            for cell in comp.cells.iter() {
+                match &cell.borrow().prototype {
+                    CellType::Component { name } => comp_deps.push(*name),
+                    CellType::Primitive { name, .. } => prims.push(*name),
+                    CellType::ThisComponent | CellType::Constant { .. } => (),
+                }
+            }
+            comp_deps.sort();
+            comp_deps.dedup();
+            prims.sort();
+            prims.dedup();
+
+            comp_deps.retain(|dep| {
+                let found = comps.contains_key(dep);
+                if !found {
+                    unresolved.insert(comp.name);
+                }
+                found
+            });
+            for dep in &comp_deps {
+                let dep_comp = comps[dep];
+                update(&mut hasher, dep.as_ref());
+                // The inliner copies the body of the sub-component into the
+                // parent.
+                if dep_comp.attributes.has(ir::BoolAttr::Inline) {
+                    update(&mut hasher, component_source(dep_comp));
+                } else {
+                    update(&mut hasher, interface(dep_comp));
+                }
+            }
+            for prim in prims {
+                let mut def = vec![];
+                ir::Printer::write_primitive(
+                    ctx.lib.get_primitive(prim),
+                    0,
+                    &mut def,
+                )?;
+                update(&mut hasher, def);
+            }
+
+            keys.insert(comp.name, hex(hasher));
+            deps.insert(comp.name, comp_deps);
+        }
+
+        let mut cache = Self {
+            dir,
+            keys,
+            deps,
+            order: ctx.components.iter().map(|comp| comp.name).collect(),
+            stale: HashSet::new(),
+            entrypoint: ctx.entrypoint,
+        };
+
+        // A component is stale if it is missing from the cache or if it was
+        // compiled against a different interface of a cached sub-component.
+        // Sub-components that are missing from the cache are checked by
+        // [ComponentCache::validate] once they have been compiled. Components
+        // with unresolved sub-components are always compiled so that the pass
+        // pipeline reports the problem.
+        let missing: HashSet<_> = cache
+            .order
+            .iter()
+            .filter(|name| {
+                let key = &cache.keys[*name];
+                unresolved.contains(*name)
+                    || !["sv", "iface", "deps"]
+                        .iter()
+                        .all(|ext| cache.entry(key, ext).exists())
+                    || (**name == cache.entrypoint
+                        && !cache.entry(key, "extra.sv").exists())
+            })
+            .copied()
+            .collect();
+        let mismatched = cache
+            .order
+            .iter()
+            .filter(|name| !missing.contains(name))
+            .filter(|name| {
+                let recorded = cache.recorded_deps(&cache.keys[*name]);
+                cache.deps[*name]
+                    .iter()
+                    .filter(|dep| !missing.contains(dep))
+                    .any(|dep| {
+                        let iface = cache.read(&cache.keys[dep], "iface");
+                        recorded.get(dep)
+                            != iface.map(|i| hash_str(&i)).as_ref()
+                    })
+            })
+            .copied()
+            .collect_vec();
+        cache.stale = missing;
+        cache.stale.extend(mismatched);
+
+        cache.log_stale();
+
+        Ok(cache)
+    }
+
+    /// Returns true if every component can be emitted from the cache.
+    pub fn is_complete(&self) -> bool {
+        self.stale.is_empty()
+    }
+
+    /// Remove the components that do not need to be compiled from `ctx`.
+    /// The sub-components of stale components are kept because passes
+    /// inspect the components instantiated by the component being compiled.
+    pub fn prune(&self, ctx: &mut ir::Context) {
+        let mut needed = HashSet::new();
+        let mut worklist = self.stale.iter().copied().collect_vec();
+        while let Some(name) = worklist.pop() {
+            if needed.insert(name) {
+                worklist.extend(self.deps[&name].iter().copied());
+            }
+        }
+        ctx.components.retain(|comp| needed.contains(&comp.name));
+    }
+
+    /// Check that the components compiled in `ctx` did not change the
+    /// interface that their cached parents were compiled against.
+    /// Returns false if some parents were invalidated; the caller must then
+    /// construct a fresh context and run the pass pipeline again.
+    pub fn validate(&mut self, ctx: &ir::Context) -> bool {
+        let compiled: HashMap<_, _> = ctx
+            .components
+            .iter()
+            .filter(|comp| self.stale.contains(&comp.name))
+            .map(|comp| (comp.name, hash_str(&interface(comp))))
+            .collect();
+
+        let invalid = self
+            .order
+            .iter()
+            .filter(|name| !self.stale.contains(name))
+            .filter(|name| {
+                let recorded = self.recorded_deps(&self.keys[*name]);
+                self.deps[name].iter().any(|dep| {
+                    compiled
+                        .get(dep)
+                        .is_some_and(|iface| recorded.get(dep) != Some(iface))
+                })
+            })
+            .copied()
+            .collect_vec();
+
+        for name in &invalid {
+            log::info!("Interface of a sub-component of `{name}' changed");
+        }
+        self.stale.extend(invalid.iter().copied());
+        if !invalid.is_empty() {
+            self.log_stale();
+        }
+        invalid.is_empty()
+    }
+
+    /// Log the components that need to be compiled in program order.
+    fn log_stale(&self) {
+        let names = self
+            .order
+            .iter()
+            .filter(|name| self.stale.contains(name))
+            .join(", ");
+        log::info!(
+            "{} of {} components need to be compiled{}",
+            self.stale.len(),
+            self.order.len(),
+            if names.is_empty() {
+                names
+            } else {
+                format!(": {names}")
+            }
+        );
+    }
+
+    /// Emit the Verilog for the entire program. Components compiled in `ctx`
+    /// are emitted and written to the cache while all other components are
+    /// read from the cache.
+    pub fn emit(
+        &self,
+        ctx: &ir::Context,
+        file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        VerilogBackend::validate(ctx)?;
+        VerilogBackend::link_externs(ctx, file)?;
+
+        let compiled: HashMap<_, _> = ctx
+            .components
+            .iter()
+            .map(|comp| (comp.name, comp))
+            .collect();
+        let out = &mut file.get_write();
+        for name in &self.order {
+            let key = &self.keys[name];
+            if !self.stale.contains(name) {
+                out.write_all(&self.load(key, "sv")?)?;
+                continue;
+            }
+            // The component was removed by the pass pipeline
+            let Some(comp) = compiled.get(name) else {
+                continue;
+            };
+            let verilog = self.generate(ctx, comp)?;
+            out.write_all(&verilog)?;
+            // A sub-component removed by the pass pipeline (for example, by
+            // inlining followed by dead component removal) has no interface
+            // to record. The component is not stored so that the next
+            // invocation treats it as a cache miss.
+            let Some(deps) = self.deps[name]
+                .iter()
+                .map(|dep| {
+                    let iface = hash_str(&interface(compiled.get(dep)?));
+                    Some(format!("{dep} {iface}\n"))
+                })
+                .collect::<Option<String>>()
+            else {
+                continue;
+            };
+            self.store(key, "sv", &verilog)?;
+            self.store(key, "iface", interface(comp).as_bytes())?;
+            self.store(key, "deps", deps.as_bytes())?;
+        }
+
+        // Components generated by the pass pipeline (for example, by
+        // `wrap-main`) are stored with the entrypoint.
+        let entry_key = &self.keys[&self.entrypoint];
+        if self.stale.contains(&self.entrypoint) {
+            let mut extra = vec![];
+            for comp in ctx
+                .components
+                .iter()
+                .filter(|comp| !self.keys.contains_key(&comp.name))
+            {
+                extra.extend(self.generate(ctx, comp)?);
+            }
+            out.write_all(&extra)?;
+            self.store(entry_key, "extra.sv", &extra)?;
+        } else {
+            out.write_all(&self.load(entry_key, "extra.sv")?)?;
+        }
+        Ok(())
+    }
+
+    /// Generate the Verilog for a component.
+    fn generate(
+        &self,
+        ctx: &ir::Context,
+        comp: &ir::Component,
+    ) -> CalyxResult<Vec<u8>> {
+        let mut buf = vec![];
+        emit_component(
+            comp,
+            ctx.bc.synthesis_mode,
+            ctx.bc.enable_verification,
+            ctx.bc.flat_assign,
+            &mut buf,
+        )?;
+        Ok(buf)
+    }
+
+    /// Hashes of the sub-component interfaces that the component with `key`
+    /// was compiled against.
+    fn recorded_deps(&self, key: &Key) -> HashMap<ir::Id, Key> {
+        self.read(key, "deps")
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (dep, iface) = line.split_once(' ')?;
+                Some((ir::Id::new(dep), iface.to_string()))
+            })
+            .collect()
+    }
+
+    fn entry(&self, key: &Key, ext: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{ext}"))
+    }
+
+    fn read(&self, key: &Key, ext: &str) -> Option<String> {
+        fs::read_to_string(self.entry(key, ext)).ok()
+    }
+
+    fn load(&self, key: &Key, ext: &str) -> CalyxResult<Vec<u8>> {
+        let path = self.entry(key, ext);
+        fs::read(&path).map_err(|err| cache_error(&path, err))
+    }
+
+    fn store(&self, key: &Key, ext: &str, contents: &[u8]) -> CalyxResult<()> {
+        let path = self.entry(key, ext);
+        fs::write(&path, contents).map_err(|err| cache_error(&path, err))
+    }
+}
+
+fn cache_error(path: &Path, err: std::io::Error) -> Error {
+    Error::write_error(format!(
+        "Failed to access cache entry {}: {err}",
+        path.display()
+    ))
+}
+
+/// The source of a component in the Calyx syntax.
+fn component_source(comp: &ir::Component) -> Vec<u8> {
+    let mut buf = vec![];
+    ir::Printer::write_component(comp, &mut buf)
+        .expect("Writing to a buffer should not fail");
+    buf
+}
+
+/// The parts of a component visible to the components that instantiate it:
+/// its signature, attributes, timing, and `ref` cells.
+fn interface(comp: &ir::Component) -> String {
+    let sig = comp.signature.borrow();
+    let mut iface = format!(
+        "{}{}{:?}({})",
+        if comp.is_comb { "comb " } else { "" },
+        ir::Printer::format_attributes(&comp.attributes),
+        comp.latency,
+        ir::Printer::format_ports(&sig.ports),
+    );
+    for cell in comp
+        .cells
+        .iter()
+        .filter(|cell| cell.borrow().is_reference())
+    {
+        let mut buf = vec![];
+        ir::Printer::write_cell(&cell.borrow(), 0, &mut buf)
+            .expect("Writing to a buffer should not fail");
+        iface.push_str(&String::from_utf8_lossy(&buf));
+    }
+    iface
+}
+
+/// Add the library files to `hasher`: the definitions of all primitives, which
+/// include the bodies of inline primitives, and the contents of the Verilog
+/// files that implement the extern primitives.
+fn update_library(
+    hasher: &mut Sha256,
+    lib: &ir::LibrarySignatures,
+) -> CalyxResult<()> {
+    for prim in lib.signatures() {
+        let mut def = vec![];
+        ir::Printer::write_primitive(prim, 0, &mut def)?;
+        update(hasher, def);
+    }
+    for path in lib.extern_paths().into_iter().unique() {
+        let contents = fs::read(path).map_err(|err| {
+            Error::invalid_file(format!(
+                "Failed to read library file {}: {err}",
+                path.display()
+            ))
+        })?;
+        update(hasher, contents);
+    }
+    Ok(())
+}
+
+/// Add a length-prefixed chunk of data to `hasher` so that the boundaries
+/// between chunks are part of the digest.
+fn update<D: AsRef<[u8]>>(hasher: &mut Sha256, data: D) {
+    let data = data.as_ref();
+    hasher.update((data.len() as u64).to_le_bytes());
+    hasher.update(data);
+}
+
+fn hex(hasher: Sha256) -> Key {
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_str(s: &str) -> Key {
+    let mut hasher = Sha256::new();
+    update(&mut hasher, s);
+    hex(hasher)
+}
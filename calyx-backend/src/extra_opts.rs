@@ -0,0 +1,47 @@
+//! Options passed to backends with `-x <prefix>:<name>=<value>`.
+use calyx_utils::{CalyxResult, Error};
+use std::str::FromStr;
+
+/// A single option meant for the backend that owns `prefix`.
+pub(crate) struct ExtraOpt<'a> {
+    prefix: &'a str,
+    /// Name of the option, without the prefix.
+    pub name: &'a str,
+    /// Value of the option. Empty when the option is given as a flag.
+    pub val: &'a str,
+}
+
+impl<'a> ExtraOpt<'a> {
+    /// The options in `opts` that start with `<prefix>:`, in the order they
+    /// were passed to the compiler.
+    pub fn parse(
+        prefix: &'a str,
+        opts: &'a [String],
+    ) -> impl Iterator<Item = ExtraOpt<'a>> {
+        opts.iter().filter_map(move |opt| {
+            let opt = opt.strip_prefix(prefix)?.strip_prefix(':')?;
+            let (name, val) = opt.split_once('=').unwrap_or((opt, ""));
+            Some(ExtraOpt { prefix, name, val })
+        })
+    }
+
+    /// The value of the option parsed as a number.
+    // unused when only backends without numeric options are enabled
+    #[allow(unused)]
+    pub fn num<T: FromStr>(&self) -> CalyxResult<T> {
+        self.val.parse().map_err(|_| self.invalid("a number"))
+    }
+
+    /// Error for an option whose value is not `expected`.
+    pub fn invalid(&self, expected: &str) -> Error {
+        Error::misc(format!(
+            "Expected {expected} for option `{}:{}', got `{}'",
+            self.prefix, self.name, self.val
+        ))
+    }
+
+    /// Warn that the backend does not know about this option.
+    pub fn unknown(&self) {
+        log::warn!("Ignoring unknown option `{}:{}'", self.prefix, self.name)
+    }
+}
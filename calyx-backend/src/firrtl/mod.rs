@@ -3,9 +3,11 @@
 //! Transforms an [`ir::Context`](crate::ir::Context) into a formatted string that represents a
 //! valid FIRRTL program.
 
+mod primitives;
+
 use crate::{traits::Backend, VerilogBackend};
 use calyx_ir::{self as ir, Binding, RRC};
-use calyx_utils::{CalyxResult, Id, OutputFile};
+use calyx_utils::{CalyxResult, Error, Id, OutputFile};
 use ir::Port;
 use std::cell::RefCell;
 use std::collections::HashSet;
@@ -35,10 +37,18 @@ impl Backend for FirrtlBackend {
     }
 
     fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        if ctx.bc.emit_primitive_extmodules && ctx.bc.emit_primitive_definitions
+        {
+            return Err(Error::misc(
+                "--emit-primitive-extmodules and --emit-primitive-definitions cannot be used together",
+            ));
+        }
         let out = &mut file.get_write();
         writeln!(out, "circuit {}:", ctx.entrypoint)?;
         if ctx.bc.emit_primitive_extmodules {
-            emit_extmodules(ctx, out)?;
+            emit_primitives(ctx, false, out)?;
+        } else if ctx.bc.emit_primitive_definitions {
+            emit_primitives(ctx, true, out)?;
         }
         for comp in ctx.components.iter() {
             emit_component(comp, out)?
@@ -47,8 +57,13 @@ impl Backend for FirrtlBackend {
     }
 }
 
-fn emit_extmodules<F: io::Write>(
+/// Emit a module for every unique primitive instantiation in the program.
+/// The module is an extmodule for the SystemVerilog implementation of the
+/// primitive unless `definitions` is set, in which case it is implemented in
+/// FIRRTL.
+fn emit_primitives<F: io::Write>(
     ctx: &ir::Context,
+    definitions: bool,
     out: &mut F,
 ) -> Result<(), calyx_utils::Error> {
     let mut extmodule_set: HashSet<String> = HashSet::new();
@@ -63,9 +78,19 @@ fn emit_extmodules<F: io::Write>(
             {
                 let curr_module_name =
                     get_primitive_module_name(name, param_binding);
-                if extmodule_set.insert(curr_module_name.clone()) {
+                if !extmodule_set.insert(curr_module_name.clone()) {
+                    continue;
+                }
+                if definitions {
+                    writeln!(out, "{}module {}:", SPACING, curr_module_name)?;
+                    for port in cell_borrowed.ports() {
+                        emit_port(port.borrow(), false, out)?;
+                    }
+                    writeln!(out)?;
+                    primitives::emit_primitive_body(name, param_binding, out)?;
+                } else {
                     emit_primitive_extmodule(
-                        cell.borrow().ports(),
+                        cell_borrowed.ports(),
                         &curr_module_name,
                         name,
                         param_binding,
@@ -94,6 +119,21 @@ fn emit_component<F: io::Write>(
         emit_port(port, true, f)?;
     }
 
+    // `@external` cells are exposed as ports, the same way that compiling a
+    // `ref` cell would expose them.
+    let external_ports: Vec<_> = comp
+        .cells
+        .iter()
+        .filter(|cell| is_external(&cell.borrow()))
+        .flat_map(|cell| cell.borrow().ports().to_vec())
+        .filter(|port| !is_clk_or_reset(&port.borrow()))
+        .collect();
+    for port_ref in &external_ports {
+        let port = port_ref.borrow();
+        let name = get_port_string(&port, false);
+        emit_named_port(&port, &name, true, f)?;
+    }
+
     // write invalid statements for all output ports.
     for port_ref in sig.ports.iter() {
         let port = port_ref.as_ref();
@@ -102,6 +142,12 @@ fn emit_component<F: io::Write>(
             dst_set.insert(port.borrow().canonical());
         }
     }
+    for port in &external_ports {
+        if port.borrow().direction == calyx_frontend::Direction::Input {
+            write_invalid_initialization(port, f)?;
+            dst_set.insert(port.borrow().canonical());
+        }
+    }
 
     // Add a COMPONENT START: <name> anchor before any code in the component
     writeln!(f, "{}; COMPONENT START: {}", SPACING.repeat(2), comp.name)?;
@@ -109,15 +155,7 @@ fn emit_component<F: io::Write>(
     // Cells
     for cell in comp.cells.iter() {
         let cell_borrowed = cell.as_ref().borrow();
-        let is_external = cell
-            .borrow()
-            .get_attribute(ir::BoolAttr::External)
-            .is_some();
-        if is_external {
-            // The FIRRTL compiler cannot read/write memories, so we must use ref.
-            panic!("FIRRTL backend only works on ref, not @external!");
-        }
-        if cell_borrowed.type_name().is_some() && !is_external {
+        if cell_borrowed.type_name().is_some() && !is_external(&cell_borrowed) {
             let module_name = match &cell_borrowed.prototype {
                 ir::CellType::Primitive {
                     name,
@@ -140,6 +178,12 @@ fn emit_component<F: io::Write>(
 
     // Emit assignments
     for asgn in &comp.continuous_assignments {
+        // The clock and reset of `@external` cells are not exposed
+        if is_external_port(&asgn.dst.borrow())
+            && is_clk_or_reset(&asgn.dst.borrow())
+        {
+            continue;
+        }
         match asgn.guard.as_ref() {
             ir::Guard::True => {
                 // Simple assignment with no guard
@@ -198,10 +242,37 @@ fn emit_primitive_extmodule<F: io::Write>(
     Ok(())
 }
 
+/// Returns true if `cell` is marked `@external`.
+fn is_external(cell: &ir::Cell) -> bool {
+    cell.get_attribute(ir::BoolAttr::External).is_some()
+}
+
+/// Returns true if `port` belongs to an `@external` cell.
+fn is_external_port(port: &Port) -> bool {
+    match &port.parent {
+        ir::PortParent::Cell(cell) => is_external(&cell.upgrade().borrow()),
+        _ => false,
+    }
+}
+
+fn is_clk_or_reset(port: &Port) -> bool {
+    port.has_attribute(ir::BoolAttr::Clk)
+        || port.has_attribute(ir::BoolAttr::Reset)
+}
+
 fn emit_port<F: io::Write>(
     port: std::cell::Ref<'_, Port>,
     reverse_direction: bool,
     f: &mut F,
+) -> Result<(), io::Error> {
+    emit_named_port(&port, port.name.as_ref(), reverse_direction, f)
+}
+
+fn emit_named_port<F: io::Write>(
+    port: &Port,
+    name: &str,
+    reverse_direction: bool,
+    f: &mut F,
 ) -> Result<(), io::Error> {
     let direction_string = match port.direction {
         calyx_frontend::Direction::Input => {
@@ -228,7 +299,7 @@ fn emit_port<F: io::Write>(
             "{}{} {}: Clock",
             SPACING.repeat(2),
             direction_string,
-            port.name
+            name
         )?;
     } else {
         writeln!(
@@ -236,7 +307,7 @@ fn emit_port<F: io::Write>(
             "{}{} {}: UInt<{}>",
             SPACING.repeat(2),
             direction_string,
-            port.name,
+            name,
             port.width
         )?;
     };
@@ -299,6 +370,9 @@ fn get_port_string(port: &calyx_ir::Port, is_dst: bool) -> String {
                     }
                 }
                 ir::CellType::ThisComponent => String::from(port.name.as_ref()),
+                _ if is_external(&parent) => {
+                    format!("{}_{}", parent.name(), port.name)
+                }
                 _ => {
                     format!("{}.{}", parent.name().as_ref(), port.name.as_ref())
                 }
@@ -0,0 +1,243 @@
+//! FIRRTL implementations of the core Calyx primitives.
+//!
+//! Every primitive instantiation becomes its own module with the parameters
+//! substituted into the body, mirroring the SystemVerilog implementations in
+//! `primitives/`.
+
+use super::SPACING;
+use calyx_ir::Binding;
+use calyx_utils::{CalyxResult, Error, Id};
+use std::io;
+
+/// Emit the body of the module implementing the primitive `name` with the
+/// parameters in `params`. The caller emits the module header and ports.
+pub(super) fn emit_primitive_body<F: io::Write>(
+    name: &Id,
+    params: &Binding,
+    f: &mut F,
+) -> CalyxResult<()> {
+    let param = |p: &str| {
+        params
+            .iter()
+            .find(|(id, _)| id == p)
+            .map(|(_, v)| *v)
+            .unwrap_or_else(|| {
+                unreachable!("primitive `{name}' has no parameter `{p}'")
+            })
+    };
+    let binary = |op: &str| format!("out <= {op}(left, right)");
+
+    let body = match name.as_ref() {
+        "std_const" => format!("out <= UInt({})", param("VALUE")),
+        "std_wire" => "out <= in".to_string(),
+        "std_slice" => format!("out <= bits(in, {}, 0)", param("OUT_WIDTH") - 1),
+        "std_pad" => format!("out <= pad(in, {})", param("OUT_WIDTH")),
+        "std_cat" => "out <= cat(left, right)".to_string(),
+        "std_bit_slice" => format!(
+            "out <= bits(in, {}, {})",
+            param("END_IDX"),
+            param("START_IDX")
+        ),
+        "std_not" => "out <= not(in)".to_string(),
+        "std_and" => binary("and"),
+        "std_or" => binary("or"),
+        "std_xor" => binary("xor"),
+        "std_add" => "out <= tail(add(left, right), 1)".to_string(),
+        "std_sub" => "out <= tail(sub(left, right), 1)".to_string(),
+        "std_gt" => binary("gt"),
+        "std_lt" => binary("lt"),
+        "std_eq" => binary("eq"),
+        "std_neq" => binary("neq"),
+        "std_ge" => binary("geq"),
+        "std_le" => binary("leq"),
+        "std_rsh" => "out <= dshr(left, right)".to_string(),
+        "std_lsh" => {
+            // Only shift by the bits that can keep a value in range so that
+            // `dshl` does not create a huge intermediate result.
+            let width = param("WIDTH");
+            let bits = bit_width(width - 1);
+            format!(
+                "\
+node shifted = dshl(left, bits(right, {}, 0))
+out <= mux(geq(right, UInt({width})), UInt(0), bits(shifted, {}, 0))",
+                bits - 1,
+                width - 1
+            )
+        }
+        "std_mux" => "out <= mux(cond, tru, fal)".to_string(),
+        "undef" => "out is invalid".to_string(),
+        "std_reg" => {
+            let width = param("WIDTH");
+            format!(
+                "\
+reg internal_reg : UInt<{width}>, clk
+reg done_reg : UInt<1>, clk
+
+when eq(reset, UInt(1)):
+{SPACING}internal_reg <= UInt(0)
+{SPACING}done_reg <= UInt(0)
+else:
+{SPACING}when eq(write_en, UInt(1)):
+{SPACING}{SPACING}internal_reg <= in
+{SPACING}{SPACING}done_reg <= UInt(1)
+{SPACING}else:
+{SPACING}{SPACING}done_reg <= UInt(0)
+
+out <= internal_reg
+done <= done_reg"
+            )
+        }
+        "std_mult_pipe" => {
+            let width = param("WIDTH");
+            format!(
+                "\
+reg rtmp : UInt<{width}>, clk
+reg ltmp : UInt<{width}>, clk
+reg out_tmp : UInt<{}>, clk
+reg done_buf : UInt<1>[2], clk
+
+; Start sending the done signal and push it through the pipeline.
+done_buf[0] <= go
+when eq(go, UInt(1)):
+{SPACING}done_buf[1] <= done_buf[0]
+else:
+{SPACING}done_buf[1] <= UInt(0)
+
+; Register the inputs
+when or(eq(reset, UInt(1)), eq(go, UInt(0))):
+{SPACING}ltmp <= UInt(0)
+{SPACING}rtmp <= UInt(0)
+else:
+{SPACING}ltmp <= left
+{SPACING}rtmp <= right
+
+; Compute the output and save it to out_tmp
+when eq(reset, UInt(1)):
+{SPACING}out_tmp <= UInt(0)
+else:
+{SPACING}when eq(go, UInt(1)):
+{SPACING}{SPACING}out_tmp <= mul(ltmp, rtmp)
+
+out <= bits(out_tmp, {}, 0)
+done <= done_buf[1]",
+                width * 2,
+                width - 1
+            )
+        }
+        name => match memory_kind(name) {
+            Some((seq, dims)) => memory(seq, dims, &param),
+            None => {
+                return Err(Error::misc(format!(
+                    "The FIRRTL backend has no implementation of primitive `{name}'. Use --emit-primitive-extmodules to use its SystemVerilog implementation instead."
+                )))
+            }
+        },
+    };
+
+    for line in body.lines() {
+        if line.is_empty() {
+            writeln!(f)?;
+        } else {
+            writeln!(f, "{}{}", SPACING.repeat(2), line)?;
+        }
+    }
+    writeln!(f)?;
+    Ok(())
+}
+
+/// Number of bits needed to represent `value`, which is at least one.
+fn bit_width(value: u64) -> u64 {
+    (u64::BITS - value.leading_zeros()).max(1) as u64
+}
+
+/// Returns whether the memory `name` is sequential and its dimensions if it
+/// is one of `comb_mem_d{1-4}` or `seq_mem_d{1-4}`.
+fn memory_kind(name: &str) -> Option<(bool, u64)> {
+    let (seq, dims) = if let Some(dims) = name.strip_prefix("comb_mem_d") {
+        (false, dims)
+    } else {
+        (true, name.strip_prefix("seq_mem_d")?)
+    };
+    match dims.parse() {
+        Ok(dims @ 1..=4) => Some((seq, dims)),
+        _ => None,
+    }
+}
+
+/// Body of a memory with `dims` dimensions. Multi-dimensional memories are
+/// stored in row-major order in a single FIRRTL `mem`.
+fn memory(seq: bool, dims: u64, param: &dyn Fn(&str) -> u64) -> String {
+    let width = param("WIDTH");
+    let sizes: Vec<u64> = if dims == 1 {
+        vec![param("SIZE")]
+    } else {
+        (0..dims).map(|i| param(&format!("D{i}_SIZE"))).collect()
+    };
+    let depth: u64 = sizes.iter().product();
+    let addr_width = bit_width(depth - 1);
+    let mut addr = "addr0".to_string();
+    for (i, size) in sizes.iter().enumerate().skip(1) {
+        addr = format!("add(mul({addr}, UInt({size})), addr{i})");
+    }
+
+    // Combinational memories are always enabled
+    let (read_en, write_en, done) = if seq {
+        (
+            "and(content_en, not(write_en))",
+            "and(content_en, write_en)",
+            "content_en",
+        )
+    } else {
+        ("UInt(1)", "write_en", "write_en")
+    };
+    let read = if seq {
+        format!(
+            "\
+reg read_out : UInt<{width}>, clk
+when eq(reset, UInt(1)):
+{SPACING}read_out <= UInt(0)
+else:
+{SPACING}when eq(read_en, UInt(1)):
+{SPACING}{SPACING}read_out <= internal_mem.internal_read.data
+read_data <= read_out"
+        )
+    } else {
+        "read_data <= internal_mem.internal_read.data".to_string()
+    };
+
+    format!(
+        "\
+mem internal_mem :
+{SPACING}data-type => UInt<{width}>
+{SPACING}depth => {depth}
+{SPACING}read-latency => 0
+{SPACING}write-latency => 1
+{SPACING}reader => internal_read
+{SPACING}writer => internal_write
+{SPACING}read-under-write => undefined
+
+node addr = bits(pad({addr}, {addr_width}), {}, 0)
+node read_en = {read_en}
+
+; read from memory
+internal_mem.internal_read.addr <= addr
+internal_mem.internal_read.en <= read_en
+internal_mem.internal_read.clk <= clk
+{read}
+
+; write to memory
+internal_mem.internal_write.addr <= addr
+internal_mem.internal_write.en <= and({write_en}, not(reset))
+internal_mem.internal_write.clk <= clk
+internal_mem.internal_write.data <= write_data
+internal_mem.internal_write.mask <= UInt(1)
+
+reg done_reg : UInt<1>, clk
+when eq(reset, UInt(1)):
+{SPACING}done_reg <= UInt(0)
+else:
+{SPACING}done_reg <= {done}
+done <= done_reg",
+        addr_width - 1
+    )
+}
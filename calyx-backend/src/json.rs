@@ -0,0 +1,38 @@
+//! Backend that emits the program using the versioned interchange schema.
+//! The output can be read back by the compiler by passing a `.json` file as
+//! the input.
+//! Usage: -b json [-o <OUTPUT_FILE>]
+
+use crate::traits::Backend;
+use calyx_ir as ir;
+use calyx_utils::{CalyxResult, OutputFile};
+
+#[derive(Default)]
+pub struct JsonBackend;
+
+impl Backend for JsonBackend {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    /// OK to run this analysis on any Calyx program
+    fn validate(_ctx: &ir::Context) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    /// Don't need to take care of this for this pass
+    fn link_externs(
+        _ctx: &ir::Context,
+        _file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        let program = ir::interchange::export(ctx);
+        let out = &mut file.get_write();
+        serde_json::to_writer_pretty(&mut *out, &program)?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
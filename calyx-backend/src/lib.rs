@@ -1,23 +1,38 @@
 //! Backends for the Calyx compiler.
 mod backend_opt;
+mod cache;
 mod firrtl;
 mod primitive_uses;
 mod traits;
 mod verilog;
 
+#[cfg(any(
+    feature = "resources",
+    feature = "timing",
+    feature = "verilator",
+    feature = "xilinx"
+))]
+mod extra_opts;
+
 pub use backend_opt::BackendOpt;
+pub use cache::ComponentCache;
 pub use firrtl::FirrtlBackend;
 pub use primitive_uses::PrimitiveUsesBackend;
 pub use traits::Backend;
 pub use verilog::VerilogBackend;
 
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::JsonBackend;
+
 #[cfg(feature = "mlir")]
 mod mlir;
 #[cfg(feature = "mlir")]
 pub use mlir::MlirBackend;
 
 #[cfg(feature = "resources")]
-mod resources;
+pub mod resources;
 #[cfg(feature = "resources")]
 pub use resources::ResourcesBackend;
 
@@ -26,5 +41,20 @@ mod sexp;
 #[cfg(feature = "sexp")]
 pub use sexp::SexpBackend;
 
+#[cfg(feature = "timing")]
+pub mod timing;
+#[cfg(feature = "timing")]
+pub use timing::TimingBackend;
+
+#[cfg(feature = "verilator")]
+mod verilator;
+#[cfg(feature = "verilator")]
+pub use verilator::VerilatorHarnessBackend;
+
 #[cfg(feature = "xilinx")]
 pub mod xilinx;
+
+#[cfg(feature = "yxi")]
+mod yxi;
+#[cfg(feature = "yxi")]
+pub use yxi::YxiBackend;
@@ -0,0 +1,414 @@
+//! Cost models used to estimate the resources used by a design.
+use calyx_ir as ir;
+use calyx_utils::{CalyxResult, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::{Add, AddAssign};
+use std::path::Path;
+
+/// Estimated FPGA resources.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct Resources {
+    /// Look-up tables
+    pub lut: u64,
+    /// Flip-flops
+    pub ff: u64,
+    /// DSP slices
+    pub dsp: u64,
+    /// Block RAMs
+    pub bram: u64,
+}
+
+impl Resources {
+    pub fn luts(lut: u64) -> Self {
+        Self {
+            lut,
+            ..Default::default()
+        }
+    }
+}
+
+impl Add for Resources {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            lut: self.lut + rhs.lut,
+            ff: self.ff + rhs.ff,
+            dsp: self.dsp + rhs.dsp,
+            bram: self.bram + rhs.bram,
+        }
+    }
+}
+
+impl AddAssign for Resources {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Assigns resource estimates to the hardware implied by a Calyx program.
+pub trait CostModel {
+    /// Resources used by an instance of the primitive `name` with the given
+    /// parameters. Returns `None` if the model does not know the primitive.
+    fn primitive(&self, name: &str, params: &ir::Binding) -> Option<Resources>;
+
+    /// Resources used by a multiplexer that selects between `inputs` values
+    /// that are `width` bits wide using one-hot select signals.
+    fn mux(&self, width: u64, inputs: u64) -> Resources;
+
+    /// Resources used by a boolean function of `inputs` 1-bit signals.
+    fn logic(&self, inputs: u64) -> Resources;
+
+    /// Resources used to compare two `width` bit values.
+    fn comparison(&self, op: &ir::PortComp, width: u64) -> Resources;
+}
+
+/// Estimates for AMD UltraScale+ devices which have 6-input LUTs, 27x18 bit
+/// DSP multipliers, and 36Kb block RAMs.
+#[derive(Default)]
+pub struct DefaultCostModel;
+
+/// Number of LUTs needed to reduce `inputs` signals into one using a tree of
+/// 6-input LUTs.
+fn lut_tree(inputs: u64) -> u64 {
+    if inputs <= 1 {
+        0
+    } else {
+        (inputs - 1).div_ceil(5)
+    }
+}
+
+fn log2(n: u64) -> u64 {
+    n.next_power_of_two().trailing_zeros() as u64
+}
+
+fn param(params: &ir::Binding, name: &str) -> u64 {
+    params
+        .iter()
+        .find(|(id, _)| id == name)
+        .map(|(_, v)| *v)
+        .unwrap_or(0)
+}
+
+/// Number of elements stored in a memory primitive.
+fn mem_size(params: &ir::Binding) -> u64 {
+    params
+        .iter()
+        .filter(|(id, _)| {
+            id == "SIZE"
+                || (id.as_ref().ends_with("_SIZE")
+                    && !id.as_ref().ends_with("IDX_SIZE"))
+        })
+        .map(|(_, v)| *v)
+        .product()
+}
+
+/// Memories with fewer bits than this are implemented with LUT RAMs.
+const BRAM_THRESHOLD: u64 = 4096;
+
+/// (depth, width) configurations of a RAMB36 block.
+const BRAM_SHAPES: [(u64, u64); 6] = [
+    (512, 72),
+    (1024, 36),
+    (2048, 18),
+    (4096, 9),
+    (8192, 4),
+    (16384, 2),
+];
+
+/// LUTs used by a `width` bit memory with `size` elements built out of
+/// 64x1 LUT RAMs, including the read multiplexers.
+fn lutram(width: u64, size: u64) -> u64 {
+    let banks = size.div_ceil(64);
+    width * (banks + lut_tree(banks))
+}
+
+fn bram(width: u64, size: u64) -> u64 {
+    BRAM_SHAPES
+        .iter()
+        .map(|(depth, w)| size.div_ceil(*depth) * width.div_ceil(*w))
+        .min()
+        .unwrap()
+}
+
+impl DefaultCostModel {
+    fn multiplier(width: u64) -> Resources {
+        let dsp = width.div_ceil(27) * width.div_ceil(18);
+        Resources {
+            // Partial products from multiple DSPs are added with LUTs
+            lut: if dsp > 1 { width } else { 0 },
+            dsp,
+            ..Default::default()
+        }
+    }
+
+    /// LUTs that detect whether either input of a floating point comparison
+    /// is a NaN.
+    fn float_nan_checks(params: &ir::Binding) -> Resources {
+        let checks = lut_tree(param(params, "EXP_WIDTH"))
+            + lut_tree(param(params, "MANT_WIDTH"));
+        Resources::luts(2 * checks)
+    }
+
+    fn sequential_mem(width: u64, size: u64) -> Resources {
+        if width * size >= BRAM_THRESHOLD {
+            Resources {
+                lut: 0,
+                ff: 1,
+                dsp: 0,
+                bram: bram(width, size),
+            }
+        } else {
+            Resources {
+                lut: lutram(width, size),
+                ff: width + 1,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+impl CostModel for DefaultCostModel {
+    fn primitive(&self, name: &str, params: &ir::Binding) -> Option<Resources> {
+        let w = param(params, "WIDTH");
+        let res = match name {
+            // Wiring
+            "std_const" | "std_wire" | "std_slice" | "std_pad" | "std_cat"
+            | "std_bit_slice" | "std_signext" | "undef" => Resources::default(),
+            // Bitwise operators
+            "std_not" | "std_and" | "std_or" | "std_xor" | "std_mux" => {
+                Resources::luts(w)
+            }
+            // Adders use the carry chain
+            "std_add" | "std_sub" | "std_sadd" | "std_ssub" | "std_fp_add"
+            | "std_fp_sub" | "std_fp_sadd" | "std_fp_ssub" => {
+                Resources::luts(w)
+            }
+            "std_eq" | "std_neq" | "std_seq" | "std_sneq" => {
+                self.comparison(&ir::PortComp::Eq, w)
+            }
+            "std_lt" | "std_gt" | "std_le" | "std_ge" | "std_slt"
+            | "std_sgt" | "std_sle" | "std_sge" | "std_fp_gt"
+            | "std_fp_sgt" | "std_fp_slt" => {
+                self.comparison(&ir::PortComp::Lt, w)
+            }
+            // Barrel shifters with two shift stages per LUT
+            "std_lsh" | "std_rsh" | "std_slsh" | "std_srsh" => {
+                Resources::luts(w * log2(w).div_ceil(2).max(1))
+            }
+            "std_reg" | "init_one_reg" => Resources {
+                ff: w + 1,
+                ..Default::default()
+            },
+            "std_sync_reg" => Resources {
+                lut: w + 4,
+                ff: 2 * w + 2,
+                ..Default::default()
+            },
+            // Combinational multipliers
+            "std_unsyn_mult" | "std_unsyn_smult" | "std_const_mult" => {
+                Self::multiplier(w)
+            }
+            // Pipelined multipliers register their inputs and output
+            "std_mult_pipe" | "std_smult_pipe" | "std_fp_mult_pipe"
+            | "std_fp_smult_pipe" | "pipelined_mult" | "pipelined_fp_smult"
+            | "stallable_mult" => {
+                Self::multiplier(w)
+                    + Resources {
+                        lut: 4,
+                        ff: 3 * w + 4,
+                        ..Default::default()
+                    }
+            }
+            // Combinational array dividers
+            "std_unsyn_div" | "std_unsyn_mod" | "std_unsyn_sdiv"
+            | "std_unsyn_smod" => Resources::luts(w * w),
+            // Iterative dividers use a subtractor and keep the quotient,
+            // remainder, divisor, and an iteration counter.
+            "std_div_pipe" | "std_sdiv_pipe" | "std_fp_div_pipe"
+            | "std_fp_sdiv_pipe" => Resources {
+                lut: 2 * w + log2(w),
+                ff: 4 * w + log2(w) + 2,
+                ..Default::default()
+            },
+            "sqrt" | "fp_sqrt" => Resources {
+                lut: 2 * w + log2(w),
+                ff: 3 * w + log2(w) + 2,
+                ..Default::default()
+            },
+            // Floating point operators compute their result in a single
+            // cycle and register it
+            "std_float_add" | "std_float_sub" => {
+                // Alignment and normalization shifters around an adder of
+                // the significands extended with guard bits
+                let m = param(params, "MANT_WIDTH") + 4;
+                Resources {
+                    lut: 2 * m * log2(m).div_ceil(2).max(1)
+                        + 2 * m
+                        + 2 * param(params, "EXP_WIDTH"),
+                    ff: w + 1,
+                    ..Default::default()
+                }
+            }
+            "std_float_mult" => {
+                Self::multiplier(param(params, "MANT_WIDTH") + 1)
+                    + Resources {
+                        lut: 2 * w,
+                        ff: w + 1,
+                        ..Default::default()
+                    }
+            }
+            // Array dividers with a subtractor for every bit of the quotient
+            // or root
+            "std_float_div" => {
+                let m = param(params, "MANT_WIDTH");
+                Resources {
+                    lut: (3 * m + 5) * (m + 2) + 2 * w,
+                    ff: w + 1,
+                    ..Default::default()
+                }
+            }
+            "std_float_sqrt" => {
+                let half = (3 * param(params, "MANT_WIDTH") + 9) / 2;
+                Resources {
+                    lut: half * (half + 2) + 2 * w,
+                    ff: w + 1,
+                    ..Default::default()
+                }
+            }
+            // Comparisons also detect NaNs
+            "std_float_lt" | "std_float_le" | "std_float_gt"
+            | "std_float_ge" => {
+                self.comparison(&ir::PortComp::Lt, w)
+                    + Self::float_nan_checks(params)
+            }
+            "std_float_eq" | "std_float_neq" => {
+                self.comparison(&ir::PortComp::Eq, w)
+                    + Self::float_nan_checks(params)
+            }
+            // Conversions shift the significand and saturate or round it
+            "std_float_to_int" | "std_float_to_sint" => {
+                let out = param(params, "OUT_WIDTH");
+                Resources::luts(out * log2(out).div_ceil(2).max(1) + out)
+            }
+            "std_int_to_float" | "std_sint_to_float" => {
+                let int = param(params, "IN_WIDTH");
+                Resources::luts(
+                    int * log2(int).div_ceil(2).max(1)
+                        + int
+                        + param(params, "MANT_WIDTH"),
+                )
+            }
+            // Combinational reads require LUT RAMs
+            "comb_mem_d1" | "comb_mem_d2" | "comb_mem_d3" | "comb_mem_d4" => {
+                Resources {
+                    lut: lutram(w, mem_size(params)),
+                    ff: 1,
+                    ..Default::default()
+                }
+            }
+            "seq_mem_d1" | "seq_mem_d2" | "seq_mem_d3" | "seq_mem_d4"
+            | "dyn_mem_d1" | "dyn_mem_d2" | "dyn_mem_d3" | "dyn_mem_d4" => {
+                Self::sequential_mem(w, mem_size(params))
+            }
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    fn mux(&self, width: u64, inputs: u64) -> Resources {
+        // Every output bit is an OR of the guarded inputs
+        Resources::luts(width * lut_tree(2 * inputs).max(1))
+    }
+
+    fn logic(&self, inputs: u64) -> Resources {
+        Resources::luts(lut_tree(inputs))
+    }
+
+    fn comparison(&self, op: &ir::PortComp, width: u64) -> Resources {
+        match op {
+            // Each LUT compares three pairs of bits
+            ir::PortComp::Eq | ir::PortComp::Neq => {
+                let luts = width.div_ceil(3);
+                Resources::luts(luts + lut_tree(luts))
+            }
+            // Magnitude comparisons use the carry chain
+            _ => Resources::luts(width.div_ceil(2)),
+        }
+    }
+}
+
+/// An entry in a [TableCostModel].
+#[derive(Deserialize)]
+struct CostEntry {
+    primitive: String,
+    /// Only use the entry for instances with these parameter values.
+    #[serde(default)]
+    params: HashMap<String, u64>,
+    #[serde(flatten)]
+    resources: Resources,
+}
+
+/// A cost model that uses user-provided costs for primitives and falls back
+/// to the [DefaultCostModel].
+///
+/// The costs are read from a JSON file that contains a list of entries:
+/// ```json
+/// [
+///   { "primitive": "std_mult_pipe", "params": { "WIDTH": 32 }, "dsp": 4 },
+///   { "primitive": "my_ip", "lut": 120, "ff": 64 }
+/// ]
+/// ```
+/// The first entry whose primitive name matches and whose `params` are a
+/// subset of the instance's parameters provides the cost.
+pub struct TableCostModel {
+    entries: Vec<CostEntry>,
+    fallback: DefaultCostModel,
+}
+
+impl TableCostModel {
+    pub fn from_file(path: &Path) -> CalyxResult<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let entries = serde_json::from_str(&contents).map_err(|err| {
+            Error::misc(format!(
+                "Failed to read cost model `{}`: {err}",
+                path.display()
+            ))
+        })?;
+        Ok(Self {
+            entries,
+            fallback: DefaultCostModel,
+        })
+    }
+}
+
+impl CostModel for TableCostModel {
+    fn primitive(&self, name: &str, params: &ir::Binding) -> Option<Resources> {
+        self.entries
+            .iter()
+            .find(|entry| {
+                entry.primitive == name
+                    && entry.params.iter().all(|(p, v)| {
+                        params.iter().any(|(id, val)| id == p && val == v)
+                    })
+            })
+            .map(|entry| entry.resources)
+            .or_else(|| self.fallback.primitive(name, params))
+    }
+
+    fn mux(&self, width: u64, inputs: u64) -> Resources {
+        self.fallback.mux(width, inputs)
+    }
+
+    fn logic(&self, inputs: u64) -> Resources {
+        self.fallback.logic(inputs)
+    }
+
+    fn comparison(&self, op: &ir::PortComp, width: u64) -> Resources {
+        self.fallback.comparison(op, width)
+    }
+}
@@ -0,0 +1,488 @@
+//! Resource estimation backend for the Calyx compiler.
+//! Transforms an [`ir::Context`](crate::ir::Context) into a report that
+//! estimates the LUTs, flip-flops, DSPs and block RAMs used by every
+//! component and instance in the design.
+//!
+//! The estimates come from a [CostModel] which assigns costs to the
+//! primitives as well as the multiplexers and guard logic implied by the
+//! assignments. The estimates are most accurate for programs that have been
+//! compiled using `-p all`.
+//!
+//! Options:
+//! - `-x resources:format=csv|json`: The format of the report. Defaults to
+//!   `csv`.
+//! - `-x resources:cost-model=<FILE>`: Use the primitive costs defined in
+//!   `FILE` instead of the defaults. See [TableCostModel].
+mod cost;
+
+pub use cost::{CostModel, DefaultCostModel, Resources, TableCostModel};
+
+use crate::extra_opts::ExtraOpt;
+use crate::traits::Backend;
+use calyx_ir::{self as ir, GetAttributes};
+use calyx_utils::{CalyxResult, Error, OutputFile};
+use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Default)]
+pub struct ResourcesBackend;
+
+impl Backend for ResourcesBackend {
+    fn name(&self) -> &'static str {
+        "resources"
+    }
+
+    /// OK to run this analysis on any Calyx program
+    fn validate(_ctx: &ir::Context) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    /// Don't need to take care of this for this pass
+    fn link_externs(
+        _ctx: &ir::Context,
+        _file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        let conf = ResourcesConf::from_opts(&ctx.extra_opts)?;
+        let report = match conf.cost_model {
+            Some(path) => {
+                Report::estimate(ctx, &TableCostModel::from_file(&path)?)
+            }
+            None => Report::estimate(ctx, &DefaultCostModel),
+        };
+        match conf.format {
+            Format::Csv => report.write_csv(file)?,
+            Format::Json => {
+                let out = &mut file.get_write();
+                serde_json::to_writer_pretty(&mut *out, &report)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Format {
+    Csv,
+    Json,
+}
+
+/// Configuration for the resources backend.
+struct ResourcesConf {
+    format: Format,
+    cost_model: Option<PathBuf>,
+}
+
+impl ResourcesConf {
+    /// Read the configuration from the extra options passed to the compiler.
+    fn from_opts(opts: &[String]) -> CalyxResult<Self> {
+        let mut conf = Self {
+            format: Format::Csv,
+            cost_model: None,
+        };
+        for opt in ExtraOpt::parse("resources", opts) {
+            match (opt.name, opt.val) {
+                ("format", "csv") => conf.format = Format::Csv,
+                ("format", "json") => conf.format = Format::Json,
+                ("format", _) => return Err(opt.invalid("`csv' or `json'")),
+                ("cost-model", path) => {
+                    conf.cost_model = Some(PathBuf::from(path))
+                }
+                _ => opt.unknown(),
+            }
+        }
+        Ok(conf)
+    }
+}
+
+/// The kind of hardware described by an [Instance].
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceKind {
+    /// An instance of a component, including everything inside it.
+    Component,
+    /// An instance of a primitive.
+    Primitive,
+    /// The multiplexers and guards implied by the assignments in a component.
+    Logic,
+}
+
+impl InstanceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            InstanceKind::Component => "component",
+            InstanceKind::Primitive => "primitive",
+            InstanceKind::Logic => "logic",
+        }
+    }
+}
+
+/// Resources used by a single instance in the design hierarchy.
+#[derive(Serialize)]
+pub struct Instance {
+    /// Hierarchical name of the instance, starting at the entrypoint.
+    pub path: String,
+    /// The component that contains the instance.
+    pub component: String,
+    pub kind: InstanceKind,
+    /// Name of the instantiated primitive or component.
+    pub prototype: String,
+    pub params: LinkedHashMap<String, u64>,
+    pub external: bool,
+    /// False if the cost model does not know the primitive or the component
+    /// contains such a primitive.
+    pub estimated: bool,
+    pub resources: Resources,
+}
+
+/// Resources used by a single instance of a component.
+#[derive(Default, Clone, Copy, Serialize)]
+pub struct ComponentCost {
+    /// Primitives instantiated directly in the component.
+    pub primitives: Resources,
+    /// Multiplexers and guards implied by the assignments.
+    pub logic: Resources,
+    /// Everything in the component, including subcomponents.
+    pub total: Resources,
+    /// False if the component transitively contains a primitive that the
+    /// cost model does not know.
+    pub estimated: bool,
+}
+
+/// Resource estimates for a program.
+#[derive(Serialize)]
+pub struct Report {
+    pub entrypoint: String,
+    pub total: Resources,
+    /// Cost of each component used by the design.
+    pub components: LinkedHashMap<String, ComponentCost>,
+    /// Every instance reachable from the entrypoint.
+    pub instances: Vec<Instance>,
+}
+
+impl Report {
+    /// Estimate the resources used by the program using the cost model.
+    pub fn estimate(ctx: &ir::Context, model: &dyn CostModel) -> Self {
+        let comps: HashMap<ir::Id, &ir::Component> =
+            ctx.components.iter().map(|c| (c.name, c)).collect();
+
+        let mut components = LinkedHashMap::new();
+        let mut unknown = HashSet::new();
+        for comp in &ctx.components {
+            component_cost(comp, &comps, model, &mut components, &mut unknown);
+        }
+
+        let mut report = Report {
+            entrypoint: ctx.entrypoint.to_string(),
+            total: components[&ctx.entrypoint.to_string()].total,
+            components: LinkedHashMap::new(),
+            instances: vec![],
+        };
+        report.instances.push(Instance {
+            path: ctx.entrypoint.to_string(),
+            component: ctx.entrypoint.to_string(),
+            kind: InstanceKind::Component,
+            prototype: ctx.entrypoint.to_string(),
+            params: LinkedHashMap::new(),
+            external: false,
+            estimated: components[&ctx.entrypoint.to_string()].estimated,
+            resources: report.total,
+        });
+        report.add_instances(
+            comps[&ctx.entrypoint],
+            ctx.entrypoint.as_ref(),
+            &comps,
+            &components,
+            model,
+        );
+        // Only report the components used by the design
+        let used: HashSet<&str> = report
+            .instances
+            .iter()
+            .map(|i| i.component.as_str())
+            .collect();
+        report.components = components
+            .iter()
+            .filter(|(name, _)| used.contains(name.as_str()))
+            .map(|(name, cost)| (name.clone(), *cost))
+            .collect();
+        report
+    }
+
+    /// Add the instances inside `comp` which is instantiated at `path`.
+    fn add_instances(
+        &mut self,
+        comp: &ir::Component,
+        path: &str,
+        comps: &HashMap<ir::Id, &ir::Component>,
+        costs: &LinkedHashMap<String, ComponentCost>,
+        model: &dyn CostModel,
+    ) {
+        for cell in comp.cells.iter() {
+            let cell = cell.borrow();
+            if cell.is_reference() {
+                continue;
+            }
+            let cell_path = format!("{path}.{}", cell.name());
+            let external = cell.get_attributes().has(ir::BoolAttr::External);
+            match &cell.prototype {
+                ir::CellType::Primitive {
+                    name,
+                    param_binding,
+                    ..
+                } => {
+                    let res = model.primitive(name.as_ref(), param_binding);
+                    self.instances.push(Instance {
+                        path: cell_path,
+                        component: comp.name.to_string(),
+                        kind: InstanceKind::Primitive,
+                        prototype: name.to_string(),
+                        params: param_binding
+                            .iter()
+                            .map(|(p, v)| (p.to_string(), *v))
+                            .collect(),
+                        external,
+                        estimated: res.is_some(),
+                        resources: res.unwrap_or_default(),
+                    })
+                }
+                ir::CellType::Component { name } => {
+                    self.instances.push(Instance {
+                        path: cell_path.clone(),
+                        component: comp.name.to_string(),
+                        kind: InstanceKind::Component,
+                        prototype: name.to_string(),
+                        params: LinkedHashMap::new(),
+                        external,
+                        estimated: costs[&name.to_string()].estimated,
+                        resources: costs[&name.to_string()].total,
+                    });
+                    self.add_instances(
+                        comps[name],
+                        &cell_path,
+                        comps,
+                        costs,
+                        model,
+                    );
+                }
+                ir::CellType::ThisComponent | ir::CellType::Constant { .. } => {
+                }
+            }
+        }
+        self.instances.push(Instance {
+            path: path.to_string(),
+            component: comp.name.to_string(),
+            kind: InstanceKind::Logic,
+            prototype: comp.name.to_string(),
+            params: LinkedHashMap::new(),
+            external: false,
+            estimated: true,
+            resources: costs[&comp.name.to_string()].logic,
+        });
+    }
+
+    /// Write the instances in the report as CSV.
+    fn write_csv(&self, file: &mut OutputFile) -> CalyxResult<()> {
+        let mut wtr = csv::Writer::from_writer(file.get_write());
+        let to_err = |err: csv::Error| Error::write_error(err.to_string());
+        wtr.write_record([
+            "Path",
+            "Component",
+            "Kind",
+            "Prototype",
+            "Parameters",
+            "External?",
+            "Estimated?",
+            "LUT",
+            "FF",
+            "DSP",
+            "BRAM",
+        ])
+        .map_err(to_err)?;
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        for inst in &self.instances {
+            let params = inst
+                .params
+                .iter()
+                .map(|(p, v)| format!("{p}={v}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let res = &inst.resources;
+            wtr.write_record([
+                inst.path.as_str(),
+                inst.component.as_str(),
+                inst.kind.as_str(),
+                inst.prototype.as_str(),
+                params.as_str(),
+                yes_no(inst.external),
+                yes_no(inst.estimated),
+                &res.lut.to_string(),
+                &res.ff.to_string(),
+                &res.dsp.to_string(),
+                &res.bram.to_string(),
+            ])
+            .map_err(to_err)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Compute the cost of `comp` and the components it instantiates, memoizing
+/// the results in `costs`.
+fn component_cost(
+    comp: &ir::Component,
+    comps: &HashMap<ir::Id, &ir::Component>,
+    model: &dyn CostModel,
+    costs: &mut LinkedHashMap<String, ComponentCost>,
+    unknown: &mut HashSet<ir::Id>,
+) -> ComponentCost {
+    if let Some(cost) = costs.get(comp.name.as_ref()) {
+        return *cost;
+    }
+    let mut cost = ComponentCost {
+        logic: logic_cost(comp, model),
+        estimated: true,
+        ..Default::default()
+    };
+    for cell in comp.cells.iter() {
+        let cell = cell.borrow();
+        if cell.is_reference() {
+            continue;
+        }
+        match &cell.prototype {
+            ir::CellType::Primitive {
+                name,
+                param_binding,
+                ..
+            } => {
+                let res = model.primitive(name.as_ref(), param_binding);
+                if res.is_none() && unknown.insert(*name) {
+                    log::warn!("No resource estimate for primitive `{name}'");
+                }
+                cost.estimated &= res.is_some();
+                cost.primitives += res.unwrap_or_default();
+            }
+            ir::CellType::Component { name } => {
+                let sub =
+                    component_cost(comps[name], comps, model, costs, unknown);
+                cost.estimated &= sub.estimated;
+                cost.total += sub.total;
+            }
+            ir::CellType::ThisComponent | ir::CellType::Constant { .. } => (),
+        }
+    }
+    cost.total += cost.primitives + cost.logic;
+    costs.insert(comp.name.to_string(), cost);
+    cost
+}
+
+/// The assignments that drive a port.
+struct Drivers {
+    width: u64,
+    count: u64,
+    guarded: bool,
+}
+
+/// Estimate the multiplexers and guards needed to implement the assignments
+/// in the component.
+fn logic_cost(comp: &ir::Component, model: &dyn CostModel) -> Resources {
+    let mut drivers = LinkedHashMap::new();
+    // Guards used by multiple assignments are only computed once
+    let mut guards = LinkedHashMap::new();
+
+    for assign in &comp.continuous_assignments {
+        visit(assign, model, &mut drivers, &mut guards);
+    }
+    for group in comp.get_groups().iter() {
+        for assign in &group.borrow().assignments {
+            visit(assign, model, &mut drivers, &mut guards);
+        }
+    }
+    for group in comp.comb_groups.iter() {
+        for assign in &group.borrow().assignments {
+            visit(assign, model, &mut drivers, &mut guards);
+        }
+    }
+    for group in comp.get_static_groups().iter() {
+        for assign in &group.borrow().assignments {
+            visit(assign, model, &mut drivers, &mut guards);
+        }
+    }
+
+    let mut cost = Resources::default();
+    for d in drivers.values() {
+        // A single unguarded assignment is just a wire
+        if d.count > 1 || d.guarded {
+            cost += model.mux(d.width, d.count);
+        }
+    }
+    for guard in guards.values() {
+        cost += *guard;
+    }
+    cost
+}
+
+fn visit<T: ToString + Clone + Eq>(
+    assign: &ir::Assignment<T>,
+    model: &dyn CostModel,
+    drivers: &mut LinkedHashMap<ir::Canonical, Drivers>,
+    guards: &mut LinkedHashMap<String, Resources>,
+) {
+    let dst = assign.dst.borrow();
+    // Holes are removed by compilation
+    if dst.is_hole() {
+        return;
+    }
+    let d = drivers.entry(dst.canonical()).or_insert(Drivers {
+        width: dst.width,
+        count: 0,
+        guarded: false,
+    });
+    d.count += 1;
+    d.guarded |= !assign.guard.is_true();
+    if let Some(cost) = guard_cost(&assign.guard, model) {
+        guards
+            .entry(ir::Printer::guard_str(&assign.guard))
+            .or_insert(cost);
+    }
+}
+
+/// Resources used to compute a guard. Returns `None` for guards that do not
+/// need any logic.
+fn guard_cost<T>(
+    guard: &ir::Guard<T>,
+    model: &dyn CostModel,
+) -> Option<Resources> {
+    /// Returns the number of 1-bit inputs to the boolean function computed by
+    /// the guard and adds the cost of comparisons to `cost`.
+    fn inputs<T>(
+        guard: &ir::Guard<T>,
+        model: &dyn CostModel,
+        cost: &mut Resources,
+    ) -> u64 {
+        match guard {
+            ir::Guard::True => 0,
+            ir::Guard::Or(l, r) | ir::Guard::And(l, r) => {
+                inputs(l, model, cost) + inputs(r, model, cost)
+            }
+            ir::Guard::Not(g) => inputs(g, model, cost),
+            ir::Guard::Port(_) | ir::Guard::Info(_) => 1,
+            ir::Guard::CompOp(op, l, _) => {
+                *cost += model.comparison(op, l.borrow().width);
+                1
+            }
+        }
+    }
+    if matches!(guard, ir::Guard::True | ir::Guard::Port(_)) {
+        return None;
+    }
+    let mut cost = Resources::default();
+    let n = inputs(guard, model, &mut cost);
+    Some(cost + model.logic(n))
+}
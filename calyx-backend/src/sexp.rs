@@ -1,5 +1,6 @@
 //! Pretty-printer for Calyx syntax.
-//! Outputs s-expressions.
+//! Outputs s-expressions using the versioned interchange schema. The output
+//! can be read back by the compiler by passing a `.sexp` file as the input.
 
 use crate::traits::Backend;
 use calyx_ir as ir;
@@ -28,7 +29,11 @@ impl Backend for SexpBackend {
 
     fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
         let out = &mut file.get_write();
-        writeln!(out, "{}", serde_sexpr::to_string(ctx).unwrap())?;
+        writeln!(
+            out,
+            "{}",
+            serde_sexpr::to_string(&ir::interchange::export(ctx)).unwrap()
+        )?;
 
         Ok(())
     }
@@ -0,0 +1,724 @@
+//! Static timing analysis backend for the Calyx compiler.
+//! Estimates the longest register-to-register paths through the
+//! combinational logic in every component.
+//!
+//! The analysis builds a graph of the ports in a component. Assignments add
+//! edges from their source and guard ports to their destination, and
+//! combinational primitives add edges from their inputs to their outputs. The
+//! [DelayModel] assigns a delay to every edge. Paths start at the outputs of
+//! sequential cells and the inputs of the component, and end at the inputs of
+//! sequential cells and the outputs of the component. Paths through instances
+//! of other components use a summary of the paths inside the component. The
+//! estimates are most accurate for programs that have been compiled using
+//! `-p all`.
+//!
+//! Options:
+//! - `-x timing:format=text|json`: The format of the report. Defaults to
+//!   `text`.
+//! - `-x timing:paths=<N>`: Number of paths reported for every component.
+//!   Defaults to 5.
+use crate::extra_opts::ExtraOpt;
+use crate::traits::Backend;
+use calyx_ir::{self as ir, DefaultDelayModel, DelayModel, GetAttributes, RRC};
+use calyx_utils::{CalyxResult, GPosIdx, OutputFile, WithPos};
+use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+#[derive(Default)]
+pub struct TimingBackend;
+
+impl Backend for TimingBackend {
+    fn name(&self) -> &'static str {
+        "timing"
+    }
+
+    /// OK to run this analysis on any Calyx program
+    fn validate(_ctx: &ir::Context) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    /// Don't need to take care of this for this pass
+    fn link_externs(
+        _ctx: &ir::Context,
+        _file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        let conf = TimingConf::from_opts(&ctx.extra_opts)?;
+        let report = Report::analyze(ctx, &DefaultDelayModel, conf.paths);
+        let out = &mut file.get_write();
+        match conf.format {
+            Format::Text => report.write_text(out)?,
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut *out, &report)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Format {
+    Text,
+    Json,
+}
+
+/// Configuration for the timing backend.
+struct TimingConf {
+    format: Format,
+    paths: usize,
+}
+
+impl TimingConf {
+    /// Read the configuration from the extra options passed to the compiler.
+    fn from_opts(opts: &[String]) -> CalyxResult<Self> {
+        let mut conf = Self {
+            format: Format::Text,
+            paths: 5,
+        };
+        for opt in ExtraOpt::parse("timing", opts) {
+            match (opt.name, opt.val) {
+                ("format", "text") => conf.format = Format::Text,
+                ("format", "json") => conf.format = Format::Json,
+                ("format", _) => return Err(opt.invalid("`text' or `json'")),
+                ("paths", _) => conf.paths = opt.num()?,
+                _ => opt.unknown(),
+            }
+        }
+        Ok(conf)
+    }
+}
+
+/// How a timing path reaches a port.
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Through {
+    /// The path starts at an input of the component.
+    Input,
+    /// The path starts at a register inside the cell.
+    Register { cell: String, prototype: String },
+    /// The source of an assignment.
+    Assignment,
+    /// The guard of an assignment.
+    Guard,
+    /// The combinational logic inside the cell.
+    Cell { cell: String, prototype: String },
+}
+
+impl std::fmt::Display for Through {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Through::Input => write!(f, "input"),
+            Through::Register { cell, prototype } => {
+                write!(f, "register in `{cell}' ({prototype})")
+            }
+            Through::Assignment => write!(f, "assignment"),
+            Through::Guard => write!(f, "guard"),
+            Through::Cell { cell, prototype } => {
+                write!(f, "cell `{cell}' ({prototype})")
+            }
+        }
+    }
+}
+
+/// A step in a timing path.
+#[derive(Serialize)]
+pub struct Hop {
+    /// The port reached by this step.
+    pub port: String,
+    /// Time at which the signal arrives at the port.
+    pub arrival: f64,
+    /// Delay added by this step.
+    pub delay: f64,
+    pub through: Through,
+    /// Source location of the assignment or cell responsible for the step.
+    pub pos: Option<String>,
+}
+
+/// A register-to-register path through the combinational logic.
+#[derive(Serialize)]
+pub struct TimingPath {
+    pub delay: f64,
+    pub hops: Vec<Hop>,
+}
+
+/// The longest paths in a component.
+#[derive(Serialize)]
+pub struct ComponentTiming {
+    pub name: String,
+    pub paths: Vec<TimingPath>,
+}
+
+/// Timing estimates for a program.
+#[derive(Serialize)]
+pub struct Report {
+    /// Delay of the longest path in the program.
+    pub critical_path: f64,
+    pub components: Vec<ComponentTiming>,
+}
+
+impl Report {
+    /// Report the `paths` longest paths in every component of the program.
+    pub fn analyze(
+        ctx: &ir::Context,
+        model: &dyn DelayModel,
+        paths: usize,
+    ) -> Self {
+        let comps: HashMap<ir::Id, &ir::Component> =
+            ctx.components.iter().map(|c| (c.name, c)).collect();
+        let mut summaries = HashMap::new();
+        let mut unknown = HashSet::new();
+
+        let mut components = vec![];
+        for comp in &ctx.components {
+            let graph =
+                Graph::new(comp, &comps, model, &mut summaries, &mut unknown);
+            let arrivals = graph.arrivals(|_| true);
+            for node in &arrivals.cycles {
+                log::warn!(
+                    "Combinational cycle through `{}.{node}'; the estimates may be inaccurate",
+                    comp.name
+                );
+            }
+            let mut ends = graph
+                .ends
+                .iter()
+                .filter_map(|(node, extra)| {
+                    let arrival = arrivals.time(node)?;
+                    let extra = extra.as_ref().map_or(0.0, |e| e.delay);
+                    Some((node, arrival + extra))
+                })
+                .collect::<Vec<_>>();
+            ends.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+            components.push(ComponentTiming {
+                name: comp.name.to_string(),
+                paths: ends
+                    .into_iter()
+                    .take(paths)
+                    .map(|(node, _)| graph.path(node, &arrivals))
+                    .collect(),
+            });
+        }
+
+        let critical_path = components
+            .iter()
+            .flat_map(|c| c.paths.first())
+            .map(|p| p.delay)
+            .fold(0.0, f64::max);
+        Report {
+            critical_path,
+            components,
+        }
+    }
+
+    fn write_text(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let critical = self.components.iter().find(|c| {
+            c.paths.first().map(|p| p.delay) == Some(self.critical_path)
+        });
+        match critical {
+            Some(comp) => writeln!(
+                out,
+                "Critical path: {:.3} ns in component `{}'",
+                self.critical_path, comp.name
+            )?,
+            None => writeln!(out, "No timing paths")?,
+        }
+        for comp in &self.components {
+            writeln!(out)?;
+            writeln!(out, "Component `{}'", comp.name)?;
+            for path in &comp.paths {
+                let start = &path.hops[0].port;
+                let end = &path.hops[path.hops.len() - 1].port;
+                writeln!(
+                    out,
+                    "  {:.3} ns from `{start}' to `{end}'",
+                    path.delay
+                )?;
+                writeln!(
+                    out,
+                    "    {:>8}  {:>8}  {:<24}  Through",
+                    "Arrival", "Delay", "Port"
+                )?;
+                for hop in &path.hops {
+                    write!(
+                        out,
+                        "    {:>8.3}  {:>8.3}  {:<24}  {}",
+                        hop.arrival, hop.delay, hop.port, hop.through
+                    )?;
+                    if let Some(pos) = &hop.pos {
+                        write!(out, " at {pos}")?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Round times to picoseconds to keep the reports readable.
+fn round(time: f64) -> f64 {
+    (time * 1000.0).round() / 1000.0
+}
+
+/// Formats a position as `file:line`.
+fn location(pos: GPosIdx) -> Option<String> {
+    pos.into_option().map(|pos| {
+        let (file, (line, _)) = pos.get_line_num();
+        format!("{file}:{line}")
+    })
+}
+
+/// A step from one port to another.
+struct Edge {
+    /// Source of the edge. `None` for the start and end of paths through
+    /// instances.
+    from: Option<ir::Canonical>,
+    delay: f64,
+    through: Through,
+    pos: GPosIdx,
+}
+
+/// Combinational paths through a component.
+#[derive(Default)]
+struct Summary {
+    /// Delay from an input to an output.
+    in_to_out: LinkedHashMap<(ir::Id, ir::Id), f64>,
+    /// Delay from an input to a register inside the component.
+    in_to_reg: LinkedHashMap<ir::Id, f64>,
+    /// Delay from a register inside the component to an output.
+    reg_to_out: LinkedHashMap<ir::Id, f64>,
+}
+
+/// The graph of ports in a component.
+struct Graph {
+    /// The incoming edges of every port.
+    edges: LinkedHashMap<ir::Canonical, Vec<Edge>>,
+    /// Ports where paths start and how paths reach them.
+    starts: LinkedHashMap<ir::Canonical, Edge>,
+    /// Ports where paths end and the delay after reaching them.
+    ends: LinkedHashMap<ir::Canonical, Option<Edge>>,
+    /// Name of the signature cell.
+    this: ir::Id,
+}
+
+/// The latest arrival time at every port.
+struct Arrivals {
+    /// The arrival time and the index of the incoming edge that arrives last.
+    /// `None` for the index means that the port is a start point.
+    times: HashMap<ir::Canonical, Option<(f64, Option<usize>)>>,
+    /// Ports at which a combinational cycle was broken.
+    cycles: Vec<ir::Canonical>,
+}
+
+impl Arrivals {
+    fn time(&self, node: &ir::Canonical) -> Option<f64> {
+        self.times.get(node).copied().flatten().map(|(t, _)| t)
+    }
+}
+
+/// The assignments that drive a port.
+#[derive(Default)]
+struct Drivers {
+    count: u64,
+    guarded: bool,
+}
+
+impl Graph {
+    fn new(
+        comp: &ir::Component,
+        comps: &HashMap<ir::Id, &ir::Component>,
+        model: &dyn DelayModel,
+        summaries: &mut HashMap<ir::Id, Summary>,
+        unknown: &mut HashSet<ir::Id>,
+    ) -> Self {
+        let this = comp.signature.borrow().name();
+        let mut graph = Graph {
+            edges: LinkedHashMap::new(),
+            starts: LinkedHashMap::new(),
+            ends: LinkedHashMap::new(),
+            this,
+        };
+
+        // The signature ports are flipped: inputs of the component are
+        // outputs of the signature cell.
+        for port in comp.signature.borrow().ports() {
+            let port = port.borrow();
+            match port.direction {
+                ir::Direction::Output => {
+                    graph.starts.insert(
+                        port.canonical(),
+                        Edge {
+                            from: None,
+                            delay: 0.0,
+                            through: Through::Input,
+                            pos: GPosIdx::UNKNOWN,
+                        },
+                    );
+                }
+                ir::Direction::Input => {
+                    graph.ends.insert(port.canonical(), None);
+                }
+                ir::Direction::Inout => (),
+            }
+        }
+
+        for cell in comp.cells.iter() {
+            graph.add_cell(&cell.borrow(), comps, model, summaries, unknown);
+        }
+
+        let mut drivers: HashMap<ir::Canonical, Drivers> = HashMap::new();
+        comp.iter_assignments(|assign| {
+            count_driver(assign, &mut drivers);
+        });
+        comp.iter_static_assignments(|assign| {
+            count_driver(assign, &mut drivers);
+        });
+        comp.iter_assignments(|assign| {
+            graph.add_assignment(assign, &drivers, model);
+        });
+        comp.iter_static_assignments(|assign| {
+            graph.add_assignment(assign, &drivers, model);
+        });
+        graph
+    }
+
+    fn add_edge(&mut self, to: ir::Canonical, edge: Edge) {
+        self.edges.entry(to).or_default().push(edge);
+    }
+
+    fn add_cell(
+        &mut self,
+        cell: &ir::Cell,
+        comps: &HashMap<ir::Id, &ir::Component>,
+        model: &dyn DelayModel,
+        summaries: &mut HashMap<ir::Id, Summary>,
+        unknown: &mut HashSet<ir::Id>,
+    ) {
+        let (inputs, outputs): (Vec<_>, Vec<_>) =
+            cell.ports().iter().map(|p| p.borrow().name).partition(|p| {
+                cell.get(p).borrow().direction == ir::Direction::Input
+            });
+        let pos = cell.get_attributes().copy_span();
+        let via =
+            |delay: f64, from: Option<ir::Canonical>, proto: ir::Id| Edge {
+                from,
+                delay,
+                through: Through::Cell {
+                    cell: cell.name().to_string(),
+                    prototype: proto.to_string(),
+                },
+                pos,
+            };
+        let port = |p: ir::Id| ir::Canonical::new(cell.name(), p);
+        match &cell.prototype {
+            ir::CellType::Primitive {
+                name,
+                param_binding,
+                is_comb,
+                ..
+            } => {
+                let mut known = false;
+                for o in &outputs {
+                    let mut combinational = false;
+                    for i in &inputs {
+                        if let Some(delay) = model.primitive(
+                            name.as_ref(),
+                            param_binding,
+                            *i,
+                            *o,
+                        ) {
+                            self.add_edge(
+                                port(*o),
+                                via(delay, Some(port(*i)), *name),
+                            );
+                            combinational = true;
+                        }
+                    }
+                    known |= combinational;
+                    if !combinational && !is_comb {
+                        self.starts.insert(
+                            port(*o),
+                            Edge {
+                                from: None,
+                                delay: 0.0,
+                                through: Through::Register {
+                                    cell: cell.name().to_string(),
+                                    prototype: name.to_string(),
+                                },
+                                pos,
+                            },
+                        );
+                    }
+                }
+                if *is_comb {
+                    if !known && !inputs.is_empty() && unknown.insert(*name) {
+                        log::warn!(
+                            "No delay estimate for combinational primitive `{name}'"
+                        );
+                    }
+                } else {
+                    for i in inputs {
+                        self.ends.insert(port(i), None);
+                    }
+                }
+            }
+            ir::CellType::Component { name } => {
+                if !summaries.contains_key(name) {
+                    let graph = Graph::new(
+                        comps[name],
+                        comps,
+                        model,
+                        summaries,
+                        unknown,
+                    );
+                    let summary = graph.summarize();
+                    summaries.insert(*name, summary);
+                }
+                let summary = &summaries[name];
+                for ((i, o), delay) in &summary.in_to_out {
+                    self.add_edge(port(*o), via(*delay, Some(port(*i)), *name));
+                }
+                for (o, delay) in &summary.reg_to_out {
+                    self.starts.insert(
+                        port(*o),
+                        Edge {
+                            from: None,
+                            delay: *delay,
+                            through: Through::Register {
+                                cell: cell.name().to_string(),
+                                prototype: name.to_string(),
+                            },
+                            pos,
+                        },
+                    );
+                }
+                for (i, delay) in &summary.in_to_reg {
+                    self.ends.insert(port(*i), Some(via(*delay, None, *name)));
+                }
+            }
+            ir::CellType::ThisComponent | ir::CellType::Constant { .. } => (),
+        }
+    }
+
+    fn add_assignment<T>(
+        &mut self,
+        assign: &ir::Assignment<T>,
+        drivers: &HashMap<ir::Canonical, Drivers>,
+        model: &dyn DelayModel,
+    ) {
+        let dst = assign.dst.borrow().canonical();
+        let d = &drivers[&dst];
+        // A single unguarded assignment is just a wire
+        let mux = if d.count > 1 || d.guarded {
+            model.mux(d.count)
+        } else {
+            0.0
+        };
+        let pos = assign.attributes.copy_span();
+        self.add_edge(
+            dst.clone(),
+            Edge {
+                from: Some(assign.src.borrow().canonical()),
+                delay: mux,
+                through: Through::Assignment,
+                pos,
+            },
+        );
+
+        let mut ports = vec![];
+        let inputs = guard_ports(&assign.guard, model, &mut ports);
+        let logic = model.logic(inputs);
+        for (port, delay) in ports {
+            self.add_edge(
+                dst.clone(),
+                Edge {
+                    from: Some(port),
+                    delay: delay + logic + mux,
+                    through: Through::Guard,
+                    pos,
+                },
+            );
+        }
+    }
+
+    /// Compute the arrival times at every port for paths that start at the
+    /// start points selected by `include`.
+    fn arrivals(&self, include: impl Fn(&ir::Canonical) -> bool) -> Arrivals {
+        let mut arrivals = Arrivals {
+            times: HashMap::new(),
+            cycles: vec![],
+        };
+        let mut visiting = HashSet::new();
+        let nodes = self
+            .edges
+            .keys()
+            .chain(self.ends.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        for node in nodes {
+            self.arrival(&node, &include, &mut arrivals, &mut visiting);
+        }
+        arrivals
+    }
+
+    fn arrival(
+        &self,
+        node: &ir::Canonical,
+        include: &impl Fn(&ir::Canonical) -> bool,
+        arrivals: &mut Arrivals,
+        visiting: &mut HashSet<ir::Canonical>,
+    ) -> Option<f64> {
+        if let Some(time) = arrivals.times.get(node) {
+            return time.map(|(t, _)| t);
+        }
+        if !visiting.insert(node.clone()) {
+            arrivals.cycles.push(node.clone());
+            return None;
+        }
+        let mut best = self
+            .starts
+            .get(node)
+            .filter(|_| include(node))
+            .map(|start| (start.delay, None));
+        for (idx, edge) in
+            self.edges.get(node).into_iter().flatten().enumerate()
+        {
+            let Some(from) = &edge.from else { continue };
+            if let Some(time) = self.arrival(from, include, arrivals, visiting)
+            {
+                let time = time + edge.delay;
+                match best {
+                    Some((t, _)) if t >= time => (),
+                    _ => best = Some((time, Some(idx))),
+                }
+            }
+        }
+        visiting.remove(node);
+        arrivals.times.insert(node.clone(), best);
+        best.map(|(t, _)| t)
+    }
+
+    /// Name of the port as it is written in the component.
+    fn port_name(&self, node: &ir::Canonical) -> String {
+        if node.cell == self.this {
+            node.port.to_string()
+        } else {
+            node.to_string()
+        }
+    }
+
+    /// The longest path ending at `end`.
+    fn path(&self, end: &ir::Canonical, arrivals: &Arrivals) -> TimingPath {
+        let mut hops = vec![];
+        let mut node = end.clone();
+        while let Some((time, edge)) = arrivals.times[&node] {
+            let edge = match edge {
+                Some(idx) => &self.edges[&node][idx],
+                None => &self.starts[&node],
+            };
+            hops.push(Hop {
+                port: self.port_name(&node),
+                arrival: round(time),
+                delay: round(edge.delay),
+                through: edge.through.clone(),
+                pos: location(edge.pos),
+            });
+            match &edge.from {
+                Some(from) if arrivals.times.contains_key(from) => {
+                    node = from.clone()
+                }
+                _ => break,
+            }
+        }
+        hops.reverse();
+        let mut delay = hops.last().map_or(0.0, |h| h.arrival);
+        if let Some(Some(edge)) = self.ends.get(end) {
+            delay += edge.delay;
+            hops.push(Hop {
+                port: end.cell.to_string(),
+                arrival: round(delay),
+                delay: round(edge.delay),
+                through: edge.through.clone(),
+                pos: location(edge.pos),
+            });
+        }
+        TimingPath {
+            delay: round(delay),
+            hops,
+        }
+    }
+
+    /// Summarize the paths through the component for its instances.
+    fn summarize(&self) -> Summary {
+        let mut summary = Summary::default();
+        let is_output = |node: &ir::Canonical| node.cell == self.this;
+
+        let internal = self.arrivals(|node| node.cell != self.this);
+        for end in self.ends.keys().filter(|n| is_output(n)) {
+            if let Some(time) = internal.time(end) {
+                summary.reg_to_out.insert(end.port, time);
+            }
+        }
+
+        for input in self.starts.keys().filter(|n| n.cell == self.this) {
+            let arrivals = self.arrivals(|node| node == input);
+            for (end, extra) in &self.ends {
+                let Some(time) = arrivals.time(end) else {
+                    continue;
+                };
+                if is_output(end) {
+                    summary.in_to_out.insert((input.port, end.port), time);
+                } else {
+                    let time = time + extra.as_ref().map_or(0.0, |e| e.delay);
+                    let max = summary.in_to_reg.entry(input.port).or_default();
+                    *max = max.max(time);
+                }
+            }
+        }
+        summary
+    }
+}
+
+fn count_driver<T>(
+    assign: &ir::Assignment<T>,
+    drivers: &mut HashMap<ir::Canonical, Drivers>,
+) {
+    let d = drivers.entry(assign.dst.borrow().canonical()).or_default();
+    d.count += 1;
+    d.guarded |= !assign.guard.is_true();
+}
+
+/// Collects the ports read by the guard along with the delay of the
+/// comparisons they are used in. Returns the number of 1-bit inputs to the
+/// boolean function computed by the guard.
+fn guard_ports<T>(
+    guard: &ir::Guard<T>,
+    model: &dyn DelayModel,
+    ports: &mut Vec<(ir::Canonical, f64)>,
+) -> u64 {
+    let mut add = |port: &RRC<ir::Port>, delay: f64| {
+        ports.push((port.borrow().canonical(), delay))
+    };
+    match guard {
+        ir::Guard::Or(l, r) | ir::Guard::And(l, r) => {
+            guard_ports(l, model, ports) + guard_ports(r, model, ports)
+        }
+        ir::Guard::Not(g) => guard_ports(g, model, ports),
+        ir::Guard::True | ir::Guard::Info(_) => 0,
+        ir::Guard::CompOp(op, l, r) => {
+            let delay = model.comparison(op, l.borrow().width);
+            add(l, delay);
+            add(r, delay);
+            1
+        }
+        ir::Guard::Port(p) => {
+            add(p, 0.0);
+            1
+        }
+    }
+}
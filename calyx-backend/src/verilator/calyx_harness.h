@@ -0,0 +1,779 @@
+// Runtime of the harnesses generated by the `verilator-harness` backend.
+// Emit it with `calyx -b verilator-harness -x verilator-harness:runtime`.
+//
+// A harness includes this file after including the header of the Verilated
+// model and naming it `Model`. The design-specific functions that are
+// declared below are defined by the harness after the include.
+
+#include <algorithm>
+#include <cctype>
+#include <cerrno>
+#include <cmath>
+#include <cstdint>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <fstream>
+#include <iostream>
+#include <memory>
+#include <sstream>
+#include <string>
+#include <utility>
+#include <vector>
+
+#include <unistd.h>
+
+#include "verilated.h"
+#if VM_TRACE
+#include "verilated_vcd_c.h"
+#endif
+
+// A scalar port of the toplevel component.
+struct Port {
+  const char *name;
+  unsigned width;
+};
+
+// An `@external` memory of the toplevel component.
+struct Memory {
+  const char *name;
+  unsigned width;
+  std::vector<uint64_t> dims;
+
+  uint64_t size() const {
+    uint64_t size = 1;
+    for (uint64_t dim : dims) {
+      size *= dim;
+    }
+    return size;
+  }
+};
+
+static const std::vector<Memory> &memories();
+static const std::vector<Port> &inputs();
+static const std::vector<Port> &outputs();
+static void set_input(Model &top, size_t idx, uint64_t value);
+static uint64_t get_output(Model &top, size_t idx);
+static void set_clk(Model &top, bool value);
+static void set_reset(Model &top, bool value);
+static void set_go(Model &top, bool value);
+// Returns true once the design is done. Static designs without a done signal
+// are done after `latency` cycles.
+static bool is_done(Model &top, uint64_t cycles);
+
+// Number of cycles for which the design is reset before it starts.
+static const int RESET_CYCLES = 3;
+
+[[noreturn]] static void fail(const std::string &msg) {
+  std::cerr << "error: " << msg << std::endl;
+  std::exit(1);
+}
+
+static uint64_t mask(unsigned width) {
+  return width >= 64 ? ~uint64_t(0) : (uint64_t(1) << width) - 1;
+}
+
+// Interpret the low `width` bits of `bits` as a two's complement number.
+static int64_t sign_extend(uint64_t bits, unsigned width) {
+  if (width < 64 && (bits >> (width - 1)) & 1) {
+    bits |= ~mask(width);
+  }
+  return static_cast<int64_t>(bits);
+}
+
+// ---------------------------------------------------------------------------
+// JSON
+// ---------------------------------------------------------------------------
+
+struct Json {
+  enum Kind { Null, Bool, Number, String, Array, Object };
+  Kind kind = Null;
+  // The token of numbers and booleans and the contents of strings.
+  std::string text;
+  std::vector<Json> items;
+  std::vector<std::pair<std::string, Json>> fields;
+
+  const Json *get(const std::string &key) const {
+    for (const auto &field : fields) {
+      if (field.first == key) {
+        return &field.second;
+      }
+    }
+    return nullptr;
+  }
+};
+
+class JsonParser {
+public:
+  JsonParser(const std::string &src, const std::string &path)
+      : src(src), path(path) {}
+
+  Json parse() {
+    Json value = parse_value();
+    skip_ws();
+    if (pos != src.size()) {
+      error("unexpected trailing characters");
+    }
+    return value;
+  }
+
+private:
+  const std::string &src;
+  const std::string &path;
+  size_t pos = 0;
+
+  [[noreturn]] void error(const std::string &msg) {
+    size_t line = 1;
+    for (size_t i = 0; i < pos && i < src.size(); i++) {
+      line += src[i] == '\n';
+    }
+    fail(path + ":" + std::to_string(line) + ": " + msg);
+  }
+
+  void skip_ws() {
+    while (pos < src.size() && std::isspace((unsigned char)src[pos])) {
+      pos++;
+    }
+  }
+
+  void expect(char c) {
+    skip_ws();
+    if (pos >= src.size() || src[pos] != c) {
+      error(std::string("expected `") + c + "'");
+    }
+    pos++;
+  }
+
+  Json parse_value() {
+    skip_ws();
+    if (pos >= src.size()) {
+      error("unexpected end of file");
+    }
+    Json value;
+    char c = src[pos];
+    if (c == '{') {
+      value.kind = Json::Object;
+      pos++;
+      skip_ws();
+      if (pos < src.size() && src[pos] == '}') {
+        pos++;
+        return value;
+      }
+      do {
+        skip_ws();
+        std::string key = parse_string();
+        expect(':');
+        value.fields.emplace_back(key, parse_value());
+        skip_ws();
+      } while (pos < src.size() && src[pos] == ',' && ++pos);
+      expect('}');
+    } else if (c == '[') {
+      value.kind = Json::Array;
+      pos++;
+      skip_ws();
+      if (pos < src.size() && src[pos] == ']') {
+        pos++;
+        return value;
+      }
+      do {
+        value.items.push_back(parse_value());
+        skip_ws();
+      } while (pos < src.size() && src[pos] == ',' && ++pos);
+      expect(']');
+    } else if (c == '"') {
+      value.kind = Json::String;
+      value.text = parse_string();
+    } else if (src.compare(pos, 4, "true") == 0 ||
+               src.compare(pos, 5, "false") == 0) {
+      value.kind = Json::Bool;
+      value.text = c == 't' ? "true" : "false";
+      pos += value.text.size();
+    } else if (src.compare(pos, 4, "null") == 0) {
+      pos += 4;
+    } else if (c == '-' || std::isdigit((unsigned char)c)) {
+      value.kind = Json::Number;
+      size_t start = pos;
+      while (pos < src.size() &&
+             (std::isalnum((unsigned char)src[pos]) || src[pos] == '-' ||
+              src[pos] == '+' || src[pos] == '.')) {
+        pos++;
+      }
+      value.text = src.substr(start, pos - start);
+    } else {
+      error(std::string("unexpected character `") + c + "'");
+    }
+    return value;
+  }
+
+  std::string parse_string() {
+    if (pos >= src.size() || src[pos] != '"') {
+      error("expected a string");
+    }
+    pos++;
+    std::string out;
+    while (pos < src.size() && src[pos] != '"') {
+      char c = src[pos++];
+      if (c == '\\' && pos < src.size()) {
+        char e = src[pos++];
+        switch (e) {
+        case 'n':
+          out += '\n';
+          break;
+        case 't':
+          out += '\t';
+          break;
+        case 'r':
+          out += '\r';
+          break;
+        case 'b':
+          out += '\b';
+          break;
+        case 'f':
+          out += '\f';
+          break;
+        case 'u':
+          // Only ASCII escapes appear in data files.
+          out += (char)std::strtol(src.substr(pos, 4).c_str(), nullptr, 16);
+          pos += 4;
+          break;
+        default:
+          out += e;
+        }
+      } else {
+        out += c;
+      }
+    }
+    if (pos >= src.size()) {
+      error("unterminated string");
+    }
+    pos++;
+    return out;
+  }
+};
+
+// ---------------------------------------------------------------------------
+// Numeric formats
+// ---------------------------------------------------------------------------
+
+// The `format` of a memory in a data file.
+struct Format {
+  enum Type { Bitnum, Fixed, Float };
+  Type type = Bitnum;
+  bool is_signed = false;
+  unsigned width = 0;
+  unsigned frac_width = 0;
+};
+
+static Format parse_format(const Json &data, const Memory &mem) {
+  const std::string name = mem.name;
+  const Json *json = data.get("format");
+  if (!json || json->kind != Json::Object) {
+    fail("memory `" + name + "' does not have a `format'");
+  }
+  auto num = [&](const char *key) -> long {
+    const Json *field = json->get(key);
+    if (!field) {
+      return -1;
+    }
+    if (field->kind != Json::Number) {
+      fail("`" + std::string(key) + "' of memory `" + name +
+           "' must be a number");
+    }
+    return std::strtol(field->text.c_str(), nullptr, 10);
+  };
+
+  Format format;
+  const Json *is_signed = json->get("is_signed");
+  format.is_signed = is_signed && is_signed->text == "true";
+  const Json *type = json->get("numeric_type");
+  std::string numeric_type = type ? type->text : "bitnum";
+  long width = num("width");
+  if (numeric_type == "bitnum") {
+    format.type = Format::Bitnum;
+  } else if (numeric_type == "fixed_point" || numeric_type == "fixed") {
+    format.type = Format::Fixed;
+    long int_width = num("int_width");
+    long frac_width = num("frac_width");
+    if (width < 0 && int_width >= 0 && frac_width >= 0) {
+      width = int_width + frac_width;
+    }
+    if (frac_width < 0 && width >= 0 && int_width >= 0) {
+      frac_width = width - int_width;
+    }
+    if (width < 0 || frac_width < 0 || frac_width > width) {
+      fail("fixed-point memory `" + name +
+           "' needs two of `width', `int_width' and `frac_width'");
+    }
+    format.frac_width = (unsigned)frac_width;
+  } else if (numeric_type == "ieee754_float" || numeric_type == "float") {
+    format.type = Format::Float;
+  } else {
+    fail("memory `" + name + "' has unknown numeric type `" + numeric_type +
+         "'");
+  }
+  if (width >= 0 && (unsigned long)width != mem.width) {
+    fail("memory `" + name + "' is " + std::to_string(mem.width) +
+         " bits wide but its format has width " + std::to_string(width));
+  }
+  format.width = mem.width;
+  if (format.type == Format::Float && format.width != 32 &&
+      format.width != 64) {
+    fail("floating-point memory `" + name + "' must be 32 or 64 bits wide");
+  }
+  return format;
+}
+
+// Encode a value from a data file as the bits stored in a memory.
+static uint64_t encode(const Json &value, const Format &format,
+                       const std::string &mem) {
+  if (value.kind != Json::Number && value.kind != Json::String) {
+    fail("memory `" + mem + "' contains a value that is not a number");
+  }
+  const std::string &text = value.text;
+  const char *str = text.c_str();
+  char *end = nullptr;
+  errno = 0;
+  uint64_t bits = 0;
+  bool fits = true;
+  switch (format.type) {
+  case Format::Bitnum: {
+    if (text[0] == '-') {
+      long long v = std::strtoll(str, &end, 0);
+      fits = format.is_signed &&
+             (format.width >= 64 || v >= -(1LL << (format.width - 1)));
+      bits = static_cast<uint64_t>(v);
+    } else {
+      bits = std::strtoull(str, &end, 0);
+      fits = bits <= (format.is_signed ? mask(format.width - 1)
+                                       : mask(format.width));
+    }
+    break;
+  }
+  case Format::Fixed: {
+    long double v = std::strtold(str, &end);
+    long double scaled = std::ldexp(v, format.frac_width);
+    long double limit = std::ldexp(1.0L, format.width - format.is_signed);
+    long double low = format.is_signed ? -limit : 0.0L;
+    scaled = std::nearbyint(scaled);
+    fits = scaled >= low && scaled < limit;
+    bits = scaled < 0 ? static_cast<uint64_t>(static_cast<int64_t>(scaled))
+                      : static_cast<uint64_t>(scaled);
+    break;
+  }
+  case Format::Float: {
+    if (format.width == 32) {
+      float f = std::strtof(str, &end);
+      uint32_t b;
+      std::memcpy(&b, &f, sizeof(b));
+      bits = b;
+    } else {
+      double d = std::strtod(str, &end);
+      std::memcpy(&bits, &d, sizeof(bits));
+    }
+    break;
+  }
+  }
+  if (end == str || *end != '\0' || errno == ERANGE) {
+    fail("memory `" + mem + "' contains malformed value `" + text + "'");
+  }
+  if (!fits) {
+    fail("value `" + text + "' in memory `" + mem + "' does not fit in " +
+         std::to_string(format.width) + " bits");
+  }
+  return bits & mask(format.width);
+}
+
+// Decode the bits stored in a memory as a JSON number.
+static std::string decode(uint64_t bits, const Format &format) {
+  bits &= mask(format.width);
+  switch (format.type) {
+  case Format::Bitnum:
+    if (format.is_signed) {
+      return std::to_string(sign_extend(bits, format.width));
+    }
+    return std::to_string(bits);
+  case Format::Fixed: {
+    bool negative = format.is_signed && (bits >> (format.width - 1)) & 1;
+    uint64_t magnitude =
+        negative ? (~bits + 1) & mask(format.width) : bits;
+    if (negative && format.width == 64 && magnitude == 0) {
+      magnitude = uint64_t(1) << 63;
+    }
+    unsigned frac = format.frac_width;
+    uint64_t int_part = frac >= 64 ? 0 : magnitude >> frac;
+    unsigned __int128 rem = magnitude & mask(frac);
+    std::string out = negative ? "-" : "";
+    out += std::to_string(int_part) + ".";
+    if (rem == 0) {
+      return out + "0";
+    }
+    // Every binary fraction has a finite decimal expansion.
+    while (rem != 0) {
+      rem *= 10;
+      out += (char)('0' + (unsigned)(rem >> frac));
+      rem &= mask(frac);
+    }
+    return out;
+  }
+  case Format::Float: {
+    double d;
+    int digits = 17;
+    if (format.width == 32) {
+      uint32_t b = (uint32_t)bits;
+      float f;
+      std::memcpy(&f, &b, sizeof(f));
+      d = f;
+      digits = 9;
+    } else {
+      std::memcpy(&d, &bits, sizeof(d));
+    }
+    if (!std::isfinite(d)) {
+      return "null";
+    }
+    char buf[64];
+    std::snprintf(buf, sizeof(buf), "%.*g", digits, d);
+    std::string out = buf;
+    if (out.find_first_of(".eE") == std::string::npos) {
+      out += ".0";
+    }
+    return out;
+  }
+  }
+  return "0";
+}
+
+// ---------------------------------------------------------------------------
+// Memory contents
+// ---------------------------------------------------------------------------
+
+// Flatten the nested arrays of a memory in row-major order, checking that
+// they have the shape of the memory.
+static void flatten(const Json &value, const Memory &mem, size_t dim,
+                    std::vector<const Json *> &out) {
+  const std::string name = mem.name;
+  if (dim == mem.dims.size()) {
+    out.push_back(&value);
+    return;
+  }
+  if (value.kind != Json::Array || value.items.size() != mem.dims[dim]) {
+    std::string shape;
+    for (uint64_t d : mem.dims) {
+      shape += (shape.empty() ? "" : " x ") + std::to_string(d);
+    }
+    fail("data for memory `" + name + "' does not have shape " + shape);
+  }
+  for (const Json &item : value.items) {
+    flatten(item, mem, dim + 1, out);
+  }
+}
+
+static std::string read_file(const std::string &path) {
+  std::ifstream in(path);
+  if (!in) {
+    fail("cannot read `" + path + "'");
+  }
+  std::stringstream ss;
+  ss << in.rdbuf();
+  return ss.str();
+}
+
+// Write the hex file that the design loads with `$readmemh`.
+static void write_dat(const std::string &dir, const Memory &mem,
+                      const Json &data, const Format &format) {
+  const Json *values = data.get("data");
+  if (!values) {
+    fail("memory `" + std::string(mem.name) + "' does not have `data'");
+  }
+  std::vector<const Json *> flat;
+  flatten(*values, mem, 0, flat);
+  std::string path = dir + "/" + mem.name + ".dat";
+  std::ofstream out(path);
+  if (!out) {
+    fail("cannot write `" + path + "'");
+  }
+  int digits = (int)((mem.width + 3) / 4);
+  char buf[32];
+  for (const Json *value : flat) {
+    std::snprintf(buf, sizeof(buf), "%0*llx", digits,
+                  (unsigned long long)encode(*value, format, mem.name));
+    out << buf << "\n";
+  }
+}
+
+// Read the hex file that the design dumps with `$writememh`.
+static std::vector<uint64_t> read_out(const std::string &dir,
+                                      const Memory &mem) {
+  std::string path = dir + "/" + mem.name + ".out";
+  std::ifstream in(path);
+  if (!in) {
+    fail("the design did not dump memory `" + std::string(mem.name) +
+         "' to `" + path + "'");
+  }
+  std::vector<uint64_t> values;
+  std::string line;
+  while (std::getline(in, line)) {
+    size_t start = line.find_first_not_of(" \t\r");
+    if (start == std::string::npos || line.compare(start, 2, "//") == 0) {
+      continue;
+    }
+    char *end = nullptr;
+    values.push_back(std::strtoull(line.c_str() + start, &end, 16));
+  }
+  if (values.size() != mem.size()) {
+    fail("expected " + std::to_string(mem.size()) + " values for memory `" +
+         mem.name + "' but the design dumped " +
+         std::to_string(values.size()));
+  }
+  return values;
+}
+
+static void write_values(std::ostream &out, const Memory &mem,
+                         const Format &format,
+                         const std::vector<uint64_t> &values, size_t dim,
+                         size_t &idx, int indent) {
+  if (dim == mem.dims.size()) {
+    out << decode(values[idx++], format);
+    return;
+  }
+  bool innermost = dim + 1 == mem.dims.size();
+  std::string pad(indent + 2, ' ');
+  out << "[";
+  for (uint64_t i = 0; i < mem.dims[dim]; i++) {
+    out << (i ? "," : "");
+    if (innermost) {
+      out << (i ? " " : "");
+    } else {
+      out << "\n" << pad;
+    }
+    write_values(out, mem, format, values, dim + 1, idx, indent + 2);
+  }
+  if (!innermost) {
+    out << "\n" << std::string(indent, ' ');
+  }
+  out << "]";
+}
+
+// ---------------------------------------------------------------------------
+// Driver
+// ---------------------------------------------------------------------------
+
+static void usage(const char *bin) {
+  std::cerr
+      << "usage: " << bin << " --data <FILE> [options]\n"
+      << "\n"
+      << "options:\n"
+      << "  --data <FILE>         memory contents in Cider's JSON format\n"
+      << "  --out <FILE>          where to write the final memory contents "
+         "(default: stdout)\n"
+      << "  --cycle-limit <N>     fail after simulating N cycles "
+         "(default: 500000000)\n"
+      << "  --set <PORT>=<VALUE>  drive an input port of the design\n"
+      << "  --data-dir <DIR>      keep the memory files in DIR\n"
+#if VM_TRACE
+      << "  --trace <FILE>        dump a VCD trace to FILE\n"
+#endif
+      ;
+  std::exit(2);
+}
+
+int main(int argc, char **argv) {
+  std::string data_path, out_path = "-", data_dir, trace_path;
+  uint64_t cycle_limit = 500000000;
+  std::vector<std::pair<size_t, uint64_t>> assigned;
+
+  for (int i = 1; i < argc; i++) {
+    std::string arg = argv[i];
+    if (i + 1 >= argc) {
+      usage(argv[0]);
+    }
+    std::string val = argv[++i];
+    if (arg == "--data") {
+      data_path = val;
+    } else if (arg == "--out") {
+      out_path = val;
+    } else if (arg == "--cycle-limit") {
+      cycle_limit = std::strtoull(val.c_str(), nullptr, 10);
+    } else if (arg == "--data-dir") {
+      data_dir = val;
+    } else if (arg == "--trace") {
+      trace_path = val;
+    } else if (arg == "--set") {
+      size_t eq = val.find('=');
+      std::string port = val.substr(0, eq);
+      size_t idx = 0;
+      while (idx < inputs().size() && port != inputs()[idx].name) {
+        idx++;
+      }
+      if (eq == std::string::npos || idx == inputs().size()) {
+        std::string names;
+        for (const Port &p : inputs()) {
+          names += std::string(names.empty() ? "" : ", ") + p.name;
+        }
+        fail("`" + port + "' is not an input of the design. Inputs: " +
+             (names.empty() ? "none" : names));
+      }
+      Format format;
+      format.width = inputs()[idx].width;
+      Json value;
+      value.kind = Json::Number;
+      value.text = val.substr(eq + 1);
+      assigned.emplace_back(idx, encode(value, format, port));
+    } else {
+      usage(argv[0]);
+    }
+  }
+  if (data_path.empty()) {
+    usage(argv[0]);
+  }
+#if !VM_TRACE
+  if (!trace_path.empty()) {
+    fail("the harness was built without --trace");
+  }
+#endif
+
+  // Load the data and write the memory files.
+  std::string src = read_file(data_path);
+  Json data = JsonParser(src, data_path).parse();
+  if (data.kind != Json::Object) {
+    fail("`" + data_path + "' must contain a JSON object");
+  }
+  for (const auto &field : data.fields) {
+    bool found = false;
+    for (const Memory &mem : memories()) {
+      found |= field.first == mem.name;
+    }
+    if (!found) {
+      fail("`" + field.first +
+           "' is not an @external memory of the toplevel component");
+    }
+  }
+  bool temp_dir = data_dir.empty();
+  if (temp_dir) {
+    char tmpl[] = "/tmp/calyx-harness-XXXXXX";
+    if (!mkdtemp(tmpl)) {
+      fail("cannot create a temporary directory");
+    }
+    data_dir = tmpl;
+  }
+  std::vector<Format> formats;
+  for (const Memory &mem : memories()) {
+    const Json *contents = data.get(mem.name);
+    if (!contents) {
+      fail("memory `" + std::string(mem.name) + "' is missing from `" +
+           data_path + "'");
+    }
+    formats.push_back(parse_format(*contents, mem));
+    write_dat(data_dir, mem, *contents, formats.back());
+  }
+
+  // Build the model. The memories are loaded from `+DATA=<dir>`.
+  auto context = std::make_unique<VerilatedContext>();
+  std::string plusarg = "+DATA=" + data_dir;
+  const char *args[] = {argv[0], plusarg.c_str()};
+  context->commandArgs(2, args);
+#if VM_TRACE
+  context->traceEverOn(!trace_path.empty());
+#endif
+  auto top = std::make_unique<Model>(context.get());
+#if VM_TRACE
+  std::unique_ptr<VerilatedVcdC> tfp;
+  if (!trace_path.empty()) {
+    tfp = std::make_unique<VerilatedVcdC>();
+    top->trace(tfp.get(), 99);
+    tfp->open(trace_path.c_str());
+  }
+#endif
+  auto step = [&](bool clk) {
+    set_clk(*top, clk);
+    top->eval();
+#if VM_TRACE
+    if (tfp) {
+      tfp->dump(context->time());
+    }
+#endif
+    context->timeInc(1);
+    if (context->gotFinish()) {
+      fail("the design called $finish");
+    }
+  };
+
+  // Reset the design and start it.
+  set_go(*top, false);
+  set_reset(*top, true);
+  for (int i = 0; i < RESET_CYCLES; i++) {
+    step(true);
+    step(false);
+  }
+  set_reset(*top, false);
+  for (const auto &input : assigned) {
+    set_input(*top, input.first, input.second);
+  }
+  set_go(*top, true);
+  step(false);
+
+  uint64_t cycles = 0;
+  while (!is_done(*top, cycles)) {
+    if (cycles == cycle_limit) {
+      fail("reached limit of " + std::to_string(cycle_limit) + " cycles");
+    }
+    step(true);
+    step(false);
+    cycles++;
+  }
+  std::vector<uint64_t> results;
+  for (size_t i = 0; i < outputs().size(); i++) {
+    results.push_back(get_output(*top, i));
+  }
+  set_go(*top, false);
+  step(true);
+  step(false);
+  top->final();
+#if VM_TRACE
+  if (tfp) {
+    tfp->close();
+  }
+#endif
+
+  // Dump the memories in the format of the input data.
+  std::ofstream file;
+  if (out_path != "-") {
+    file.open(out_path);
+    if (!file) {
+      fail("cannot write `" + out_path + "'");
+    }
+  }
+  std::ostream &out = out_path == "-" ? std::cout : file;
+  out << "{\n  \"cycles\": " << cycles << ",\n  \"memories\": {";
+  std::vector<size_t> order;
+  for (size_t i = 0; i < memories().size(); i++) {
+    order.push_back(i);
+  }
+  std::sort(order.begin(), order.end(), [](size_t a, size_t b) {
+    return std::strcmp(memories()[a].name, memories()[b].name) < 0;
+  });
+  for (size_t n = 0; n < order.size(); n++) {
+    const Memory &mem = memories()[order[n]];
+    std::vector<uint64_t> values = read_out(data_dir, mem);
+    size_t idx = 0;
+    out << (n ? "," : "") << "\n    \"" << mem.name << "\": ";
+    write_values(out, mem, formats[order[n]], values, 0, idx, 4);
+    if (temp_dir) {
+      unlink((data_dir + "/" + mem.name + ".dat").c_str());
+      unlink((data_dir + "/" + mem.name + ".out").c_str());
+    }
+  }
+  out << (order.empty() ? "}" : "\n  }");
+  if (!outputs().empty()) {
+    out << ",\n  \"ports\": {";
+    for (size_t i = 0; i < outputs().size(); i++) {
+      out << (i ? "," : "") << "\n    \"" << outputs()[i].name
+          << "\": " << results[i];
+    }
+    out << "\n  }";
+  }
+  out << "\n}\n";
+  if (temp_dir) {
+    rmdir(data_dir.c_str());
+  }
+  return 0;
+}
@@ -0,0 +1,279 @@
+//! Verilator harness backend for the Calyx compiler.
+//!
+//! Generates a C++ driver for the Verilated model of the Verilog that the
+//! `verilog` backend produces for the same program. The harness is typed
+//! using the interface of the toplevel component: it reads the contents of
+//! the `@external` memories from a data file in Cider's JSON format, checks
+//! them against the shape and width of every memory, resets and runs the
+//! design while counting cycles, and dumps the final contents of the memories
+//! as JSON.
+//!
+//! The harness includes a runtime that is the same for every design and
+//! implements the data conversion and the simulation loop. It is emitted
+//! separately as `calyx_harness.h` and has to be next to the harness.
+//!
+//! Usage: -b verilator-harness [-o <OUTPUT_FILE>]
+//!
+//! Build the simulator with:
+//! ```text
+//! calyx -b verilator-harness -x verilator-harness:runtime main.futil > calyx_harness.h
+//! verilator --cc --exe --build [--trace] main.sv harness.cpp
+//! ```
+//!
+//! Options:
+//! - `-x verilator-harness:prefix=<NAME>`: Name of the class of the
+//!   Verilated model, i.e., the value passed to Verilator's `--prefix`.
+//!   Defaults to `V` followed by the name of the toplevel component.
+//! - `-x verilator-harness:runtime`: Emit the runtime instead of the harness.
+use crate::extra_opts::ExtraOpt;
+use crate::traits::Backend;
+use calyx_ir::{self as ir, utils::ProgramInterface};
+use calyx_utils::{CalyxResult, Error, OutputFile};
+use std::io::Write;
+
+/// Runtime shared by all harnesses: data conversion and the simulation loop.
+const RUNTIME: &str = include_str!("calyx_harness.h");
+
+/// Name of the file that harnesses include the runtime from.
+const RUNTIME_FILE: &str = "calyx_harness.h";
+
+#[derive(Default)]
+pub struct VerilatorHarnessBackend;
+
+impl Backend for VerilatorHarnessBackend {
+    fn name(&self) -> &'static str {
+        "verilator-harness"
+    }
+
+    fn validate(ctx: &ir::Context) -> CalyxResult<()> {
+        let comp = ctx.entrypoint();
+        if let Some(cell) =
+            comp.cells.iter().find(|cell| cell.borrow().is_reference())
+        {
+            return Err(Error::malformed_structure(format!(
+                "The Verilator harness cannot drive `ref' cell `{}' of the toplevel component. Use `@external' memories instead.",
+                cell.borrow().name()
+            )));
+        }
+        Ok(())
+    }
+
+    /// The harness does not contain any Verilog
+    fn link_externs(
+        _ctx: &ir::Context,
+        _file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        let conf = HarnessConf::from_opts(&ctx.extra_opts);
+        let out = &mut file.get_write();
+        if conf.runtime {
+            write!(out, "{RUNTIME}")?;
+            return Ok(());
+        }
+
+        let interface = ProgramInterface::from_component(ctx.entrypoint())?;
+        let prefix = conf
+            .prefix
+            .unwrap_or_else(|| format!("V{}", interface.toplevel));
+        let design = Design::new(&interface)?;
+
+        writeln!(
+            out,
+            "// Verilator harness for the Calyx component `{}'.",
+            interface.toplevel
+        )?;
+        writeln!(out, "// Generated by `calyx -b verilator-harness`.")?;
+        writeln!(out, "#include \"{prefix}.h\"")?;
+        writeln!(out, "using Model = {prefix};")?;
+        writeln!(out, "#include \"{RUNTIME_FILE}\"")?;
+        writeln!(out)?;
+        design.write(out)?;
+        Ok(())
+    }
+}
+
+/// Configuration for the Verilator harness backend.
+struct HarnessConf {
+    prefix: Option<String>,
+    runtime: bool,
+}
+
+impl HarnessConf {
+    /// Read the configuration from the extra options passed to the compiler.
+    fn from_opts(opts: &[String]) -> Self {
+        let mut conf = Self {
+            prefix: None,
+            runtime: false,
+        };
+        for opt in ExtraOpt::parse("verilator-harness", opts) {
+            match opt.name {
+                "prefix" => conf.prefix = Some(opt.val.to_string()),
+                "runtime" => conf.runtime = true,
+                _ => opt.unknown(),
+            }
+        }
+        conf
+    }
+}
+
+/// The parts of the toplevel interface that the harness drives.
+struct Design<'a> {
+    interface: &'a ProgramInterface,
+    clk: &'a str,
+    reset: &'a str,
+    go: &'a str,
+    /// How the harness detects that the design is done.
+    done: Done<'a>,
+}
+
+enum Done<'a> {
+    /// The design asserts a done signal.
+    Signal(&'a str),
+    /// The design is static and finishes after a fixed number of cycles.
+    Latency(u64),
+}
+
+impl<'a> Design<'a> {
+    fn new(interface: &'a ProgramInterface) -> CalyxResult<Self> {
+        let signal = |name: &'a Option<String>, attr: &str| {
+            name.as_deref().ok_or_else(|| {
+                Error::malformed_structure(format!(
+                    "Toplevel component `{}' does not have a port with the `@{attr}' attribute",
+                    interface.toplevel
+                ))
+            })
+        };
+        let done = match (&interface.signals.done, interface.latency) {
+            (Some(done), _) => Done::Signal(done),
+            (None, Some(latency)) => Done::Latency(latency),
+            (None, None) => {
+                return Err(Error::malformed_structure(format!(
+                    "Toplevel component `{}' has neither a `@done' port nor a static latency",
+                    interface.toplevel
+                )))
+            }
+        };
+
+        // Verilator represents wider signals as arrays of words
+        let wide = interface
+            .inputs
+            .iter()
+            .chain(&interface.outputs)
+            .map(|port| (&port.name, port.width, "port"))
+            .chain(
+                interface
+                    .memories
+                    .iter()
+                    .map(|mem| (&mem.name, mem.data_width, "memory")),
+            )
+            .find(|(_, width, _)| *width > 64);
+        if let Some((name, width, kind)) = wide {
+            return Err(Error::misc(format!(
+                "The Verilator harness only supports values up to 64 bits but {kind} `{name}' is {width} bits wide"
+            )));
+        }
+
+        Ok(Design {
+            interface,
+            clk: signal(&interface.signals.clk, "clk")?,
+            reset: signal(&interface.signals.reset, "reset")?,
+            go: signal(&interface.signals.go, "go")?,
+            done,
+        })
+    }
+
+    /// Define the design-specific functions declared by the runtime.
+    fn write(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let iface = self.interface;
+        writeln!(out, "// Interface of `{}'.", iface.toplevel)?;
+        writeln!(out)?;
+
+        writeln!(out, "static const std::vector<Memory> &memories() {{")?;
+        writeln!(out, "  static const std::vector<Memory> memories = {{")?;
+        for mem in &iface.memories {
+            let dims = mem
+                .dimension_sizes
+                .iter()
+                .map(|size| size.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "      {{\"{}\", {}, {{{dims}}}}},",
+                mem.name, mem.data_width
+            )?;
+        }
+        writeln!(out, "  }};")?;
+        writeln!(out, "  return memories;")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+
+        for (kind, ports) in
+            [("inputs", &iface.inputs), ("outputs", &iface.outputs)]
+        {
+            writeln!(out, "static const std::vector<Port> &{kind}() {{")?;
+            writeln!(out, "  static const std::vector<Port> ports = {{")?;
+            for port in ports.iter() {
+                writeln!(out, "      {{\"{}\", {}}},", port.name, port.width)?;
+            }
+            writeln!(out, "  }};")?;
+            writeln!(out, "  return ports;")?;
+            writeln!(out, "}}")?;
+            writeln!(out)?;
+        }
+
+        writeln!(
+            out,
+            "static void set_input(Model &top, size_t idx, uint64_t value) {{"
+        )?;
+        writeln!(out, "  switch (idx) {{")?;
+        for (idx, port) in iface.inputs.iter().enumerate() {
+            writeln!(out, "  case {idx}:")?;
+            writeln!(out, "    top.{} = value;", port.name)?;
+            writeln!(out, "    break;")?;
+        }
+        writeln!(out, "  }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+
+        writeln!(out, "static uint64_t get_output(Model &top, size_t idx) {{")?;
+        writeln!(out, "  switch (idx) {{")?;
+        for (idx, port) in iface.outputs.iter().enumerate() {
+            writeln!(out, "  case {idx}:")?;
+            writeln!(out, "    return top.{};", port.name)?;
+        }
+        writeln!(out, "  }}")?;
+        writeln!(out, "  return 0;")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+
+        for (func, port) in
+            [("clk", self.clk), ("reset", self.reset), ("go", self.go)]
+        {
+            writeln!(
+                out,
+                "static void set_{func}(Model &top, bool value) {{ top.{port} = value; }}"
+            )?;
+        }
+        writeln!(out)?;
+
+        match self.done {
+            Done::Signal(done) => {
+                writeln!(
+                    out,
+                    "static bool is_done(Model &top, uint64_t) {{ return top.{done}; }}"
+                )?;
+            }
+            Done::Latency(latency) => {
+                writeln!(
+                    out,
+                    "static bool is_done(Model &, uint64_t cycles) {{ return cycles == {latency}; }}"
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -223,7 +223,7 @@ fn emit_prim_inline<F: io::Write>(
     Ok(())
 }
 
-fn emit_component<F: io::Write>(
+pub(crate) fn emit_component<F: io::Write>(
     comp: &ir::Component,
     synthesis_mode: bool,
     enable_verification: bool,
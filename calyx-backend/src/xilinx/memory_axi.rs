@@ -2,10 +2,83 @@ use super::{
     axi::{AxiChannel, AxiInterface, ChannelDirection},
     fsm,
 };
-use calyx_utils as utils;
+use crate::extra_opts::ExtraOpt;
+use calyx_utils::{self as utils, CalyxResult, Error};
 use std::rc::Rc;
 use vast::v05::ast as v;
 
+/// Configures the bursts that memory controllers use to move data between
+/// the host and the kernel. Set with `-x xilinx:burst-len=<n>` and
+/// `-x xilinx:outstanding=<n>`.
+#[derive(Clone, Copy, Debug)]
+pub struct BurstConf {
+    /// Maximum number of beats in a burst.
+    pub burst_len: u64,
+    /// Maximum number of bursts in flight on each of the read and write
+    /// channels.
+    pub outstanding: u64,
+}
+
+impl Default for BurstConf {
+    fn default() -> Self {
+        Self {
+            burst_len: 16,
+            outstanding: 4,
+        }
+    }
+}
+
+impl BurstConf {
+    /// Read the configuration from the extra options passed to the compiler.
+    pub fn from_opts(opts: &[String]) -> CalyxResult<Self> {
+        let mut conf = Self::default();
+        for opt in ExtraOpt::parse("xilinx", opts) {
+            match opt.name {
+                "burst-len" => {
+                    let val: u64 = opt.num()?;
+                    // AXI4 limits incrementing bursts to 256 beats
+                    if !val.is_power_of_two() || val > 256 {
+                        return Err(
+                            opt.invalid("a power of two no larger than 256")
+                        );
+                    }
+                    conf.burst_len = val
+                }
+                "outstanding" => {
+                    let val: u64 = opt.num()?;
+                    if val == 0 {
+                        return Err(Error::misc(
+                            "At least one transaction has to be outstanding",
+                        ));
+                    }
+                    conf.outstanding = val
+                }
+                _ => opt.unknown(),
+            }
+        }
+        Ok(conf)
+    }
+}
+
+/// Number of bytes used by each element of a memory with `data_width` bits
+/// wide elements in host memory.
+pub fn element_bytes(data_width: u64) -> u64 {
+    data_width.div_ceil(8).next_power_of_two()
+}
+
+/// Width of the AXI data bus used to transfer a memory with `data_width` bits
+/// wide elements. Every beat carries a single element, so this is the
+/// narrowest width supported by Vitis kernels that fits an element.
+pub fn bus_data_width(data_width: u64) -> CalyxResult<u64> {
+    let width = element_bytes(data_width) * 8;
+    if width > 512 {
+        return Err(Error::misc(format!(
+            "Memory elements of width {data_width} do not fit on a 512 bit AXI bus"
+        )));
+    }
+    Ok(width.max(32))
+}
+
 /// Represents the interface for a AXI master that controls
 /// a memory with an AXI slave interface.
 pub trait MemoryInterface {
@@ -21,6 +94,7 @@ pub trait MemoryInterface {
         data_width: u64,
         memory_size: u64,
         addr_width: u64,
+        burst: BurstConf,
     ) -> v::Module;
 }
 
@@ -99,6 +173,7 @@ impl MemoryInterface for AxiInterface {
         data_width: u64,
         memory_size: u64,
         addr_width: u64,
+        burst: BurstConf,
     ) -> v::Module {
         let mut module = v::Module::new(name);
 
@@ -106,7 +181,8 @@ impl MemoryInterface for AxiInterface {
         module.add_input("ARESET", 1);
 
         // add axi interface ports
-        let axi4 = AxiInterface::memory_channels(bus_addr_width, 512, "");
+        let axi4 =
+            AxiInterface::memory_channels(bus_addr_width, bus_data_width, "");
         axi4.add_ports_to(&mut module);
 
         module.add_input("BASE_ADDRESS", bus_addr_width);
@@ -122,41 +198,38 @@ impl MemoryInterface for AxiInterface {
         module.add_input("WE", 1);
         module.add_output("DONE", 1);
 
+        let elem_bytes = element_bytes(data_width);
+        let transfer = Transfer {
+            memory_size,
+            elem_bytes,
+            lanes: bus_data_width / (elem_bytes * 8),
+            // a burst starting at a multiple of its length never crosses a
+            // 4KB boundary as long as the host buffer is 4KB aligned
+            burst_len: burst.burst_len.min(4096 / elem_bytes).min(memory_size),
+            outstanding: burst.outstanding,
+            // add 1 so offset can count up to memory size inclusively
+            offset_width: utils::bits_needed_for(memory_size) + 1,
+            bus_addr_width,
+        };
+
         // internal signals
         module.add_decl(v::Decl::new_wire("copy_done", 1));
         module.add_stmt(v::Parallel::Assign(
             "copy_done".into(),
             v::Expr::new_eq("copy_addr_offset", memory_size as i32),
         ));
+        // the last write is only done once its response has arrived
         module.add_decl(v::Decl::new_wire("send_done", 1));
         module.add_stmt(v::Parallel::Assign(
             "send_done".into(),
-            v::Expr::new_eq("send_addr_offset", memory_size as i32),
+            v::Expr::new_logical_and(
+                v::Expr::new_eq("send_addr_offset", memory_size as i32),
+                v::Expr::new_eq("write_outstanding", 0),
+            ),
         ));
 
         // module mode fsm
         let mode_fsm = module_mode_fsm(&mut module);
-        // module.add_stmt(v::Parallel::Assign(
-        //     "SEND_TO_HOST_DONE".into(),
-        //     v::Expr::new_mux(mode_fsm.state_is("send"), "send_done", 0),
-        // ));
-
-        // count the number of read transactions we've received
-        module.add_decl(v::Decl::new_reg(
-            "read_txn_count",
-            utils::bits_needed_for(bus_data_width / data_width),
-        ));
-        module.add_stmt(super::utils::cond_non_blk_assign(
-            "ACLK",
-            "read_txn_count",
-            vec![
-                (Some("ARESET".into()), 0.into()),
-                (
-                    Some(axi4.read_data.handshake()),
-                    v::Expr::new_add("read_txn_count", 1),
-                ),
-            ],
-        ));
 
         // bram reading / writing logic
         bram_logic(
@@ -164,7 +237,7 @@ impl MemoryInterface for AxiInterface {
             &axi4,
             &mut module,
             &mode_fsm,
-            "read_txn_count".into(),
+            &transfer,
             data_width,
             addr_width,
         );
@@ -173,19 +246,26 @@ impl MemoryInterface for AxiInterface {
             "bram_read_data".into(),
         ));
 
-        // add 1 so offset can count up to memory size inclusively
-        let offset_width = utils::bits_needed_for(memory_size) + 1;
-
-        // synchronise channels
-        let read_controller = axi4
-            .read_address
-            .then(&axi4.read_data)
-            .prefix("r")
-            .trigger(mode_fsm.next_state_is("copy"));
-        read_controller.emit(&mut module);
+        // request bursts from the host and accept every beat that arrives
+        let rlast = axi4.read_data.get("LAST");
+        address_channel(
+            &mut module,
+            &transfer,
+            "read",
+            &axi4.read_address,
+            mode_fsm.state_is("copy"),
+            v::Expr::new_logical_and(axi4.read_data.handshake(), rlast),
+        );
+        module.add_stmt(v::Parallel::Assign(
+            axi4.read_data.ready().into(),
+            mode_fsm.state_is("copy"),
+        ));
 
         // increment copy address offset
-        module.add_decl(v::Decl::new_reg("copy_addr_offset", offset_width));
+        module.add_decl(v::Decl::new_reg(
+            "copy_addr_offset",
+            transfer.offset_width,
+        ));
         incr_addr(
             &mut module,
             mode_fsm.state_is("copy"),
@@ -193,122 +273,287 @@ impl MemoryInterface for AxiInterface {
             axi4.read_data.handshake(),
         );
 
-        // addresses are byte addressed which means addresses are computed as
-        // base + (offset << shift_by)
-        // TODO(nathanielnrn): Fix the burst size and shifting values based on
-        // pynq(?) input? Or memory size? unclear.
-        let shift_by = 2;
-        let burst_size = v::Expr::new_ulit_dec(
-            3,
-            &utils::bits_needed_for(data_width / 8).to_string(),
+        // send bursts to the host and wait for their responses
+        let wlast = axi4.write_data.get("LAST");
+        address_channel(
+            &mut module,
+            &transfer,
+            "write",
+            &axi4.write_address,
+            mode_fsm.state_is("send"),
+            axi4.write_response.handshake(),
         );
-        //AxBURST corresponds to type of burst as follows:
-        // 0b00: Fixed
-        // 0b01 (default): Incr
-        // 0b10: Wrap
-        // 0b11: Reserved
-        let burst_type = v::Expr::new_ulit_bin(2, "01");
-
-        module.add_stmt(axi4.read_address.assign("ID", 0));
-
-        //add reg to store value of shift to circumvent order of operations
-        //issue on bit shift
-        let copy_shift = "copy_shift";
-        module.add_decl(v::Decl::new_wire(copy_shift, bus_addr_width));
-        let mut concat = v::ExprConcat::default();
-        concat.add_expr("copy_addr_offset");
-        concat.add_expr(v::Expr::new_repeat(
-            bus_addr_width - offset_width,
-            v::Expr::new_ulit_bin(1, "0"),
-        ));
         module.add_stmt(v::Parallel::Assign(
-            copy_shift.into(),
-            v::Expr::new_shift_left(concat, shift_by),
+            axi4.write_response.ready().into(),
+            mode_fsm.state_is("send"),
         ));
-        module.add_stmt(
-            axi4.read_address
-                .assign("ADDR", v::Expr::new_add("BASE_ADDRESS", copy_shift)),
-        );
-        // Currently we do not utilize burst capabilities, therefore AxLEN is 0
-        module.add_stmt(axi4.read_address.assign("LEN", 0));
-        module.add_stmt(axi4.read_address.assign("SIZE", burst_size.clone()));
-        module.add_stmt(axi4.read_address.assign("BURST", burst_type.clone()));
-
-        let write_controller = axi4
-            .write_address
-            .then(&axi4.write_data)
-            .then(&axi4.write_response)
-            .prefix("w")
-            .trigger(mode_fsm.next_state_is("send"));
-        write_controller.emit(&mut module);
 
         // increment send address offset
-        module.add_decl(v::Decl::new_reg("send_addr_offset", offset_width));
+        module.add_decl(v::Decl::new_reg(
+            "send_addr_offset",
+            transfer.offset_width,
+        ));
         incr_addr(
             &mut module,
             mode_fsm.state_is("send"),
             "send_addr_offset",
-            axi4.write_response.handshake(),
+            axi4.write_data.handshake(),
         );
 
-        module.add_stmt(axi4.write_address.assign("ID", 0));
-        // assign shift to a wire to circumvent `vast` order of operations issues
-        //we shift to convert offset to byte length
-        let send_shift = "send_shift";
-        module.add_decl(v::Decl::new_wire(send_shift, bus_addr_width));
-        let mut concat = v::ExprConcat::default();
-        concat.add_expr("send_addr_offset");
-        concat.add_expr(v::Expr::new_repeat(
-            bus_addr_width - offset_width,
-            v::Expr::new_ulit_bin(1, "0"),
-        ));
+        // write data channel: only send the data of bursts whose address has
+        // already been sent
         module.add_stmt(v::Parallel::Assign(
-            send_shift.into(),
-            v::Expr::new_shift_left(concat, shift_by),
+            axi4.write_data.ready().into(),
+            v::Expr::new_logical_and(
+                mode_fsm.state_is("send"),
+                v::Expr::new_neq("send_addr_offset", "write_issue_offset"),
+            ),
         ));
+        module.add_stmt(axi4.write_data.assign("ID", 0));
 
-        module.add_stmt(
-            axi4.write_address
-                .assign("ADDR", v::Expr::new_add("BASE_ADDRESS", send_shift)),
-        );
-        // Currently we do not utilize burst capabilities, therefore AxLEN is 0
-        module.add_stmt(axi4.write_address.assign("LEN", 0));
-        module.add_stmt(axi4.write_address.assign("SIZE", burst_size));
-        module.add_stmt(axi4.write_address.assign("BURST", burst_type));
+        // index of the current beat in its burst
+        module.add_decl(v::Decl::new_reg(
+            "write_beat",
+            utils::bits_needed_for(transfer.burst_len),
+        ));
+        module.add_stmt(counter(
+            "write_beat",
+            mode_fsm.state_is("send"),
+            vec![
+                (
+                    v::Expr::new_logical_and(
+                        axi4.write_data.handshake(),
+                        wlast.as_str(),
+                    ),
+                    0.into(),
+                ),
+                (
+                    axi4.write_data.handshake(),
+                    v::Expr::new_add("write_beat", 1),
+                ),
+            ],
+        ));
+        module.add_stmt(axi4.write_data.assign(
+            "LAST",
+            v::Expr::new_logical_or(
+                v::Expr::new_eq("write_beat", (transfer.burst_len - 1) as i32),
+                v::Expr::new_eq("send_addr_offset", (memory_size - 1) as i32),
+            ),
+        ));
 
-        // write data channel
-        module.add_stmt(axi4.write_data.assign("ID", 0));
+        // narrow elements are placed in the byte lanes matching their address
         let mut concat = v::ExprConcat::default();
         concat.add_expr("bram_read_data");
-        concat.add_expr(v::Expr::new_repeat(
-            (bus_data_width / data_width) - 1,
-            v::Expr::new_ulit_bin(32, "0"),
+        if bus_data_width > data_width {
+            concat.add_expr(v::Expr::new_repeat(
+                bus_data_width - data_width,
+                v::Expr::new_ulit_bin(1, "0"),
+            ));
+        }
+        let mut strobe = v::ExprConcat::default();
+        strobe.add_expr(v::Expr::new_repeat(
+            elem_bytes,
+            v::Expr::new_ulit_bin(1, "1"),
         ));
-        // used for alignment issues where host reads from WDATA at offset if
-        // AWADDR is not alligned with data-bus-width
-        let wdata = v::Expr::new_shift_left(
-            concat,
-            v::Expr::new_mul("send_addr_offset", data_width as i32),
-        );
+        if bus_data_width / 8 > elem_bytes {
+            strobe.add_expr(v::Expr::new_repeat(
+                bus_data_width / 8 - elem_bytes,
+                v::Expr::new_ulit_bin(1, "0"),
+            ));
+        }
+        let (wdata, wstrb): (v::Expr, v::Expr) =
+            match transfer.lane("send_addr_offset") {
+                Some(lane) => (
+                    v::Expr::new_shift_left(
+                        concat,
+                        v::Expr::new_mul(lane.clone(), (elem_bytes * 8) as i32),
+                    ),
+                    //divided by 8 because WSTRB bits refer to entire bytes
+                    v::Expr::new_shift_left(
+                        strobe,
+                        v::Expr::new_mul(lane, elem_bytes as i32),
+                    ),
+                ),
+                None => (concat.into(), strobe.into()),
+            };
         module.add_stmt(axi4.write_data.assign("DATA", wdata));
-        let mut concat = v::ExprConcat::default();
-        concat.add_expr(v::Expr::new_ulit_hex(4, "F"));
-        concat.add_expr(v::Expr::new_repeat(
-            (bus_data_width / (8 * 4)) - 1,
-            v::Expr::new_ulit_hex(4, "0"),
-        ));
-        let wstrb = v::Expr::new_shift_left(
-            concat,
-            //divided by 8 because WSTRB bits refer to entire bytes
-            v::Expr::new_mul("send_addr_offset", (data_width / 8) as i32),
-        );
         module.add_stmt(axi4.write_data.assign("STRB", wstrb));
-        module.add_stmt(axi4.write_data.assign("LAST", 1));
 
         module
     }
 }
 
+/// Describes how a memory is laid out in host memory and how it is moved
+/// over the bus.
+struct Transfer {
+    /// Number of elements in the memory.
+    memory_size: u64,
+    /// Number of bytes used by each element in host memory.
+    elem_bytes: u64,
+    /// Number of elements that fit side by side on the data bus.
+    lanes: u64,
+    /// Maximum number of beats in a burst.
+    burst_len: u64,
+    /// Maximum number of bursts in flight on each channel.
+    outstanding: u64,
+    /// Width of the registers that count elements.
+    offset_width: u64,
+    /// Width of the address bus.
+    bus_addr_width: u64,
+}
+
+impl Transfer {
+    /// The lane of the data bus used by the element at `offset`, or `None`
+    /// if every element uses the whole bus.
+    fn lane(&self, offset: &str) -> Option<v::Expr> {
+        (self.lanes > 1).then(|| {
+            let bits = self.lanes.trailing_zeros() as i32;
+            v::Expr::new_slice(offset, bits - 1, 0)
+        })
+    }
+}
+
+/// Drive the address channel `channel` for the transfer `prefix`. While
+/// `active`, the channel requests bursts covering the whole memory as long as
+/// fewer than `outstanding` of them are in flight. A burst is no longer in
+/// flight once `burst_done` is true.
+fn address_channel(
+    module: &mut v::Module,
+    transfer: &Transfer,
+    prefix: &str,
+    channel: &AxiChannel,
+    active: v::Expr,
+    burst_done: v::Expr,
+) {
+    let issue_offset = format!("{prefix}_issue_offset");
+    let outstanding = format!("{prefix}_outstanding");
+    let remaining = format!("{prefix}_remaining");
+    let burst_len = format!("{prefix}_burst_len");
+    let shift = format!("{prefix}_shift");
+
+    // number of elements already requested
+    module.add_decl(v::Decl::new_reg(&issue_offset, transfer.offset_width));
+    module.add_stmt(counter(
+        &issue_offset,
+        active.clone(),
+        vec![(
+            channel.handshake(),
+            v::Expr::new_add(issue_offset.as_str(), burst_len.as_str()),
+        )],
+    ));
+
+    // number of bursts in flight
+    module.add_decl(v::Decl::new_reg(
+        &outstanding,
+        utils::bits_needed_for(transfer.outstanding + 1),
+    ));
+    module.add_stmt(counter(
+        &outstanding,
+        active.clone(),
+        vec![
+            (
+                v::Expr::new_logical_and(
+                    channel.handshake(),
+                    burst_done.clone(),
+                ),
+                outstanding.as_str().into(),
+            ),
+            (
+                channel.handshake(),
+                v::Expr::new_add(outstanding.as_str(), 1),
+            ),
+            (burst_done, v::Expr::new_sub(outstanding.as_str(), 1)),
+        ],
+    ));
+
+    // the last burst only covers the remaining elements
+    module.add_decl(v::Decl::new_wire(&remaining, transfer.offset_width));
+    module.add_stmt(v::Parallel::Assign(
+        remaining.as_str().into(),
+        v::Expr::new_sub(transfer.memory_size as i32, issue_offset.as_str()),
+    ));
+    module.add_decl(v::Decl::new_wire(&burst_len, transfer.offset_width));
+    module.add_stmt(v::Parallel::Assign(
+        burst_len.as_str().into(),
+        v::Expr::new_mux(
+            v::Expr::new_lt(remaining.as_str(), transfer.burst_len as i32),
+            remaining.as_str(),
+            transfer.burst_len as i32,
+        ),
+    ));
+
+    module.add_stmt(v::Parallel::Assign(
+        channel.ready().into(),
+        v::Expr::new_logical_and(
+            active,
+            v::Expr::new_logical_and(
+                v::Expr::new_neq(
+                    issue_offset.as_str(),
+                    transfer.memory_size as i32,
+                ),
+                v::Expr::new_neq(
+                    outstanding.as_str(),
+                    transfer.outstanding as i32,
+                ),
+            ),
+        ),
+    ));
+    module.add_stmt(channel.assign("ID", 0));
+
+    // addresses are byte addressed which means addresses are computed as
+    // base + (offset << shift_by)
+    let shift_by = transfer.elem_bytes.trailing_zeros() as i32;
+    // assign shift to a wire to circumvent `vast` order of operations issues
+    module.add_decl(v::Decl::new_wire(&shift, transfer.bus_addr_width));
+    let mut concat = v::ExprConcat::default();
+    concat.add_expr(issue_offset.as_str());
+    concat.add_expr(v::Expr::new_repeat(
+        transfer.bus_addr_width - transfer.offset_width,
+        v::Expr::new_ulit_bin(1, "0"),
+    ));
+    module.add_stmt(v::Parallel::Assign(
+        shift.as_str().into(),
+        v::Expr::new_shift_left(concat, shift_by),
+    ));
+    module.add_stmt(
+        channel
+            .assign("ADDR", v::Expr::new_add("BASE_ADDRESS", shift.as_str())),
+    );
+    // AxLEN is the number of beats in the burst minus one
+    module.add_stmt(
+        channel.assign("LEN", v::Expr::new_sub(burst_len.as_str(), 1)),
+    );
+    // every beat transfers a single element
+    module.add_stmt(
+        channel.assign("SIZE", v::Expr::new_ulit_dec(3, &shift_by.to_string())),
+    );
+    //AxBURST corresponds to type of burst as follows:
+    // 0b00: Fixed
+    // 0b01 (default): Incr
+    // 0b10: Wrap
+    // 0b11: Reserved
+    module.add_stmt(channel.assign("BURST", v::Expr::new_ulit_bin(2, "01")));
+}
+
+/// A register that is reset to zero when `active` is false. Otherwise, it is
+/// updated with the first of `updates` whose condition holds and keeps its
+/// value if there is none.
+fn counter(
+    reg: &str,
+    active: v::Expr,
+    updates: Vec<(v::Expr, v::Expr)>,
+) -> v::Stmt {
+    let mut branches: Vec<(Option<v::Expr>, v::Expr)> = updates
+        .into_iter()
+        .map(|(cond, expr)| {
+            (Some(v::Expr::new_logical_and(active.clone(), cond)), expr)
+        })
+        .collect();
+    branches.push((Some(active), reg.into()));
+    branches.push((None, 0.into()));
+    super::utils::cond_non_blk_assign("ACLK", reg, branches)
+}
+
 fn module_mode_fsm(module: &mut v::Module) -> fsm::LinearFsm {
     // states:
     //  0: idle, start when COPY_TO_HOST
@@ -330,7 +575,7 @@ fn bram_logic(
     axi4: &AxiInterface,
     module: &mut v::Module,
     mode_fsm: &fsm::LinearFsm,
-    txn_count: v::Expr,
+    transfer: &Transfer,
     data_width: u64,
     addr_width: u64,
 ) {
@@ -386,11 +631,18 @@ fn bram_logic(
     module.add_stmt(v::Parallel::Assign("bram_we".into(), mux_we));
 
     // bram write data
-    let copy_data: v::Expr = v::Expr::new_index_slice(
-        &axi4.read_data.get("DATA"),
-        v::Expr::new_mul(txn_count, 32),
-        data_width as u32, // bram data width
-    );
+    let copy_data: v::Expr = match transfer.lane("copy_addr_offset") {
+        Some(lane) => v::Expr::new_index_slice(
+            &axi4.read_data.get("DATA"),
+            v::Expr::new_mul(lane, (transfer.elem_bytes * 8) as i32),
+            data_width as u32, // bram data width
+        ),
+        None => v::Expr::new_slice(
+            &axi4.read_data.get("DATA"),
+            (data_width - 1) as i32,
+            0,
+        ),
+    };
     let bram_data: v::Expr = "WRITE_DATA".into();
     let mux_data = v::Expr::new_mux(
         v::Expr::new_logical_and(
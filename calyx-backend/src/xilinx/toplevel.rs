@@ -1,6 +1,9 @@
 use super::{
-    axi, control_axi::ControlInterface, fsm, memory_axi::bram,
-    memory_axi::MemoryInterface, utils,
+    axi,
+    control_axi::ControlInterface,
+    fsm,
+    memory_axi::{bram, bus_data_width, BurstConf, MemoryInterface},
+    utils,
 };
 use crate::traits::Backend;
 use calyx_ir as ir;
@@ -46,8 +49,13 @@ impl Backend for XilinxInterfaceBackend {
         }
 
         let mem_info = toplevel.get_mem_info();
+        let burst = BurstConf::from_opts(&prog.extra_opts)?;
+        let bus_widths = mem_info
+            .iter()
+            .map(|mem| bus_data_width(mem.data_width))
+            .collect::<CalyxResult<Vec<_>>>()?;
 
-        let mut modules = vec![top_level(toplevel)];
+        let mut modules = vec![top_level(toplevel, &bus_widths)];
         for (i, mem) in mem_info.iter().enumerate() {
             modules.push(bram(
                 &format!("SINGLE_PORT_BRAM_{}", i),
@@ -68,11 +76,12 @@ impl Backend for XilinxInterfaceBackend {
         for (i, mem) in mem_info.iter().enumerate() {
             modules.push(axi::AxiInterface::memory_module(
                 &format!("Memory_controller_axi_{}", i),
-                512,
+                bus_widths[i],
                 64,
                 mem.data_width,
                 mem.total_size,
                 mem.idx_sizes[0],
+                burst,
             ))
         }
 
@@ -106,7 +115,7 @@ fn external_1d_memories_cells(comp: &ir::Component) -> Vec<ir::RRC<ir::Cell>> {
     memories
 }
 
-fn top_level(toplevel: &ir::Component) -> v::Module {
+fn top_level(toplevel: &ir::Component, bus_widths: &[u64]) -> v::Module {
     let memories = &ir::utils::external_and_ref_memories_names(toplevel);
     let mem_info = &external_1d_memories_cells(toplevel).get_mem_info();
     assert!(!memories.is_empty()); // At least 1 memory should exist within the toplevel
@@ -130,9 +139,13 @@ fn top_level(toplevel: &ir::Component) -> v::Module {
     axi4.add_ports_to(&mut module);
 
     // add an axi interface for each external memory
-    for (idx, _mem) in memories.iter().enumerate() {
-        axi::AxiInterface::memory_channels(64, 512, &format!("m{}_axi_", idx))
-            .add_ports_to(&mut module);
+    for (idx, width) in bus_widths.iter().enumerate() {
+        axi::AxiInterface::memory_channels(
+            64,
+            *width,
+            &format!("m{}_axi_", idx),
+        )
+        .add_ports_to(&mut module);
     }
 
     // wires
@@ -1,6 +1,7 @@
+use super::memory_axi::bus_data_width;
 use crate::traits::Backend;
 use calyx_ir as ir;
-use calyx_utils::CalyxResult;
+use calyx_utils::{CalyxResult, Error};
 use serde::Serialize;
 
 /// Backend that generates XML that Xilinx needs to define the address
@@ -149,7 +150,7 @@ impl Backend for XilinxXmlBackend {
             host_size: "0x4",
         }];
 
-        let memories: Vec<(String, String)> = toplevel
+        let memories: Vec<(String, String, u64)> = toplevel
             .cells
             .iter()
             .filter(|cell_ref| {
@@ -160,27 +161,37 @@ impl Backend for XilinxXmlBackend {
             })
             .enumerate()
             .map(|(i, cell_ref)| {
-                (cell_ref.borrow().name().to_string(), format!("m{}_axi", i))
+                let cell = cell_ref.borrow();
+                let width = cell.get_parameter("WIDTH").ok_or_else(|| {
+                    Error::misc(format!(
+                        "External cell `{}' has no WIDTH parameter",
+                        cell.name()
+                    ))
+                })?;
+                Ok((
+                    cell.name().to_string(),
+                    format!("m{}_axi", i),
+                    bus_data_width(width)?,
+                ))
             })
-            .collect();
+            .collect::<CalyxResult<_>>()?;
         // make the lifetime of the &str long enough
-        let memories_ref: Vec<(&str, &str)> = memories
+        let memories_ref: Vec<(&str, &str, u64)> = memories
             .iter()
-            .map(|(x, y)| (x.as_ref(), y.as_ref()))
+            .map(|(x, y, w)| (x.as_ref(), y.as_ref(), *w))
             .collect();
         let offsets: Vec<String> = (0..memories.len())
             .map(|i| format!("{:#x}", 0x18 + (8 * i)))
             .collect();
 
-        for (i, (name, axi_name)) in memories_ref.iter().enumerate() {
+        for (i, (name, axi_name, width)) in memories_ref.iter().enumerate() {
             ports.push(Port {
                 name: axi_name,
                 mode: "master",
                 range: "0xFFFFFFFFFFFFFFFF",
-                // Width should match the bus data width of memory modules
-                // described in hardware, for example see
-                // https://github.com/calyxir/calyx/blob/c2b12a0fe6b1ee3aaaae0c66e7c4619ee6c82614/src/backend/xilinx/toplevel.rs#L58
-                data_width: 512,
+                // Width should match the bus data width of the memory modules
+                // generated by the `xilinx` backend
+                data_width: *width,
                 port_type: "addressable",
                 base: "0x0",
             });
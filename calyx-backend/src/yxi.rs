@@ -0,0 +1,42 @@
+//! YXI backend for the Calyx compiler.
+//!
+//! Transforms an [`ir::Context`](crate::ir::Context) into a JSON description
+//! of the interface of the toplevel component: its memories, scalar ports,
+//! interface signals, `ref` cells and static latency.
+//! Usage: -b yxi [-o <OUTPUT_FILE>]
+
+use crate::traits::Backend;
+use calyx_ir as ir;
+use calyx_ir::utils::ProgramInterface;
+use calyx_utils::{CalyxResult, OutputFile};
+
+/// Backend that generates the YXI Interface Definition Language.
+/// YXI aims to be a description of toplevel hardware modules that we can then consume
+/// to create things like AXI wrappers on arbitrary programs
+#[derive(Default)]
+pub struct YxiBackend;
+
+impl Backend for YxiBackend {
+    fn name(&self) -> &'static str {
+        "yxi"
+    }
+
+    /// OK to run this analysis on any Calyx program
+    fn validate(_ctx: &ir::Context) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    /// Don't need to take care of this for this pass
+    fn link_externs(
+        _ctx: &ir::Context,
+        _file: &mut OutputFile,
+    ) -> CalyxResult<()> {
+        Ok(())
+    }
+
+    fn emit(ctx: &ir::Context, file: &mut OutputFile) -> CalyxResult<()> {
+        let interface = ProgramInterface::from_component(ctx.entrypoint())?;
+        serde_json::to_writer_pretty(file.get_write(), &interface)?;
+        Ok(())
+    }
+}
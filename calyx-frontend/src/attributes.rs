@@ -151,6 +151,16 @@ impl Attributes {
         self
     }
 
+    /// Iterate over the attributes and their values. Boolean attributes have
+    /// the value 1.
+    pub fn iter(&self) -> impl Iterator<Item = (Attribute, u64)> + '_ {
+        self.hinfo
+            .attrs
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .chain(self.inl.iter().map(|k| (Attribute::Bool(k), 1)))
+    }
+
     pub fn to_string_with<F>(&self, sep: &'static str, fmt: F) -> String
     where
         F: Fn(String, u64) -> String,
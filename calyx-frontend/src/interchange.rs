@@ -0,0 +1,828 @@
+//! A versioned, serializable description of Calyx programs.
+//!
+//! The [Program] schema mirrors the structure of the IR but only uses plain
+//! data so that tools written in other languages can read, transform, and
+//! write Calyx programs without going through the text syntax. A [Program] is
+//! imported by lowering it to the frontend AST, which means that it goes
+//! through exactly the same checks as a parsed program.
+//!
+//! Compiler-internal attributes (the all-caps ones) are not part of the
+//! schema since they cannot be parsed back.
+use crate::{
+    ast, Attribute, Attributes, BoolAttr, LibrarySignatures, PortDef, Workspace,
+};
+use calyx_utils::{CalyxResult, Error, GPosIdx, Id};
+use linked_hash_map::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+
+/// Attributes and their values. Boolean attributes have the value 1.
+pub type Attrs = LinkedHashMap<String, u64>;
+
+/// A complete Calyx program.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Program {
+    /// Version of the schema that the program uses.
+    pub version: u32,
+    /// Name of the toplevel component.
+    pub entrypoint: String,
+    /// Primitive definitions used by the program.
+    pub externs: Vec<Extern>,
+    pub components: Vec<Component>,
+    /// Optional opaque metadata.
+    pub metadata: Option<String>,
+}
+
+/// Primitives defined in the same file, or a single inline primitive if the
+/// `path` is missing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Extern {
+    /// Path to the file that implements the primitives. Files in the library
+    /// or next to the input program are relative to the library path or the
+    /// directory of the input program; others are absolute.
+    pub path: Option<PathBuf>,
+    /// True if the primitives are defined in the program's source file.
+    pub is_source: bool,
+    pub primitives: Vec<Primitive>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Primitive {
+    pub name: String,
+    pub params: Vec<String>,
+    pub ports: Vec<PrimitivePort>,
+    pub attributes: Attrs,
+    pub is_comb: bool,
+    pub latency: Option<u64>,
+    /// Body of inline primitives.
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrimitivePort {
+    pub name: String,
+    pub width: Width,
+    pub direction: Direction,
+    pub attributes: Attrs,
+}
+
+/// The width of a primitive port.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Width {
+    Const(u64),
+    /// The width is given by the named parameter.
+    Param(String),
+}
+
+/// The direction of a port, as seen from outside the component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Port {
+    pub name: String,
+    pub width: u64,
+    pub direction: Direction,
+    pub attributes: Attrs,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Component {
+    pub name: String,
+    /// The signature of the component, including the interface ports.
+    pub ports: Vec<Port>,
+    pub attributes: Attrs,
+    pub is_comb: bool,
+    pub latency: Option<u64>,
+    pub cells: Vec<Cell>,
+    pub groups: Vec<Group>,
+    pub comb_groups: Vec<Group>,
+    pub static_groups: Vec<StaticGroup>,
+    pub continuous_assignments: Vec<Assignment>,
+    pub control: Control,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cell {
+    pub name: String,
+    pub prototype: Prototype,
+    pub attributes: Attrs,
+    /// True for `ref` cells.
+    pub reference: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Prototype {
+    Primitive { name: String, params: Vec<u64> },
+    Component { name: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub attributes: Attrs,
+    pub assignments: Vec<Assignment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaticGroup {
+    pub name: String,
+    pub attributes: Attrs,
+    pub latency: u64,
+    pub assignments: Vec<Assignment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Assignment {
+    pub dst: PortRef,
+    pub src: Atom,
+    pub guard: Guard,
+    pub attributes: Attrs,
+}
+
+/// A reference to a port.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PortRef {
+    /// A port of the component being defined.
+    This { port: String },
+    /// A port of a cell.
+    Cell { cell: String, port: String },
+    /// A `go` or `done` hole of a group.
+    Hole { group: String, port: String },
+}
+
+/// A value that can be read.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Atom {
+    Port(PortRef),
+    Const { value: u64, width: u64 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Guard {
+    True,
+    And(Box<Guard>, Box<Guard>),
+    Or(Box<Guard>, Box<Guard>),
+    Not(Box<Guard>),
+    Port(Atom),
+    Comp {
+        op: CompOp,
+        left: Atom,
+        right: Atom,
+    },
+    /// Only valid in static groups: true during cycles `[start, end)`.
+    Timing {
+        start: u64,
+        end: u64,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum CompOp {
+    Eq,
+    Neq,
+    Gt,
+    Lt,
+    Geq,
+    Leq,
+}
+
+/// Bindings of an invoke's ports.
+pub type Bindings = Vec<(String, Atom)>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Control {
+    Empty {
+        attributes: Attrs,
+    },
+    Enable {
+        group: String,
+        attributes: Attrs,
+    },
+    Seq {
+        stmts: Vec<Control>,
+        attributes: Attrs,
+    },
+    Par {
+        stmts: Vec<Control>,
+        attributes: Attrs,
+    },
+    If {
+        port: PortRef,
+        cond: Option<String>,
+        tbranch: Box<Control>,
+        fbranch: Box<Control>,
+        attributes: Attrs,
+    },
+    While {
+        port: PortRef,
+        cond: Option<String>,
+        body: Box<Control>,
+        attributes: Attrs,
+    },
+    Repeat {
+        num_repeats: u64,
+        body: Box<Control>,
+        attributes: Attrs,
+    },
+    Invoke {
+        cell: String,
+        inputs: Bindings,
+        outputs: Bindings,
+        ref_cells: Vec<(String, String)>,
+        comb_group: Option<String>,
+        attributes: Attrs,
+    },
+    StaticEnable {
+        group: String,
+        attributes: Attrs,
+    },
+    StaticSeq {
+        stmts: Vec<Control>,
+        latency: u64,
+        attributes: Attrs,
+    },
+    StaticPar {
+        stmts: Vec<Control>,
+        latency: u64,
+        attributes: Attrs,
+    },
+    StaticIf {
+        port: PortRef,
+        tbranch: Box<Control>,
+        fbranch: Box<Control>,
+        latency: u64,
+        attributes: Attrs,
+    },
+    StaticRepeat {
+        num_repeats: u64,
+        body: Box<Control>,
+        attributes: Attrs,
+    },
+    StaticInvoke {
+        cell: String,
+        inputs: Bindings,
+        outputs: Bindings,
+        ref_cells: Vec<(String, String)>,
+        comb_group: Option<String>,
+        latency: u64,
+        attributes: Attrs,
+    },
+}
+
+impl Program {
+    /// Version of the schema implemented by this module.
+    pub const VERSION: u32 = 1;
+
+    /// Construct a [Workspace] with the components and primitives of this
+    /// program. Relative extern paths are resolved like imports: first in
+    /// `parent`, the directory of the serialized program, and then in
+    /// `lib_path`.
+    pub fn into_workspace(
+        self,
+        parent: &Path,
+        lib_path: &Path,
+    ) -> CalyxResult<Workspace> {
+        if self.version != Self::VERSION {
+            return Err(Error::misc(format!(
+                "Program uses version {} of the interchange schema but only version {} is supported",
+                self.version,
+                Self::VERSION
+            )));
+        }
+        let mut lib = LibrarySignatures::default();
+        for ext in self.externs {
+            let prims = ext
+                .primitives
+                .into_iter()
+                .map(Primitive::into_ast)
+                .collect::<CalyxResult<Vec<_>>>()?;
+            for prim in &prims {
+                if lib.find_primitive(prim.name).is_some() {
+                    return Err(Error::already_bound(
+                        prim.name,
+                        "primitive".to_string(),
+                    ));
+                }
+                if prim.body.is_some() == ext.path.is_some() {
+                    return Err(Error::misc(format!(
+                        "Primitive `{}` must either have a body or be defined in an extern file",
+                        prim.name
+                    )));
+                }
+            }
+            match ext.path {
+                Some(path) => {
+                    let path = resolve_extern(&path, parent, lib_path)?;
+                    if lib.extern_paths().contains(&&path) {
+                        return Err(Error::misc(format!(
+                            "Extern file `{}` is defined multiple times",
+                            path.display()
+                        )));
+                    }
+                    let info = lib.add_extern(path, prims);
+                    if ext.is_source {
+                        info.set_source();
+                    }
+                }
+                None => {
+                    for prim in prims {
+                        let info = lib.add_inline_primitive(prim);
+                        if ext.is_source {
+                            info.set_source();
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut components = self
+            .components
+            .into_iter()
+            .map(Component::into_ast)
+            .collect::<CalyxResult<Vec<_>>>()?;
+        let Some(entry) = components
+            .iter_mut()
+            .find(|comp| comp.name == self.entrypoint.as_str())
+        else {
+            return Err(Error::misc(format!(
+                "Entrypoint `{}` is not one of the program's components",
+                self.entrypoint
+            )));
+        };
+        if entry.name != "main" {
+            entry.attributes.insert(BoolAttr::TopLevel, 1);
+        }
+
+        Ok(Workspace {
+            components,
+            lib,
+            metadata: self.metadata,
+            ..Default::default()
+        })
+    }
+}
+
+/// Returns the absolute location of an extern file.
+fn resolve_extern(
+    path: &Path,
+    parent: &Path,
+    lib_path: &Path,
+) -> CalyxResult<PathBuf> {
+    [parent, lib_path]
+        .iter()
+        .map(|root| root.join(path))
+        .find(|path| path.exists())
+        .and_then(|path| path.canonicalize().ok())
+        .ok_or_else(|| {
+            Error::invalid_file(format!(
+                "Extern path `{}` found neither as an absolute path, nor in the parent ({}), nor in library path ({})",
+                path.to_string_lossy(),
+                parent.to_string_lossy(),
+                lib_path.to_string_lossy()
+            ))
+        })
+}
+
+fn attributes(attrs: Attrs) -> CalyxResult<Attributes> {
+    let attrs = attrs
+        .into_iter()
+        .map(|(name, val)| Ok((name.parse::<Attribute>()?, val)))
+        .collect::<CalyxResult<Vec<_>>>()?;
+    attrs.try_into()
+}
+
+fn latency(latency: u64) -> CalyxResult<NonZeroU64> {
+    NonZeroU64::new(latency)
+        .ok_or_else(|| Error::misc("Static latencies must be positive"))
+}
+
+impl From<Direction> for crate::Direction {
+    fn from(dir: Direction) -> Self {
+        match dir {
+            Direction::Input => crate::Direction::Input,
+            Direction::Output => crate::Direction::Output,
+        }
+    }
+}
+
+impl Primitive {
+    fn into_ast(self) -> CalyxResult<crate::Primitive> {
+        let signature = self
+            .ports
+            .into_iter()
+            .map(|port| {
+                let width = match port.width {
+                    Width::Const(value) => crate::Width::Const { value },
+                    Width::Param(value) => crate::Width::Param {
+                        value: value.into(),
+                    },
+                };
+                Ok(PortDef::new(
+                    port.name,
+                    width,
+                    port.direction.into(),
+                    attributes(port.attributes)?,
+                ))
+            })
+            .collect::<CalyxResult<_>>()?;
+        Ok(crate::Primitive {
+            name: self.name.into(),
+            params: self.params.into_iter().map(Id::from).collect(),
+            signature,
+            attributes: attributes(self.attributes)?,
+            is_comb: self.is_comb,
+            latency: self.latency.map(latency).transpose()?,
+            body: self.body,
+        })
+    }
+}
+
+impl Component {
+    fn into_ast(self) -> CalyxResult<ast::ComponentDef> {
+        let signature = self
+            .ports
+            .into_iter()
+            .map(|port| {
+                Ok(PortDef::new(
+                    port.name,
+                    port.width,
+                    port.direction.into(),
+                    attributes(port.attributes)?,
+                ))
+            })
+            .collect::<CalyxResult<_>>()?;
+        let mut comp = ast::ComponentDef::new(
+            self.name,
+            self.is_comb,
+            self.latency.map(latency).transpose()?,
+            signature,
+        );
+        comp.attributes = attributes(self.attributes)?;
+        comp.cells = self
+            .cells
+            .into_iter()
+            .map(Cell::into_ast)
+            .collect::<CalyxResult<_>>()?;
+        let groups = self.groups.into_iter().map(|group| (group, false));
+        let comb_groups =
+            self.comb_groups.into_iter().map(|group| (group, true));
+        comp.groups = groups
+            .chain(comb_groups)
+            .map(|(group, is_comb)| group.into_ast(is_comb))
+            .collect::<CalyxResult<_>>()?;
+        comp.static_groups = self
+            .static_groups
+            .into_iter()
+            .map(StaticGroup::into_ast)
+            .collect::<CalyxResult<_>>()?;
+        comp.continuous_assignments = self
+            .continuous_assignments
+            .into_iter()
+            .map(Assignment::into_ast)
+            .collect::<CalyxResult<_>>()?;
+        comp.control = self.control.into_ast()?;
+        Ok(comp)
+    }
+}
+
+impl Cell {
+    fn into_ast(self) -> CalyxResult<ast::Cell> {
+        let (name, params) = match self.prototype {
+            Prototype::Primitive { name, params } => (name, params),
+            Prototype::Component { name } => (name, vec![]),
+        };
+        Ok(ast::Cell {
+            name: self.name.into(),
+            prototype: ast::Proto {
+                name: name.into(),
+                params,
+            },
+            attributes: attributes(self.attributes)?,
+            reference: self.reference,
+        })
+    }
+}
+
+impl Group {
+    fn into_ast(self, is_comb: bool) -> CalyxResult<ast::Group> {
+        Ok(ast::Group {
+            name: self.name.into(),
+            wires: self
+                .assignments
+                .into_iter()
+                .map(Assignment::into_ast)
+                .collect::<CalyxResult<_>>()?,
+            attributes: attributes(self.attributes)?,
+            is_comb,
+        })
+    }
+}
+
+impl StaticGroup {
+    fn into_ast(self) -> CalyxResult<ast::StaticGroup> {
+        Ok(ast::StaticGroup {
+            name: self.name.into(),
+            wires: self
+                .assignments
+                .into_iter()
+                .map(Assignment::into_static_ast)
+                .collect::<CalyxResult<_>>()?,
+            attributes: attributes(self.attributes)?,
+            latency: latency(self.latency)?,
+        })
+    }
+}
+
+impl Assignment {
+    fn into_ast(self) -> CalyxResult<ast::Wire> {
+        Ok(ast::Wire {
+            src: ast::Guard {
+                guard: self.guard.into_ast()?,
+                expr: self.src.into(),
+            },
+            dest: self.dst.into(),
+            attributes: attributes(self.attributes)?,
+        })
+    }
+
+    fn into_static_ast(self) -> CalyxResult<ast::StaticWire> {
+        Ok(ast::StaticWire {
+            src: ast::StaticGuard {
+                guard: self.guard.into_static_ast()?,
+                expr: self.src.into(),
+            },
+            dest: self.dst.into(),
+            attributes: attributes(self.attributes)?,
+        })
+    }
+}
+
+impl From<PortRef> for ast::Port {
+    fn from(port: PortRef) -> Self {
+        match port {
+            PortRef::This { port } => ast::Port::This { port: port.into() },
+            PortRef::Cell { cell, port } => ast::Port::Comp {
+                component: cell.into(),
+                port: port.into(),
+            },
+            PortRef::Hole { group, port } => ast::Port::Hole {
+                group: group.into(),
+                name: port.into(),
+            },
+        }
+    }
+}
+
+impl From<Atom> for ast::Atom {
+    fn from(atom: Atom) -> Self {
+        match atom {
+            Atom::Port(port) => ast::Atom::Port(port.into()),
+            Atom::Const { value, width } => ast::Atom::Num(ast::BitNum {
+                width,
+                num_type: ast::NumType::Decimal,
+                val: value,
+                span: GPosIdx::UNKNOWN,
+            }),
+        }
+    }
+}
+
+impl From<CompOp> for ast::GuardComp {
+    fn from(op: CompOp) -> Self {
+        match op {
+            CompOp::Eq => ast::GuardComp::Eq,
+            CompOp::Neq => ast::GuardComp::Neq,
+            CompOp::Gt => ast::GuardComp::Gt,
+            CompOp::Lt => ast::GuardComp::Lt,
+            CompOp::Geq => ast::GuardComp::Geq,
+            CompOp::Leq => ast::GuardComp::Leq,
+        }
+    }
+}
+
+impl Guard {
+    /// Convert a guard of a dynamic assignment. `None` represents `true`.
+    fn into_ast(self) -> CalyxResult<Option<ast::GuardExpr>> {
+        let expr = |g: Box<Guard>| {
+            Ok::<_, Error>(g.into_ast()?.unwrap_or_else(|| {
+                ast::GuardExpr::Atom(Atom::Const { value: 1, width: 1 }.into())
+            }))
+        };
+        Ok(Some(match self {
+            Guard::True => return Ok(None),
+            Guard::And(l, r) => {
+                ast::GuardExpr::And(Box::new(expr(l)?), Box::new(expr(r)?))
+            }
+            Guard::Or(l, r) => {
+                ast::GuardExpr::Or(Box::new(expr(l)?), Box::new(expr(r)?))
+            }
+            Guard::Not(g) => ast::GuardExpr::Not(Box::new(expr(g)?)),
+            Guard::Port(atom) => ast::GuardExpr::Atom(atom.into()),
+            Guard::Comp { op, left, right } => {
+                ast::GuardExpr::CompOp((op.into(), left.into(), right.into()))
+            }
+            Guard::Timing { .. } => {
+                return Err(Error::misc(
+                    "Timing guards can only be used in static groups",
+                ))
+            }
+        }))
+    }
+
+    /// Convert a guard of a static assignment. `None` represents `true`.
+    fn into_static_ast(self) -> CalyxResult<Option<ast::StaticGuardExpr>> {
+        let expr = |g: Box<Guard>| {
+            Ok::<_, Error>(g.into_static_ast()?.unwrap_or_else(|| {
+                ast::StaticGuardExpr::Atom(
+                    Atom::Const { value: 1, width: 1 }.into(),
+                )
+            }))
+        };
+        Ok(Some(match self {
+            Guard::True => return Ok(None),
+            Guard::And(l, r) => ast::StaticGuardExpr::And(
+                Box::new(expr(l)?),
+                Box::new(expr(r)?),
+            ),
+            Guard::Or(l, r) => {
+                ast::StaticGuardExpr::Or(Box::new(expr(l)?), Box::new(expr(r)?))
+            }
+            Guard::Not(g) => ast::StaticGuardExpr::Not(Box::new(expr(g)?)),
+            Guard::Port(atom) => ast::StaticGuardExpr::Atom(atom.into()),
+            Guard::Comp { op, left, right } => ast::StaticGuardExpr::CompOp((
+                op.into(),
+                left.into(),
+                right.into(),
+            )),
+            Guard::Timing { start, end } => {
+                ast::StaticGuardExpr::StaticInfo((start, end))
+            }
+        }))
+    }
+}
+
+fn bindings(bindings: Bindings) -> Vec<(Id, ast::Atom)> {
+    bindings
+        .into_iter()
+        .map(|(port, atom)| (port.into(), atom.into()))
+        .collect()
+}
+
+fn ref_cells(cells: Vec<(String, String)>) -> Vec<(Id, Id)> {
+    cells
+        .into_iter()
+        .map(|(name, cell)| (name.into(), cell.into()))
+        .collect()
+}
+
+impl Control {
+    fn into_ast(self) -> CalyxResult<ast::Control> {
+        let stmts = |stmts: Vec<Control>| {
+            stmts
+                .into_iter()
+                .map(Control::into_ast)
+                .collect::<CalyxResult<Vec<_>>>()
+        };
+        let boxed = |c: Box<Control>| c.into_ast().map(Box::new);
+        Ok(match self {
+            Control::Empty { attributes: attrs } => ast::Control::Empty {
+                attributes: attributes(attrs)?,
+            },
+            Control::Enable {
+                group,
+                attributes: attrs,
+            }
+            | Control::StaticEnable {
+                group,
+                attributes: attrs,
+            } => ast::Control::Enable {
+                comp: group.into(),
+                attributes: attributes(attrs)?,
+            },
+            Control::Seq {
+                stmts: s,
+                attributes: attrs,
+            } => ast::Control::Seq {
+                stmts: stmts(s)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::Par {
+                stmts: s,
+                attributes: attrs,
+            } => ast::Control::Par {
+                stmts: stmts(s)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::If {
+                port,
+                cond,
+                tbranch,
+                fbranch,
+                attributes: attrs,
+            } => ast::Control::If {
+                port: port.into(),
+                cond: cond.map(Id::from),
+                tbranch: boxed(tbranch)?,
+                fbranch: boxed(fbranch)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::While {
+                port,
+                cond,
+                body,
+                attributes: attrs,
+            } => ast::Control::While {
+                port: port.into(),
+                cond: cond.map(Id::from),
+                body: boxed(body)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::Repeat {
+                num_repeats,
+                body,
+                attributes: attrs,
+            } => ast::Control::Repeat {
+                num_repeats,
+                body: boxed(body)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::Invoke {
+                cell,
+                inputs,
+                outputs,
+                ref_cells: refs,
+                comb_group,
+                attributes: attrs,
+            } => ast::Control::Invoke {
+                comp: cell.into(),
+                inputs: bindings(inputs),
+                outputs: bindings(outputs),
+                attributes: attributes(attrs)?,
+                comb_group: comb_group.map(Id::from),
+                ref_cells: ref_cells(refs),
+            },
+            Control::StaticSeq {
+                stmts: s,
+                latency: l,
+                attributes: attrs,
+            } => ast::Control::StaticSeq {
+                stmts: stmts(s)?,
+                attributes: attributes(attrs)?,
+                latency: Some(latency(l)?),
+            },
+            Control::StaticPar {
+                stmts: s,
+                latency: l,
+                attributes: attrs,
+            } => ast::Control::StaticPar {
+                stmts: stmts(s)?,
+                attributes: attributes(attrs)?,
+                latency: Some(latency(l)?),
+            },
+            Control::StaticIf {
+                port,
+                tbranch,
+                fbranch,
+                latency: l,
+                attributes: attrs,
+            } => ast::Control::StaticIf {
+                port: port.into(),
+                tbranch: boxed(tbranch)?,
+                fbranch: boxed(fbranch)?,
+                attributes: attributes(attrs)?,
+                latency: Some(latency(l)?),
+            },
+            Control::StaticRepeat {
+                num_repeats,
+                body,
+                attributes: attrs,
+            } => ast::Control::StaticRepeat {
+                num_repeats,
+                body: boxed(body)?,
+                attributes: attributes(attrs)?,
+            },
+            Control::StaticInvoke {
+                cell,
+                inputs,
+                outputs,
+                ref_cells: refs,
+                comb_group,
+                latency: l,
+                attributes: attrs,
+            } => ast::Control::StaticInvoke {
+                comp: cell.into(),
+                inputs: bindings(inputs),
+                outputs: bindings(outputs),
+                attributes: attributes(attrs)?,
+                ref_cells: ref_cells(refs),
+                comb_group: comb_group.map(Id::from),
+                latency: Some(latency(l)?),
+            },
+        })
+    }
+}
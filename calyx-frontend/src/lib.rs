@@ -5,6 +5,8 @@
 //! in the `ir` module.
 
 pub mod ast;
+#[cfg(feature = "serialize")]
+pub mod interchange;
 pub mod parser;
 
 mod attribute;
@@ -258,7 +258,41 @@ impl Workspace {
             .as_ref()
             .map(|p| Self::get_parent(p))
             .unwrap_or_else(|| PathBuf::from("."));
+        Self::construct_from_namespace::<SHALLOW>(
+            ns,
+            &parent_path,
+            files,
+            lib_path,
+        )
+    }
+
+    /// Construct the Workspace for the program in `text`, which is treated as
+    /// if it were the contents of the file at `path`. Imports are resolved
+    /// relative to `path`, but the components they define are only added as
+    /// declarations, as in [Workspace::construct_shallow].
+    /// This is useful for tools that work with unsaved buffers.
+    pub fn construct_shallow_from_str(
+        text: &str,
+        path: &Path,
+        lib_path: &Path,
+    ) -> CalyxResult<Self> {
+        let ns = NamespaceDef::construct_from_str(text)?;
+        Self::construct_from_namespace::<true>(
+            ns,
+            &Self::get_parent(path),
+            vec![],
+            lib_path,
+        )
+    }
 
+    /// Construct the Workspace from the namespace `ns` of the main file, which
+    /// is located in `parent_path`, and the `files` it depends on.
+    fn construct_from_namespace<const SHALLOW: bool>(
+        ns: NamespaceDef,
+        parent_path: &Path,
+        files: Vec<PathBuf>,
+        lib_path: &Path,
+    ) -> CalyxResult<Self> {
         // Set of current dependencies and whether they are considered source files.
         let mut dependencies: Vec<(PathBuf, bool)> =
             files.into_iter().map(|p| (p, true)).collect();
@@ -3,6 +3,7 @@
 //! Passes usually have transform/analyze the components in the IR.
 use super::{Component, Id};
 use calyx_frontend::LibrarySignatures;
+use std::path::PathBuf;
 
 /// Configuration information for the backends.
 #[derive(Default)]
@@ -16,6 +17,11 @@ pub struct BackendConf {
     /// [FIRRTL backend only] Emit extmodule declarations for primtives
     /// for use with SystemVerilog implementations
     pub emit_primitive_extmodules: bool,
+    /// [FIRRTL backend only] Emit FIRRTL implementations of primitives
+    pub emit_primitive_definitions: bool,
+    /// [Interchange backends only] Directories that extern paths are written
+    /// relative to, in order of preference
+    pub extern_roots: Vec<PathBuf>,
 }
 
 /// The IR Context that represents an entire Calyx program with all of its
@@ -34,6 +40,8 @@ pub struct Context {
     pub extra_opts: Vec<String>,
     /// An optional opaque metadata string which is used by Cider
     pub metadata: Option<String>,
+    /// Number of threads that passes may use to visit components in parallel.
+    pub threads: usize,
 }
 
 impl Context {
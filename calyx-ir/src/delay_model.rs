@@ -0,0 +1,184 @@
+//! Delay models used to estimate the timing of a design.
+use crate::{Binding, Id, PortComp};
+
+/// Assigns delay estimates, in nanoseconds, to the combinational logic implied
+/// by a Calyx program.
+pub trait DelayModel {
+    /// Delay from the `input` port to the `output` port of an instance of the
+    /// primitive `name` with the given parameters. Returns `None` if the output
+    /// does not combinationally depend on the input or if the model does not
+    /// know the primitive.
+    fn primitive(
+        &self,
+        name: &str,
+        params: &Binding,
+        input: Id,
+        output: Id,
+    ) -> Option<f64>;
+
+    /// Delay through a multiplexer that selects between `inputs` values using
+    /// one-hot select signals.
+    fn mux(&self, inputs: u64) -> f64;
+
+    /// Delay through a boolean function of `inputs` 1-bit signals.
+    fn logic(&self, inputs: u64) -> f64;
+
+    /// Delay of comparing two `width` bit values.
+    fn comparison(&self, op: &PortComp, width: u64) -> f64;
+}
+
+/// Rough estimates for the fastest speed grade of AMD UltraScale+ devices.
+/// Every LUT level includes the delay of the net that drives it.
+#[derive(Default)]
+pub struct DefaultDelayModel;
+
+/// Delay of a LUT and its input net.
+const LUT: f64 = 0.5;
+/// Delay added by every CARRY8 block in a carry chain.
+const CARRY8: f64 = 0.05;
+/// Delay through an unregistered DSP multiplier.
+const DSP: f64 = 2.0;
+/// Delay of an asynchronous LUT RAM read.
+const LUTRAM: f64 = 1.0;
+
+/// Number of levels in a tree of 6-input LUTs that reduces `inputs` signals
+/// into one.
+fn lut_depth(inputs: u64) -> u64 {
+    let mut depth = 0;
+    let mut signals = inputs;
+    while signals > 1 {
+        signals = signals.div_ceil(6);
+        depth += 1;
+    }
+    depth
+}
+
+fn log2(n: u64) -> u64 {
+    n.next_power_of_two().trailing_zeros() as u64
+}
+
+fn param(params: &Binding, name: &str) -> u64 {
+    params
+        .iter()
+        .find(|(id, _)| id == name)
+        .map(|(_, v)| *v)
+        .unwrap_or(0)
+}
+
+/// Number of elements stored in a memory primitive.
+fn mem_size(params: &Binding) -> u64 {
+    params
+        .iter()
+        .filter(|(id, _)| {
+            id == "SIZE"
+                || (id.as_ref().ends_with("_SIZE")
+                    && !id.as_ref().ends_with("IDX_SIZE"))
+        })
+        .map(|(_, v)| *v)
+        .product()
+}
+
+impl DefaultDelayModel {
+    /// Delay of a `width` bit adder built using the carry chain.
+    fn adder(width: u64) -> f64 {
+        LUT + CARRY8 * width.div_ceil(8) as f64
+    }
+
+    fn multiplier(width: u64) -> f64 {
+        // Partial products from multiple DSPs are added with LUTs
+        if width.div_ceil(27) * width.div_ceil(18) > 1 {
+            DSP + Self::adder(width)
+        } else {
+            DSP
+        }
+    }
+}
+
+impl DelayModel for DefaultDelayModel {
+    fn primitive(
+        &self,
+        name: &str,
+        params: &Binding,
+        input: Id,
+        output: Id,
+    ) -> Option<f64> {
+        let w = param(params, "WIDTH");
+        let delay = match name {
+            // Wiring
+            "std_wire" | "std_slice" | "std_pad" | "std_cat"
+            | "std_bit_slice" | "std_signext" => 0.0,
+            "std_not" | "std_and" | "std_or" | "std_xor" | "std_mux" => LUT,
+            "std_add" | "std_sub" | "std_sadd" | "std_ssub" | "std_fp_add"
+            | "std_fp_sub" | "std_fp_sadd" | "std_fp_ssub" => Self::adder(w),
+            "std_eq" | "std_neq" | "std_seq" | "std_sneq" => {
+                self.comparison(&PortComp::Eq, w)
+            }
+            "std_lt" | "std_gt" | "std_le" | "std_ge" | "std_slt"
+            | "std_sgt" | "std_sle" | "std_sge" | "std_fp_gt"
+            | "std_fp_sgt" | "std_fp_slt" => self.comparison(&PortComp::Lt, w),
+            // Barrel shifters with two shift stages per LUT
+            "std_lsh" | "std_rsh" | "std_slsh" | "std_srsh" => {
+                LUT * log2(w).div_ceil(2).max(1) as f64
+            }
+            "std_unsyn_mult" | "std_unsyn_smult" | "std_const_mult" => {
+                Self::multiplier(w)
+            }
+            // The arithmetic floating point operators register their output
+            // but comparisons and conversions are combinational
+            "std_float_lt" | "std_float_le" | "std_float_gt"
+            | "std_float_ge" => self.comparison(&PortComp::Lt, w) + LUT,
+            "std_float_eq" | "std_float_neq" => {
+                self.comparison(&PortComp::Eq, w) + LUT
+            }
+            "std_float_to_int" | "std_float_to_sint" => {
+                // Shift the significand and negate or saturate the result
+                let out = param(params, "OUT_WIDTH");
+                LUT * log2(out).div_ceil(2).max(1) as f64 + Self::adder(out)
+            }
+            "std_int_to_float" | "std_sint_to_float" => {
+                // Take the magnitude, find the leading one, normalize and
+                // round the significand
+                let int = param(params, "IN_WIDTH");
+                Self::adder(int)
+                    + LUT * lut_depth(int) as f64
+                    + LUT * log2(int).div_ceil(2).max(1) as f64
+                    + Self::adder(param(params, "MANT_WIDTH") + 1)
+            }
+            // Array dividers have a subtractor for every bit of the quotient
+            "std_unsyn_div" | "std_unsyn_mod" | "std_unsyn_sdiv"
+            | "std_unsyn_smod" => w as f64 * Self::adder(w),
+            // Only the read port of combinational memories is asynchronous
+            "comb_mem_d1" | "comb_mem_d2" | "comb_mem_d3" | "comb_mem_d4" => {
+                if !(input.as_ref().starts_with("addr")
+                    && output == "read_data")
+                {
+                    return None;
+                }
+                let banks = mem_size(params).div_ceil(64);
+                LUTRAM + LUT * lut_depth(banks) as f64
+            }
+            _ => return None,
+        };
+        Some(delay)
+    }
+
+    fn mux(&self, inputs: u64) -> f64 {
+        // Every output bit is an OR of the guarded inputs
+        LUT * lut_depth(2 * inputs).max(1) as f64
+    }
+
+    fn logic(&self, inputs: u64) -> f64 {
+        LUT * lut_depth(inputs) as f64
+    }
+
+    fn comparison(&self, op: &PortComp, width: u64) -> f64 {
+        match op {
+            // Each LUT compares three pairs of bits
+            PortComp::Eq | PortComp::Neq => {
+                LUT * (1 + lut_depth(width.div_ceil(3))) as f64
+            }
+            // Magnitude comparisons use the carry chain
+            _ => Self::adder(width),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Conversion between [Context] and the serializable [Program] schema defined
+//! in [calyx_frontend::interchange].
+use crate::{
+    self as ir, from_ast, Attributes, CellType, Context, PortComp,
+    PrimitiveInfo, RRC,
+};
+use calyx_frontend::interchange::{self as ic, Attrs, Program};
+use calyx_utils::CalyxResult;
+use std::path::{Path, PathBuf};
+
+/// Build a [Context] from a serialized program. See
+/// [Program::into_workspace] for how `parent` and `lib_path` are used.
+pub fn import(
+    program: Program,
+    parent: &Path,
+    lib_path: &Path,
+) -> CalyxResult<Context> {
+    from_ast::ast_to_ir(program.into_workspace(parent, lib_path)?)
+}
+
+/// Export the program in the [Context] to the serializable schema. Extern
+/// paths are written relative to the first of the [extern
+/// roots](ir::BackendConf::extern_roots) that contains them.
+pub fn export(ctx: &Context) -> Program {
+    let externs = ctx
+        .lib
+        .prim_infos()
+        .iter()
+        .map(|info| match info {
+            PrimitiveInfo::Extern {
+                path,
+                primitives,
+                is_source,
+            } => ic::Extern {
+                path: Some(relative_path(path, &ctx.bc.extern_roots)),
+                is_source: *is_source,
+                primitives: primitives.values().map(primitive).collect(),
+            },
+            PrimitiveInfo::Inline {
+                primitive: prim,
+                is_source,
+            } => ic::Extern {
+                path: None,
+                is_source: *is_source,
+                primitives: vec![primitive(prim)],
+            },
+        })
+        .collect();
+
+    Program {
+        version: Program::VERSION,
+        entrypoint: ctx.entrypoint.to_string(),
+        externs,
+        components: ctx.components.iter().map(component).collect(),
+        metadata: ctx.metadata.clone(),
+    }
+}
+
+/// Returns `path` relative to the first of `roots` that contains it.
+fn relative_path(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .find_map(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Attributes that can be parsed back. Internal attributes are dropped.
+fn attributes(attrs: &Attributes) -> Attrs {
+    attrs
+        .iter()
+        .filter(|(attr, _)| !matches!(attr, ir::Attribute::Internal(_)))
+        .map(|(attr, val)| (attr.to_string(), val))
+        .collect()
+}
+
+fn direction(dir: &ir::Direction) -> ic::Direction {
+    match dir {
+        ir::Direction::Input => ic::Direction::Input,
+        ir::Direction::Output => ic::Direction::Output,
+        ir::Direction::Inout => {
+            unreachable!("Inout ports cannot be serialized")
+        }
+    }
+}
+
+fn primitive(prim: &ir::Primitive) -> ic::Primitive {
+    let ports = prim
+        .signature
+        .iter()
+        .map(|pd| ic::PrimitivePort {
+            name: pd.name().to_string(),
+            width: match &pd.width {
+                ir::Width::Const { value } => ic::Width::Const(*value),
+                ir::Width::Param { value } => {
+                    ic::Width::Param(value.to_string())
+                }
+            },
+            direction: direction(&pd.direction),
+            attributes: attributes(&pd.attributes),
+        })
+        .collect();
+    ic::Primitive {
+        name: prim.name.to_string(),
+        params: prim.params.iter().map(|p| p.to_string()).collect(),
+        ports,
+        attributes: attributes(&prim.attributes),
+        is_comb: prim.is_comb,
+        latency: prim.latency.map(|l| l.get()),
+        body: prim.body.clone(),
+    }
+}
+
+fn component(comp: &ir::Component) -> ic::Component {
+    let ports = comp
+        .signature
+        .borrow()
+        .ports
+        .iter()
+        .map(|port| {
+            let port = port.borrow();
+            ic::Port {
+                name: port.name.to_string(),
+                width: port.width,
+                // The signature stores the ports in reversed direction.
+                direction: direction(&port.direction.reverse()),
+                attributes: attributes(&port.attributes),
+            }
+        })
+        .collect();
+
+    let cells = comp
+        .cells
+        .iter()
+        .filter_map(|cell| {
+            let cell = cell.borrow();
+            let prototype = match &cell.prototype {
+                CellType::Primitive {
+                    name,
+                    param_binding,
+                    ..
+                } => ic::Prototype::Primitive {
+                    name: name.to_string(),
+                    params: param_binding.iter().map(|(_, v)| *v).collect(),
+                },
+                CellType::Component { name } => ic::Prototype::Component {
+                    name: name.to_string(),
+                },
+                CellType::ThisComponent | CellType::Constant { .. } => {
+                    return None
+                }
+            };
+            Some(ic::Cell {
+                name: cell.name().to_string(),
+                prototype,
+                attributes: attributes(&cell.attributes),
+                reference: cell.is_reference(),
+            })
+        })
+        .collect();
+
+    let groups = comp
+        .get_groups()
+        .iter()
+        .map(|group| {
+            let group = group.borrow();
+            ic::Group {
+                name: group.name().to_string(),
+                attributes: attributes(&group.attributes),
+                assignments: group.assignments.iter().map(assignment).collect(),
+            }
+        })
+        .collect();
+    let comb_groups = comp
+        .comb_groups
+        .iter()
+        .map(|group| {
+            let group = group.borrow();
+            ic::Group {
+                name: group.name().to_string(),
+                attributes: attributes(&group.attributes),
+                assignments: group.assignments.iter().map(assignment).collect(),
+            }
+        })
+        .collect();
+    let static_groups = comp
+        .get_static_groups()
+        .iter()
+        .map(|group| {
+            let group = group.borrow();
+            ic::StaticGroup {
+                name: group.name().to_string(),
+                attributes: attributes(&group.attributes),
+                latency: group.get_latency(),
+                assignments: group.assignments.iter().map(assignment).collect(),
+            }
+        })
+        .collect();
+
+    ic::Component {
+        name: comp.name.to_string(),
+        ports,
+        attributes: attributes(&comp.attributes),
+        is_comb: comp.is_comb,
+        latency: comp.latency.map(|l| l.get()),
+        cells,
+        groups,
+        comb_groups,
+        static_groups,
+        continuous_assignments: comp
+            .continuous_assignments
+            .iter()
+            .map(assignment)
+            .collect(),
+        control: control(&comp.control.borrow()),
+    }
+}
+
+fn port_ref(port: &RRC<ir::Port>) -> ic::PortRef {
+    let port = port.borrow();
+    let name = port.name.to_string();
+    match &port.parent {
+        ir::PortParent::Cell(cell) => {
+            let cell = cell.upgrade();
+            let cell = cell.borrow();
+            if cell.is_this() {
+                ic::PortRef::This { port: name }
+            } else {
+                ic::PortRef::Cell {
+                    cell: cell.name().to_string(),
+                    port: name,
+                }
+            }
+        }
+        ir::PortParent::Group(group) => ic::PortRef::Hole {
+            group: group.upgrade().borrow().name().to_string(),
+            port: name,
+        },
+        ir::PortParent::StaticGroup(group) => ic::PortRef::Hole {
+            group: group.upgrade().borrow().name().to_string(),
+            port: name,
+        },
+    }
+}
+
+fn atom(port: &RRC<ir::Port>) -> ic::Atom {
+    let parent = match &port.borrow().parent {
+        ir::PortParent::Cell(cell) => Some(cell.upgrade()),
+        _ => None,
+    };
+    if let Some(cell) = parent {
+        if let CellType::Constant { val, width } = cell.borrow().prototype {
+            return ic::Atom::Const { value: val, width };
+        }
+    }
+    ic::Atom::Port(port_ref(port))
+}
+
+/// Guards that can carry extra information.
+trait ExportInfo {
+    fn export(&self) -> ic::Guard;
+}
+
+impl ExportInfo for ir::Nothing {
+    fn export(&self) -> ic::Guard {
+        unreachable!("dynamic guards do not carry extra information")
+    }
+}
+
+impl ExportInfo for ir::StaticTiming {
+    fn export(&self) -> ic::Guard {
+        let (start, end) = self.get_interval();
+        ic::Guard::Timing { start, end }
+    }
+}
+
+fn guard<T: ExportInfo>(guard: &ir::Guard<T>) -> ic::Guard {
+    let boxed = |g: &ir::Guard<T>| Box::new(self::guard(g));
+    match guard {
+        ir::Guard::True => ic::Guard::True,
+        ir::Guard::And(l, r) => ic::Guard::And(boxed(l), boxed(r)),
+        ir::Guard::Or(l, r) => ic::Guard::Or(boxed(l), boxed(r)),
+        ir::Guard::Not(g) => ic::Guard::Not(boxed(g)),
+        ir::Guard::Port(p) => ic::Guard::Port(atom(p)),
+        ir::Guard::CompOp(op, l, r) => ic::Guard::Comp {
+            op: match op {
+                PortComp::Eq => ic::CompOp::Eq,
+                PortComp::Neq => ic::CompOp::Neq,
+                PortComp::Gt => ic::CompOp::Gt,
+                PortComp::Lt => ic::CompOp::Lt,
+                PortComp::Geq => ic::CompOp::Geq,
+                PortComp::Leq => ic::CompOp::Leq,
+            },
+            left: atom(l),
+            right: atom(r),
+        },
+        ir::Guard::Info(info) => info.export(),
+    }
+}
+
+fn assignment<T: ExportInfo>(assign: &ir::Assignment<T>) -> ic::Assignment {
+    ic::Assignment {
+        dst: port_ref(&assign.dst),
+        src: atom(&assign.src),
+        guard: guard(&assign.guard),
+        attributes: attributes(&assign.attributes),
+    }
+}
+
+fn bindings(ports: &[(ir::Id, RRC<ir::Port>)]) -> ic::Bindings {
+    ports
+        .iter()
+        .map(|(name, port)| (name.to_string(), atom(port)))
+        .collect()
+}
+
+fn ref_cells(cells: &[(ir::Id, RRC<ir::Cell>)]) -> Vec<(String, String)> {
+    cells
+        .iter()
+        .map(|(name, cell)| {
+            (name.to_string(), cell.borrow().name().to_string())
+        })
+        .collect()
+}
+
+fn comb_group(group: &Option<RRC<ir::CombGroup>>) -> Option<String> {
+    group.as_ref().map(|g| g.borrow().name().to_string())
+}
+
+fn control(con: &ir::Control) -> ic::Control {
+    let boxed = |c: &ir::Control| Box::new(control(c));
+    match con {
+        ir::Control::Empty(ir::Empty { attributes: attrs }) => {
+            ic::Control::Empty {
+                attributes: attributes(attrs),
+            }
+        }
+        ir::Control::Enable(en) => ic::Control::Enable {
+            group: en.group.borrow().name().to_string(),
+            attributes: attributes(&en.attributes),
+        },
+        ir::Control::Seq(seq) => ic::Control::Seq {
+            stmts: seq.stmts.iter().map(control).collect(),
+            attributes: attributes(&seq.attributes),
+        },
+        ir::Control::Par(par) => ic::Control::Par {
+            stmts: par.stmts.iter().map(control).collect(),
+            attributes: attributes(&par.attributes),
+        },
+        ir::Control::If(cif) => ic::Control::If {
+            port: port_ref(&cif.port),
+            cond: comb_group(&cif.cond),
+            tbranch: boxed(&cif.tbranch),
+            fbranch: boxed(&cif.fbranch),
+            attributes: attributes(&cif.attributes),
+        },
+        ir::Control::While(wh) => ic::Control::While {
+            port: port_ref(&wh.port),
+            cond: comb_group(&wh.cond),
+            body: boxed(&wh.body),
+            attributes: attributes(&wh.attributes),
+        },
+        ir::Control::Repeat(rep) => ic::Control::Repeat {
+            num_repeats: rep.num_repeats,
+            body: boxed(&rep.body),
+            attributes: attributes(&rep.attributes),
+        },
+        ir::Control::Invoke(inv) => ic::Control::Invoke {
+            cell: inv.comp.borrow().name().to_string(),
+            inputs: bindings(&inv.inputs),
+            outputs: bindings(&inv.outputs),
+            ref_cells: ref_cells(&inv.ref_cells),
+            comb_group: comb_group(&inv.comb_group),
+            attributes: attributes(&inv.attributes),
+        },
+        ir::Control::Static(sc) => static_control(sc),
+    }
+}
+
+fn static_control(sc: &ir::StaticControl) -> ic::Control {
+    let boxed = |c: &ir::StaticControl| Box::new(static_control(c));
+    match sc {
+        ir::StaticControl::Empty(ir::Empty { attributes: attrs }) => {
+            ic::Control::Empty {
+                attributes: attributes(attrs),
+            }
+        }
+        ir::StaticControl::Enable(en) => ic::Control::StaticEnable {
+            group: en.group.borrow().name().to_string(),
+            attributes: attributes(&en.attributes),
+        },
+        ir::StaticControl::Seq(seq) => ic::Control::StaticSeq {
+            stmts: seq.stmts.iter().map(static_control).collect(),
+            latency: seq.latency,
+            attributes: attributes(&seq.attributes),
+        },
+        ir::StaticControl::Par(par) => ic::Control::StaticPar {
+            stmts: par.stmts.iter().map(static_control).collect(),
+            latency: par.latency,
+            attributes: attributes(&par.attributes),
+        },
+        ir::StaticControl::If(sif) => ic::Control::StaticIf {
+            port: port_ref(&sif.port),
+            tbranch: boxed(&sif.tbranch),
+            fbranch: boxed(&sif.fbranch),
+            latency: sif.latency,
+            attributes: attributes(&sif.attributes),
+        },
+        ir::StaticControl::Repeat(rep) => ic::Control::StaticRepeat {
+            num_repeats: rep.num_repeats,
+            body: boxed(&rep.body),
+            attributes: attributes(&rep.attributes),
+        },
+        ir::StaticControl::Invoke(inv) => ic::Control::StaticInvoke {
+            cell: inv.comp.borrow().name().to_string(),
+            inputs: bindings(&inv.inputs),
+            outputs: bindings(&inv.outputs),
+            ref_cells: ref_cells(&inv.ref_cells),
+            comb_group: comb_group(&inv.comb_group),
+            latency: inv.latency,
+            attributes: attributes(&inv.attributes),
+        },
+    }
+}
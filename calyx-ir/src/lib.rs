@@ -12,6 +12,7 @@ mod common;
 mod component;
 mod context;
 mod control;
+mod delay_model;
 mod flat_guard;
 mod guard;
 mod printer;
@@ -32,6 +33,7 @@ pub use control::{
     Seq, StaticControl, StaticEnable, StaticIf, StaticInvoke, StaticPar,
     StaticRepeat, StaticSeq, While,
 };
+pub use delay_model::{DefaultDelayModel, DelayModel};
 pub use flat_guard::{FlatGuard, GuardPool, GuardRef};
 pub use guard::{Guard, Nothing, PortComp, StaticTiming};
 pub use printer::Printer;
@@ -58,4 +60,8 @@ mod macros;
 /// Serializer methods for IR nodes.
 pub mod serializers;
 
+/// Versioned schema for exchanging programs with other tools.
+#[cfg(feature = "serialize")]
+pub mod interchange;
+
 pub mod utils;
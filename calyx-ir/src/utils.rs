@@ -1,10 +1,14 @@
 //! Helpers used to examine calyx programs. Used in Xilinx and Yxi backends among others.
-use super::{BoolAttr, Cell, Component, RRC};
-use calyx_utils::Id;
+use super::{
+    Attribute, BoolAttr, Cell, CellType, Component, Direction, GetAttributes,
+    NumAttr, Port, RRC,
+};
+use calyx_utils::{CalyxResult, Error, Id};
 #[cfg(feature = "serialize")]
 use serde::Serialize;
 
-// Returns Vec<String> of `@external` or `ref` memory names
+// Returns Vec<String> of `@external` or `ref` memory names. Like
+// [external_and_ref_memories_cells], this skips cells that are not memories.
 pub fn external_and_ref_memories_names(comp: &Component) -> Vec<String> {
     external_and_ref_memories_cells(comp)
         .iter()
@@ -13,20 +17,44 @@ pub fn external_and_ref_memories_names(comp: &Component) -> Vec<String> {
 }
 
 /// Gets all memory cells in top level marked `@external` or `ref`.
+/// Other `@external` and `ref` cells, such as registers or `ref` components,
+/// are skipped so that the result lines up with [Component::get_mem_info].
 pub fn external_and_ref_memories_cells(comp: &Component) -> Vec<RRC<Cell>> {
+    external_and_ref_cells(comp)
+        .filter(|cell_ref| is_memory(&cell_ref.borrow()))
+        .collect()
+}
+
+/// Gets all cells in `comp` marked `@external` or `ref`.
+fn external_and_ref_cells(
+    comp: &Component,
+) -> impl Iterator<Item = RRC<Cell>> + '_ {
     comp.cells
         .iter()
-        // find external and ref memories
         .filter(|cell_ref| {
             let cell = cell_ref.borrow();
             cell.attributes.has(BoolAttr::External) || cell.is_reference()
         })
         .cloned()
-        .collect()
+}
+
+/// Returns true if `cell` is an instance of one of the `std` memories.
+fn is_memory(cell: &Cell) -> bool {
+    match &cell.prototype {
+        CellType::Primitive { name, .. } => {
+            let name = name.as_ref();
+            ["comb", "seq", "dyn"].iter().any(|kind| {
+                name.strip_prefix(kind)
+                    .and_then(|rest| rest.strip_prefix("_mem_d"))
+                    .is_some_and(|dims| matches!(dims, "1" | "2" | "3" | "4"))
+            })
+        }
+        _ => false,
+    }
 }
 
 #[cfg_attr(feature = "serialize", derive(Serialize))]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MemoryType {
     Combinational,
     Sequential,
@@ -103,6 +131,204 @@ impl GetMemInfo for Component {
     }
 }
 
+/// Description of the interface that the toplevel component of a program
+/// exposes. This is the model that the YXI backend serializes and that AXI
+/// wrappers are generated from.
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct ProgramInterface {
+    pub toplevel: String,
+    pub memories: Vec<Memory>,
+    /// Names of the interface signals of the toplevel
+    pub signals: Signals,
+    /// Scalar input ports, excluding the interface signals
+    pub inputs: Vec<ScalarPort>,
+    /// Scalar output ports, excluding the interface signals
+    pub outputs: Vec<ScalarPort>,
+    /// `@external` and `ref` registers
+    pub registers: Vec<Register>,
+    /// `ref` cells that are neither memories nor registers
+    pub ref_cells: Vec<RefCellInterface>,
+    /// Latency of the toplevel if it is a static component
+    pub latency: Option<u64>,
+}
+
+/// An `@external` or `ref` memory of the toplevel component
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Memory {
+    pub name: String,
+    pub memory_type: MemoryType,
+    pub data_width: u64,
+    pub dimensions: u64,
+    pub dimension_sizes: Vec<u64>,
+    pub total_size: u64, //number of cells in memory
+    pub idx_sizes: Vec<u64>,
+}
+
+/// Names of the ports that carry the clock, reset, go and done signals.
+/// Missing signals are `None`, i.e. static components may not have a `done`.
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Signals {
+    pub clk: Option<String>,
+    pub reset: Option<String>,
+    pub go: Option<String>,
+    pub done: Option<String>,
+}
+
+/// A port with a name and a width
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct ScalarPort {
+    pub name: String,
+    pub width: u64,
+}
+
+/// An `@external` or `ref` instance of `std_reg`
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Register {
+    pub name: String,
+    pub width: u64,
+}
+
+/// Any other `ref` cell of the toplevel component
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct RefCellInterface {
+    pub name: String,
+    /// Name of the primitive or component that the cell instantiates
+    pub prototype: String,
+    /// Ports of the cell. Inputs are driven by the toplevel.
+    pub ports: Vec<RefCellInterfacePort>,
+}
+
+/// A port of a [RefCellInterface]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct RefCellInterfacePort {
+    pub name: String,
+    pub width: u64,
+    pub direction: Direction,
+}
+
+impl ProgramInterface {
+    /// Describe the interface of `comp`. Fails if an `@external` or `ref`
+    /// register does not have a `WIDTH` parameter.
+    pub fn from_component(comp: &Component) -> CalyxResult<Self> {
+        let memories = external_and_ref_memories_names(comp)
+            .into_iter()
+            .zip(comp.get_mem_info())
+            .map(|(name, info)| Memory {
+                name,
+                memory_type: info.memory_type,
+                data_width: info.data_width,
+                dimensions: info.dimensions,
+                dimension_sizes: info.dimension_sizes,
+                total_size: info.total_size,
+                idx_sizes: info.idx_sizes,
+            })
+            .collect();
+
+        let sig = comp.signature.borrow();
+        let interface_port = |port: &Port| {
+            let attrs = port.get_attributes();
+            attrs.has(BoolAttr::Clk)
+                || attrs.has(BoolAttr::Reset)
+                || attrs.has(NumAttr::Go)
+                || attrs.has(NumAttr::Done)
+        };
+        // The signature has the opposite directions of the component's ports
+        let scalars = |dir: Direction| {
+            sig.ports()
+                .iter()
+                .map(|port| port.borrow())
+                .filter(|port| port.direction == dir && !interface_port(port))
+                .map(|port| ScalarPort {
+                    name: port.name.to_string(),
+                    width: port.width,
+                })
+                .collect()
+        };
+        let signal = |attr: Attribute| {
+            sig.find_all_with_attr(attr)
+                .next()
+                .map(|port| port.borrow().name.to_string())
+        };
+        let signals = Signals {
+            clk: signal(BoolAttr::Clk.into()),
+            reset: signal(BoolAttr::Reset.into()),
+            go: signal(NumAttr::Go.into()),
+            done: signal(NumAttr::Done.into()),
+        };
+
+        let mut registers = vec![];
+        let mut ref_cells = vec![];
+        for cell_ref in external_and_ref_cells(comp) {
+            let cell = cell_ref.borrow();
+            if is_memory(&cell) {
+                continue;
+            }
+            let name = cell.name().to_string();
+            if cell.is_primitive(Some("std_reg")) {
+                let Some(width) = cell.get_parameter("WIDTH") else {
+                    return Err(Error::malformed_structure(format!(
+                        "Register `{name}' of the interface does not have a `WIDTH' parameter"
+                    ))
+                    .with_pos(&cell.attributes));
+                };
+                registers.push(Register { name, width });
+            } else if cell.is_reference() {
+                ref_cells.push(RefCellInterface {
+                    name,
+                    prototype: cell
+                        .type_name()
+                        .map(|name| name.to_string())
+                        .unwrap_or_default(),
+                    ports: cell
+                        .ports()
+                        .iter()
+                        .map(|port| {
+                            let port = port.borrow();
+                            RefCellInterfacePort {
+                                name: port.name.to_string(),
+                                width: port.width,
+                                direction: port.direction.clone(),
+                            }
+                        })
+                        .collect(),
+                });
+            }
+        }
+
+        Ok(ProgramInterface {
+            toplevel: comp.name.to_string(),
+            memories,
+            signals,
+            inputs: scalars(Direction::Output),
+            outputs: scalars(Direction::Input),
+            registers,
+            ref_cells,
+            latency: comp.latency.map(|latency| latency.get()),
+        })
+    }
+}
+
+impl Memory {
+    /// Name of the primitive that implements this memory, i.e. `seq_mem_d2`
+    pub fn primitive(&self) -> String {
+        let kind = match self.memory_type {
+            MemoryType::Combinational => "comb",
+            MemoryType::Sequential => "seq",
+            MemoryType::Dynamic => "dyn",
+        };
+        format!("{kind}_mem_d{}", self.dimensions)
+    }
+
+    /// Parameters of the memory primitive in the order they are declared:
+    /// the width, the size of every dimension and the width of every index.
+    pub fn parameters(&self) -> Vec<u64> {
+        std::iter::once(self.data_width)
+            .chain(self.dimension_sizes.iter().copied())
+            .chain(self.idx_sizes.iter().copied())
+            .collect()
+    }
+}
+
 fn dimension_count(mem_id: Id) -> u64 {
     let mem_name = mem_id.as_ref();
 
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tower_lsp::lsp_types as lspt;
+use tree_sitter as ts;
+
+use crate::{convert::Point, document::Document};
+
+/// The error for a group that never writes to its `done` hole
+static MISSING_DONE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"No writes to the `done' hole for group `(.+)'").unwrap()
+});
+
+/// The error for a memory that is only read from or only written to
+static UNUSED_MEMORY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"Only (reads|writes) performed on memory `(.+)'").unwrap()
+});
+
+pub trait CodeActionProvider {
+    /// Return quick fixes for the errors described by `diagnostics`.
+    fn code_actions(
+        &self,
+        diagnostics: &[lspt::Diagnostic],
+    ) -> Vec<lspt::CodeAction>;
+}
+
+impl CodeActionProvider for Document {
+    fn code_actions(
+        &self,
+        diagnostics: &[lspt::Diagnostic],
+    ) -> Vec<lspt::CodeAction> {
+        diagnostics
+            .iter()
+            .filter_map(|diag| {
+                let point: Point = diag.range.start.into();
+                let node = self.node_at_point(&point)?;
+                if let Some(caps) = MISSING_DONE.captures(&diag.message) {
+                    self.add_done_condition(node, &caps[1])
+                } else if let Some(caps) = UNUSED_MEMORY.captures(&diag.message)
+                {
+                    self.add_external(node, &caps[2])
+                } else {
+                    None
+                }
+                .map(|(title, edit)| lspt::CodeAction {
+                    title,
+                    kind: Some(lspt::CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(lspt::WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            self.url.clone(),
+                            vec![edit],
+                        )])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+impl Document {
+    /// Assign the `done` condition of the group `name` to the `done` port of
+    /// the first cell that the group starts by writing to its `write_en` or
+    /// `go` port.
+    fn add_done_condition(
+        &self,
+        node: ts::Node,
+        name: &str,
+    ) -> Option<(String, lspt::TextEdit)> {
+        let group = self
+            .enclosing_groups(node)
+            .find(|g| self.node_text(g) == name)
+            .and_then(|g| g.parent())?;
+        let ports = self.captures(
+            group,
+            "(wire_assignment (lhs (port (ident) @cell (ident) @port)))",
+        );
+        let cell = ports["cell"]
+            .iter()
+            .zip(&ports["port"])
+            .find(|(_, port)| matches!(self.node_text(port), "write_en" | "go"))
+            .map(|(cell, _)| self.node_text(cell))?;
+
+        let assign = format!("{name}[done] = {cell}.done;");
+        let end = group.end_position();
+        let line = self.text().lines().nth(end.row)?;
+        let before_brace = &line[..end.column.saturating_sub(1)];
+        let (pos, text) = if before_brace.trim().is_empty() {
+            // the closing brace is on its own line, so add a new line
+            let indent = self
+                .text()
+                .lines()
+                .nth(group.start_position().row)?
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .collect::<String>();
+            (Point::new(end.row, 0), format!("{indent}  {assign}\n"))
+        } else {
+            (Point::new(end.row, end.column - 1), format!("{assign} "))
+        };
+        Some((
+            format!("Add `{assign}'"),
+            lspt::TextEdit::new(
+                lspt::Range::new(pos.clone().into(), pos.into()),
+                text,
+            ),
+        ))
+    }
+
+    /// Mark the memory `name` as `@external`
+    fn add_external(
+        &self,
+        node: ts::Node,
+        name: &str,
+    ) -> Option<(String, lspt::TextEdit)> {
+        let cell = self
+            .enclosing_cells(node)
+            .find(|c| self.node_text(c) == name)
+            .and_then(|c| c.parent())?;
+        let pos: Point = cell.start_position().into();
+        Some((
+            format!("Add @external to `{name}'"),
+            lspt::TextEdit::new(
+                lspt::Range::new(pos.clone().into(), pos.into()),
+                "@external ".to_string(),
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Diagnostic;
+    use crate::test_utils::{lib_path, write_files};
+
+    /// Save `text` as `name`.futil, and return the document for it along with
+    /// the quick fixes for the diagnostics that the compiler reports.
+    fn quick_fixes(
+        name: &str,
+        text: &str,
+    ) -> (Document, Vec<lspt::CodeAction>) {
+        let dir = write_files(name, &[("main.futil", text)]);
+        let path = dir.join("main.futil");
+        let doc = Document::new_with_text(
+            lspt::Url::from_file_path(&path).unwrap(),
+            text,
+        );
+        let diags = Diagnostic::did_save(&path, &lib_path())
+            .into_iter()
+            .flat_map(|diag| diag.into_lspt_diagnostics(&doc))
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(dir).unwrap();
+        let actions = doc.code_actions(&diags);
+        (doc, actions)
+    }
+
+    /// The single edit of `action`
+    fn edit(doc: &Document, action: &lspt::CodeAction) -> lspt::TextEdit {
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let [edit] = changes[&doc.url].as_slice() else {
+            panic!("expected a single edit: {changes:?}")
+        };
+        edit.clone()
+    }
+
+    #[test]
+    fn missing_done() {
+        let (doc, actions) = quick_fixes(
+            "missing-done",
+            r#"import "primitives/core.futil";
+component main() -> () {
+  cells { r = std_reg(32); }
+  wires {
+    group write {
+      r.in = 32'd1;
+      r.write_en = 1'd1;
+    }
+  }
+  control { write; }
+}
+"#,
+        );
+        let [action] = actions.as_slice() else {
+            panic!("expected a single quick fix: {actions:?}")
+        };
+        assert_eq!(action.title, "Add `write[done] = r.done;'");
+        assert_eq!(
+            edit(&doc, action),
+            lspt::TextEdit::new(
+                lspt::Range::new(
+                    lspt::Position::new(7, 0),
+                    lspt::Position::new(7, 0)
+                ),
+                "      write[done] = r.done;\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn unused_memory() {
+        let (doc, actions) = quick_fixes(
+            "unused-memory",
+            r#"import "primitives/memories/comb.futil";
+component main() -> () {
+  cells { m = comb_mem_d1(32, 1, 1); }
+  wires {
+    group write {
+      m.addr0 = 1'd0;
+      m.write_data = 32'd1;
+      m.write_en = 1'd1;
+      write[done] = m.done;
+    }
+  }
+  control { write; }
+}
+"#,
+        );
+        let [action] = actions.as_slice() else {
+            panic!("expected a single quick fix: {actions:?}")
+        };
+        assert_eq!(action.title, "Add @external to `m'");
+        assert_eq!(
+            edit(&doc, action),
+            lspt::TextEdit::new(
+                lspt::Range::new(
+                    lspt::Position::new(2, 10),
+                    lspt::Position::new(2, 10)
+                ),
+                "@external ".to_string()
+            )
+        );
+    }
+}
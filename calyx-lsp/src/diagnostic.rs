@@ -1,10 +1,12 @@
 use std::path::Path;
 use tower_lsp::lsp_types::{self as lspt};
 
+use calyx_ir as ir;
 use calyx_opt::{
     passes::{Papercut, SynthesisPapercut, WellFormed},
-    traversal::{ConstructVisitor, DiagnosticPass, Visitor},
+    traversal::{ConstructVisitor, DiagnosticPass, Named, Visitor},
 };
+use calyx_utils::{CalyxResult, Error};
 use resolve_path::PathResolveExt;
 
 use crate::document::Document;
@@ -14,7 +16,6 @@ pub struct Diagnostic;
 /// A Calyx error message
 #[derive(Debug)]
 pub struct CalyxError {
-    #[allow(unused)]
     pub file_name: String,
     pub pos_start: usize,
     pub pos_end: usize,
@@ -24,80 +25,74 @@ pub struct CalyxError {
 }
 
 impl Diagnostic {
-    /// Run the `calyx` compiler on `path` with libraries at `lib_path`
+    /// Run the `calyx` compiler on `path` with libraries at `lib_path`.
+    /// This constructs the full workspace, lowers it into the IR and runs the
+    /// diagnostic passes from the `validate` alias over it. Only errors that
+    /// are located in `path`, or that don't have a location, are returned.
     pub fn did_save(path: &Path, lib_path: &Path) -> Vec<CalyxError> {
-        calyx_frontend::Workspace::construct_shallow(
+        let mut diags = vec![];
+        let res = calyx_frontend::Workspace::construct(
             &Some(path.to_path_buf()),
             lib_path.resolve().as_ref(),
         )
         .and_then(calyx_ir::from_ast::ast_to_ir)
         .and_then(|mut ctx| {
-            let mut wellformed = <WellFormed as ConstructVisitor>::from(&ctx)?;
-            wellformed.do_pass(&mut ctx)?;
-
-            let mut diag_papercut = <Papercut as ConstructVisitor>::from(&ctx)?;
-            diag_papercut.do_pass(&mut ctx)?;
-
-            let mut synth_papercut =
-                <SynthesisPapercut as ConstructVisitor>::from(&ctx)?;
-            synth_papercut.do_pass(&mut ctx)?;
-
-            let warnings = wellformed
-                .diagnostics()
-                .warning_iter()
-                .chain(diag_papercut.diagnostics().warning_iter())
-                .chain(synth_papercut.diagnostics().warning_iter())
-                .map(|e| {
-                    let (file_name, pos_start, pos_end) = e.location();
-                    let msg = e.message();
-                    let annotations = e.annotations();
-                    CalyxError {
-                        file_name: file_name.to_string(),
-                        pos_start,
-                        pos_end,
-                        msg,
-                        annotations,
-                        severity: lspt::DiagnosticSeverity::WARNING,
-                    }
-                });
+            Self::run_pass::<WellFormed>(&mut ctx, &mut diags)?;
+            Self::run_pass::<Papercut>(&mut ctx, &mut diags)?;
+            Self::run_pass::<SynthesisPapercut>(&mut ctx, &mut diags)
+        });
+        if let Err(e) = res {
+            diags.push(CalyxError::new(&e, lspt::DiagnosticSeverity::ERROR));
+        }
+
+        let file = path.canonicalize().ok();
+        diags
+            .into_iter()
+            .filter(|diag| {
+                diag.file_name == "unknown"
+                    || Path::new(&diag.file_name).canonicalize().ok() == file
+            })
+            .collect()
+    }
 
-            Ok(wellformed
-                .diagnostics()
+    /// Run the diagnostic pass `P` on `ctx` and add all the errors and
+    /// warnings it finds to `diags`. If the pass had to stop early, the
+    /// error that stopped it is returned after the diagnostics that it
+    /// gathered up to that point have been added.
+    fn run_pass<P>(
+        ctx: &mut ir::Context,
+        diags: &mut Vec<CalyxError>,
+    ) -> CalyxResult<()>
+    where
+        P: Visitor + ConstructVisitor + Named + DiagnosticPass,
+    {
+        let mut pass = P::from(ctx)?;
+        let res = pass.do_pass(ctx);
+        diags.extend(
+            pass.diagnostics()
                 .errors_iter()
-                .chain(diag_papercut.diagnostics().errors_iter())
-                .chain(synth_papercut.diagnostics().errors_iter())
-                .map(|e| {
-                    let (file_name, pos_start, pos_end) = e.location();
-                    let msg = e.message();
-                    let annotations = e.annotations();
-                    CalyxError {
-                        file_name: file_name.to_string(),
-                        pos_start,
-                        pos_end,
-                        msg,
-                        annotations,
-                        severity: lspt::DiagnosticSeverity::ERROR,
-                    }
-                })
-                .chain(warnings)
-                .collect::<Vec<_>>())
-        })
-        .unwrap_or_else(|e| {
-            let (file_name, pos_start, pos_end) = e.location();
-            let msg = e.message();
-            vec![CalyxError {
-                file_name: file_name.to_string(),
-                pos_start,
-                pos_end,
-                msg,
-                annotations: vec![],
-                severity: lspt::DiagnosticSeverity::ERROR,
-            }]
-        })
+                .map(|e| CalyxError::new(e, lspt::DiagnosticSeverity::ERROR))
+                .chain(pass.diagnostics().warning_iter().map(|e| {
+                    CalyxError::new(e, lspt::DiagnosticSeverity::WARNING)
+                })),
+        );
+        res
     }
 }
 
 impl CalyxError {
+    fn new(e: &Error, severity: lspt::DiagnosticSeverity) -> Self {
+        let (file_name, pos_start, pos_end) = e.location();
+        CalyxError {
+            file_name: file_name.to_string(),
+            pos_start,
+            pos_end,
+            msg: e.message(),
+            annotations: e.annotations(),
+            severity,
+        }
+    }
+
     pub fn into_lspt_diagnostics(
         self,
         doc: &Document,
@@ -155,3 +150,70 @@ impl CalyxError {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{lib_path, write_files};
+
+    #[test]
+    fn imported_errors_belong_to_the_imported_file() {
+        let lib_path = lib_path();
+        let dir = write_files(
+            "imported-diagnostics",
+            &[
+                (
+                    "main.futil",
+                    r#"import "primitives/core.futil";
+import "lib.futil";
+component main() -> () {
+  cells { w = wrapper(); }
+  wires {}
+  control { invoke w()(); }
+}
+"#,
+                ),
+                // `wrapper` is the entry point when `lib.futil` is saved
+                (
+                    "lib.futil",
+                    r#"import "primitives/core.futil";
+component wrapper<"toplevel"=1>() -> () {
+  cells { r = std_reg(32); }
+  wires {
+    group write {
+      r.in = 32'd1;
+      r.write_en = 1'd1;
+    }
+  }
+  control { write; }
+}
+"#,
+                ),
+            ],
+        );
+
+        // the missing done condition is in `lib.futil`, so it is not
+        // reported when `main.futil` is saved
+        let main = Diagnostic::did_save(&dir.join("main.futil"), &lib_path);
+        assert!(main.is_empty(), "{main:?}");
+
+        let lib_file = dir.join("lib.futil");
+        let lib = Diagnostic::did_save(&lib_file, &lib_path);
+        assert_eq!(lib.len(), 1, "{lib:?}");
+        assert_eq!(
+            Path::new(&lib[0].file_name).canonicalize().unwrap(),
+            lib_file.canonicalize().unwrap()
+        );
+        assert!(lib[0].msg.contains("`done'"), "{}", lib[0].msg);
+
+        // the diagnostic is placed on the group in the imported document
+        let doc = Document::new_with_text(
+            lspt::Url::from_file_path(&lib_file).unwrap(),
+            &std::fs::read_to_string(&lib_file).unwrap(),
+        );
+        let diags = lib.into_iter().next().unwrap().into_lspt_diagnostics(&doc);
+        assert_eq!(diags[0].range.start, lspt::Position::new(4, 4));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -47,6 +47,8 @@ pub enum Things<'a> {
     Cell(ts::Node<'a>, String),
     /// Identifier referring to a port
     SelfPort(ts::Node<'a>, String),
+    /// Identifier referring to a port on a cell, with the name of the cell
+    CellPort(ts::Node<'a>, String, String),
     /// Identifier refeferring to a component
     Component(String),
     /// Identifier referring to a group
@@ -112,15 +114,17 @@ impl Document {
 
     /// Translate a `byte_offset` into a `Point`.
     pub fn byte_to_point(&self, byte_offset: usize) -> Option<Point> {
-        if byte_offset == 0 {
-            Some(Point::zero())
-        } else if byte_offset < self.text.len() {
-            let portion = &self.text[..byte_offset];
-            let lines = portion.lines();
-            let line_num = lines.clone().count();
-            let res = lines.last().map(|l| Point::new(line_num - 1, l.len()));
+        if byte_offset <= self.text.len() {
+            let portion = &self.text.as_bytes()[..byte_offset];
+            let row = portion.iter().filter(|&&b| b == b'\n').count();
+            let line_start = portion
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map(|idx| idx + 1)
+                .unwrap_or(0);
+            let res = Point::new(row, byte_offset - line_start);
             log::stdout!("{byte_offset} -> {res:?}");
-            res
+            Some(res)
         } else {
             None
         }
@@ -399,7 +403,8 @@ impl Document {
             if node.parent().is_some_and(|p| p.kind() == "port") {
                 // when our parent is a port and we have a next sibling
                 // we are looking at a cell. if we don't have a next
-                // sibling, we are looking at a port on our current component
+                // sibling, we are looking at a port on our current component.
+                // otherwise, we are looking at the port of a cell
                 if node.next_sibling().is_some() {
                     Some(Things::Cell(node, self.node_text(&node).to_string()))
                 } else if node.prev_sibling().is_none() {
@@ -408,7 +413,13 @@ impl Document {
                         self.node_text(&node).to_string(),
                     ))
                 } else {
-                    None
+                    node.parent().and_then(|p| p.named_child(0)).map(|cell| {
+                        Things::CellPort(
+                            node,
+                            self.node_text(&cell).to_string(),
+                            self.node_text(&node).to_string(),
+                        )
+                    })
                 }
             } else if node.parent().is_some_and(|p| p.kind() == "enable") {
                 // if we are in an enable control statement, we are looking
@@ -429,6 +440,61 @@ impl Document {
             {
                 // inside a cell instantiation, we are looking at a component
                 Some(Things::Component(self.node_text(&node).to_string()))
+            } else if node
+                .parent()
+                .is_some_and(|p| p.kind() == "cell_assignment")
+            {
+                // the name of a cell where it is defined
+                Some(Things::Cell(node, self.node_text(&node).to_string()))
+            } else if node.parent().is_some_and(|p| p.kind() == "component") {
+                // the name of a component where it is defined
+                Some(Things::Component(self.node_text(&node).to_string()))
+            } else if node.parent().is_some_and(|p| p.kind() == "group") {
+                // the name of a group where it is defined
+                Some(Things::Group(node, self.node_text(&node).to_string()))
+            } else if node.parent().is_some_and(|p| p.kind() == "io_port")
+                && node.next_sibling().is_some_and(|n| n.kind() == ":")
+            {
+                // the name of a port in the signature of a component
+                Some(Things::SelfPort(node, self.node_text(&node).to_string()))
+            } else if node.parent().is_some_and(|p| p.kind() == "invoke") {
+                // an invoke names the invoked cell, and optionally the
+                // combinational group that is used with it
+                match node.prev_sibling().map(|n| n.kind()) {
+                    Some("invoke") => Some(Things::Cell(
+                        node,
+                        self.node_text(&node).to_string(),
+                    )),
+                    Some("with") => Some(Things::Group(
+                        node,
+                        self.node_text(&node).to_string(),
+                    )),
+                    _ => None,
+                }
+            } else if node
+                .parent()
+                .is_some_and(|p| p.kind() == "invoke_ref_arg")
+            {
+                // the right hand side of a ref argument is a cell
+                if node.prev_sibling().is_some() {
+                    Some(Things::Cell(node, self.node_text(&node).to_string()))
+                } else {
+                    None
+                }
+            } else if node.parent().is_some_and(|p| p.kind() == "invoke_arg") {
+                // the left hand side of an invoke argument is a port on
+                // the invoked cell
+                node.parent()
+                    .and_then(|p| p.parent_until_names(&["invoke"]))
+                    .and_then(|invoke| self.invoked_cell(invoke))
+                    .filter(|_| node.prev_sibling().is_none())
+                    .map(|cell| {
+                        Things::CellPort(
+                            node,
+                            self.node_text(&cell).to_string(),
+                            self.node_text(&node).to_string(),
+                        )
+                    })
             } else if node.parent().is_some_and(|p| p.kind() == "import") {
                 // inside an import, we are ofc looking at an import
                 Some(Things::Import(
@@ -441,6 +507,29 @@ impl Document {
         })
     }
 
+    /// Find the name of the cell that is invoked by the `invoke` node
+    pub fn invoked_cell<'a>(
+        &'a self,
+        invoke: ts::Node<'a>,
+    ) -> Option<ts::Node<'a>> {
+        self.captures(invoke, "(invoke (ident) @cell)")["cell"]
+            .iter()
+            .find(|n| n.prev_sibling().is_some_and(|p| p.kind() == "invoke"))
+            .copied()
+    }
+
+    /// Find the component that contains `node`
+    pub fn enclosing_component<'a>(
+        &'a self,
+        node: ts::Node<'a>,
+    ) -> Option<ts::Node<'a>> {
+        if node.kind() == "component" {
+            Some(node)
+        } else {
+            node.parent_until_names(&["component"])
+        }
+    }
+
     /// Find the context of the thing at point
     pub fn context_at_point(&self, point: &Point) -> Context {
         // to find the context at point, we can't just find the node and
@@ -494,8 +583,38 @@ impl Document {
         })
     }
 
+    /// Return the text of the whole document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
     /// Return text string for `node`.
     pub fn node_text(&self, node: &ts::Node) -> &str {
         node.utf8_text(self.text.as_bytes()).unwrap()
     }
 }
+
+#[cfg(test)]
+impl Document {
+    /// Create a document for `text` that is not backed by a file on disk.
+    pub fn for_test(text: &str) -> Self {
+        Self::new_with_text(
+            lspt::Url::parse("file:///test/main.futil").unwrap(),
+            text,
+        )
+    }
+
+    /// The point at the start of the `n`th occurrence of `needle`.
+    pub fn point_of(&self, needle: &str, n: usize) -> Point {
+        let (offset, _) =
+            self.text.match_indices(needle).nth(n).unwrap_or_else(|| {
+                panic!("`{needle}' does not occur {} times", n + 1)
+            });
+        self.byte_to_point(offset).unwrap()
+    }
+
+    /// The thing under the `n`th occurrence of `needle`.
+    pub fn thing_at_text(&self, needle: &str, n: usize) -> Option<Things<'_>> {
+        self.thing_at_point(self.point_of(needle, n))
+    }
+}
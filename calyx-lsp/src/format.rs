@@ -0,0 +1,383 @@
+//! Reformat a document by printing it with the canonical Calyx printer and
+//! reinserting the comments of the original document.
+//!
+//! The printer does not know about comments, so each comment is attached to
+//! a nearby node in the original document. Nodes are identified by their path
+//! from the root of the tree, where each step uses the name of the node when
+//! it has one (components, cells, groups) and its position amongst siblings of
+//! the same kind otherwise. The comment is placed next to the node with the
+//! same path in the printed document.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use calyx_ir as ir;
+use calyx_utils::{CalyxResult, Error};
+use itertools::Itertools;
+use resolve_path::PathResolveExt;
+use tree_sitter as ts;
+
+use crate::document::Document;
+
+/// Where a comment goes relative to the node it is attached to
+#[derive(Clone, Copy, Debug)]
+enum Placement {
+    /// On its own line in front of the node
+    Before,
+    /// At the end of the last line of the node
+    After,
+    /// On its own line before the closing brace of the node
+    End,
+}
+
+/// A comment in the original document and where it should go
+#[derive(Debug)]
+struct Comment {
+    text: String,
+    /// The path of the node that the comment is attached to. Comments
+    /// without a node go to the end of the document.
+    key: Option<Vec<String>>,
+    placement: Placement,
+}
+
+/// A line of the printed document with the comments that are attached to it
+#[derive(Default)]
+struct Line {
+    before: Vec<String>,
+    text: String,
+    after: Vec<String>,
+}
+
+pub trait Formatter {
+    /// Format the document using libraries located at `lib_path`.
+    fn format(&self, lib_path: &Path) -> CalyxResult<String>;
+}
+
+impl Formatter for Document {
+    fn format(&self, lib_path: &Path) -> CalyxResult<String> {
+        let path = self.url.to_file_path().map_err(|_| {
+            Error::invalid_file(format!("`{}' is not a file", self.url))
+        })?;
+        let mut ws = calyx_frontend::Workspace::construct_shallow_from_str(
+            self.text(),
+            &path,
+            lib_path.resolve().as_ref(),
+        )?;
+        let imports = ws.original_imports.drain(..).collect_vec();
+        let ctx = ir::from_ast::ast_to_ir(ws)?;
+
+        let mut out = vec![];
+        for import in imports {
+            writeln!(out, "import \"{}\";", import)?;
+        }
+        ir::Printer::write_context(&ctx, true, &mut out)?;
+        let printed = String::from_utf8(out).map_err(|err| {
+            Error::write_error(format!("Printed invalid UTF-8: {err}"))
+        })?;
+        Ok(self.insert_comments(&printed))
+    }
+}
+
+impl Document {
+    /// Insert the comments of this document into `printed`, which is the
+    /// printed version of this document.
+    fn insert_comments(&self, printed: &str) -> String {
+        let comments = self.comments();
+        if comments.is_empty() {
+            return printed.to_string();
+        }
+
+        let formatted = Document::new_with_text(self.url.clone(), printed);
+        let nodes = formatted.keyed_nodes();
+        let mut lines = printed
+            .lines()
+            .map(|l| Line {
+                text: l.to_string(),
+                ..Default::default()
+            })
+            .collect_vec();
+        let last = lines.len().saturating_sub(1);
+        let mut end_of_file = vec![];
+
+        for comment in comments {
+            // find the node that the comment is attached to, or its closest
+            // ancestor if the printer got rid of the node
+            let found = comment.key.as_ref().and_then(|key| {
+                (1..=key.len()).rev().find_map(|len| {
+                    nodes.get(&key[..len]).map(|n| (n, len == key.len()))
+                })
+            });
+            let Some((node, exact)) = found else {
+                end_of_file.push(comment.text);
+                continue;
+            };
+            let placement = if exact {
+                comment.placement
+            } else {
+                Placement::Before
+            };
+            match placement {
+                Placement::Before => {
+                    let row = node.start_position().row.min(last);
+                    let text =
+                        format!("{}{}", indent(&lines[row].text), comment.text);
+                    lines[row].before.push(text);
+                }
+                Placement::After => lines[node.end_position().row.min(last)]
+                    .after
+                    .push(comment.text),
+                Placement::End => {
+                    let text = format!(
+                        "{}  {}",
+                        indent(
+                            &lines[node.start_position().row.min(last)].text
+                        ),
+                        comment.text
+                    );
+                    lines[node.end_position().row.min(last)].before.push(text);
+                }
+            }
+        }
+
+        let mut out = lines
+            .into_iter()
+            .flat_map(|line| {
+                let text = if line.after.is_empty() {
+                    line.text
+                } else {
+                    format!("{} {}", line.text, line.after.join(" "))
+                };
+                line.before.into_iter().chain(std::iter::once(text))
+            })
+            .chain(end_of_file)
+            .join("\n");
+        if printed.ends_with('\n') {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Collect all the comments in this document along with the node that
+    /// they are attached to.
+    fn comments(&self) -> Vec<Comment> {
+        let Some(root) = self.root_node() else {
+            return vec![];
+        };
+        self.captures(root, "(comment) @comment")["comment"]
+            .iter()
+            .map(|comment| {
+                let text = self.node_text(comment).trim_end().to_string();
+                let line =
+                    self.text().lines().nth(comment.start_position().row);
+                let trailing = line.is_some_and(|l| {
+                    !l[..comment.start_position().column].trim().is_empty()
+                });
+                if trailing {
+                    // attach the comment to the largest node that ends
+                    // right before it
+                    let key =
+                        neighbour(*comment, |n| n.prev_sibling()).map(|prev| {
+                            let node = climb(prev, |n, p| {
+                                p.end_byte() == n.end_byte() || !n.is_named()
+                            });
+                            self.key(node)
+                        });
+                    Comment {
+                        text,
+                        key,
+                        placement: Placement::After,
+                    }
+                } else {
+                    match neighbour(*comment, |n| n.next_sibling()) {
+                        // the comment is the last thing in a block
+                        Some(next) if next.kind() == "}" => Comment {
+                            text,
+                            key: next.parent().map(|p| self.key(p)),
+                            placement: Placement::End,
+                        },
+                        // attach the comment to the largest node that starts
+                        // right after it
+                        Some(next) => {
+                            let node = climb(next, |n, p| {
+                                p.start_byte() == n.start_byte()
+                                    || !n.is_named()
+                            });
+                            Comment {
+                                text,
+                                key: Some(self.key(node)),
+                                placement: Placement::Before,
+                            }
+                        }
+                        None => Comment {
+                            text,
+                            key: None,
+                            placement: Placement::Before,
+                        },
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Map the path of every named node in this document to the node.
+    fn keyed_nodes(&self) -> HashMap<Vec<String>, ts::Node<'_>> {
+        let mut map = HashMap::new();
+        let mut stack = self.root_node().into_iter().collect_vec();
+        while let Some(node) = stack.pop() {
+            map.insert(self.key(node), node);
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        map
+    }
+
+    /// The path from the root of the document to `node`.
+    fn key(&self, node: ts::Node) -> Vec<String> {
+        let mut key = vec![];
+        let mut cur = node;
+        while let Some(parent) = cur.parent() {
+            key.push(self.label(cur, parent));
+            cur = parent;
+        }
+        key.reverse();
+        key
+    }
+
+    /// Identify `node` amongst the children of `parent`.
+    fn label(&self, node: ts::Node, parent: ts::Node) -> String {
+        let kind = node.kind();
+        if matches!(
+            kind,
+            "component" | "primitive" | "cell_assignment" | "group"
+        ) {
+            let mut cursor = node.walk();
+            let name = node
+                .named_children(&mut cursor)
+                .find(|n| n.kind() == "ident");
+            if let Some(name) = name {
+                return format!("{kind}:{}", self.node_text(&name));
+            }
+        }
+        let mut cursor = parent.walk();
+        let idx = parent
+            .named_children(&mut cursor)
+            .take_while(|n| n.id() != node.id())
+            .filter(|n| n.kind() == kind)
+            .count();
+        format!("{kind}#{idx}")
+    }
+}
+
+/// The leading whitespace of `line`
+fn indent(line: &str) -> String {
+    line.chars().take_while(|c| c.is_whitespace()).collect()
+}
+
+/// Find the closest node in the direction of `step` that is not a comment,
+/// moving up the tree when we run out of siblings.
+fn neighbour<'a, F>(node: ts::Node<'a>, step: F) -> Option<ts::Node<'a>>
+where
+    F: Fn(&ts::Node<'a>) -> Option<ts::Node<'a>>,
+{
+    let mut cur = node;
+    loop {
+        match step(&cur) {
+            Some(n) if n.kind() == "comment" => cur = n,
+            Some(n) => return Some(n),
+            None => cur = cur.parent()?,
+        }
+    }
+}
+
+/// Move up from `node` as long as `pred` holds for the node and its parent.
+/// Never returns the root of the tree.
+fn climb<'a, F>(node: ts::Node<'a>, pred: F) -> ts::Node<'a>
+where
+    F: Fn(&ts::Node<'a>, &ts::Node<'a>) -> bool,
+{
+    let mut cur = node;
+    while let Some(parent) = cur.parent() {
+        if parent.parent().is_none() || !pred(&cur, &parent) {
+            break;
+        }
+        cur = parent;
+    }
+    cur
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{lib_path, write_files};
+    use tower_lsp::lsp_types as lspt;
+
+    /// Format `text` as if it were the file `name`.futil
+    fn format(name: &str, text: &str) -> CalyxResult<String> {
+        let dir = write_files(name, &[]);
+        let doc = Document::new_with_text(
+            lspt::Url::from_file_path(dir.join("main.futil")).unwrap(),
+            text,
+        );
+        let res = doc.format(&lib_path());
+        std::fs::remove_dir_all(dir).unwrap();
+        res
+    }
+
+    #[test]
+    fn keeps_comments() {
+        let formatted = format(
+            "format-comments",
+            r#"import "primitives/core.futil";
+// the entry point
+component main() -> () {
+  cells {
+      r = std_reg(32); // the register
+  }
+  wires {
+    group   write {
+      r.in = 32'd1; r.write_en = 1'd1;
+      write[done] = r.done;
+    }
+    // no continuous assignments
+  }
+  control {
+    // run once
+    write;
+  }
+}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            formatted,
+            r#"import "primitives/core.futil";
+// the entry point
+component main(@go go: 1, @clk clk: 1, @reset reset: 1) -> (@done done: 1) {
+  cells {
+    r = std_reg(32); // the register
+  }
+  wires {
+    group write {
+      r.in = 32'd1;
+      r.write_en = 1'd1;
+      write[done] = r.done;
+    }
+    // no continuous assignments
+  }
+  control {
+    // run once
+    write;
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err =
+            format("format-errors", "component main() -> ( {}").unwrap_err();
+        assert!(!err.message().is_empty());
+    }
+}
@@ -58,6 +58,9 @@ pub trait DefinitionProvider {
             Things::SelfPort(node, name) => {
                 self.find_self_port(url, node, name)
             }
+            // ports of cells are defined by the signature of another
+            // component, which we don't jump to yet
+            Things::CellPort(..) => None,
             Things::Group(node, name) => self.find_group(url, node, name),
             Things::Import(_node, name) => self.find_import(config, url, name),
             Things::Component(name) => self.find_component(config, name),
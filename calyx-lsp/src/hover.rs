@@ -0,0 +1,483 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use tree_sitter as ts;
+
+use crate::{
+    document::{Document, Things},
+    query_result::QueryResult,
+    Config,
+};
+
+/// A port in the signature of a component or primitive
+#[derive(Clone, Debug)]
+struct PortDef {
+    name: String,
+    /// Either a literal width or the name of a parameter
+    width: String,
+}
+
+/// The signature of a component or primitive, as it is written in the source
+#[derive(Clone, Debug)]
+struct Definition {
+    /// `primitive` or `component`
+    kind: &'static str,
+    name: String,
+    /// `comb` and `static` annotations in front of the definition
+    annotations: Option<String>,
+    params: Vec<String>,
+    inputs: Vec<PortDef>,
+    outputs: Vec<PortDef>,
+}
+
+impl Definition {
+    /// Resolve the width of `port` using `args` as values for the parameters.
+    fn width(&self, port: &PortDef, args: &[String]) -> String {
+        self.params
+            .iter()
+            .zip(args)
+            .find(|(param, _)| **param == port.width)
+            .map(|(_, arg)| arg.to_string())
+            .unwrap_or_else(|| port.width.to_string())
+    }
+
+    /// Find the port called `name` along with its direction.
+    fn port(&self, name: &str) -> Option<(&PortDef, &'static str)> {
+        self.inputs
+            .iter()
+            .map(|p| (p, "input"))
+            .chain(self.outputs.iter().map(|p| (p, "output")))
+            .find(|(p, _)| p.name == name)
+    }
+
+    /// Render the signature with `args` substituted for the parameters.
+    fn render(&self, args: &[String]) -> String {
+        let ports = |ports: &[PortDef]| {
+            ports
+                .iter()
+                .map(|p| format!("{}: {}", p.name, self.width(p, args)))
+                .join(", ")
+        };
+        let params = if self.params.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "[{}]",
+                self.params
+                    .iter()
+                    .enumerate()
+                    .map(|(i, param)| match args.get(i) {
+                        Some(arg) => format!("{param} = {arg}"),
+                        None => param.to_string(),
+                    })
+                    .join(", ")
+            )
+        };
+        format!(
+            "{}{} {}{params}({}) -> ({})",
+            self.annotations
+                .as_ref()
+                .map(|a| format!("{a} "))
+                .unwrap_or_default(),
+            self.kind,
+            self.name,
+            ports(&self.inputs),
+            ports(&self.outputs)
+        )
+    }
+}
+
+/// What we want to show information about, once we have found the definition
+/// of `component`.
+#[derive(Clone, Debug)]
+pub struct Needle {
+    component: String,
+    /// Arguments that the component was instantiated with
+    args: Vec<String>,
+    /// The cell that instantiates `component`
+    cell: Option<String>,
+    /// The port of the component that we are looking at
+    port: Option<String>,
+}
+
+impl Needle {
+    /// Describe the needle in markdown, using `def` as its definition.
+    fn describe(&self, def: &Definition) -> Option<String> {
+        let sig = code(def.render(&self.args));
+        match (&self.cell, &self.port) {
+            (Some(cell), Some(port)) => def.port(port).map(|(p, dir)| {
+                format!(
+                    "{}\n{dir} of `{cell}`\n\n{sig}",
+                    code(format!(
+                        "{cell}.{port}: {}",
+                        def.width(p, &self.args)
+                    ))
+                )
+            }),
+            (Some(cell), None) => Some(format!(
+                "{}\n{sig}",
+                code(format!(
+                    "{cell} = {}({})",
+                    self.component,
+                    self.args.join(", ")
+                ))
+            )),
+            (None, _) => Some(sig),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum HoverRes {
+    /// The markdown text to show
+    Found(String),
+    /// Continue searching these paths for the definition needed by `Needle`
+    Continue(Vec<PathBuf>, Needle),
+}
+
+impl HoverRes {
+    /// Look for the definition of `needle` in `doc`. If it isn't there,
+    /// continue with the imports of `doc`.
+    fn search(
+        config: &Config,
+        doc: &Document,
+        needle: &Needle,
+    ) -> Option<Self> {
+        match doc.definition(&needle.component) {
+            Some(def) => needle.describe(&def).map(HoverRes::Found),
+            None => Some(HoverRes::Continue(
+                doc.resolved_imports(config).collect(),
+                needle.clone(),
+            )),
+        }
+    }
+}
+
+impl QueryResult for HoverRes {
+    type Data = String;
+    type Needle = Needle;
+
+    fn found(&self) -> Option<Self::Data> {
+        match self {
+            HoverRes::Found(text) => Some(text.clone()),
+            HoverRes::Continue(..) => None,
+        }
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            HoverRes::Found(_) => vec![],
+            HoverRes::Continue(paths, _) => paths.clone(),
+        }
+    }
+
+    fn resume(&self, config: &Config, doc: &Document) -> Option<Self> {
+        match self {
+            HoverRes::Found(_) => Some(self.clone()),
+            HoverRes::Continue(_, needle) => Self::search(config, doc, needle),
+        }
+    }
+}
+
+/// Wrap `text` in a calyx code block.
+fn code<S: AsRef<str>>(text: S) -> String {
+    format!("```calyx\n{}\n```", text.as_ref())
+}
+
+pub trait HoverProvider {
+    fn hover(&self, config: &Config, thing: Things) -> Option<HoverRes>;
+}
+
+impl HoverProvider for Document {
+    fn hover(&self, config: &Config, thing: Things) -> Option<HoverRes> {
+        match thing {
+            Things::Cell(node, name) => {
+                self.instance(node, &name).and_then(|(component, args)| {
+                    HoverRes::search(
+                        config,
+                        self,
+                        &Needle {
+                            component,
+                            args,
+                            cell: Some(name),
+                            port: None,
+                        },
+                    )
+                })
+            }
+            Things::CellPort(node, cell, port) => {
+                self.instance(node, &cell).and_then(|(component, args)| {
+                    HoverRes::search(
+                        config,
+                        self,
+                        &Needle {
+                            component,
+                            args,
+                            cell: Some(cell),
+                            port: Some(port),
+                        },
+                    )
+                })
+            }
+            Things::Component(name) => HoverRes::search(
+                config,
+                self,
+                &Needle {
+                    component: name,
+                    args: vec![],
+                    cell: None,
+                    port: None,
+                },
+            ),
+            Things::SelfPort(node, name) => self
+                .enclosing_component(node)
+                .and_then(|comp| self.definition_of(comp))
+                .and_then(|def| {
+                    def.port(&name).map(|(p, dir)| {
+                        HoverRes::Found(format!(
+                            "{}\n{dir} of `{}`",
+                            code(format!("{}: {}", p.name, p.width)),
+                            def.name
+                        ))
+                    })
+                }),
+            Things::Group(node, name) => self
+                .enclosing_groups(node)
+                .find(|g| self.node_text(g) == name)
+                .and_then(|g| g.parent())
+                .map(|group| HoverRes::Found(self.describe_group(group))),
+            Things::Import(..) => None,
+        }
+    }
+}
+
+impl Document {
+    /// Find the component and arguments that the cell `name` in the
+    /// component containing `node` is instantiated with.
+    fn instance(
+        &self,
+        node: ts::Node,
+        name: &str,
+    ) -> Option<(String, Vec<String>)> {
+        self.enclosing_cells(node)
+            .find(|n| self.node_text(n) == name)
+            .and_then(|n| n.next_named_sibling())
+            .filter(|inst| inst.kind() == "instantiation")
+            .map(|inst| {
+                let map = self.captures(inst, "(arg_list (number) @arg)");
+                (
+                    inst.named_child(0)
+                        .map(|n| self.node_text(&n).to_string())
+                        .unwrap_or_default(),
+                    map["arg"]
+                        .iter()
+                        .map(|n| self.node_text(n).to_string())
+                        .collect(),
+                )
+            })
+    }
+
+    /// Find the definition of the component or primitive called `name`
+    fn definition(&self, name: &str) -> Option<Definition> {
+        self.components()
+            .find(|n| self.node_text(n) == name)
+            .and_then(|n| n.parent())
+            .and_then(|n| self.definition_of(n))
+    }
+
+    /// Construct the definition of a `component` or `primitive` node
+    fn definition_of(&self, node: ts::Node) -> Option<Definition> {
+        let kind = match node.kind() {
+            "component" => "component",
+            "primitive" => "primitive",
+            _ => return None,
+        };
+        let child = |kind: &str| {
+            let mut cursor = node.walk();
+            let res =
+                node.named_children(&mut cursor).find(|n| n.kind() == kind);
+            res
+        };
+        let ports = |list: Option<ts::Node>| {
+            list.into_iter()
+                .flat_map(|l| {
+                    self.captures(l, "(io_port) @port")["port"].clone()
+                })
+                .filter_map(|port| {
+                    // the name of a port is the identifier before the `:`
+                    let mut cursor = port.walk();
+                    let name = port.named_children(&mut cursor).find(|n| {
+                        n.next_sibling().is_some_and(|s| s.kind() == ":")
+                    });
+                    // the width is the last named child, which is missing
+                    // while the port is still being typed
+                    let last = port.named_child_count().checked_sub(1)?;
+                    let width = port.named_child(last)?;
+                    name.map(|n| PortDef {
+                        name: self.node_text(&n).to_string(),
+                        width: self.node_text(&width).to_string(),
+                    })
+                })
+                .collect()
+        };
+        let (inputs, outputs) = match child("signature") {
+            Some(sig) => (sig.named_child(0), sig.named_child(1)),
+            None => (None, None),
+        };
+        Some(Definition {
+            kind,
+            name: child("ident")
+                .map(|n| self.node_text(&n).to_string())
+                .unwrap_or_default(),
+            annotations: child("comb_or_static")
+                .map(|n| self.node_text(&n).to_string()),
+            params: child("params")
+                .map(|p| {
+                    self.captures(p, "(params (ident) @param)")["param"]
+                        .iter()
+                        .map(|n| self.node_text(n).to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            inputs: ports(inputs),
+            outputs: ports(outputs),
+        })
+    }
+
+    /// Describe the header, attributes and static latency of a `group` node
+    fn describe_group(&self, group: ts::Node) -> String {
+        let mut cursor = group.walk();
+        let header = group
+            .children(&mut cursor)
+            .take_while(|n| n.kind() != "{")
+            .map(|n| self.node_text(&n))
+            .join(" ");
+        let attrs = self
+            .captures(group, "(group (attributes (attribute) @attr))")["attr"]
+            .iter()
+            .map(|n| format!("- `{}`", self.node_text(n)))
+            .collect_vec();
+        let latency = self.captures(
+            group,
+            "(group (static_annotation (latency_annotation (number) @n)))",
+        )["n"]
+            .first()
+            .map(|n| self.node_text(n).to_string());
+
+        let mut text = code(header);
+        if !attrs.is_empty() {
+            text.push_str(&format!("\n\nAttributes:\n{}", attrs.join("\n")));
+        }
+        if let Some(latency) = latency {
+            text.push_str(&format!("\n\nStatic latency: {latency}"));
+        } else if group.child(0).is_some_and(|n| n.kind() == "comb") {
+            text.push_str("\n\nCombinational group");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = r#"extern "prims.sv" {
+  primitive std_reg[WIDTH](in: WIDTH, write_en: 1) -> (out: WIDTH, done: 1);
+  comb primitive half(in:) -> (out: 1);
+}
+component main(@go go: 1, in: 32) -> (out: 32) {
+  cells {
+    r = std_reg(32);
+    h = half();
+  }
+  wires {
+    group incr<"promotable"=1> {
+      r.in = in;
+      r.write_en = 1'd1;
+      incr[done] = r.done;
+    }
+    static<2> group wait {
+      h.in = in;
+    }
+    out = r.out;
+  }
+  control {
+    seq { incr; wait; }
+  }
+}
+"#;
+
+    fn hover(doc: &Document, needle: &str, n: usize) -> Option<String> {
+        let thing = doc.thing_at_text(needle, n).expect("nothing at point");
+        doc.hover(&Config::default(), thing)
+            .and_then(|res| res.found())
+    }
+
+    #[test]
+    fn cell() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "r = std_reg", 0).unwrap(),
+            "```calyx\nr = std_reg(32)\n```\n\
+             ```calyx\nprimitive std_reg[WIDTH = 32](in: 32, write_en: 1) -> (out: 32, done: 1)\n```"
+        );
+    }
+
+    #[test]
+    fn cell_port() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "write_en = 1'd1", 0).unwrap(),
+            "```calyx\nr.write_en: 1\n```\ninput of `r`\n\n\
+             ```calyx\nprimitive std_reg[WIDTH = 32](in: 32, write_en: 1) -> (out: 32, done: 1)\n```"
+        );
+    }
+
+    #[test]
+    fn self_port() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "in;", 0).unwrap(),
+            "```calyx\nin: 32\n```\ninput of `main`"
+        );
+    }
+
+    #[test]
+    fn component() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "main", 0).unwrap(),
+            "```calyx\ncomponent main(go: 1, in: 32) -> (out: 32)\n```"
+        );
+    }
+
+    #[test]
+    fn group() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "incr;", 0).unwrap(),
+            "```calyx\ngroup incr <\"promotable\"=1>\n```\n\nAttributes:\n- `\"promotable\"=1`"
+        );
+        assert_eq!(
+            hover(&doc, "wait;", 0).unwrap(),
+            "```calyx\nstatic<2> group wait\n```\n\nStatic latency: 2"
+        );
+    }
+
+    #[test]
+    fn incomplete_port() {
+        // the width of `half.in` is still being typed, so the parser inserts a
+        // missing node in its place
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            hover(&doc, "in = in", 1).unwrap(),
+            "```calyx\nh.in: \n```\ninput of `h`\n\n\
+             ```calyx\ncomb primitive half(in: ) -> (out: 1)\n```"
+        );
+        assert_eq!(
+            hover(&doc, "h = half", 0).unwrap(),
+            "```calyx\nh = half()\n```\n\
+             ```calyx\ncomb primitive half(in: ) -> (out: 1)\n```"
+        );
+    }
+}
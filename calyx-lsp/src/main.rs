@@ -1,10 +1,18 @@
+mod code_action;
 mod completion;
 mod convert;
 mod diagnostic;
 mod document;
+mod format;
 mod goto_definition;
+mod hover;
 mod log;
 mod query_result;
+mod references;
+mod rename;
+mod symbols;
+#[cfg(test)]
+mod test_utils;
 mod ts_utils;
 
 use std::collections::HashMap;
@@ -12,12 +20,18 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::RwLock;
 
-use convert::Point;
+use code_action::CodeActionProvider;
+use convert::{Point, Range};
 use diagnostic::Diagnostic;
 use document::Document;
+use format::Formatter;
 use goto_definition::DefinitionProvider;
+use hover::HoverProvider;
 use query_result::QueryResult;
+use references::ReferenceProvider;
+use rename::{RenameProvider, RenameTarget};
 use serde::Deserialize;
+use symbols::SymbolProvider;
 use tower_lsp::lsp_types::{self as lspt, Url};
 use tower_lsp::{jsonrpc, Client, LanguageServer, LspService, Server};
 use tree_sitter as ts;
@@ -125,12 +139,42 @@ impl Backend {
             .map(|mut map| map.get_mut(url).map(updater));
     }
 
+    /// The library path that is passed to the compiler.
+    fn lib_path(&self) -> PathBuf {
+        self.config.read().unwrap().calyx_lsp.library_paths[0]
+            .to_string()
+            .into()
+    }
+
+    /// Find all the documents that may refer to components defined in `url`:
+    /// the open documents, and the documents transitively imported by `url`.
+    fn related_documents(&self, url: &lspt::Url) -> Vec<lspt::Url> {
+        let config = self.config.read().unwrap();
+        let mut seen: Vec<lspt::Url> =
+            self.open_docs.read().unwrap().keys().cloned().collect();
+        let mut todo = vec![url.clone()];
+        while let Some(url) = todo.pop() {
+            let imports = self
+                .read_and_open(&url, |doc| {
+                    Some(doc.resolved_imports(&config).collect::<Vec<_>>())
+                })
+                .unwrap_or_default();
+            todo.extend(
+                imports
+                    .into_iter()
+                    .filter_map(|p| lspt::Url::from_file_path(p).ok())
+                    .filter(|u| !seen.contains(u)),
+            );
+            if !seen.contains(&url) {
+                seen.push(url);
+            }
+        }
+        seen
+    }
+
     /// Publish diagnostics for document `url`.
     async fn publish_diagnostics(&self, url: &lspt::Url) {
-        let lib_path: PathBuf =
-            self.config.read().unwrap().calyx_lsp.library_paths[0]
-                .to_string()
-                .into();
+        let lib_path = self.lib_path();
         let diags = self
             .read_document(url, |doc| {
                 Some(
@@ -198,8 +242,15 @@ impl LanguageServer for Backend {
                     completion_item: None,
                 }),
                 hover_provider: Some(lspt::HoverProviderCapability::Simple(
-                    false,
+                    true,
                 )),
+                document_symbol_provider: Some(lspt::OneOf::Left(true)),
+                references_provider: Some(lspt::OneOf::Left(true)),
+                rename_provider: Some(lspt::OneOf::Left(true)),
+                document_formatting_provider: Some(lspt::OneOf::Left(true)),
+                code_action_provider: Some(
+                    lspt::CodeActionProviderCapability::Simple(true),
+                ),
                 ..Default::default()
             },
         })
@@ -345,6 +396,173 @@ impl LanguageServer for Backend {
             }))
     }
 
+    /// LSP method: 'textDocument/hover'
+    /// Called when the client hovers over a point in the file.
+    async fn hover(
+        &self,
+        params: lspt::HoverParams,
+    ) -> jsonrpc::Result<Option<lspt::Hover>> {
+        let url = &params.text_document_position_params.text_document.uri;
+        let config = &self.config.read().unwrap();
+        Ok(self
+            .read_document(url, |doc| {
+                doc.thing_at_point(
+                    params.text_document_position_params.position.into(),
+                )
+                .and_then(|thing| doc.hover(config, thing))
+            })
+            .and_then(|res| {
+                res.resolve(|res, path| {
+                    let url = lspt::Url::from_file_path(path).unwrap();
+                    self.read_and_open(&url, |doc| res.resume(config, doc))
+                })
+            })
+            .map(|text| lspt::Hover {
+                contents: lspt::HoverContents::Markup(lspt::MarkupContent {
+                    kind: lspt::MarkupKind::Markdown,
+                    value: text,
+                }),
+                range: None,
+            }))
+    }
+
+    /// LSP method: 'textDocument/documentSymbol'
+    /// Called when the client requests an outline of the file.
+    async fn document_symbol(
+        &self,
+        params: lspt::DocumentSymbolParams,
+    ) -> jsonrpc::Result<Option<lspt::DocumentSymbolResponse>> {
+        Ok(self
+            .read_document(&params.text_document.uri, |doc| {
+                Some(doc.document_symbols())
+            })
+            .map(lspt::DocumentSymbolResponse::Nested))
+    }
+
+    /// LSP method: 'textDocument/references'
+    /// Called when the client requests all references to the thing at a
+    /// point in the file.
+    async fn references(
+        &self,
+        params: lspt::ReferenceParams,
+    ) -> jsonrpc::Result<Option<Vec<lspt::Location>>> {
+        let url = &params.text_document_position.text_document.uri;
+        Ok(self.read_document(url, |doc| {
+            doc.thing_at_point(params.text_document_position.position.into())
+                .map(|thing| {
+                    doc.references(thing, params.context.include_declaration)
+                })
+        }))
+    }
+
+    /// LSP method: 'textDocument/rename'
+    /// Called when the client wants to rename the thing at a point in the
+    /// file. Cells and groups are renamed in their component, components are
+    /// renamed in every document that can refer to them.
+    async fn rename(
+        &self,
+        params: lspt::RenameParams,
+    ) -> jsonrpc::Result<Option<lspt::WorkspaceEdit>> {
+        let url = &params.text_document_position.text_document.uri;
+        let new_name = params.new_name;
+        if !rename::is_ident(&new_name) {
+            return Err(jsonrpc::Error::invalid_params(format!(
+                "`{new_name}' is not a valid identifier"
+            )));
+        }
+        let target = self.read_document(url, |doc| {
+            doc.thing_at_point(params.text_document_position.position.into())
+                .and_then(|thing| doc.rename_target(thing))
+        });
+        let locations = match target {
+            None => return Ok(None),
+            Some(RenameTarget::Local(locations)) => locations,
+            Some(RenameTarget::Component(name)) => {
+                let docs = self.related_documents(url);
+                if docs.iter().any(|url| {
+                    self.read_document(url, |doc| {
+                        Some(doc.defines_primitive(&name))
+                    })
+                    .unwrap_or_default()
+                }) {
+                    return Err(jsonrpc::Error::invalid_params(format!(
+                        "`{name}' is a primitive and cannot be renamed"
+                    )));
+                }
+                docs.iter()
+                    .flat_map(|url| {
+                        self.read_document(url, |doc| {
+                            Some(doc.component_locations(&name))
+                        })
+                        .unwrap_or_default()
+                    })
+                    .collect()
+            }
+        };
+        let mut changes: HashMap<lspt::Url, Vec<lspt::TextEdit>> =
+            HashMap::default();
+        for loc in locations {
+            changes
+                .entry(loc.uri)
+                .or_default()
+                .push(lspt::TextEdit::new(loc.range, new_name.clone()));
+        }
+        Ok(Some(lspt::WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    /// LSP method: 'textDocument/formatting'
+    /// Called when the client wants to format a document. The document is
+    /// printed by the compiler, so it has to be a valid program.
+    async fn formatting(
+        &self,
+        params: lspt::DocumentFormattingParams,
+    ) -> jsonrpc::Result<Option<Vec<lspt::TextEdit>>> {
+        let lib_path = self.lib_path();
+        let res = self.read_document(&params.text_document.uri, |doc| {
+            Some(doc.format(&lib_path).map(|text| {
+                let end = doc
+                    .byte_to_point(doc.text().len())
+                    .unwrap_or_else(Point::zero);
+                lspt::TextEdit::new(Range::new(Point::zero(), end).into(), text)
+            }))
+        });
+        match res {
+            Some(Ok(edit)) => Ok(Some(vec![edit])),
+            Some(Err(err)) => {
+                self.client
+                    .show_message(
+                        lspt::MessageType::ERROR,
+                        format!("Failed to format document: {}", err.message()),
+                    )
+                    .await;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// LSP method: 'textDocument/codeAction'
+    /// Called when the client requests actions for a range in the file. We
+    /// provide quick fixes for some of the diagnostics in the range.
+    async fn code_action(
+        &self,
+        params: lspt::CodeActionParams,
+    ) -> jsonrpc::Result<Option<lspt::CodeActionResponse>> {
+        Ok(self
+            .read_document(&params.text_document.uri, |doc| {
+                Some(doc.code_actions(&params.context.diagnostics))
+            })
+            .map(|actions| {
+                actions
+                    .into_iter()
+                    .map(lspt::CodeActionOrCommand::CodeAction)
+                    .collect()
+            }))
+    }
+
     /// LSP method: 'shutdown'
     async fn shutdown(&self) -> jsonrpc::Result<()> {
         log::stdout!("shutdown");
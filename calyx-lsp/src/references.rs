@@ -0,0 +1,273 @@
+use itertools::multizip;
+use tower_lsp::lsp_types as lspt;
+use tree_sitter as ts;
+
+use crate::{
+    convert::Range,
+    document::{Document, Things},
+};
+
+pub trait ReferenceProvider {
+    /// Find all references to `thing` in the component that contains it.
+    /// Definitions are included when `include_declaration` is true.
+    fn references(
+        &self,
+        thing: Things,
+        include_declaration: bool,
+    ) -> Vec<lspt::Location>;
+}
+
+impl ReferenceProvider for Document {
+    fn references(
+        &self,
+        thing: Things,
+        include_declaration: bool,
+    ) -> Vec<lspt::Location> {
+        let (decls, uses) = match thing {
+            Things::Cell(node, name) => self.cell_refs(node, &name),
+            Things::Group(node, name) => self.group_refs(node, &name),
+            Things::SelfPort(node, name) => self.self_port_refs(node, &name),
+            Things::CellPort(node, cell, port) => {
+                (vec![], self.cell_port_refs(node, &cell, &port))
+            }
+            Things::Component(name) => self.component_refs(&name),
+            Things::Import(..) => return vec![],
+        };
+        decls
+            .into_iter()
+            .filter(|_| include_declaration)
+            .chain(uses)
+            .map(|n| {
+                lspt::Location::new(self.url.clone(), Range::from(n).into())
+            })
+            .collect()
+    }
+}
+
+/// Definitions and uses of a name
+pub type Refs<'a> = (Vec<ts::Node<'a>>, Vec<ts::Node<'a>>);
+
+impl Document {
+    /// Run `pattern` on the component that contains `node` and return the
+    /// captured identifiers that are called `name`.
+    fn named<'a>(
+        &'a self,
+        node: ts::Node<'a>,
+        pattern: &str,
+        name: &str,
+    ) -> Vec<ts::Node<'a>> {
+        self.enclosing_component(node)
+            .into_iter()
+            .flat_map(|comp| self.captures(comp, pattern)["id"].clone())
+            .filter(|n| self.node_text(n) == name)
+            .collect()
+    }
+
+    /// References to the component or primitive `name` in this document
+    pub fn component_refs(&self, name: &str) -> Refs<'_> {
+        let named = |pattern| {
+            self.root_node()
+                .into_iter()
+                .flat_map(|root| self.captures(root, pattern)["id"].clone())
+                .filter(|n| self.node_text(n) == name)
+                .collect()
+        };
+        (
+            named("(component (ident) @id) (primitive (ident) @id)"),
+            named("(instantiation (ident) @id)"),
+        )
+    }
+
+    /// Whether this document defines a primitive called `name`
+    pub fn defines_primitive(&self, name: &str) -> bool {
+        self.root_node().is_some_and(|root| {
+            self.captures(root, "(primitive (ident) @id)")["id"]
+                .iter()
+                .any(|n| self.node_text(n) == name)
+        })
+    }
+
+    /// References to the cell `name`
+    fn cell_refs<'a>(&'a self, node: ts::Node<'a>, name: &str) -> Refs<'a> {
+        let decls = self.named(node, "(cell_assignment (ident) @id)", name);
+        let mut uses = self.named(
+            node,
+            r#"(port (ident) @id "." (ident))
+               (invoke_ref_arg (ident) "=" (ident) @id)"#,
+            name,
+        );
+        uses.extend(
+            self.named(node, "(invoke (ident) @id)", name)
+                .into_iter()
+                .filter(|n| {
+                    n.prev_sibling().is_some_and(|p| p.kind() == "invoke")
+                }),
+        );
+        uses.sort_by_key(|n| n.start_byte());
+        (decls, uses)
+    }
+
+    /// References to the group `name`
+    fn group_refs<'a>(&'a self, node: ts::Node<'a>, name: &str) -> Refs<'a> {
+        let decls = self.named(node, "(group (ident) @id)", name);
+        let mut uses = self.named(
+            node,
+            r#"(enable (ident) @id)
+               (hole . (ident) @id)
+               (port_with (ident) @id)"#,
+            name,
+        );
+        uses.extend(
+            self.named(node, "(invoke (ident) @id)", name)
+                .into_iter()
+                .filter(|n| {
+                    n.prev_sibling().is_some_and(|p| p.kind() == "with")
+                }),
+        );
+        uses.sort_by_key(|n| n.start_byte());
+        (decls, uses)
+    }
+
+    /// References to the port `name` of the enclosing component
+    fn self_port_refs<'a>(
+        &'a self,
+        node: ts::Node<'a>,
+        name: &str,
+    ) -> Refs<'a> {
+        let decls = self
+            .named(node, "(io_port (ident) @id)", name)
+            .into_iter()
+            // the width of a port can also be an identifier
+            .filter(|n| n.next_sibling().is_some_and(|s| s.kind() == ":"))
+            .collect();
+        let uses = self
+            .named(node, "(port (ident) @id)", name)
+            .into_iter()
+            .filter(|n| n.parent().is_some_and(|p| p.named_child_count() == 1))
+            .collect();
+        (decls, uses)
+    }
+
+    /// References to the port `port` of the cell `cell`
+    fn cell_port_refs<'a>(
+        &'a self,
+        node: ts::Node<'a>,
+        cell: &str,
+        port: &str,
+    ) -> Vec<ts::Node<'a>> {
+        let Some(comp) = self.enclosing_component(node) else {
+            return vec![];
+        };
+        let accesses =
+            self.captures(comp, "(port (ident) @cell (ident) @port)");
+        let args = self.captures(
+            comp,
+            "(invoke (ident) @cell (invoke_args (invoke_arg . (ident) @port)))",
+        );
+        let mut uses: Vec<_> = multizip((
+            accesses["cell"].iter().chain(args["cell"].iter()),
+            accesses["port"].iter().chain(args["port"].iter()),
+        ))
+        .filter(|(c, p)| self.node_text(c) == cell && self.node_text(p) == port)
+        .map(|(_, p)| *p)
+        .collect();
+        uses.sort_by_key(|n| n.start_byte());
+        uses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Point;
+
+    const PROGRAM: &str = r#"component main(in: 32) -> (out: 32) {
+  cells {
+    r = std_reg(32);
+    s = std_reg(32);
+  }
+  wires {
+    group write {
+      r.in = in;
+      r.write_en = 1'd1;
+      write[done] = r.done;
+    }
+    out = r.out;
+  }
+  control {
+    seq { write; invoke s(in = r.out)(); write; }
+  }
+}
+component other(in: 32) -> () {
+  cells { r = std_reg(32); m = main(); }
+  wires { r.in = in; }
+  control {}
+}
+"#;
+
+    /// The start of every location that refers to the thing under the `n`th
+    /// occurrence of `needle`
+    fn refs(
+        doc: &Document,
+        needle: &str,
+        n: usize,
+        include_declaration: bool,
+    ) -> Vec<(usize, usize)> {
+        let thing = doc.thing_at_text(needle, n).expect("nothing at point");
+        doc.references(thing, include_declaration)
+            .into_iter()
+            .map(|loc| {
+                assert_eq!(loc.uri, doc.url);
+                let p = Point::from(loc.range.start);
+                (p.row(), p.column())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cell() {
+        let doc = Document::for_test(PROGRAM);
+        // uses of `r` in `main`, but not in `other`
+        assert_eq!(
+            refs(&doc, "r = std_reg", 0, true),
+            vec![(2, 4), (7, 6), (8, 6), (9, 20), (11, 10), (14, 31)]
+        );
+        assert_eq!(
+            refs(&doc, "r.in", 0, false),
+            vec![(7, 6), (8, 6), (9, 20), (11, 10), (14, 31)]
+        );
+        // the invoked cell
+        assert_eq!(refs(&doc, "s(in", 0, true), vec![(3, 4), (14, 24)]);
+    }
+
+    #[test]
+    fn group() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(
+            refs(&doc, "write {", 0, true),
+            vec![(6, 10), (9, 6), (14, 10), (14, 41)]
+        );
+        assert_eq!(
+            refs(&doc, "write;", 0, false),
+            vec![(9, 6), (14, 10), (14, 41)]
+        );
+    }
+
+    #[test]
+    fn ports() {
+        let doc = Document::for_test(PROGRAM);
+        // the port of the component, but not the port of `r` or `s`
+        assert_eq!(refs(&doc, "in: 32", 0, true), vec![(0, 15), (7, 13)]);
+        assert_eq!(refs(&doc, "out = r", 0, false), vec![(11, 4)]);
+        // the port of a cell, including invoke arguments
+        assert_eq!(refs(&doc, "in = in", 0, true), vec![(7, 8)]);
+        assert_eq!(refs(&doc, "in = r.out", 0, true), vec![(14, 26)]);
+    }
+
+    #[test]
+    fn component() {
+        let doc = Document::for_test(PROGRAM);
+        assert_eq!(refs(&doc, "main()", 0, true), vec![(0, 10), (18, 31)]);
+        assert_eq!(refs(&doc, "main()", 0, false), vec![(18, 31)]);
+    }
+}
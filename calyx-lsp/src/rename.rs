@@ -0,0 +1,151 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tower_lsp::lsp_types as lspt;
+
+use crate::{
+    convert::Range,
+    document::{Document, Things},
+    references::ReferenceProvider,
+};
+
+/// Describes what has to change to rename a thing
+#[derive(Debug)]
+pub enum RenameTarget {
+    /// The thing is local to a component, so all the locations are in the
+    /// current document
+    Local(Vec<lspt::Location>),
+    /// The thing is a component, which can be used in other documents
+    Component(String),
+}
+
+/// Identifiers in a Calyx program
+static IDENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-zA-Z_]+[a-zA-Z0-9_\-]*$").unwrap());
+
+/// Check that `name` can be used as an identifier in a Calyx program
+pub fn is_ident(name: &str) -> bool {
+    IDENT.is_match(name)
+}
+
+pub trait RenameProvider {
+    /// Find what needs to change to rename `thing`
+    fn rename_target(&self, thing: Things) -> Option<RenameTarget>;
+
+    /// Find all the definitions and uses of the component `name`
+    fn component_locations(&self, name: &str) -> Vec<lspt::Location>;
+}
+
+impl RenameProvider for Document {
+    fn rename_target(&self, thing: Things) -> Option<RenameTarget> {
+        match thing {
+            Things::Cell(..) | Things::Group(..) => {
+                Some(RenameTarget::Local(self.references(thing, true)))
+            }
+            Things::Component(name) => Some(RenameTarget::Component(name)),
+            // renaming ports changes the interface of the component
+            Things::SelfPort(..)
+            | Things::CellPort(..)
+            | Things::Import(..) => None,
+        }
+    }
+
+    fn component_locations(&self, name: &str) -> Vec<lspt::Location> {
+        let (decls, uses) = self.component_refs(name);
+        decls
+            .into_iter()
+            .chain(uses)
+            .map(|n| {
+                lspt::Location::new(self.url.clone(), Range::from(n).into())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = r#"component add() -> () {
+  cells {}
+  wires {}
+  control {}
+}
+component main(in: 32) -> () {
+  cells {
+    r = std_reg(32);
+    a = add();
+  }
+  wires {
+    group write {
+      r.in = in;
+      r.write_en = 1'd1;
+      write[done] = r.done;
+    }
+  }
+  control {
+    seq { write; invoke a()(); }
+  }
+}
+"#;
+
+    /// The start of every location in `locations`
+    fn starts(locations: Vec<lspt::Location>) -> Vec<(u32, u32)> {
+        locations
+            .into_iter()
+            .map(|loc| (loc.range.start.line, loc.range.start.character))
+            .collect()
+    }
+
+    #[test]
+    fn identifiers() {
+        assert!(is_ident("r"));
+        assert!(is_ident("_tmp0"));
+        assert!(is_ident("read-data"));
+        assert!(!is_ident(""));
+        assert!(!is_ident("0r"));
+        assert!(!is_ident("r.out"));
+        assert!(!is_ident("r out"));
+    }
+
+    #[test]
+    fn local_names() {
+        let doc = Document::for_test(PROGRAM);
+        let Some(RenameTarget::Local(cell)) =
+            doc.rename_target(doc.thing_at_text("r.in", 0).unwrap())
+        else {
+            panic!("cells are local to their component")
+        };
+        assert_eq!(starts(cell), vec![(7, 4), (12, 6), (13, 6), (14, 20)]);
+
+        let Some(RenameTarget::Local(group)) =
+            doc.rename_target(doc.thing_at_text("write;", 0).unwrap())
+        else {
+            panic!("groups are local to their component")
+        };
+        assert_eq!(starts(group), vec![(11, 10), (14, 6), (18, 10)]);
+    }
+
+    #[test]
+    fn components() {
+        let doc = Document::for_test(PROGRAM);
+        let target = doc.rename_target(doc.thing_at_text("add()", 0).unwrap());
+        assert!(
+            matches!(target, Some(RenameTarget::Component(name)) if name == "add")
+        );
+        assert_eq!(
+            starts(doc.component_locations("add")),
+            vec![(0, 10), (8, 8)]
+        );
+    }
+
+    #[test]
+    fn ports_are_not_renamed() {
+        let doc = Document::for_test(PROGRAM);
+        assert!(doc
+            .rename_target(doc.thing_at_text("in;", 0).unwrap())
+            .is_none());
+        assert!(doc
+            .rename_target(doc.thing_at_text("write_en", 0).unwrap())
+            .is_none());
+    }
+}
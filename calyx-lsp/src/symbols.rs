@@ -0,0 +1,258 @@
+use itertools::multizip;
+use tower_lsp::lsp_types as lspt;
+use tree_sitter as ts;
+
+use crate::{convert::Range, document::Document};
+
+/// Construct a symbol for `node`, which is named by `name_node`.
+#[allow(deprecated)]
+fn symbol(
+    name: String,
+    detail: Option<String>,
+    kind: lspt::SymbolKind,
+    node: ts::Node,
+    name_node: ts::Node,
+    children: Vec<lspt::DocumentSymbol>,
+) -> lspt::DocumentSymbol {
+    lspt::DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        // deprecated in favor of `tags`, but still has to be provided
+        deprecated: None,
+        range: Range::from(node).into(),
+        selection_range: Range::from(name_node).into(),
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
+
+pub trait SymbolProvider {
+    /// Return an outline of the components and primitives in this document
+    fn document_symbols(&self) -> Vec<lspt::DocumentSymbol>;
+}
+
+impl SymbolProvider for Document {
+    fn document_symbols(&self) -> Vec<lspt::DocumentSymbol> {
+        self.root_node()
+            .into_iter()
+            .flat_map(|root| {
+                let map = self.captures(
+                    root,
+                    "(component (ident) @comp_name) @comp (primitive (ident) @prim_name) @prim",
+                );
+                let comps = multizip((map["comp"].iter(), map["comp_name"].iter()))
+                    .map(|(comp, name)| {
+                        symbol(
+                            self.node_text(name).to_string(),
+                            Some("component".to_string()),
+                            lspt::SymbolKind::MODULE,
+                            *comp,
+                            *name,
+                            self.component_symbols(*comp),
+                        )
+                    });
+                let prims = multizip((map["prim"].iter(), map["prim_name"].iter()))
+                    .map(|(prim, name)| {
+                        symbol(
+                            self.node_text(name).to_string(),
+                            Some("primitive".to_string()),
+                            lspt::SymbolKind::INTERFACE,
+                            *prim,
+                            *name,
+                            vec![],
+                        )
+                    });
+                comps.chain(prims).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl Document {
+    /// Return symbols for the cells, groups and control of `comp`.
+    fn component_symbols(&self, comp: ts::Node) -> Vec<lspt::DocumentSymbol> {
+        let map = self.captures(
+            comp,
+            r#"(cell_assignment (ident) @cell_name (instantiation) @inst) @cell
+               (group (ident) @group_name) @group
+               (control "control" @control_kw) @control"#,
+        );
+        let cells = multizip((
+            map["cell"].iter(),
+            map["cell_name"].iter(),
+            map["inst"].iter(),
+        ))
+        .map(|(cell, name, inst)| {
+            symbol(
+                self.node_text(name).to_string(),
+                Some(self.node_text(inst).to_string()),
+                lspt::SymbolKind::VARIABLE,
+                *cell,
+                *name,
+                vec![],
+            )
+        });
+        let groups = multizip((map["group"].iter(), map["group_name"].iter()))
+            .map(|(group, name)| {
+                // everything in front of the `group` keyword describes what
+                // kind of group this is
+                let mut cursor = group.walk();
+                let detail = group
+                    .children(&mut cursor)
+                    .take_while(|n| n.kind() != "group")
+                    .map(|n| self.node_text(&n))
+                    .chain(["group"])
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                symbol(
+                    self.node_text(name).to_string(),
+                    Some(detail),
+                    lspt::SymbolKind::FUNCTION,
+                    *group,
+                    *name,
+                    vec![],
+                )
+            });
+        let control =
+            multizip((map["control"].iter(), map["control_kw"].iter())).map(
+                |(control, kw)| {
+                    symbol(
+                        "control".to_string(),
+                        None,
+                        lspt::SymbolKind::NAMESPACE,
+                        *control,
+                        *kw,
+                        vec![],
+                    )
+                },
+            );
+        cells.chain(groups).chain(control).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Point;
+
+    /// Flatten `symbols` into `(depth, name, detail, kind)`
+    fn outline(
+        symbols: &[lspt::DocumentSymbol],
+        depth: usize,
+    ) -> Vec<(usize, String, Option<String>, lspt::SymbolKind)> {
+        symbols
+            .iter()
+            .flat_map(|s| {
+                std::iter::once((
+                    depth,
+                    s.name.clone(),
+                    s.detail.clone(),
+                    s.kind,
+                ))
+                .chain(outline(
+                    s.children.as_deref().unwrap_or_default(),
+                    depth + 1,
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn components_and_primitives() {
+        let doc = Document::for_test(
+            r#"extern "prims.sv" {
+  primitive std_reg[WIDTH](in: WIDTH, write_en: 1) -> (out: WIDTH, done: 1);
+}
+component main() -> () {
+  cells {
+    r = std_reg(32);
+    @external m = comb_mem_d1(32, 1, 1);
+  }
+  wires {
+    group write {
+      r.in = 32'd1;
+      r.write_en = 1'd1;
+      write[done] = r.done;
+    }
+    static<1> group noop { }
+    comb group cond { }
+  }
+  control {
+    write;
+  }
+}
+component empty() -> () {}
+"#,
+        );
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            outline(&doc.document_symbols(), 0),
+            vec![
+                (
+                    0,
+                    "main".into(),
+                    some("component"),
+                    lspt::SymbolKind::MODULE
+                ),
+                (
+                    1,
+                    "r".into(),
+                    some("std_reg(32)"),
+                    lspt::SymbolKind::VARIABLE
+                ),
+                (
+                    1,
+                    "m".into(),
+                    some("comb_mem_d1(32, 1, 1)"),
+                    lspt::SymbolKind::VARIABLE
+                ),
+                (1, "write".into(), some("group"), lspt::SymbolKind::FUNCTION),
+                (
+                    1,
+                    "noop".into(),
+                    some("static<1> group"),
+                    lspt::SymbolKind::FUNCTION
+                ),
+                (
+                    1,
+                    "cond".into(),
+                    some("comb group"),
+                    lspt::SymbolKind::FUNCTION
+                ),
+                (1, "control".into(), None, lspt::SymbolKind::NAMESPACE),
+                (
+                    0,
+                    "empty".into(),
+                    some("component"),
+                    lspt::SymbolKind::MODULE
+                ),
+                (
+                    0,
+                    "std_reg".into(),
+                    some("primitive"),
+                    lspt::SymbolKind::INTERFACE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_range_is_the_name() {
+        let doc = Document::for_test("component main() -> () {}\n");
+        let symbols = doc.document_symbols();
+        let main = &symbols[0];
+        assert_eq!(
+            main.range,
+            Range::new(Point::new(0, 0), Point::new(0, 25)).into()
+        );
+        assert_eq!(
+            main.selection_range,
+            Range::new(Point::new(0, 10), Point::new(0, 14)).into()
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! Helpers for the unit tests of the language server
+
+use std::path::{Path, PathBuf};
+
+/// The root of the repository, which the primitive imports are resolved
+/// against.
+pub fn lib_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Write `files` to a fresh directory for the test called `name` and return
+/// the path of the directory.
+pub fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("calyx-lsp-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, text) in files {
+        std::fs::write(dir.join(file), text).unwrap();
+    }
+    dir
+}
@@ -1,16 +1,17 @@
 //! Defines the default passes available to [PassManager].
 use crate::pass_manager::PassResult;
 use crate::passes::{
-    AddGuard, Canonicalize, CellShare, ClkInsertion, CollapseControl, CombProp,
-    CompileInvoke, CompileRepeat, CompileStatic, CompileSync,
-    CompileSyncWithoutSyncReg, ComponentInliner, DataPathInfer,
+    AddGuard, AxiWrapper, Canonicalize, CellShare, ClkInsertion,
+    CollapseControl, CombProp, CompileInvoke, CompileRepeat, CompileStatic,
+    CompileSync, CompileSyncWithoutSyncReg, ComponentInliner, DataPathInfer,
     DeadAssignmentRemoval, DeadCellRemoval, DeadGroupRemoval, DefaultAssigns,
     DiscoverExternal, ExternalToRef, Externalize, GoInsertion, GroupToInvoke,
     GroupToSeq, HoleInliner, InferShare, LowerGuards, MergeAssign, Metadata,
     Papercut, ParToSeq, RegisterUnsharing, RemoveIds, ResetInsertion,
-    SimplifyStaticGuards, SimplifyWithControl, StaticFSMOpts, StaticInference,
-    StaticInliner, StaticPromotion, SynthesisPapercut, TopDownCompileControl,
-    UnrollBounded, WellFormed, WireInliner, WrapMain,
+    SimplifyStaticGuards, SimplifyWithControl, SingleCycleRetiming,
+    StaticFSMOpts, StaticInference, StaticInliner, StaticPromotion,
+    SynthesisPapercut, TopDownCompileControl, UnrollBounded, WellFormed,
+    WireInliner, WrapMain,
 };
 use crate::traversal::Named;
 use crate::{pass_manager::PassManager, register_alias};
@@ -28,16 +29,16 @@ impl PassManager {
         // Optimization passes
         pm.register_pass::<CombProp>()?;
         pm.register_pass::<ComponentInliner>()?;
-        pm.register_pass::<CollapseControl>()?;
-        pm.register_pass::<DeadAssignmentRemoval>()?;
-        pm.register_pass::<DeadCellRemoval>()?;
-        pm.register_pass::<DeadGroupRemoval>()?;
+        pm.register_parallel_pass::<CollapseControl>()?;
+        pm.register_parallel_pass::<DeadAssignmentRemoval>()?;
+        pm.register_parallel_pass::<DeadCellRemoval>()?;
+        pm.register_parallel_pass::<DeadGroupRemoval>()?;
         pm.register_pass::<GroupToSeq>()?;
         pm.register_pass::<InferShare>()?;
         pm.register_pass::<CellShare>()?;
         pm.register_pass::<StaticInference>()?;
         pm.register_pass::<StaticPromotion>()?;
-        pm.register_pass::<SimplifyStaticGuards>()?;
+        pm.register_parallel_pass::<SimplifyStaticGuards>()?;
         pm.register_pass::<DataPathInfer>()?;
 
         // Compilation passes
@@ -50,14 +51,14 @@ impl PassManager {
         pm.register_pass::<TopDownCompileControl>()?;
         pm.register_pass::<CompileSync>()?;
         pm.register_pass::<CompileSyncWithoutSyncReg>()?;
-        pm.register_pass::<AddGuard>()?;
+        pm.register_parallel_pass::<AddGuard>()?;
 
         // Lowering passes
-        pm.register_pass::<GoInsertion>()?;
+        pm.register_parallel_pass::<GoInsertion>()?;
         pm.register_pass::<WireInliner>()?;
-        pm.register_pass::<ClkInsertion>()?;
-        pm.register_pass::<ResetInsertion>()?;
-        pm.register_pass::<MergeAssign>()?;
+        pm.register_parallel_pass::<ClkInsertion>()?;
+        pm.register_parallel_pass::<ResetInsertion>()?;
+        pm.register_parallel_pass::<MergeAssign>()?;
         pm.register_pass::<WrapMain>()?;
         pm.register_pass::<DefaultAssigns>()?;
 
@@ -75,6 +76,8 @@ impl PassManager {
         pm.register_pass::<HoleInliner>()?;
         pm.register_pass::<RemoveIds>()?;
         pm.register_pass::<ExternalToRef>()?;
+        pm.register_pass::<AxiWrapper>()?;
+        pm.register_pass::<SingleCycleRetiming>()?;
 
         //add metadata
         pm.register_pass::<Metadata>()?;
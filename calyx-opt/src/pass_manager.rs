@@ -43,6 +43,19 @@ impl PassManager {
         }))
     }
 
+    /// Register a pass that visits components independently of each other.
+    /// The pass uses [ir::Context::threads] threads to visit the components.
+    pub fn register_parallel_pass<Pass>(&mut self) -> PassResult<()>
+    where
+        Pass: traversal::ParallelVisitor,
+    {
+        self.register_generic_pass::<Pass>(Box::new(|ir| {
+            let threads = ir.threads;
+            Pass::do_pass_parallel(ir, threads)?;
+            Ok(())
+        }))
+    }
+
     /// Registers a diagnostic pass as a normal pass. If there is an error,
     /// this will report the first error gathered by the pass.
     pub fn register_diagnostic<Pass>(&mut self) -> PassResult<()>
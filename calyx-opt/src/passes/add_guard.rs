@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir as ir;
 
 #[derive(Default)]
@@ -14,6 +14,8 @@ impl Named for AddGuard {
     }
 }
 
+impl ParallelVisitor for AddGuard {}
+
 impl Visitor for AddGuard {
     fn start_static_control(
         &mut self,
@@ -0,0 +1,607 @@
+//! Components that implement the individual channels of an AXI manager.
+//!
+//! Every channel performs a single handshake on its `VALID`/`READY` pair and
+//! is invoked by the wrapper once per burst. The address channels issue the
+//! burst, the data channels move the beats of that burst, and the write
+//! response channel waits for the subordinate to acknowledge the burst.
+use calyx_ir::{self as ir, build_assignments, guard, structure};
+use ir::utils::{Memory, MemoryType};
+use ir::Nothing;
+
+use super::port;
+
+/// Where the beats of a data channel come from or go to
+#[derive(Clone, Copy)]
+pub(super) enum Data<'a> {
+    /// Move consecutive elements of a memory passed in as `ref mem_ref`. The
+    /// channel starts at the first element when its `first_burst` input is
+    /// set and continues after the last element it moved otherwise.
+    Memory(&'a Memory),
+    /// Move a single element through a `read_data` or `write_data` port
+    Port(u64),
+}
+
+impl Data<'_> {
+    fn width(&self) -> u64 {
+        match self {
+            Data::Memory(mem) => mem.data_width,
+            Data::Port(width) => *width,
+        }
+    }
+}
+
+/// Invoke the register `reg` to store the `width`-bit constant `val`.
+pub(super) fn set_reg(
+    builder: &mut ir::Builder,
+    reg: &ir::RRC<ir::Cell>,
+    val: u64,
+    width: u64,
+) -> ir::Control {
+    let val = builder.add_constant(val, width);
+    let out = val.borrow().get("out");
+    ir::Control::invoke(reg.clone(), vec![("in".into(), out)], vec![])
+}
+
+/// Assignments that raise the `valid` register until the other side accepts
+/// the transfer on `ready`. `handshake` remembers that the transfer happened
+/// and `bt_reg` signals it to the group one cycle later.
+fn handshake(
+    builder: &mut ir::Builder,
+    valid: &ir::RRC<ir::Cell>,
+    handshake: &ir::RRC<ir::Cell>,
+    bt_reg: &ir::RRC<ir::Cell>,
+    ready: ir::RRC<ir::Port>,
+) -> Vec<ir::Assignment<Nothing>> {
+    structure!(builder;
+        let hi = constant(1, 1);
+        let lo = constant(0, 1);
+    );
+    let done: ir::Guard<Nothing> = guard!(valid["out"]) & ready.into();
+    let occurred = guard!(handshake["out"]);
+    let raise = !done.clone() & !occurred.clone();
+    let lower = done.clone() | occurred.clone();
+    let not_occurred = !occurred;
+    let not_done = !done.clone();
+    let assigns = build_assignments!(builder;
+        valid["in"] = raise ? hi["out"];
+        valid["in"] = lower ? lo["out"];
+        valid["write_en"] = ? hi["out"];
+        handshake["in"] = done ? hi["out"];
+        handshake["write_en"] = not_occurred ? hi["out"];
+        bt_reg["in"] = done ? hi["out"];
+        bt_reg["in"] = not_done ? lo["out"];
+        bt_reg["write_en"] = ? hi["out"];
+    );
+    assigns.to_vec()
+}
+
+/// Assignments that accept a transfer on a channel where the subordinate
+/// drives `valid` and we drive `ready` from the `ready` register.
+fn accept(
+    builder: &mut ir::Builder,
+    ready: &ir::RRC<ir::Cell>,
+    bt_reg: &ir::RRC<ir::Cell>,
+    valid: ir::RRC<ir::Port>,
+) -> Vec<ir::Assignment<Nothing>> {
+    structure!(builder;
+        let hi = constant(1, 1);
+        let lo = constant(0, 1);
+    );
+    let done: ir::Guard<Nothing> = guard!(ready["out"]) & valid.into();
+    let not_done = !done.clone();
+    let assigns = build_assignments!(builder;
+        ready["in"] = not_done ? hi["out"];
+        ready["in"] = done ? lo["out"];
+        ready["write_en"] = ? hi["out"];
+        bt_reg["in"] = done ? hi["out"];
+        bt_reg["in"] = not_done ? lo["out"];
+        bt_reg["write_en"] = ? hi["out"];
+    );
+    assigns.to_vec()
+}
+
+/// Address registers that walk over a memory in row-major order. The
+/// registers keep their values between invocations of the channel, so a
+/// transfer that is split into several bursts picks up where the previous
+/// burst stopped.
+struct Indices {
+    regs: Vec<ir::RRC<ir::Cell>>,
+}
+
+impl Indices {
+    fn new(builder: &mut ir::Builder, mem: &Memory) -> Self {
+        let regs = mem
+            .idx_sizes
+            .iter()
+            .map(|width| builder.add_primitive("idx", "std_reg", &[*width]))
+            .collect();
+        Indices { regs }
+    }
+
+    /// Reset all the indices to zero when the `first_burst` port of `this`
+    /// is set.
+    fn reset(
+        &self,
+        builder: &mut ir::Builder,
+        mem: &Memory,
+        this: &ir::RRC<ir::Cell>,
+    ) -> ir::Control {
+        let mut resets: Vec<_> = self
+            .regs
+            .iter()
+            .zip(&mem.idx_sizes)
+            .map(|(reg, width)| set_reg(builder, reg, 0, *width))
+            .collect();
+        let reset = if resets.len() == 1 {
+            resets.pop().unwrap()
+        } else {
+            ir::Control::par(resets)
+        };
+        ir::Control::if_(
+            this.borrow().get("first_burst"),
+            None,
+            Box::new(reset),
+            Box::new(ir::Control::empty()),
+        )
+    }
+
+    /// Drive the address ports of `mem` with the indices.
+    fn address(
+        &self,
+        builder: &mut ir::Builder,
+        mem: &ir::RRC<ir::Cell>,
+    ) -> Vec<ir::Assignment<Nothing>> {
+        self.regs
+            .iter()
+            .enumerate()
+            .map(|(i, reg)| {
+                builder.build_assignment(
+                    mem.borrow().get(format!("addr{i}")),
+                    reg.borrow().get("out"),
+                    ir::Guard::True,
+                )
+            })
+            .collect()
+    }
+
+    /// A group that moves the indices to the next element. The innermost
+    /// dimension is always incremented and wraps around to zero, carrying
+    /// into the next dimension.
+    fn increment(
+        &self,
+        builder: &mut ir::Builder,
+        mem: &Memory,
+    ) -> ir::RRC<ir::Group> {
+        structure!(builder;
+            let hi = constant(1, 1);
+        );
+        let group = builder.add_group("incr_idx");
+        let mut carry: ir::Guard<Nothing> = ir::Guard::True;
+        for (i, reg) in self.regs.iter().enumerate().rev() {
+            let width = mem.idx_sizes[i];
+            let adder = builder.add_primitive("idx_adder", "std_add", &[width]);
+            let max = builder.add_constant(mem.dimension_sizes[i] - 1, width);
+            let zero = builder.add_constant(0, width);
+            let one = builder.add_constant(1, width);
+            let at_max = guard!(reg["out"] == max["out"]);
+            let wrap = carry.clone() & at_max.clone();
+            let step = carry.clone() & !at_max.clone();
+            let assigns = build_assignments!(builder;
+                adder["left"] = ? reg["out"];
+                adder["right"] = ? one["out"];
+                reg["in"] = wrap ? zero["out"];
+                reg["in"] = step ? adder["out"];
+                reg["write_en"] = carry ? hi["out"];
+            );
+            group.borrow_mut().assignments.extend(assigns);
+            carry &= at_max;
+        }
+        let inner = self.regs.last().unwrap();
+        let done = builder.build_assignment(
+            group.borrow().get("done"),
+            inner.borrow().get("done"),
+            ir::Guard::True,
+        );
+        group.borrow_mut().assignments.push(done);
+        group
+    }
+}
+
+/// Add a `ref mem_ref` cell with the same type as `mem`.
+fn memory_ref(builder: &mut ir::Builder, mem: &Memory) -> ir::RRC<ir::Cell> {
+    let mem =
+        builder.add_primitive("mem_ref", mem.primitive(), &mem.parameters());
+    mem.borrow_mut().set_reference(true);
+    mem
+}
+
+/// Build the read (`AR`) or write (`AW`) address channel for the memory
+/// called `name`. The channel issues a burst that starts at the address on
+/// its `axi_address` input. With `burst`, the length of the burst minus one
+/// is taken from the `axi_len` input. Otherwise the burst has a single beat.
+pub(super) fn address_channel(
+    lib: &ir::LibrarySignatures,
+    kind: &str,
+    name: &str,
+    axsize: u64,
+    burst: bool,
+) -> ir::Component {
+    use ir::Direction::{Input, Output};
+    let lower = kind.to_lowercase();
+    let mut ports = vec![
+        port("ARESETn", 1, Input),
+        port(format!("{kind}READY"), 1, Input),
+        port("axi_address", 64, Input),
+    ];
+    if burst {
+        ports.push(port("axi_len", 8, Input));
+    }
+    ports.extend([
+        port(format!("{kind}VALID"), 1, Output),
+        port(format!("{kind}ADDR"), 64, Output),
+        port(format!("{kind}SIZE"), 3, Output),
+        port(format!("{kind}LEN"), 8, Output),
+        port(format!("{kind}BURST"), 2, Output),
+        port(format!("{kind}PROT"), 3, Output),
+    ]);
+    let mut comp = ir::Component::new(
+        format!("m_{lower}_channel_{name}"),
+        ports,
+        true,
+        false,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    let valid = builder.add_primitive(format!("{lower}valid"), "std_reg", &[1]);
+    let occurred = builder.add_primitive(
+        format!("{lower}_handshake_occurred"),
+        "std_reg",
+        &[1],
+    );
+    let bt_reg = builder.add_primitive("bt_reg", "std_reg", &[1]);
+    let addr = this.borrow().get("axi_address");
+    let len = if burst {
+        this.borrow().get("axi_len")
+    } else {
+        builder.add_constant(0, 8).borrow().get("out")
+    };
+    structure!(builder;
+        let size = constant(axsize, 3);
+        let incr = constant(0b01, 2);
+        let prot = constant(0b110, 3);
+    );
+
+    let group = builder.add_group(format!("do_{lower}_transfer"));
+    let ready = this.borrow().get(format!("{kind}READY"));
+    let mut assigns =
+        handshake(&mut builder, &valid, &occurred, &bt_reg, ready);
+    let addr_port = this.borrow().get(format!("{kind}ADDR"));
+    assigns.push(builder.build_assignment(addr_port, addr, ir::Guard::True));
+    let len_port = this.borrow().get(format!("{kind}LEN"));
+    assigns.push(builder.build_assignment(len_port, len, ir::Guard::True));
+    let (sz, bu, pr) = (
+        format!("{kind}SIZE"),
+        format!("{kind}BURST"),
+        format!("{kind}PROT"),
+    );
+    assigns.extend(build_assignments!(builder;
+        this[sz] = ? size["out"];
+        this[bu] = ? incr["out"];
+        this[pr] = ? prot["out"];
+        group["done"] = ? bt_reg["out"];
+    ));
+    group.borrow_mut().assignments = assigns;
+
+    let vl = format!("{kind}VALID");
+    let cont = build_assignments!(builder;
+        this[vl] = ? valid["out"];
+    );
+    builder.add_continuous_assignments(cont.to_vec());
+
+    let control = ir::Control::seq(vec![
+        ir::Control::par(vec![
+            set_reg(&mut builder, &bt_reg, 0, 1),
+            set_reg(&mut builder, &occurred, 0, 1),
+        ]),
+        ir::Control::enable(group),
+        set_reg(&mut builder, &valid, 0, 1),
+    ]);
+    *builder.component.control.borrow_mut() = control;
+    comp
+}
+
+/// Build the read data (`R`) channel for the memory called `name`.
+pub(super) fn read_channel(
+    lib: &ir::LibrarySignatures,
+    name: &str,
+    data: Data,
+) -> ir::Component {
+    use ir::Direction::{Input, Output};
+    let width = data.width();
+    let mut ports = vec![
+        port("ARESETn", 1, Input),
+        port("RVALID", 1, Input),
+        port("RLAST", 1, Input),
+        port("RDATA", width, Input),
+        port("RRESP", 2, Input),
+        port("RREADY", 1, Output),
+    ];
+    match data {
+        Data::Port(_) => ports.push(port("read_data", width, Output)),
+        Data::Memory(_) => ports.push(port("first_burst", 1, Input)),
+    }
+    let mut comp = ir::Component::new(
+        format!("m_read_channel_{name}"),
+        ports,
+        true,
+        false,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    structure!(builder;
+        let rready = prim std_reg(1);
+        let read_data_reg = prim std_reg(width);
+        let bt_reg = prim std_reg(1);
+        let hi = constant(1, 1);
+        let lo = constant(0, 1);
+    );
+
+    let block_transfer = builder.add_group("block_transfer");
+    let rvalid = this.borrow().get("RVALID");
+    let mut assigns = accept(&mut builder, &rready, &bt_reg, rvalid);
+    let beat = guard!(rready["out"] & this["RVALID"]);
+    assigns.extend(build_assignments!(builder;
+        read_data_reg["in"] = ? this["RDATA"];
+        read_data_reg["write_en"] = beat ? hi["out"];
+        block_transfer["done"] = ? bt_reg["out"];
+    ));
+    let mut cont = build_assignments!(builder;
+        this["RREADY"] = ? rready["out"];
+    )
+    .to_vec();
+
+    let control = match data {
+        Data::Port(_) => {
+            cont.extend(build_assignments!(builder;
+                this["read_data"] = ? read_data_reg["out"];
+            ));
+            block_transfer.borrow_mut().assignments = assigns;
+            ir::Control::seq(vec![
+                set_reg(&mut builder, &bt_reg, 0, 1),
+                ir::Control::enable(block_transfer),
+            ])
+        }
+        Data::Memory(info) => {
+            // Track the end of the burst so that we stop after the last beat
+            let n_rlast = builder.add_primitive("n_RLAST", "std_reg", &[1]);
+            let last = guard!(this["RLAST"]);
+            let not_last = !last.clone();
+            assigns.extend(build_assignments!(builder;
+                n_rlast["in"] = last ? lo["out"];
+                n_rlast["in"] = not_last ? hi["out"];
+                n_rlast["write_en"] = beat ? hi["out"];
+            ));
+            block_transfer.borrow_mut().assignments = assigns;
+
+            let mem = memory_ref(&mut builder, info);
+            let idx = Indices::new(&mut builder, info);
+            let service = builder.add_group("service_read_transfer");
+            let mut assigns = idx.address(&mut builder, &mem);
+            assigns.extend(build_assignments!(builder;
+                rready["in"] = ? lo["out"];
+                rready["write_en"] = ? hi["out"];
+                mem["write_data"] = ? read_data_reg["out"];
+                mem["write_en"] = ? hi["out"];
+                service["done"] = ? mem["done"];
+            ));
+            if info.memory_type != MemoryType::Combinational {
+                assigns.extend(build_assignments!(builder;
+                    mem["content_en"] = ? hi["out"];
+                ));
+            }
+            service.borrow_mut().assignments = assigns;
+            let incr = idx.increment(&mut builder, info);
+
+            let cond = n_rlast.borrow().get("out");
+            let body = ir::Control::seq(vec![
+                set_reg(&mut builder, &bt_reg, 0, 1),
+                ir::Control::enable(block_transfer),
+                ir::Control::enable(service),
+                ir::Control::enable(incr),
+            ]);
+            ir::Control::seq(vec![
+                set_reg(&mut builder, &n_rlast, 1, 1),
+                idx.reset(&mut builder, info, &this),
+                ir::Control::while_(cond, None, Box::new(body)),
+            ])
+        }
+    };
+    builder.add_continuous_assignments(cont);
+    *builder.component.control.borrow_mut() = control;
+    comp
+}
+
+/// Build the write data (`W`) channel for the memory called `name`.
+pub(super) fn write_channel(
+    lib: &ir::LibrarySignatures,
+    name: &str,
+    data: Data,
+) -> ir::Component {
+    use ir::Direction::{Input, Output};
+    let width = data.width();
+    let mut ports = vec![port("ARESETn", 1, Input), port("WREADY", 1, Input)];
+    match data {
+        Data::Port(_) => ports.push(port("write_data", width, Input)),
+        Data::Memory(_) => ports
+            .extend([port("first_burst", 1, Input), port("axi_len", 8, Input)]),
+    }
+    ports.extend([
+        port("WVALID", 1, Output),
+        port("WLAST", 1, Output),
+        port("WDATA", width, Output),
+    ]);
+    let mut comp = ir::Component::new(
+        format!("m_write_channel_{name}"),
+        ports,
+        true,
+        false,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    structure!(builder;
+        let wvalid = prim std_reg(1);
+        let w_handshake_occurred = prim std_reg(1);
+        let bt_reg = prim std_reg(1);
+        let hi = constant(1, 1);
+        let lo = constant(0, 1);
+    );
+
+    let service = builder.add_group("service_write_transfer");
+    let wready = this.borrow().get("WREADY");
+    let mut assigns = handshake(
+        &mut builder,
+        &wvalid,
+        &w_handshake_occurred,
+        &bt_reg,
+        wready,
+    );
+    assigns.extend(build_assignments!(builder;
+        service["done"] = ? bt_reg["out"];
+    ));
+    let mut cont = build_assignments!(builder;
+        this["WVALID"] = ? wvalid["out"];
+    )
+    .to_vec();
+
+    let reset_handshake = |builder: &mut ir::Builder| {
+        ir::Control::par(vec![
+            set_reg(builder, &bt_reg, 0, 1),
+            set_reg(builder, &w_handshake_occurred, 0, 1),
+        ])
+    };
+    let control = match data {
+        Data::Port(_) => {
+            assigns.extend(build_assignments!(builder;
+                this["WDATA"] = ? this["write_data"];
+                this["WLAST"] = ? hi["out"];
+            ));
+            service.borrow_mut().assignments = assigns;
+            ir::Control::seq(vec![
+                reset_handshake(&mut builder),
+                ir::Control::enable(service),
+            ])
+        }
+        Data::Memory(info) => {
+            let n_finished_last_transfer = builder.add_primitive(
+                "n_finished_last_transfer",
+                "std_reg",
+                &[1],
+            );
+            // Count the beats of the burst to find the last one
+            let beat = builder.add_primitive("beat", "std_reg", &[8]);
+            let beat_adder =
+                builder.add_primitive("beat_adder", "std_add", &[8]);
+            let mem = memory_ref(&mut builder, info);
+            let idx = Indices::new(&mut builder, info);
+            let last = guard!(beat["out"] == this["axi_len"]);
+            let sent_last =
+                last.clone() & guard!(wvalid["out"] & this["WREADY"]);
+            assigns.extend(idx.address(&mut builder, &mem));
+            assigns.extend(build_assignments!(builder;
+                this["WDATA"] = ? mem["read_data"];
+                this["WLAST"] = last ? hi["out"];
+                n_finished_last_transfer["in"] = sent_last ? lo["out"];
+                n_finished_last_transfer["write_en"] = sent_last ? hi["out"];
+            ));
+            match info.memory_type {
+                MemoryType::Combinational => {}
+                _ => {
+                    assigns.extend(build_assignments!(builder;
+                        mem["content_en"] = ? hi["out"];
+                    ));
+                    cont.extend(build_assignments!(builder;
+                        mem["write_en"] = ? lo["out"];
+                    ));
+                }
+            }
+            service.borrow_mut().assignments = assigns;
+            let incr = idx.increment(&mut builder, info);
+            structure!(builder;
+                let one = constant(1, 8);
+            );
+            let count = build_assignments!(builder;
+                beat_adder["left"] = ? beat["out"];
+                beat_adder["right"] = ? one["out"];
+                beat["in"] = ? beat_adder["out"];
+                beat["write_en"] = ? hi["out"];
+            );
+            incr.borrow_mut().assignments.extend(count);
+
+            let cond = n_finished_last_transfer.borrow().get("out");
+            let body = ir::Control::seq(vec![
+                reset_handshake(&mut builder),
+                ir::Control::enable(service),
+                ir::Control::enable(incr),
+            ]);
+            ir::Control::seq(vec![
+                ir::Control::par(vec![
+                    set_reg(&mut builder, &n_finished_last_transfer, 1, 1),
+                    set_reg(&mut builder, &beat, 0, 8),
+                ]),
+                idx.reset(&mut builder, info, &this),
+                ir::Control::while_(cond, None, Box::new(body)),
+            ])
+        }
+    };
+    builder.add_continuous_assignments(cont);
+    *builder.component.control.borrow_mut() = control;
+    comp
+}
+
+/// Build the write response (`B`) channel for the memory called `name`.
+pub(super) fn bresp_channel(
+    lib: &ir::LibrarySignatures,
+    name: &str,
+) -> ir::Component {
+    use ir::Direction::{Input, Output};
+    let ports = vec![
+        port("ARESETn", 1, Input),
+        port("BVALID", 1, Input),
+        port("BRESP", 2, Input),
+        port("BREADY", 1, Output),
+    ];
+    let mut comp = ir::Component::new(
+        format!("m_bresp_channel_{name}"),
+        ports,
+        true,
+        false,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    structure!(builder;
+        let bready = prim std_reg(1);
+        let bt_reg = prim std_reg(1);
+    );
+    let block_transfer = builder.add_group("block_transfer");
+    let bvalid = this.borrow().get("BVALID");
+    let mut assigns = accept(&mut builder, &bready, &bt_reg, bvalid);
+    assigns.extend(build_assignments!(builder;
+        block_transfer["done"] = ? bt_reg["out"];
+    ));
+    block_transfer.borrow_mut().assignments = assigns;
+    let cont = build_assignments!(builder;
+        this["BREADY"] = ? bready["out"];
+    );
+    builder.add_continuous_assignments(cont.to_vec());
+
+    let control = ir::Control::seq(vec![
+        set_reg(&mut builder, &bt_reg, 0, 1),
+        ir::Control::enable(block_transfer),
+    ]);
+    *builder.component.control.borrow_mut() = control;
+    comp
+}
@@ -0,0 +1,209 @@
+//! Memories that translate every access into a single-beat AXI transaction.
+use calyx_ir::{self as ir, Nothing, RRC};
+use ir::utils::Memory;
+
+use super::{
+    axi_signals, axi_size, channels, connect, instance, invoke, port, Data,
+};
+
+/// Compute `left + right` and return the port with the result.
+fn add(
+    builder: &mut ir::Builder,
+    left: RRC<ir::Port>,
+    right: RRC<ir::Port>,
+    assigns: &mut Vec<ir::Assignment<Nothing>>,
+) -> RRC<ir::Port> {
+    let adder = builder.add_primitive("add", "std_add", &[64]);
+    assigns.push(builder.build_assignment(
+        adder.borrow().get("left"),
+        left,
+        ir::Guard::True,
+    ));
+    assigns.push(builder.build_assignment(
+        adder.borrow().get("right"),
+        right,
+        ir::Guard::True,
+    ));
+    let out = adder.borrow().get("out");
+    out
+}
+
+/// Multiply the 64-bit value on `value` by the constant `factor` using one
+/// shift and one addition for every bit that is set in `factor`.
+fn mul_const(
+    builder: &mut ir::Builder,
+    value: RRC<ir::Port>,
+    factor: u64,
+    assigns: &mut Vec<ir::Assignment<Nothing>>,
+) -> RRC<ir::Port> {
+    let terms: Vec<_> = (0..64)
+        .filter(|bit| factor >> bit & 1 == 1)
+        .map(|bit| {
+            if bit == 0 {
+                return value.clone();
+            }
+            let shift = builder.add_primitive("shift", "std_lsh", &[64]);
+            let amount = builder.add_constant(bit, 64);
+            assigns.push(builder.build_assignment(
+                shift.borrow().get("left"),
+                value.clone(),
+                ir::Guard::True,
+            ));
+            assigns.push(builder.build_assignment(
+                shift.borrow().get("right"),
+                amount.borrow().get("out"),
+                ir::Guard::True,
+            ));
+            let out = shift.borrow().get("out");
+            out
+        })
+        .collect();
+    let mut terms = terms.into_iter();
+    match terms.next() {
+        Some(first) => {
+            terms.fold(first, |acc, term| add(builder, acc, term, assigns))
+        }
+        None => builder.add_constant(0, 64).borrow().get("out"),
+    }
+}
+
+/// A combinational component that computes the byte address of the element
+/// of `mem` at the indices on its `addr` ports.
+fn address_translator(
+    lib: &ir::LibrarySignatures,
+    mem: &Memory,
+) -> ir::Component {
+    let mut ports: Vec<_> = mem
+        .idx_sizes
+        .iter()
+        .enumerate()
+        .map(|(i, width)| {
+            port(format!("addr{i}"), *width, ir::Direction::Input)
+        })
+        .collect();
+    ports.push(port("axi_address", 64, ir::Direction::Output));
+    let mut comp = ir::Component::new(
+        format!("address_translator_{}", mem.name),
+        ports,
+        false,
+        true,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    let mut assigns = vec![];
+
+    // Flatten the indices in row-major order
+    let mut index: Option<RRC<ir::Port>> = None;
+    for (i, (size, width)) in
+        mem.dimension_sizes.iter().zip(&mem.idx_sizes).enumerate()
+    {
+        let pad = builder.add_primitive("pad", "std_pad", &[*width, 64]);
+        assigns.push(builder.build_assignment(
+            pad.borrow().get("in"),
+            this.borrow().get(format!("addr{i}")),
+            ir::Guard::True,
+        ));
+        let padded = pad.borrow().get("out");
+        index = Some(match index {
+            None => padded,
+            Some(outer) => {
+                let outer = mul_const(&mut builder, outer, *size, &mut assigns);
+                add(&mut builder, outer, padded, &mut assigns)
+            }
+        });
+    }
+    let index = index.expect("memories have at least one dimension");
+    let bytes = mem.data_width / 8;
+    let address = mul_const(&mut builder, index, bytes, &mut assigns);
+    assigns.push(builder.build_assignment(
+        this.borrow().get("axi_address"),
+        address,
+        ir::Guard::True,
+    ));
+    builder.add_continuous_assignments(assigns);
+    comp
+}
+
+/// Build a component with the same interface as `cell`, which is the memory
+/// described by `mem`, that performs every access over AXI. Returns the
+/// components that it uses along with the component itself.
+pub(super) fn axi_memory(
+    lib: &ir::LibrarySignatures,
+    mem: &Memory,
+    cell: &ir::Cell,
+) -> (Vec<ir::Component>, ir::Component) {
+    let name = &mem.name;
+    let width = mem.data_width;
+    let size = axi_size(mem);
+    let parts = vec![
+        address_translator(lib, mem),
+        channels::address_channel(lib, "AR", name, size, false),
+        channels::read_channel(lib, name, Data::Port(width)),
+        channels::address_channel(lib, "AW", name, size, false),
+        channels::write_channel(lib, name, Data::Port(width)),
+        channels::bresp_channel(lib, name),
+    ];
+
+    let mut ports = cell.get_signature();
+    ports.extend(
+        axi_signals(width, false)
+            .into_iter()
+            .map(|(name, width, dir)| port(name, width, dir)),
+    );
+    let mut comp = ir::Component::new(
+        format!("axi_dyn_mem_{name}"),
+        ports,
+        true,
+        false,
+        None,
+    );
+    let mut builder = ir::Builder::new(&mut comp, lib);
+    let this = builder.component.signature.clone();
+    let [translator, ar, read, aw, write, bresp] = [
+        "address_translator",
+        "ar_channel",
+        "read_channel",
+        "aw_channel",
+        "write_channel",
+        "bresp_channel",
+    ]
+    .into_iter()
+    .zip(&parts)
+    .map(|(prefix, comp)| {
+        instance(&mut builder, format!("{prefix}_{name}"), comp)
+    })
+    .collect::<Vec<_>>()
+    .try_into()
+    .unwrap();
+
+    let mut assigns = vec![];
+    for cell in [&translator, &ar, &read, &aw, &write, &bresp] {
+        assigns.extend(connect(&builder, cell, &this, ""));
+    }
+    for channel in [&ar, &aw] {
+        assigns.push(builder.build_assignment(
+            channel.borrow().get("axi_address"),
+            translator.borrow().get("axi_address"),
+            ir::Guard::True,
+        ));
+    }
+    builder.add_continuous_assignments(assigns);
+
+    let write_en = this.borrow().get("write_en");
+    let control = ir::Control::if_(
+        write_en,
+        None,
+        Box::new(ir::Control::seq(vec![
+            invoke(aw, vec![], vec![]),
+            invoke(write, vec![], vec![]),
+            invoke(bresp, vec![], vec![]),
+        ])),
+        Box::new(ir::Control::seq(vec![
+            invoke(ar, vec![], vec![]),
+            invoke(read, vec![], vec![]),
+        ])),
+    );
+    *builder.component.control.borrow_mut() = control;
+    (parts, comp)
+}
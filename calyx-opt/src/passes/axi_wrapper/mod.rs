@@ -0,0 +1,458 @@
+mod channels;
+mod dynamic;
+
+use crate::traversal::{
+    Action, ConstructVisitor, Named, ParseVal, PassOpt, VisResult, Visitor,
+};
+use calyx_ir::{self as ir, build_assignments, structure, Nothing, RRC};
+use calyx_utils::{CalyxResult, Error};
+use channels::Data;
+use ir::utils::{Memory, MemoryType, ProgramInterface};
+
+/// Address of the first element of every memory when it is copied in bursts
+const BASE_ADDRESS: u64 = 0x1000;
+/// The longest burst supported by AXI4
+const MAX_BURST_LEN: u64 = 256;
+/// Bursts must not cross a boundary of this many bytes
+const MAX_BURST_BYTES: u64 = 4096;
+
+/// Wraps the toplevel component in a new toplevel component called `wrapper`
+/// that exposes an AXI manager interface for every `@external` and `ref`
+/// memory of the original toplevel. The memories of the original toplevel
+/// become `ref` cells and are passed to it by the wrapper.
+///
+/// By default, the wrapper copies every memory into a local memory with AXI
+/// bursts, runs the original toplevel, and copies the local memories back.
+/// With `-x axi-wrapper:dynamic`, the memories are replaced by components
+/// that translate every access into a single-beat AXI transaction instead.
+/// Combinational memories are still copied in bursts in that mode because
+/// their reads cannot wait for a transaction.
+///
+/// The interface of the wrapper is computed from the same [ProgramInterface]
+/// that the `yxi` tool emits.
+pub struct AxiWrapper {
+    /// Translate every memory access into an AXI transaction
+    dynamic: bool,
+}
+
+impl Named for AxiWrapper {
+    fn name() -> &'static str {
+        "axi-wrapper"
+    }
+
+    fn description() -> &'static str {
+        "Wrap the toplevel component in a component that accesses its memories over AXI"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "dynamic",
+            "translate every memory access into an AXI transaction instead of copying the memories in bursts",
+            ParseVal::Bool(false),
+            PassOpt::parse_bool,
+        )]
+    }
+}
+
+impl ConstructVisitor for AxiWrapper {
+    fn from(ctx: &ir::Context) -> CalyxResult<Self> {
+        let opts = Self::get_opts(ctx);
+        Ok(AxiWrapper {
+            dynamic: opts["dynamic"].bool(),
+        })
+    }
+
+    fn clear_data(&mut self) {}
+}
+
+/// The signals of an AXI manager interface for a bus that is `width` bits
+/// wide. The ID signals are only exposed by the wrapper.
+fn axi_signals(
+    width: u64,
+    ids: bool,
+) -> Vec<(&'static str, u64, ir::Direction)> {
+    use ir::Direction::{Input, Output};
+    let mut signals = vec![
+        ("ARESETn", 1, Input),
+        // read address channel
+        ("ARVALID", 1, Output),
+        ("ARREADY", 1, Input),
+        ("ARADDR", 64, Output),
+        ("ARSIZE", 3, Output),
+        ("ARLEN", 8, Output),
+        ("ARBURST", 2, Output),
+        ("ARPROT", 3, Output),
+        // read data channel
+        ("RVALID", 1, Input),
+        ("RREADY", 1, Output),
+        ("RLAST", 1, Input),
+        ("RDATA", width, Input),
+        ("RRESP", 2, Input),
+        // write address channel
+        ("AWVALID", 1, Output),
+        ("AWREADY", 1, Input),
+        ("AWADDR", 64, Output),
+        ("AWSIZE", 3, Output),
+        ("AWLEN", 8, Output),
+        ("AWBURST", 2, Output),
+        ("AWPROT", 3, Output),
+        // write data channel
+        ("WVALID", 1, Output),
+        ("WREADY", 1, Input),
+        ("WLAST", 1, Output),
+        ("WDATA", width, Output),
+        // write response channel
+        ("BVALID", 1, Input),
+        ("BREADY", 1, Output),
+        ("BRESP", 2, Input),
+    ];
+    if ids {
+        signals.extend([
+            ("ARID", 1, Output),
+            ("AWID", 1, Output),
+            ("RID", 1, Input),
+            ("BID", 1, Input),
+        ]);
+    }
+    signals
+}
+
+fn port(
+    name: impl Into<ir::Id>,
+    width: u64,
+    direction: ir::Direction,
+) -> ir::PortDef<u64> {
+    ir::PortDef::new(name, width, direction, ir::Attributes::default())
+}
+
+/// The value of the `xSIZE` signals, which is the number of bytes in a beat
+/// in log2.
+fn axi_size(mem: &Memory) -> u64 {
+    (mem.data_width / 8).trailing_zeros() as u64
+}
+
+/// Add an instance of `comp` called `prefix`.
+fn instance(
+    builder: &mut ir::Builder,
+    prefix: String,
+    comp: &ir::Component,
+) -> RRC<ir::Cell> {
+    let mut ports = comp.signature.borrow().get_signature();
+    ports
+        .iter_mut()
+        .for_each(|pd| pd.direction = pd.direction.reverse());
+    builder.add_component(ir::Id::from(prefix), comp.name, ports)
+}
+
+/// Connect every port of `cell` to the port of `sig` with the same name,
+/// preceded by `prefix`. The interface ports of `cell` are left alone.
+fn connect(
+    builder: &ir::Builder,
+    cell: &RRC<ir::Cell>,
+    sig: &RRC<ir::Cell>,
+    prefix: &str,
+) -> Vec<ir::Assignment<Nothing>> {
+    cell.borrow()
+        .ports()
+        .iter()
+        .filter_map(|inner| {
+            let port = inner.borrow();
+            let attrs = &port.attributes;
+            if attrs.has(ir::NumAttr::Go)
+                || attrs.has(ir::NumAttr::Done)
+                || attrs.has(ir::BoolAttr::Clk)
+                || attrs.has(ir::BoolAttr::Reset)
+            {
+                return None;
+            }
+            let outer = sig.borrow().find(format!("{prefix}{}", port.name))?;
+            Some(match port.direction {
+                ir::Direction::Input => builder.build_assignment(
+                    inner.clone(),
+                    outer,
+                    ir::Guard::True,
+                ),
+                _ => builder.build_assignment(
+                    outer,
+                    inner.clone(),
+                    ir::Guard::True,
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Invoke `comp` with the given inputs and `ref` cells.
+fn invoke(
+    comp: RRC<ir::Cell>,
+    inputs: Vec<(ir::Id, RRC<ir::Port>)>,
+    ref_cells: Vec<(ir::Id, RRC<ir::Cell>)>,
+) -> ir::Control {
+    ir::Control::Invoke(ir::Invoke {
+        comp,
+        inputs,
+        outputs: vec![],
+        attributes: ir::Attributes::default(),
+        comb_group: None,
+        ref_cells,
+    })
+}
+
+/// Split the transfer of all the elements of `mem` into bursts and return the
+/// address and the number of beats of every burst. A burst moves at most
+/// [MAX_BURST_LEN] elements and never crosses a [MAX_BURST_BYTES] boundary.
+fn bursts(mem: &Memory) -> Vec<(u64, u64)> {
+    let bytes = mem.data_width / 8;
+    let beats = MAX_BURST_LEN.min(MAX_BURST_BYTES / bytes);
+    (0..mem.total_size)
+        .step_by(beats as usize)
+        .map(|start| {
+            (
+                BASE_ADDRESS + start * bytes,
+                beats.min(mem.total_size - start),
+            )
+        })
+        .collect()
+}
+
+impl AxiWrapper {
+    /// Check that `mem` can be accessed over AXI.
+    fn check(mem: &Memory) -> CalyxResult<()> {
+        let width = mem.data_width;
+        if !width.is_power_of_two() || !(8..=1024).contains(&width) {
+            return Err(Error::misc(format!(
+                "Memory `{}' is {width} bits wide but AXI data buses must be a power of two between 8 and 1024 bits wide",
+                mem.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether every access to `mem` is translated into an AXI transaction.
+    fn translated(&self, mem: &Memory) -> bool {
+        self.dynamic && mem.memory_type != MemoryType::Combinational
+    }
+}
+
+impl Visitor for AxiWrapper {
+    fn precondition(ctx: &ir::Context) -> Option<String>
+    where
+        Self: Sized,
+    {
+        if !ctx.components.iter().any(|c| c.name == ctx.entrypoint) {
+            Some("Entrypoint is not part of the context".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn start_context(&mut self, ctx: &mut ir::Context) -> VisResult {
+        let interface = ProgramInterface::from_component(ctx.entrypoint())?;
+        for mem in &interface.memories {
+            Self::check(mem)?;
+        }
+        // Only memories can be passed to the original toplevel
+        for cell in ctx.entrypoint().cells.iter() {
+            let cell = cell.borrow();
+            let passed = cell.attributes.has(ir::BoolAttr::External)
+                || cell.is_reference();
+            if passed
+                && !interface
+                    .memories
+                    .iter()
+                    .any(|m| cell.name() == m.name.as_str())
+            {
+                return Err(Error::misc(format!(
+                    "Cannot generate an AXI wrapper because `{}' is an `@external' or `ref' cell of the toplevel that is not a memory",
+                    cell.name()
+                ))
+                .with_pos(&cell.attributes));
+            }
+        }
+        if ctx.components.iter().any(|c| c.name == "wrapper") {
+            return Err(Error::misc(
+                "Cannot generate an AXI wrapper because the program already defines a component called `wrapper'",
+            ));
+        }
+        for prim in ["std_reg", "std_add", "std_pad", "std_lsh"] {
+            if ctx.lib.find_primitive(prim).is_none() {
+                return Err(Error::misc(format!(
+                    "The AXI wrapper requires the `{prim}' primitive. Import `primitives/core.futil' to define it"
+                )));
+            }
+        }
+
+        // The wrapper passes the memories to the original toplevel
+        let entry = ctx.entrypoint_mut();
+        entry.attributes.remove(ir::BoolAttr::TopLevel);
+        let memories = ir::utils::external_and_ref_memories_cells(entry);
+        // Pair every memory of the interface with the cell that it describes
+        let paired = interface
+            .memories
+            .iter()
+            .map(|mem| {
+                memories
+                    .iter()
+                    .find(|cell| cell.borrow().name() == mem.name.as_str())
+                    .map(|cell| (mem, cell.clone()))
+                    .ok_or_else(|| {
+                        Error::misc(format!(
+                            "Memory `{}' of the interface is not a cell of the toplevel component",
+                            mem.name
+                        ))
+                    })
+            })
+            .collect::<CalyxResult<Vec<_>>>()?;
+        for (mem, cell) in &paired {
+            let mut cell = cell.borrow_mut();
+            cell.attributes.remove(ir::BoolAttr::External);
+            cell.set_reference(true);
+            // Accesses over AXI take a dynamic number of cycles
+            if self.translated(mem) {
+                for port in &cell.ports {
+                    port.borrow_mut().attributes.remove(ir::NumAttr::Interval);
+                }
+            }
+        }
+
+        let ports = interface
+            .memories
+            .iter()
+            .flat_map(|mem| {
+                axi_signals(mem.data_width, true).into_iter().map(
+                    |(name, width, dir)| {
+                        port(format!("{}_{name}", mem.name), width, dir)
+                    },
+                )
+            })
+            .collect();
+        let mut wrapper =
+            ir::Component::new("wrapper", ports, true, false, None);
+        wrapper.attributes.insert(ir::BoolAttr::TopLevel, 1);
+
+        let mut comps = vec![];
+        let mut builder = ir::Builder::new(&mut wrapper, &ctx.lib);
+        let this = builder.component.signature.clone();
+        let main =
+            instance(&mut builder, "main_compute".into(), ctx.entrypoint());
+        structure!(builder;
+            let lo = constant(0, 1);
+        );
+        let mut ref_cells = vec![];
+        let mut reads = vec![];
+        let mut writes = vec![];
+        for (mem, cell) in paired {
+            let name = &mem.name;
+            let lib = &ctx.lib;
+            let mut parts = vec![];
+            if self.translated(mem) {
+                let (uses, memory) =
+                    dynamic::axi_memory(lib, mem, &cell.borrow());
+                comps.extend(uses);
+                parts.push(("axi_dyn_mem", memory));
+            } else {
+                let size = axi_size(mem);
+                parts.extend([
+                    (
+                        "ar_channel",
+                        channels::address_channel(lib, "AR", name, size, true),
+                    ),
+                    (
+                        "read_channel",
+                        channels::read_channel(lib, name, Data::Memory(mem)),
+                    ),
+                    (
+                        "aw_channel",
+                        channels::address_channel(lib, "AW", name, size, true),
+                    ),
+                    (
+                        "write_channel",
+                        channels::write_channel(lib, name, Data::Memory(mem)),
+                    ),
+                    ("bresp_channel", channels::bresp_channel(lib, name)),
+                ]);
+            }
+
+            let mut cont = vec![];
+            let mut cells = vec![];
+            for (prefix, comp) in parts {
+                let cell =
+                    instance(&mut builder, format!("{prefix}_{name}"), &comp);
+                cont.extend(connect(
+                    &builder,
+                    &cell,
+                    &this,
+                    &format!("{name}_"),
+                ));
+                cells.push(cell);
+                comps.push(comp);
+            }
+
+            let name_id = ir::Id::from(name.as_str());
+            if self.translated(mem) {
+                ref_cells.push((name_id, cells.pop().unwrap()));
+            } else {
+                let internal = builder.add_primitive(
+                    format!("internal_mem_{name}"),
+                    mem.primitive(),
+                    &mem.parameters(),
+                );
+                let mem_ref = || vec![("mem_ref".into(), internal.clone())];
+                let [ar, read, aw, write, bresp] =
+                    <[_; 5]>::try_from(cells).ok().unwrap();
+                let mut read_bursts = vec![];
+                let mut write_bursts = vec![];
+                for (i, (address, beats)) in bursts(mem).into_iter().enumerate()
+                {
+                    let mut constant = |val, width| {
+                        builder.add_constant(val, width).borrow().get("out")
+                    };
+                    let first =
+                        ("first_burst".into(), constant(u64::from(i == 0), 1));
+                    let len = ("axi_len".into(), constant(beats - 1, 8));
+                    let address = vec![
+                        ("axi_address".into(), constant(address, 64)),
+                        len.clone(),
+                    ];
+                    read_bursts.extend([
+                        invoke(ar.clone(), address.clone(), vec![]),
+                        invoke(read.clone(), vec![first.clone()], mem_ref()),
+                    ]);
+                    write_bursts.extend([
+                        invoke(aw.clone(), address, vec![]),
+                        invoke(write.clone(), vec![first, len], mem_ref()),
+                        invoke(bresp.clone(), vec![], vec![]),
+                    ]);
+                }
+                reads.push(ir::Control::seq(read_bursts));
+                writes.push(ir::Control::seq(write_bursts));
+                ref_cells.push((name_id, internal));
+            }
+            let (arid, awid) = (format!("{name}_ARID"), format!("{name}_AWID"));
+            cont.extend(build_assignments!(builder;
+                this[arid] = ? lo["out"];
+                this[awid] = ? lo["out"];
+            ));
+            builder.add_continuous_assignments(cont);
+        }
+
+        let compute = invoke(main, vec![], ref_cells);
+        let control = if reads.is_empty() {
+            compute
+        } else {
+            ir::Control::seq(vec![
+                ir::Control::par(reads),
+                compute,
+                ir::Control::par(writes),
+            ])
+        };
+        *builder.component.control.borrow_mut() = control;
+
+        ctx.entrypoint = wrapper.name;
+        ctx.components.extend(comps);
+        ctx.components.push(wrapper);
+
+        // Purely context directed pass
+        Ok(Action::Stop)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir, GetAttributes, LibrarySignatures};
 
 #[derive(Default)]
@@ -61,6 +61,8 @@ impl Named for CollapseControl {
     }
 }
 
+impl ParallelVisitor for CollapseControl {}
+
 impl Visitor for CollapseControl {
     /// Collapse seq { seq { A }; B } into seq { A; B }.
     fn finish_seq(
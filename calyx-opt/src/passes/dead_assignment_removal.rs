@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir};
 use std::collections::{HashMap, HashSet};
 
@@ -99,6 +99,8 @@ fn saturate_dep_maps(
     used_combs
 }
 
+impl ParallelVisitor for DeadAssignmentRemoval {}
+
 impl Visitor for DeadAssignmentRemoval {
     fn start(
         &mut self,
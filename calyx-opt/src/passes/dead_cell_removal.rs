@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir};
 use std::collections::HashSet;
 use std::iter;
@@ -67,6 +67,8 @@ impl DeadCellRemoval {
     }
 }
 
+impl ParallelVisitor for DeadCellRemoval {}
+
 impl Visitor for DeadCellRemoval {
     fn start_if(
         &mut self,
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir, LibrarySignatures};
 use std::collections::HashSet;
 
@@ -22,6 +22,8 @@ impl Named for DeadGroupRemoval {
     }
 }
 
+impl ParallelVisitor for DeadGroupRemoval {}
+
 impl Visitor for DeadGroupRemoval {
     fn enable(
         &mut self,
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::guard;
 use calyx_ir::{self as ir, LibrarySignatures};
 
@@ -32,6 +32,8 @@ impl Named for GoInsertion {
     }
 }
 
+impl ParallelVisitor for GoInsertion {}
+
 impl Visitor for GoInsertion {
     fn start(
         &mut self,
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir, LibrarySignatures};
 use itertools::Itertools;
 use linked_hash_map::LinkedHashMap;
@@ -57,6 +57,8 @@ fn merge_assigns<T: Eq>(
         .collect::<Vec<_>>()
 }
 
+impl ParallelVisitor for MergeAssign {}
+
 impl Visitor for MergeAssign {
     fn start(
         &mut self,
@@ -1,4 +1,5 @@
 //! Passes for the Calyx compiler.
+mod axi_wrapper;
 mod canonical;
 mod cell_share;
 mod clk_insertion;
@@ -28,6 +29,7 @@ mod register_unsharing;
 mod remove_ids;
 mod reset_insertion;
 mod simplify_static_guards;
+mod single_cycle_retiming;
 mod static_fsm_opts;
 mod static_inference;
 mod static_inliner;
@@ -47,6 +49,7 @@ mod well_formed;
 mod wire_inliner;
 mod wrap_main;
 
+pub use axi_wrapper::AxiWrapper;
 pub use canonical::Canonicalize;
 pub use cell_share::CellShare;
 pub use clk_insertion::ClkInsertion;
@@ -79,6 +82,7 @@ pub use remove_ids::RemoveIds;
 pub use reset_insertion::ResetInsertion;
 pub use simplify_static_guards::SimplifyStaticGuards;
 pub use simplify_with_control::SimplifyWithControl;
+pub use single_cycle_retiming::SingleCycleRetiming;
 pub use static_fsm_opts::StaticFSMOpts;
 pub use static_inference::StaticInference;
 pub use static_inliner::StaticInliner;
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir::{self as ir, LibrarySignatures};
 use calyx_utils::Error;
 use std::rc::Rc;
@@ -18,6 +18,8 @@ impl Named for ResetInsertion {
     }
 }
 
+impl ParallelVisitor for ResetInsertion {}
+
 impl Visitor for ResetInsertion {
     fn start(
         &mut self,
@@ -1,4 +1,4 @@
-use crate::traversal::{Action, Named, VisResult, Visitor};
+use crate::traversal::{Action, Named, ParallelVisitor, VisResult, Visitor};
 use calyx_ir as ir;
 
 #[derive(Default)]
@@ -147,6 +147,8 @@ impl SimplifyStaticGuards {
     }
 }
 
+impl ParallelVisitor for SimplifyStaticGuards {}
+
 impl Visitor for SimplifyStaticGuards {
     fn start(
         &mut self,
@@ -0,0 +1,944 @@
+use crate::traversal::{
+    Action, ConstructVisitor, Named, Order, ParseVal, PassOpt, VisResult,
+    Visitor,
+};
+use calyx_ir::{
+    self as ir, DefaultDelayModel, DelayModel, LibrarySignatures, RRC,
+};
+use calyx_utils::CalyxResult;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU64;
+use std::rc::Rc;
+
+/// Inserts pipeline registers into static groups whose combinational paths
+/// are longer than the delay budget, and increases the latency of the groups
+/// to account for the new pipeline stages.
+///
+/// The delays of combinational primitives, multiplexers, and guards come from
+/// the [DefaultDelayModel]. The pass walks the combinational cells used by a
+/// group in dataflow order and moves a cell into the next stage when its
+/// output would arrive after the budget. The inputs of sequential cells and
+/// the outputs of the component are written in the last stage.
+///
+/// A `static<n>` group with `k` new stages is turned into a `static<n+k>`
+/// group in which the computation of every original cycle is pipelined: an
+/// assignment that was active in cycle `i` and writes a port computed in stage
+/// `s` is active in cycle `i+s`, so the sequential cells are written `k`
+/// cycles later than before.
+///
+/// The pass only retimes a group when the transformation cannot change the
+/// behavior of the program:
+/// 1. The group does not drive the ports of component instances.
+/// 2. The combinational cells in the group are not used anywhere else.
+/// 3. If the group takes more than one cycle, it does not read the sequential
+///    cells that it writes, since the writes happen later after retiming.
+/// 4. If the group is enabled inside a `static par`, the other arms of the
+///    `static par` do not use any of its cells.
+///
+/// The latencies of the static control programs that enable retimed groups
+/// are updated, along with the latency and `@interval` annotations of static
+/// components and the `static invoke`s of those components. Static components
+/// whose instances are used by groups are never retimed since their users
+/// depend on their latency.
+pub struct SingleCycleRetiming {
+    /// Longest combinational delay allowed in a cycle, in nanoseconds.
+    budget: f64,
+    /// The new latencies of static components whose latency changed.
+    latencies: HashMap<ir::Id, u64>,
+    /// Static components whose latency must not change.
+    fixed: HashSet<ir::Id>,
+}
+
+impl ConstructVisitor for SingleCycleRetiming {
+    fn from(ctx: &ir::Context) -> CalyxResult<Self> {
+        let opts = Self::get_opts(ctx);
+        Ok(SingleCycleRetiming {
+            budget: opts["delay-budget"].pos_num().unwrap() as f64 / 1000.0,
+            latencies: HashMap::new(),
+            fixed: Self::fixed_components(ctx),
+        })
+    }
+
+    // The new latencies of components are needed by the components that
+    // instantiate them.
+    fn clear_data(&mut self) {}
+}
+
+impl Named for SingleCycleRetiming {
+    fn name() -> &'static str {
+        "single-cycle-retiming"
+    }
+
+    fn description() -> &'static str {
+        "insert pipeline registers into static groups with long combinational paths"
+    }
+
+    fn opts() -> Vec<PassOpt> {
+        vec![PassOpt::new(
+            "delay-budget",
+            "longest combinational delay allowed in a cycle, in picoseconds",
+            ParseVal::Num(4000),
+            PassOpt::parse_num,
+        )]
+    }
+}
+
+impl SingleCycleRetiming {
+    /// Static components whose instances are used by groups, along with the
+    /// components they statically invoke.
+    fn fixed_components(ctx: &ir::Context) -> HashSet<ir::Id> {
+        let mut fixed = HashSet::new();
+        for comp in &ctx.components {
+            let mut add = |port: &RRC<ir::Port>| {
+                let port = port.borrow();
+                if let ir::PortParent::Cell(cell) = &port.parent {
+                    if let ir::CellType::Component { name } =
+                        &cell.upgrade().borrow().prototype
+                    {
+                        fixed.insert(*name);
+                    }
+                }
+            };
+            comp.iter_assignments(|assign| {
+                assign.iter_ports().for_each(|p| add(&p));
+            });
+            comp.iter_static_assignments(|assign| {
+                assign.iter_ports().for_each(|p| add(&p));
+            });
+        }
+
+        let comps: HashMap<ir::Id, &ir::Component> =
+            ctx.components.iter().map(|c| (c.name, c)).collect();
+        let mut worklist = fixed.iter().copied().collect::<Vec<_>>();
+        while let Some(name) = worklist.pop() {
+            let mut invoked = vec![];
+            static_invokes(&comps[&name].control.borrow(), &mut invoked);
+            for name in invoked {
+                if fixed.insert(name) {
+                    worklist.push(name);
+                }
+            }
+        }
+        fixed
+    }
+}
+
+/// Components invoked by `static invoke`s in the control program.
+fn static_invokes(con: &ir::Control, invoked: &mut Vec<ir::Id>) {
+    fn visit(sc: &ir::StaticControl, invoked: &mut Vec<ir::Id>) {
+        match sc {
+            ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+            | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => {
+                stmts.iter().for_each(|s| visit(s, invoked))
+            }
+            ir::StaticControl::If(sif) => {
+                visit(&sif.tbranch, invoked);
+                visit(&sif.fbranch, invoked);
+            }
+            ir::StaticControl::Repeat(rep) => visit(&rep.body, invoked),
+            ir::StaticControl::Invoke(inv) => {
+                invoked.extend(inv.comp.borrow().type_name())
+            }
+            ir::StaticControl::Enable(_) | ir::StaticControl::Empty(_) => (),
+        }
+    }
+    match con {
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            stmts.iter().for_each(|s| static_invokes(s, invoked))
+        }
+        ir::Control::If(cif) => {
+            static_invokes(&cif.tbranch, invoked);
+            static_invokes(&cif.fbranch, invoked);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            static_invokes(body, invoked)
+        }
+        ir::Control::Static(sc) => visit(sc, invoked),
+        ir::Control::Invoke(_)
+        | ir::Control::Enable(_)
+        | ir::Control::Empty(_) => (),
+    }
+}
+
+/// Adds the cell of the port to `cells` unless it is a constant or the
+/// component itself.
+fn port_cell(port: &RRC<ir::Port>, cells: &mut HashSet<ir::Id>) {
+    let port = port.borrow();
+    if let ir::PortParent::Cell(cell) = &port.parent {
+        let cell = cell.upgrade();
+        let cell = cell.borrow();
+        if !matches!(
+            cell.prototype,
+            ir::CellType::Constant { .. } | ir::CellType::ThisComponent
+        ) {
+            cells.insert(cell.name());
+        }
+    }
+}
+
+/// Cells used by the assignments.
+fn assignment_cells<T>(
+    assigns: &[ir::Assignment<T>],
+    cells: &mut HashSet<ir::Id>,
+) {
+    for assign in assigns {
+        assign.iter_ports().for_each(|p| port_cell(&p, cells));
+    }
+}
+
+/// Cells used by the static control program.
+fn control_cells(sc: &ir::StaticControl, cells: &mut HashSet<ir::Id>) {
+    match sc {
+        ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+        | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => {
+            stmts.iter().for_each(|s| control_cells(s, cells))
+        }
+        ir::StaticControl::If(sif) => {
+            port_cell(&sif.port, cells);
+            control_cells(&sif.tbranch, cells);
+            control_cells(&sif.fbranch, cells);
+        }
+        ir::StaticControl::Repeat(rep) => control_cells(&rep.body, cells),
+        ir::StaticControl::Enable(en) => {
+            assignment_cells(&en.group.borrow().assignments, cells)
+        }
+        ir::StaticControl::Invoke(inv) => {
+            cells.insert(inv.comp.borrow().name());
+            inv.inputs
+                .iter()
+                .chain(&inv.outputs)
+                .for_each(|(_, p)| port_cell(p, cells));
+            cells.extend(inv.ref_cells.iter().map(|(_, c)| c.borrow().name()));
+        }
+        ir::StaticControl::Empty(_) => (),
+    }
+}
+
+/// Names of the static groups enabled in the static control program.
+fn enabled_groups(sc: &ir::StaticControl, groups: &mut Vec<ir::Id>) {
+    match sc {
+        ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+        | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => {
+            stmts.iter().for_each(|s| enabled_groups(s, groups))
+        }
+        ir::StaticControl::If(sif) => {
+            enabled_groups(&sif.tbranch, groups);
+            enabled_groups(&sif.fbranch, groups);
+        }
+        ir::StaticControl::Repeat(rep) => enabled_groups(&rep.body, groups),
+        ir::StaticControl::Enable(en) => groups.push(en.group.borrow().name()),
+        ir::StaticControl::Invoke(_) | ir::StaticControl::Empty(_) => (),
+    }
+}
+
+/// For every static group enabled inside a `static par`, the cells used by
+/// the other arms of the `static par`.
+fn par_conflicts(
+    sc: &ir::StaticControl,
+    conflicts: &mut HashMap<ir::Id, HashSet<ir::Id>>,
+) {
+    match sc {
+        ir::StaticControl::Par(par) => {
+            let used = par
+                .stmts
+                .iter()
+                .map(|s| {
+                    let mut cells = HashSet::new();
+                    control_cells(s, &mut cells);
+                    cells
+                })
+                .collect::<Vec<_>>();
+            for (idx, stmt) in par.stmts.iter().enumerate() {
+                let mut groups = vec![];
+                enabled_groups(stmt, &mut groups);
+                for group in groups {
+                    let others = conflicts.entry(group).or_default();
+                    for (_, cells) in
+                        used.iter().enumerate().filter(|(i, _)| *i != idx)
+                    {
+                        others.extend(cells.iter().copied());
+                    }
+                }
+                par_conflicts(stmt, conflicts);
+            }
+        }
+        ir::StaticControl::Seq(seq) => {
+            seq.stmts.iter().for_each(|s| par_conflicts(s, conflicts))
+        }
+        ir::StaticControl::If(sif) => {
+            par_conflicts(&sif.tbranch, conflicts);
+            par_conflicts(&sif.fbranch, conflicts);
+        }
+        ir::StaticControl::Repeat(rep) => par_conflicts(&rep.body, conflicts),
+        ir::StaticControl::Enable(_)
+        | ir::StaticControl::Invoke(_)
+        | ir::StaticControl::Empty(_) => (),
+    }
+}
+
+/// Calls `f` on every static control program in `con`.
+fn for_each_static<F>(con: &mut ir::Control, f: &mut F)
+where
+    F: FnMut(&mut ir::StaticControl),
+{
+    match con {
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            stmts.iter_mut().for_each(|s| for_each_static(s, f))
+        }
+        ir::Control::If(cif) => {
+            for_each_static(&mut cif.tbranch, f);
+            for_each_static(&mut cif.fbranch, f);
+        }
+        ir::Control::While(ir::While { body, .. })
+        | ir::Control::Repeat(ir::Repeat { body, .. }) => {
+            for_each_static(body, f)
+        }
+        ir::Control::Static(sc) => f(sc),
+        ir::Control::Invoke(_)
+        | ir::Control::Enable(_)
+        | ir::Control::Empty(_) => (),
+    }
+}
+
+/// Recompute the latencies of the static control program using the current
+/// latencies of its groups and the new latencies of invoked components.
+fn update_latency(
+    sc: &mut ir::StaticControl,
+    latencies: &HashMap<ir::Id, u64>,
+) -> u64 {
+    match sc {
+        ir::StaticControl::Enable(en) => en.group.borrow().get_latency(),
+        ir::StaticControl::Seq(seq) => {
+            seq.latency = seq
+                .stmts
+                .iter_mut()
+                .map(|s| update_latency(s, latencies))
+                .sum();
+            seq.latency
+        }
+        ir::StaticControl::Par(par) => {
+            par.latency = par
+                .stmts
+                .iter_mut()
+                .map(|s| update_latency(s, latencies))
+                .max()
+                .unwrap_or_default();
+            par.latency
+        }
+        ir::StaticControl::If(sif) => {
+            sif.latency = std::cmp::max(
+                update_latency(&mut sif.tbranch, latencies),
+                update_latency(&mut sif.fbranch, latencies),
+            );
+            sif.latency
+        }
+        ir::StaticControl::Repeat(rep) => {
+            rep.latency =
+                rep.num_repeats * update_latency(&mut rep.body, latencies);
+            rep.latency
+        }
+        ir::StaticControl::Invoke(inv) => {
+            let name = inv.comp.borrow().type_name();
+            if let Some(latency) = name.and_then(|n| latencies.get(&n)) {
+                inv.latency = *latency;
+            }
+            inv.latency
+        }
+        ir::StaticControl::Empty(_) => 0,
+    }
+}
+
+/// Set the `@interval` annotation of the go ports of a component's signature
+/// or of one of its instances.
+fn set_interval(cell: &ir::Cell, latency: u64) {
+    for port in cell.find_all_with_attr(ir::NumAttr::Go) {
+        let mut port = port.borrow_mut();
+        if port.attributes.has(ir::NumAttr::Interval) {
+            port.attributes.insert(ir::NumAttr::Interval, latency);
+        }
+    }
+}
+
+/// The time at which a value is available.
+#[derive(Clone, Copy)]
+enum Avail {
+    /// The value does not change while the group runs.
+    Stable,
+    /// The value is computed in the stage and is available after the delay.
+    Stage(u64, f64),
+}
+
+/// Uses of the ports in a component.
+#[derive(Default)]
+struct PortUses {
+    /// The static groups that read or write a port.
+    groups: HashMap<ir::Canonical, HashSet<ir::Id>>,
+    /// Ports used by the control program, continuous assignments, or
+    /// dynamic groups.
+    other: HashSet<ir::Canonical>,
+    /// The number of assignments that write to a port and whether any of
+    /// them is guarded.
+    drivers: HashMap<ir::Canonical, (u64, bool)>,
+}
+
+impl PortUses {
+    fn new(comp: &ir::Component) -> Self {
+        let mut uses = PortUses::default();
+        comp.iter_assignments(|assign| {
+            assign.iter_ports().for_each(|p| {
+                uses.other.insert(p.borrow().canonical());
+            });
+            uses.add_driver(assign);
+        });
+        for group in comp.get_static_groups().iter() {
+            let group = group.borrow();
+            for assign in &group.assignments {
+                for port in assign.iter_ports() {
+                    uses.groups
+                        .entry(port.borrow().canonical())
+                        .or_default()
+                        .insert(group.name());
+                }
+                uses.add_driver(assign);
+            }
+        }
+        let mut ports = vec![];
+        control_ports(&comp.control.borrow(), &mut ports);
+        uses.other
+            .extend(ports.iter().map(|p| p.borrow().canonical()));
+        uses
+    }
+
+    fn add_driver<T>(&mut self, assign: &ir::Assignment<T>) {
+        let d = self
+            .drivers
+            .entry(assign.dst.borrow().canonical())
+            .or_default();
+        d.0 += 1;
+        d.1 |= !assign.guard.is_true();
+    }
+
+    /// True if the port is only used by the group.
+    fn private(&self, port: &ir::Canonical, group: ir::Id) -> bool {
+        !self.other.contains(port)
+            && self
+                .groups
+                .get(port)
+                .into_iter()
+                .flatten()
+                .all(|g| *g == group)
+    }
+}
+
+/// Ports read by the control program.
+fn control_ports(con: &ir::Control, ports: &mut Vec<RRC<ir::Port>>) {
+    fn visit(sc: &ir::StaticControl, ports: &mut Vec<RRC<ir::Port>>) {
+        match sc {
+            ir::StaticControl::Seq(ir::StaticSeq { stmts, .. })
+            | ir::StaticControl::Par(ir::StaticPar { stmts, .. }) => {
+                stmts.iter().for_each(|s| visit(s, ports))
+            }
+            ir::StaticControl::If(sif) => {
+                ports.push(Rc::clone(&sif.port));
+                visit(&sif.tbranch, ports);
+                visit(&sif.fbranch, ports);
+            }
+            ir::StaticControl::Repeat(rep) => visit(&rep.body, ports),
+            ir::StaticControl::Invoke(inv) => ports.extend(
+                inv.inputs
+                    .iter()
+                    .chain(&inv.outputs)
+                    .map(|(_, p)| p.clone()),
+            ),
+            ir::StaticControl::Enable(_) | ir::StaticControl::Empty(_) => (),
+        }
+    }
+    match con {
+        ir::Control::Seq(ir::Seq { stmts, .. })
+        | ir::Control::Par(ir::Par { stmts, .. }) => {
+            stmts.iter().for_each(|s| control_ports(s, ports))
+        }
+        ir::Control::If(cif) => {
+            ports.push(Rc::clone(&cif.port));
+            control_ports(&cif.tbranch, ports);
+            control_ports(&cif.fbranch, ports);
+        }
+        ir::Control::While(wh) => {
+            ports.push(Rc::clone(&wh.port));
+            control_ports(&wh.body, ports);
+        }
+        ir::Control::Repeat(rep) => control_ports(&rep.body, ports),
+        ir::Control::Invoke(inv) => ports.extend(
+            inv.inputs
+                .iter()
+                .chain(&inv.outputs)
+                .map(|(_, p)| p.clone()),
+        ),
+        ir::Control::Static(sc) => visit(sc, ports),
+        ir::Control::Enable(_) | ir::Control::Empty(_) => (),
+    }
+}
+
+/// A combinational cell used by a group.
+struct CombCell {
+    /// Inputs that combinationally affect the outputs.
+    inputs: Vec<ir::Id>,
+    /// Delay from the inputs to the outputs.
+    delay: f64,
+    /// Stage in which the cell computes its outputs.
+    stage: u64,
+    /// Time at which the outputs are available in the stage.
+    time: f64,
+}
+
+/// Computes the stage in which a set of ports is written. `edges` contains
+/// the values read by the assignments to the ports along with the delay
+/// between the value and the ports, and `delay` is the delay after the ports.
+/// Returns the stage and the time at which the ports are available.
+fn place(edges: &[(Avail, f64)], delay: f64, budget: f64) -> (u64, f64) {
+    let stage = edges
+        .iter()
+        .filter_map(|(a, _)| match a {
+            Avail::Stage(s, _) => Some(*s),
+            Avail::Stable => None,
+        })
+        .max()
+        .unwrap_or_default();
+    // Values from earlier stages are read from pipeline registers
+    let arrival = |stage: u64| {
+        edges
+            .iter()
+            .map(|(a, d)| match a {
+                Avail::Stage(s, t) if *s == stage => t + d,
+                _ => *d,
+            })
+            .fold(0.0, f64::max)
+    };
+    let time = arrival(stage) + delay;
+    // Only move to the next stage if registering the inputs shortens the path
+    let helps = edges.iter().any(
+        |(a, _)| matches!(a, Avail::Stage(s, t) if *s == stage && *t > 0.0),
+    );
+    if time > budget && helps {
+        (stage + 1, arrival(stage + 1) + delay)
+    } else {
+        (stage, time)
+    }
+}
+
+impl SingleCycleRetiming {
+    /// Retime the group. Returns `false` if the group could not be retimed.
+    fn retime(
+        &self,
+        group_ref: &RRC<ir::StaticGroup>,
+        uses: &PortUses,
+        builder: &mut ir::Builder,
+    ) -> bool {
+        let model = DefaultDelayModel;
+        let name = group_ref.borrow().name();
+        let latency = group_ref.borrow().get_latency();
+
+        // Find the combinational cells used by the group
+        let mut cells: HashMap<ir::Id, CombCell> = HashMap::new();
+        let mut this_inputs = HashSet::new();
+        for assign in &group_ref.borrow().assignments {
+            for port_ref in assign.iter_ports() {
+                let port = port_ref.borrow();
+                if port.is_hole() {
+                    return false;
+                }
+                let cell_ref = port.cell_parent();
+                let cell = cell_ref.borrow();
+                match &cell.prototype {
+                    ir::CellType::Component { .. } => return false,
+                    ir::CellType::ThisComponent => {
+                        if port.direction == ir::Direction::Output {
+                            this_inputs.insert(port.canonical());
+                        }
+                    }
+                    ir::CellType::Constant { .. } => (),
+                    ir::CellType::Primitive {
+                        name: prim,
+                        param_binding,
+                        is_comb,
+                        ..
+                    } => {
+                        if cells.contains_key(&cell.name()) {
+                            continue;
+                        }
+                        let (ins, outs): (Vec<_>, Vec<_>) =
+                            cell.ports().iter().partition(|p| {
+                                p.borrow().direction == ir::Direction::Input
+                            });
+                        let mut inputs = vec![];
+                        let mut delay: Option<f64> = None;
+                        for i in &ins {
+                            let i = i.borrow().name;
+                            for o in &outs {
+                                let o = o.borrow().name;
+                                if let Some(d) = model.primitive(
+                                    prim.as_ref(),
+                                    param_binding,
+                                    i,
+                                    o,
+                                ) {
+                                    delay = Some(delay.unwrap_or(0.0).max(d));
+                                    if !inputs.contains(&i) {
+                                        inputs.push(i);
+                                    }
+                                }
+                            }
+                        }
+                        if !is_comb && delay.is_none() {
+                            continue;
+                        }
+                        if *is_comb {
+                            inputs =
+                                ins.iter().map(|p| p.borrow().name).collect();
+                        }
+                        // Every port that depends on the combinational inputs
+                        // must be private to the group.
+                        let private = cell.ports().iter().all(|p| {
+                            let p = p.borrow();
+                            let comb = *is_comb
+                                || inputs.contains(&p.name)
+                                || p.direction == ir::Direction::Output;
+                            !comb || uses.private(&p.canonical(), name)
+                        });
+                        // The group cannot write the sequential inputs
+                        let writes_state = !is_comb
+                            && group_ref.borrow().assignments.iter().any(|a| {
+                                let dst = a.dst.borrow();
+                                dst.get_parent_name() == cell.name()
+                                    && !inputs.contains(&dst.name)
+                            });
+                        if !private || writes_state {
+                            return false;
+                        }
+                        cells.insert(
+                            cell.name(),
+                            CombCell {
+                                inputs,
+                                delay: delay.unwrap_or(0.0),
+                                stage: 0,
+                                time: 0.0,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // Sequential cells are read in the stage that uses their outputs. In
+        // a group that takes more than one cycle, that is only correct if the
+        // group does not write them.
+        if latency > 1 {
+            let state = |port: &RRC<ir::Port>| {
+                let cell = port.borrow().get_parent_name();
+                let seq = !cells.contains_key(&cell)
+                    && matches!(
+                        port.borrow().cell_parent().borrow().prototype,
+                        ir::CellType::Primitive { .. }
+                    );
+                seq.then_some(cell)
+            };
+            let group = group_ref.borrow();
+            let written = group
+                .assignments
+                .iter()
+                .filter_map(|a| state(&a.dst))
+                .collect::<HashSet<_>>();
+            let reads_written = group.assignments.iter().any(|a| {
+                a.guard
+                    .all_ports()
+                    .iter()
+                    .chain(std::iter::once(&a.src))
+                    .filter_map(state)
+                    .any(|cell| written.contains(&cell))
+            });
+            if reads_written {
+                return false;
+            }
+        }
+
+        // The values read by the assignments to every port
+        let mut edges: HashMap<ir::Canonical, Vec<(ir::Canonical, f64)>> =
+            HashMap::new();
+        for assign in &group_ref.borrow().assignments {
+            let dst = assign.dst.borrow().canonical();
+            let (count, guarded) = uses.drivers[&dst];
+            let mux = if count > 1 || guarded {
+                model.mux(count)
+            } else {
+                0.0
+            };
+            let e = edges.entry(dst).or_default();
+            e.push((assign.src.borrow().canonical(), mux));
+            let mut guard = vec![];
+            let inputs = guard_ports(&assign.guard, &model, &mut guard);
+            let logic = model.logic(inputs);
+            e.extend(guard.into_iter().map(|(p, d)| (p, d + logic + mux)));
+        }
+
+        // Place the combinational cells in dataflow order
+        let mut order = vec![];
+        let mut state = HashMap::new();
+        for cell in cells.keys() {
+            if !topo_sort(*cell, &cells, &edges, &mut state, &mut order) {
+                log::warn!(
+                    "single-cycle-retiming: combinational cycle in group `{name}'"
+                );
+                return false;
+            }
+        }
+        let avail = |port: &ir::Canonical,
+                     cells: &HashMap<ir::Id, CombCell>| {
+            if let Some(cell) = cells.get(&port.cell) {
+                Avail::Stage(cell.stage, cell.time)
+            } else if this_inputs.contains(port) {
+                Avail::Stage(0, 0.0)
+            } else {
+                Avail::Stable
+            }
+        };
+        for cell in &order {
+            let c = &cells[cell];
+            let incoming = c
+                .inputs
+                .iter()
+                .flat_map(|i| {
+                    edges
+                        .get(&ir::Canonical::new(*cell, *i))
+                        .into_iter()
+                        .flatten()
+                })
+                .map(|(p, d)| (avail(p, &cells), *d))
+                .collect::<Vec<_>>();
+            let (stage, time) = place(&incoming, c.delay, self.budget);
+            let c = cells.get_mut(cell).unwrap();
+            c.stage = stage;
+            c.time = time;
+        }
+
+        // All other ports are written in the last stage
+        let is_end = |port: &ir::Canonical| !cells.contains_key(&port.cell);
+        let incoming = edges
+            .iter()
+            .filter(|(dst, _)| is_end(dst))
+            .flat_map(|(_, e)| e)
+            .map(|(p, d)| (avail(p, &cells), *d))
+            .collect::<Vec<_>>();
+        let (last, _) = place(&incoming, 0.0, self.budget);
+        if last == 0 {
+            return false;
+        }
+
+        // Rewrite the assignments to read values from pipeline registers
+        let stage_of = |port: &ir::Canonical| match cells.get(&port.cell) {
+            Some(c) => c.stage,
+            None => last,
+        };
+        let avail_stage = |port: &ir::Canonical| match avail(port, &cells) {
+            Avail::Stage(s, _) => Some(s),
+            Avail::Stable => None,
+        };
+        let mut pipes: HashMap<ir::Canonical, Vec<RRC<ir::Port>>> =
+            HashMap::new();
+        let mut pipe_assigns = vec![];
+        let mut delayed = |port: RRC<ir::Port>, stage: u64| {
+            let canon = port.borrow().canonical();
+            let Some(avail) = avail_stage(&canon) else {
+                return port;
+            };
+            let regs = pipes.entry(canon.clone()).or_insert_with(|| vec![port]);
+            while (regs.len() as u64) <= stage - avail {
+                let prev = Rc::clone(regs.last().unwrap());
+                let width = prev.borrow().width;
+                let reg = builder.add_primitive(
+                    format!("{}_{}_reg", canon.cell, canon.port),
+                    "std_reg",
+                    &[width],
+                );
+                let one = builder.add_constant(1, 1);
+                pipe_assigns.push(builder.build_assignment(
+                    reg.borrow().get("in"),
+                    prev,
+                    ir::Guard::True,
+                ));
+                // Only latch the values computed for the cycles of the group
+                let mut guard = ir::Guard::True;
+                if latency > 1 {
+                    let beg = avail + regs.len() as u64 - 1;
+                    guard.add_interval(ir::StaticTiming::new((
+                        beg,
+                        beg + latency,
+                    )));
+                }
+                pipe_assigns.push(builder.build_assignment(
+                    reg.borrow().get("write_en"),
+                    one.borrow().get("out"),
+                    guard,
+                ));
+                regs.push(reg.borrow().get("out"));
+            }
+            Rc::clone(&regs[(stage - avail) as usize])
+        };
+
+        let mut group = group_ref.borrow_mut();
+        for assign in group.assignments.iter_mut() {
+            let dst = assign.dst.borrow().canonical();
+            let stage = stage_of(&dst);
+            assign.src = delayed(Rc::clone(&assign.src), stage);
+            assign
+                .guard
+                .for_each(&mut |p| Some(ir::Guard::port(delayed(p, stage))));
+            let mut timed = false;
+            assign.guard.for_each_info(&mut |timing| {
+                timed = true;
+                if latency == 1 {
+                    // Timing guards of single-cycle groups are always true
+                    Some(ir::Guard::True)
+                } else {
+                    let (beg, end) = timing.get_interval();
+                    timing.set_interval((beg + stage, end + stage));
+                    None
+                }
+            });
+            if is_end(&dst) && (latency == 1 || !timed) {
+                assign.guard.add_interval(ir::StaticTiming::new((
+                    last,
+                    last + latency,
+                )));
+            }
+        }
+        group.assignments.extend(pipe_assigns);
+        group.latency = last + latency;
+        true
+    }
+}
+
+/// Visit the combinational cells in dataflow order. Returns `false` if there
+/// is a cycle.
+fn topo_sort(
+    cell: ir::Id,
+    cells: &HashMap<ir::Id, CombCell>,
+    edges: &HashMap<ir::Canonical, Vec<(ir::Canonical, f64)>>,
+    // `true` once the cell has been visited, `false` while it is visited.
+    state: &mut HashMap<ir::Id, bool>,
+    order: &mut Vec<ir::Id>,
+) -> bool {
+    match state.get(&cell) {
+        Some(true) => return true,
+        Some(false) => return false,
+        None => (),
+    }
+    state.insert(cell, false);
+    for input in &cells[&cell].inputs {
+        let canon = ir::Canonical::new(cell, *input);
+        for (src, _) in edges.get(&canon).into_iter().flatten() {
+            if cells.contains_key(&src.cell)
+                && !topo_sort(src.cell, cells, edges, state, order)
+            {
+                return false;
+            }
+        }
+    }
+    state.insert(cell, true);
+    order.push(cell);
+    true
+}
+
+/// Collects the ports read by the guard along with the delay of the
+/// comparisons they are used in. Returns the number of 1-bit inputs to the
+/// boolean function computed by the guard.
+fn guard_ports<T>(
+    guard: &ir::Guard<T>,
+    model: &dyn DelayModel,
+    ports: &mut Vec<(ir::Canonical, f64)>,
+) -> u64 {
+    match guard {
+        ir::Guard::Or(l, r) | ir::Guard::And(l, r) => {
+            guard_ports(l, model, ports) + guard_ports(r, model, ports)
+        }
+        ir::Guard::Not(g) => guard_ports(g, model, ports),
+        ir::Guard::True | ir::Guard::Info(_) => 0,
+        ir::Guard::CompOp(op, l, r) => {
+            let delay = model.comparison(op, l.borrow().width);
+            ports.push((l.borrow().canonical(), delay));
+            ports.push((r.borrow().canonical(), delay));
+            1
+        }
+        ir::Guard::Port(p) => {
+            ports.push((p.borrow().canonical(), 0.0));
+            1
+        }
+    }
+}
+
+impl Visitor for SingleCycleRetiming {
+    fn iteration_order() -> Order {
+        Order::Post
+    }
+
+    fn start(
+        &mut self,
+        comp: &mut ir::Component,
+        sigs: &LibrarySignatures,
+        _comps: &[ir::Component],
+    ) -> VisResult {
+        // Update the instances of components whose latency changed
+        for cell in comp.cells.iter() {
+            let cell = cell.borrow();
+            if let Some(latency) =
+                cell.type_name().and_then(|n| self.latencies.get(&n))
+            {
+                set_interval(&cell, *latency);
+            }
+        }
+
+        if !self.fixed.contains(&comp.name) {
+            let uses = PortUses::new(comp);
+            let mut conflicts = HashMap::new();
+            for_each_static(&mut comp.control.borrow_mut(), &mut |sc| {
+                par_conflicts(sc, &mut conflicts)
+            });
+            let groups = comp
+                .get_static_groups()
+                .iter()
+                .filter(|g| {
+                    let g = g.borrow();
+                    let mut cells = HashSet::new();
+                    assignment_cells(&g.assignments, &mut cells);
+                    conflicts
+                        .get(&g.name())
+                        .into_iter()
+                        .all(|others| others.is_disjoint(&cells))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut builder = ir::Builder::new(comp, sigs);
+            for group in groups {
+                self.retime(&group, &uses, &mut builder);
+            }
+        }
+
+        let latencies = &self.latencies;
+        for_each_static(&mut comp.control.borrow_mut(), &mut |sc| {
+            update_latency(sc, latencies);
+        });
+        if let (Some(old), Some(new)) =
+            (comp.latency, comp.control.borrow().get_latency())
+        {
+            if u64::from(old) != new {
+                comp.latency = NonZeroU64::new(new);
+                set_interval(&comp.signature.borrow(), new);
+                self.latencies.insert(comp.name, new);
+            }
+        }
+
+        Ok(Action::Stop)
+    }
+}
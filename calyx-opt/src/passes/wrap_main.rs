@@ -27,7 +27,10 @@ impl Visitor for WrapMain {
     where
         Self: Sized,
     {
-        if ctx.entrypoint().name == "main" {
+        if !ctx.components.iter().any(|c| c.name == ctx.entrypoint) {
+            // Happens when only a subset of the program is being compiled
+            Some("Entrypoint is not part of the context".to_string())
+        } else if ctx.entrypoint().name == "main" {
             Some("Top-level component is already named `main'".to_string())
         } else {
             None
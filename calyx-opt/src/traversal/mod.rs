@@ -9,4 +9,4 @@ pub use action::{Action, VisResult};
 pub use construct::{ConstructVisitor, Named, ParseVal, PassOpt};
 pub use diagnostics::{DiagnosticContext, DiagnosticPass, DiagnosticResult};
 pub use post_order::{CompTraversal, Order};
-pub use visitor::{Visitable, Visitor};
+pub use visitor::{ParallelVisitor, Visitable, Visitor};
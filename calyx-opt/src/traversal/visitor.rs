@@ -3,11 +3,16 @@
 //! [`ir::Context`] to compile every [`ir::Component`] using the pass.
 use super::action::{Action, VisResult};
 use super::{CompTraversal, ConstructVisitor, Named, Order};
+use crate::analysis::ReadWriteSet;
 use calyx_ir::{
     self as ir, Component, Context, Control, LibrarySignatures, StaticControl,
 };
-use calyx_utils::CalyxResult;
+use calyx_utils::{CalyxResult, Error};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 /// The visiting interface for a [`ir::Control`](crate::Control) program.
 /// Contains two kinds of functions:
@@ -426,6 +431,212 @@ pub trait Visitor {
     }
 }
 
+/// Wrapper that allows a component to be moved to a different thread.
+///
+/// The cells, groups and ports of a component are shared through `Rc`, whose
+/// reference counts are not atomic. Moving a component to another thread is
+/// only sound if no `Rc` reachable from it is reachable from anything that
+/// stays on the current thread or moves to a different thread.
+/// [SendComponent::check] checks this for the ports of the component, which
+/// are the only nodes that assignments and control statements refer to
+/// outside of the groups and cells that own them. Components must only be
+/// wrapped after all of them passed the check.
+struct SendComponent(ir::Component);
+
+// SAFETY: [SendComponent::check] ensures that every port reachable from the
+// assignments and the control program of the component is owned by one of its
+// cells or groups, and that no port is owned by two components. Since cells
+// and groups of one component never refer to another component, every clone
+// of a pointer into the component moves to the worker thread along with it.
+// The library signatures shared with the workers are `Sync` and do not refer to
+// any component.
+unsafe impl Send for SendComponent {}
+
+type PortPtr = *const RefCell<ir::Port>;
+
+impl SendComponent {
+    /// Checks that `comp` can be moved to another thread: it must not share
+    /// any ports with the components checked before it, whose ports are
+    /// collected in `owned`, and it must only refer to ports that it owns.
+    fn check(
+        comp: &ir::Component,
+        owned: &mut HashSet<PortPtr>,
+    ) -> CalyxResult<()> {
+        let mut own: HashSet<PortPtr> = HashSet::new();
+        for cell in std::iter::once(&comp.signature).chain(comp.cells.iter()) {
+            own.extend(cell.borrow().ports().iter().map(Rc::as_ptr));
+        }
+        for group in comp.get_groups().iter() {
+            own.extend(group.borrow().holes.iter().map(Rc::as_ptr));
+        }
+        for group in comp.get_static_groups().iter() {
+            own.extend(group.borrow().holes.iter().map(Rc::as_ptr));
+        }
+        if own.iter().any(|port| !owned.insert(*port)) {
+            return Err(Error::misc(format!(
+                "Component `{}' shares a port with another component",
+                comp.name
+            )));
+        }
+
+        let mut used = vec![];
+        comp.iter_assignments(|assign| {
+            used.push(Rc::clone(&assign.dst));
+            used.push(Rc::clone(&assign.src));
+            used.extend(assign.guard.all_ports());
+        });
+        comp.iter_static_assignments(|assign| {
+            used.push(Rc::clone(&assign.dst));
+            used.push(Rc::clone(&assign.src));
+            used.extend(assign.guard.all_ports());
+        });
+        let (reads, writes) = ReadWriteSet::control_port_read_write_set::<true>(
+            &comp.control.borrow(),
+        );
+        used.extend(reads);
+        used.extend(writes);
+        if let Some(port) =
+            used.iter().find(|port| !own.contains(&Rc::as_ptr(port)))
+        {
+            return Err(Error::misc(format!(
+                "Component `{}' refers to port `{}' of another component",
+                comp.name,
+                port.borrow().canonical()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of the cells and groups defined by the component.
+    fn names(&self) -> HashSet<ir::Id> {
+        let comp = &self.0;
+        comp.cells
+            .iter()
+            .map(|cell| cell.borrow().name())
+            .chain(comp.get_groups().iter().map(|g| g.borrow().name()))
+            .chain(comp.get_static_groups().iter().map(|g| g.borrow().name()))
+            .chain(comp.comb_groups.iter().map(|g| g.borrow().name()))
+            .collect()
+    }
+}
+
+/// A [Visitor] whose traversal of a component only reads and modifies that
+/// component.
+/// Such a pass does not share data between components, does not inspect the
+/// other components in the program, and does not use
+/// [Visitor::start_context] or [Visitor::finish_context] to build state used
+/// while visiting components.
+/// This allows [ParallelVisitor::do_pass_parallel] to visit several components
+/// at the same time.
+///
+/// The pass must not create cells or groups. Identifiers are ordered by the
+/// order in which they are first interned, so names created on several
+/// threads at once would make the output of later passes depend on the
+/// scheduling of the threads. [ParallelVisitor::do_pass_parallel] reports an
+/// error if the pass defines a new cell or group.
+///
+/// The `Send` bound guarantees that the visitor does not hold on to pointers
+/// into the program.
+pub trait ParallelVisitor:
+    Visitor + ConstructVisitor + Named + Send + Sized
+{
+    /// Run the visitor on a given program [`ir::Context`] using up to
+    /// `threads` threads.
+    ///
+    /// Every thread constructs its own visitor and traverses components
+    /// with the library signatures of the program but without access to the
+    /// other components in the program.
+    /// The components are returned to the context in their original order and
+    /// the error reported for the first component (in program order) is
+    /// returned, which makes the result independent of how the components were
+    /// scheduled.
+    ///
+    /// Components that share ports with each other cannot be moved to
+    /// different threads. If the program contains such components, the pass
+    /// visits all components sequentially instead.
+    fn do_pass_parallel(
+        context: &mut Context,
+        threads: usize,
+    ) -> CalyxResult<()> {
+        if threads <= 1 || context.components.len() <= 1 {
+            Self::do_pass_default(context)?;
+            return Ok(());
+        }
+        if let Some(msg) = Self::precondition(&*context) {
+            log::info!("Skipping `{}': {msg}", Self::name());
+            return Ok(());
+        }
+
+        let mut owned = HashSet::new();
+        if let Err(err) = context
+            .components
+            .iter()
+            .try_for_each(|comp| SendComponent::check(comp, &mut owned))
+        {
+            log::info!(
+                "Running `{}' sequentially: {}",
+                Self::name(),
+                err.message()
+            );
+            Self::do_pass_default(context)?;
+            return Ok(());
+        }
+
+        let threads = threads.min(context.components.len());
+        let visitors = (0..threads)
+            .map(|_| Self::from(&*context))
+            .collect::<CalyxResult<Vec<_>>>()?;
+
+        let slots = std::mem::take(&mut context.components)
+            .into_iter()
+            .map(|comp| Mutex::new((SendComponent(comp), Ok(()))))
+            .collect::<Vec<_>>();
+        let next = AtomicUsize::new(0);
+        let signatures = &context.lib;
+
+        std::thread::scope(|scope| {
+            for mut visitor in visitors {
+                let (slots, next) = (&slots, &next);
+                scope.spawn(move || loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(slot) = slots.get(idx) else {
+                        break;
+                    };
+                    let (comp, res) = &mut *slot.lock().unwrap();
+                    let names = comp.names();
+                    *res = visitor.traverse_component(
+                        &mut comp.0,
+                        signatures,
+                        &[],
+                    );
+                    visitor.clear_data();
+                    if let Some(name) = comp.names().difference(&names).next() {
+                        *res = Err(Error::pass_assumption(
+                            Self::name(),
+                            format!(
+                                "defined `{name}' in component `{}' but \
+                                    passes that run in parallel cannot create \
+                                    cells or groups",
+                                comp.0.name
+                            ),
+                        ));
+                    }
+                });
+            }
+        });
+
+        let mut result = Ok(());
+        for slot in slots {
+            let (SendComponent(comp), res) = slot.into_inner().unwrap();
+            if result.is_ok() {
+                result = res;
+            }
+            context.components.push(comp);
+        }
+        result
+    }
+}
+
 /// Describes types that can be visited by things implementing [Visitor].
 /// This performs a recursive walk of the tree.
 ///
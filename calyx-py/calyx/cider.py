@@ -0,0 +1,256 @@
+"""Python bindings for the Cider interpreter.
+
+The bindings call the C API exported by the `interp` crate in-process. Build it
+with `cargo build -p interp`, which produces `target/debug/libinterp.so` (or
+`libinterp.dylib` on macOS), and pass the path of the library to `Simulator`
+or set the `CIDER_LIB` environment variable.
+
+    with Simulator("prog.futil", lib_path="/path/to/calyx", data=data) as sim:
+        sim.run()
+        print(sim.dump_json())
+"""
+
+import ctypes
+import json
+import os
+from typing import Optional
+
+CIDER_OK = 0
+CIDER_UNDEFINED = 1
+
+
+class CiderError(Exception):
+    """An error reported by Cider."""
+
+
+def _load_library(path: Optional[str]) -> ctypes.CDLL:
+    path = path or os.environ.get("CIDER_LIB")
+    if path is None:
+        raise CiderError(
+            "the path to the Cider library was not given and CIDER_LIB is unset"
+        )
+    lib = ctypes.CDLL(path)
+
+    sim = ctypes.c_void_p
+    string = ctypes.c_char_p
+    buf = ctypes.POINTER(ctypes.c_uint8)
+    signatures = {
+        "cider_last_error": (string, []),
+        "cider_sim_new": (
+            sim,
+            [string, string, string, ctypes.c_bool, ctypes.c_bool],
+        ),
+        "cider_sim_free": (None, [sim]),
+        "cider_sim_set_input": (
+            ctypes.c_int32,
+            [sim, string, buf, ctypes.c_size_t],
+        ),
+        "cider_sim_release_input": (ctypes.c_int32, [sim, string]),
+        "cider_sim_eval": (ctypes.c_int32, [sim]),
+        "cider_sim_step": (ctypes.c_int32, [sim]),
+        "cider_sim_run": (ctypes.c_int32, [sim]),
+        "cider_sim_is_done": (ctypes.c_int32, [sim]),
+        "cider_sim_port_width": (ctypes.c_int64, [sim, string]),
+        "cider_sim_get_port": (
+            ctypes.c_int32,
+            [sim, string, buf, ctypes.c_size_t],
+        ),
+        "cider_sim_memory_width": (ctypes.c_int64, [sim, string]),
+        "cider_sim_memory_size": (ctypes.c_int64, [sim, string]),
+        "cider_sim_peek_memory": (
+            ctypes.c_int32,
+            [sim, string, ctypes.c_size_t, buf, ctypes.c_size_t],
+        ),
+        "cider_sim_poke_memory": (
+            ctypes.c_int32,
+            [sim, string, ctypes.c_size_t, buf, ctypes.c_size_t],
+        ),
+        "cider_sim_dump": (
+            buf,
+            [sim, ctypes.c_bool, ctypes.c_bool, ctypes.POINTER(ctypes.c_size_t)],
+        ),
+        "cider_bytes_free": (None, [buf, ctypes.c_size_t]),
+        # returned as a raw pointer so that it can be freed afterwards
+        "cider_sim_dump_json": (
+            ctypes.c_void_p,
+            [sim, ctypes.c_bool, ctypes.c_bool],
+        ),
+        "cider_string_free": (None, [ctypes.c_void_p]),
+    }
+    for name, (restype, argtypes) in signatures.items():
+        func = getattr(lib, name)
+        func.restype = restype
+        func.argtypes = argtypes
+    return lib
+
+
+def _to_buffer(value: int, width: int):
+    size = (width + 7) // 8
+    if value < 0 or value >= 1 << width:
+        raise CiderError(f"{value} does not fit in {width} bits")
+    return (ctypes.c_uint8 * size)(*value.to_bytes(size, "little")), size
+
+
+class Simulator:
+    """A Cider simulator for a Calyx program.
+
+    By default the simulator holds the `go` port of the entrypoint component
+    high, like the `cider` binary. An `embedded` simulator instead leaves every
+    input, including `go`, to be driven with `set_input`.
+
+    `data` initializes the memories of the entrypoint component. It is either a
+    dictionary or a string in the Calyx JSON data format.
+    """
+
+    def __init__(
+        self,
+        program: str,
+        lib_path: str = ".",
+        data=None,
+        embedded: bool = False,
+        skip_verification: bool = False,
+        library: Optional[str] = None,
+    ):
+        self._lib = _load_library(library)
+        if data is not None and not isinstance(data, str):
+            data = json.dumps(data)
+        self._sim = self._lib.cider_sim_new(
+            str(program).encode(),
+            str(lib_path).encode(),
+            None if data is None else data.encode(),
+            skip_verification,
+            embedded,
+        )
+        if not self._sim:
+            self._raise()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.close()
+
+    def __del__(self):
+        self.close()
+
+    def close(self):
+        """Releases the simulator."""
+        if getattr(self, "_sim", None):
+            self._lib.cider_sim_free(self._sim)
+            self._sim = None
+
+    def _raise(self):
+        raise CiderError(self._lib.cider_last_error().decode())
+
+    def _check(self, status: int) -> int:
+        if status < 0:
+            self._raise()
+        return status
+
+    def set_input(self, port: str, value: int):
+        """Drives the input port of the entrypoint component until it is set
+        again or released."""
+        buf, size = _to_buffer(value, self.port_width(port))
+        self._check(
+            self._lib.cider_sim_set_input(self._sim, port.encode(), buf, size)
+        )
+
+    def release_input(self, port: str):
+        """Stops driving the input port of the entrypoint component."""
+        self._check(self._lib.cider_sim_release_input(self._sim, port.encode()))
+
+    def eval(self):
+        """Propagates the inputs without advancing the clock."""
+        self._check(self._lib.cider_sim_eval(self._sim))
+
+    def step(self):
+        """Advances the clock by one cycle."""
+        self._check(self._lib.cider_sim_step(self._sim))
+
+    def run(self):
+        """Runs the program until the entrypoint component is done."""
+        self._check(self._lib.cider_sim_run(self._sim))
+
+    def is_done(self) -> bool:
+        return self._check(self._lib.cider_sim_is_done(self._sim)) == 1
+
+    def port_width(self, port: str) -> int:
+        return self._check(
+            self._lib.cider_sim_port_width(self._sim, port.encode())
+        )
+
+    def _memory_width(self, name: str) -> int:
+        return self._check(
+            self._lib.cider_sim_memory_width(self._sim, name.encode())
+        )
+
+    def get_port(self, port: str) -> Optional[int]:
+        """Returns the value of the port of the entrypoint component, or
+        `None` if it is undefined."""
+        size = (self.port_width(port) + 7) // 8
+        buf = (ctypes.c_uint8 * size)()
+        status = self._check(
+            self._lib.cider_sim_get_port(self._sim, port.encode(), buf, size)
+        )
+        if status == CIDER_UNDEFINED:
+            return None
+        return int.from_bytes(bytes(buf), "little")
+
+    def peek_memory(self, name: str, index: int) -> int:
+        """Returns the entry at the row-major index of the memory or register.
+        Cells in sub-components are named by their path, e.g. `pe0.acc`."""
+        size = (self._memory_width(name) + 7) // 8
+        buf = (ctypes.c_uint8 * size)()
+        self._check(
+            self._lib.cider_sim_peek_memory(
+                self._sim, name.encode(), index, buf, size
+            )
+        )
+        return int.from_bytes(bytes(buf), "little")
+
+    def poke_memory(self, name: str, index: int, value: int):
+        """Overwrites the entry at the row-major index of the memory or
+        register."""
+        buf, size = _to_buffer(value, self._memory_width(name))
+        self._check(
+            self._lib.cider_sim_poke_memory(
+                self._sim, name.encode(), index, buf, size
+            )
+        )
+
+    def memory(self, name: str) -> list:
+        """Returns the contents of the memory or register in row-major
+        order."""
+        size = self._check(
+            self._lib.cider_sim_memory_size(self._sim, name.encode())
+        )
+        return [self.peek_memory(name, i) for i in range(size)]
+
+    def dump(
+        self, dump_registers: bool = False, all_memories: bool = False
+    ) -> bytes:
+        """Returns the memories of the entrypoint component as a Cider data
+        dump."""
+        size = ctypes.c_size_t()
+        buf = self._lib.cider_sim_dump(
+            self._sim, dump_registers, all_memories, ctypes.byref(size)
+        )
+        if not buf:
+            self._raise()
+        data = ctypes.string_at(buf, size.value)
+        self._lib.cider_bytes_free(buf, size)
+        return data
+
+    def dump_json(
+        self, dump_registers: bool = False, all_memories: bool = False
+    ) -> dict:
+        """Returns the memories of the entrypoint component in the format
+        printed by `cider-data-converter --to json`."""
+        string = self._lib.cider_sim_dump_json(
+            self._sim, dump_registers, all_memories
+        )
+        if not string:
+            self._raise()
+        data = ctypes.string_at(string).decode()
+        self._lib.cider_string_free(string)
+        return json.loads(data)
@@ -0,0 +1,22 @@
+"""Runs a program in-process with the Cider bindings, which load the library
+named by the `CIDER_LIB` environment variable."""
+
+import json
+from pathlib import Path
+
+from calyx.cider import CiderError, Simulator
+
+ROOT = Path(__file__).resolve().parents[3]
+PROGRAM = ROOT / "interp" / "tests" / "control" / "if.futil"
+
+with open(f"{PROGRAM}.data") as data:
+    with Simulator(PROGRAM, lib_path=ROOT, data=json.load(data)) as sim:
+        sim.run()
+        print(json.dumps(sim.dump_json(), sort_keys=True))
+
+        sim.poke_memory("mem", 0, 7)
+        print(sim.memory("mem"))
+        try:
+            sim.peek_memory("mem", 1)
+        except CiderError as err:
+            print(f"error: {err}")
@@ -28,12 +28,13 @@ load_prims! { DYN_MEMORIES, "memories/dyn.futil", "memories/dyn.sv" }
 load_prims! { PIPELINED, "pipelined.futil", "pipelined.sv" }
 load_prims! { STALLABLE, "stallable.futil", "stallable.sv" }
 load_prims! { SYNC, "sync.futil", "sync.sv" }
+load_prims! { FLOAT, "float.futil", "float.sv" }
 
 /// The core primitive in the compiler
 pub const COMPILE_LIB: (&str, &str) =
     ("compile.futil", include_str!("../primitives/compile.futil"));
 
-pub const KNOWN_LIBS: [(&str, [(&str, &str); 2]); 9] = [
+pub const KNOWN_LIBS: [(&str, [(&str, &str); 2]); 10] = [
     ("core", CORE),
     ("binary_operators", BINARY_OPERATORS),
     ("math", MATH),
@@ -43,4 +44,5 @@ pub const KNOWN_LIBS: [(&str, [(&str, &str); 2]); 9] = [
     ("pipelined", PIPELINED),
     ("stallable", STALLABLE),
     ("sync", SYNC),
+    ("float", FLOAT),
 ];
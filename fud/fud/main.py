@@ -104,7 +104,7 @@ def register_stages(registry):
         futil.CalyxStage(
             "resources",
             "-b resources",
-            "Generate a CSV that estimates a Calyx program's resource usage",
+            "Estimate the LUTs, FFs, DSPs and BRAMs used by a Calyx program",
         )
     )
 
@@ -1,6 +1,8 @@
 import simplejson as sjson
 import numpy as np
+import struct
 from calyx.numeric_types import FixedPoint, Bitnum, InvalidNumericType
+from decimal import Decimal
 from pathlib import Path
 from fud.errors import Malformed
 import logging as log
@@ -14,7 +16,33 @@ def float_to_fixed(value: float, N: int) -> float:
     return round(value * w) / float(w)
 
 
-def parse_dat(path, args):
+# `struct` formats of the IEEE-754 floats supported by `primitives/float.futil`.
+FLOAT_FORMATS = {16: "e", 32: "f", 64: "d"}
+
+
+def float_to_bits(value: float, width: int) -> int:
+    """Returns the bits of `value` as an IEEE-754 float with `width` bits."""
+    fmt = f"<{FLOAT_FORMATS[width]}"
+    return int.from_bytes(struct.pack(fmt, value), "little")
+
+
+def bits_to_float(bits: int, width: int) -> Decimal:
+    """Returns the IEEE-754 float with `width` bits represented by `bits`.
+    Like Cider, uses the shortest decimal number that rounds to the same
+    single-precision float, or double-precision float for 64-bit values.
+    """
+    fmt = f"<{FLOAT_FORMATS[width]}"
+    (value,) = struct.unpack(fmt, bits.to_bytes(width // 8, "little"))
+    shortest = "<d" if width == 64 else "<f"
+    for precision in range(1, 18):
+        digits = f"{value:.{precision}g}"
+        if struct.pack(shortest, float(digits)) == struct.pack(shortest, value):
+            break
+    # Print the number without an exponent
+    return Decimal(format(Decimal(digits), "f"))
+
+
+def parse_dat(path, args, numeric_type="bitnum"):
     """Parses a number with the given numeric type
     arguments from the array at the given `path`.
     """
@@ -32,7 +60,12 @@ def parse_dat(path, args):
 
     def parse(hex_value: str):
         hex_value = f"0x{hex_value}"
-        if "int_width" in args:
+        if numeric_type == "ieee754_float":
+            bn = Bitnum(hex_value, args["width"], False)
+            if bn.is_undef:
+                return bn.str_value()
+            return bits_to_float(bn.unsigned_integer(), args["width"])
+        elif "int_width" in args:
             return FixedPoint(hex_value, **args).str_value()
         else:
             bn = Bitnum(hex_value, **args)
@@ -90,8 +123,12 @@ def parse_fp_widths(format):
         )
 
 
-def convert(x, round: bool, is_signed: bool, width: int, int_width=None):
+def convert(
+    x, round: bool, is_signed: bool, width: int, int_width=None, is_float=False
+):
     with_prefix = False
+    if is_float:
+        return format(float_to_bits(float(x), width), "x")
     # If `int_width` is not defined, then this is a `Bitnum`
     if int_width is None:
         return Bitnum(x, width, is_signed).hex_string(with_prefix)
@@ -133,12 +170,22 @@ def convert2dat(output_dir, data, extension, round: bool):
         numeric_type = format["numeric_type"]
         is_signed = format["is_signed"]
 
-        if numeric_type not in {"bitnum", "fixed_point"}:
-            raise InvalidNumericType('Fud only supports "fixed_point" and "bitnum".')
+        if numeric_type not in {"bitnum", "fixed_point", "ieee754_float"}:
+            raise InvalidNumericType(
+                'Fud only supports "fixed_point", "bitnum" and "ieee754_float".'
+            )
 
         is_fp = numeric_type == "fixed_point"
+        is_float = numeric_type == "ieee754_float"
         if is_fp:
             width, int_width = parse_fp_widths(format)
+        elif is_float:
+            width = format["width"]
+            int_width = None
+            if width not in FLOAT_FORMATS:
+                raise InvalidNumericType(
+                    f"IEEE-754 floats must be 16, 32 or 64 bits wide, not {width}"
+                )
         else:
             # `Bitnum`s only have a bit width.
             width = format["width"]
@@ -154,7 +201,9 @@ def convert2dat(output_dir, data, extension, round: bool):
 
         with path.open("w") as f:
             for v in arr.flatten():
-                f.write(convert(v, round, is_signed, width, int_width) + "\n")
+                f.write(
+                    convert(v, round, is_signed, width, int_width, is_float) + "\n"
+                )
 
         shape[k]["shape"] = list(arr.shape)
         shape[k]["numeric_type"] = numeric_type
@@ -185,8 +234,8 @@ def convert2json(input_dir, extension):
         # for building the FixedPoint or Bitnum classes.
         args = form.copy()
         del args["shape"]
-        del args["numeric_type"]
-        arr = parse_dat(path, args)
+        numeric_type = args.pop("numeric_type")
+        arr = parse_dat(path, args, numeric_type)
         if form["shape"] == [0]:
             raise Malformed(
                 "Data format shape",
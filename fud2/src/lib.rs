@@ -325,6 +325,62 @@ pub fn build_driver(bld: &mut DriverBuilder) {
         |e, input, output| verilator_build(e, input[0], output[0], false),
     );
 
+    // Simulators built from a harness generated by the `verilator-harness`
+    // backend. The harness reads and writes JSON data itself.
+    let harness_sim = bld.state("verilator-harness", &["exe"]);
+    let harness_setup = bld.setup("Verilator harness", |e| {
+        e.rule(
+            "verilator-compile-harness",
+            "$verilator $in --cc --exe --build --trace -fno-inline -Mdir $out-dir -o harness",
+        )?;
+        e.rule(
+            "harness-run",
+            "./$in --data $sim_data --cycle-limit $cycle-limit --out $out > sim.log",
+        )?;
+        Ok(())
+    });
+    bld.op(
+        "calyx-to-verilator-harness",
+        &[sim_setup, calyx_setup, verilator_setup, harness_setup],
+        calyx,
+        harness_sim,
+        |e, input, output| {
+            let out_dir = "verilator-harness-out";
+            let harness_bin = format!("{}/harness", out_dir);
+            e.build_cmd(&["verilog.sv"], "calyx", &[input[0]], &[])?;
+            e.arg("backend", "verilog")?;
+            e.build_cmd(&["harness.cpp"], "calyx", &[input[0]], &[])?;
+            e.arg("backend", "verilator-harness")?;
+            e.build_cmd(&["calyx_harness.h"], "calyx", &[input[0]], &[])?;
+            e.arg("backend", "verilator-harness")?;
+            e.arg("args", "-x verilator-harness:runtime")?;
+            e.build_cmd(
+                &[&harness_bin],
+                "verilator-compile-harness",
+                &["verilog.sv", "harness.cpp"],
+                &["calyx_harness.h"],
+            )?;
+            e.arg("out-dir", out_dir)?;
+            e.build("cp", &harness_bin, output[0])?;
+            Ok(())
+        },
+    );
+    bld.op(
+        "harness-simulate",
+        &[sim_setup, verilator_setup, harness_setup],
+        harness_sim,
+        dat,
+        |e, input, output| {
+            e.build_cmd(
+                &[output[0]],
+                "harness-run",
+                &[input[0]],
+                &["$sim_data"],
+            )?;
+            Ok(())
+        },
+    );
+
     // Interpreter.
     let debug = bld.state("cider-debug", &[]); // A pseudo-state.
                                                // A pseudo-state for cider input
@@ -721,21 +777,6 @@ e.var("cocotb-args", if waves {"WAVES=1"} else {""})?;
         },
     );
 
-    // setup for FIRRTL-implemented primitives
-    let firrtl_primitives_setup = bld.setup("FIRRTL with primitives", |e| {
-        // Produce FIRRTL with FIRRTL-defined primitives.
-        e.var(
-            "gen-firrtl-primitives-script",
-            "$calyx-base/tools/firrtl/generate-firrtl-with-primitives.py",
-        )?;
-        e.rule(
-            "generate-firrtl-with-primitives",
-            "python3 $gen-firrtl-primitives-script $in > $out",
-        )?;
-
-        Ok(())
-    });
-
     fn calyx_to_firrtl_helper(
         e: &mut StreamEmitter,
         input: &str,
@@ -764,25 +805,12 @@ e.var("cocotb-args", if waves {"WAVES=1"} else {""})?;
         )?;
 
         if firrtl_primitives {
-            let core_program_firrtl = "core.fir";
-
-            // Obtain FIRRTL of core program
-            e.build_cmd(&[core_program_firrtl], "calyx", &[input], &[])?;
+            // emit FIRRTL implementations of the primitives
+            e.build_cmd(&[tmp_out], "calyx", &[input], &[])?;
             e.arg("backend", "firrtl")?;
-            e.arg("args", "-p external-to-ref -p all --synthesis")?;
-
-            // Obtain primitive uses JSON for metaprogramming
-            let primitive_uses_json = "primitive-uses.json";
-            e.build_cmd(&[primitive_uses_json], "calyx", &[input], &[])?;
-            e.arg("backend", "primitive-uses")?;
-            e.arg("args", "-p external-to-ref -p all --synthesis")?;
-
-            // run metaprogramming script to get FIRRTL with primitives
-            e.build_cmd(
-                &[tmp_out],
-                "generate-firrtl-with-primitives",
-                &[core_program_firrtl, primitive_uses_json],
-                &[],
+            e.arg(
+                "args",
+                "-p external-to-ref -p all --synthesis --emit-primitive-definitions",
             )?;
         } else {
             // emit extmodule declarations to use Verilog primitive implementations
@@ -816,12 +844,7 @@ e.var("cocotb-args", if waves {"WAVES=1"} else {""})?;
 
     bld.op(
         "firrtl-with-primitives",
-        &[
-            calyx_setup,
-            yxi_setup,
-            firrtl_primitives_setup,
-            custom_testbench_setup,
-        ],
+        &[calyx_setup, yxi_setup, custom_testbench_setup],
         calyx,
         firrtl_with_primitives,
         |e, input, output| calyx_to_firrtl_helper(e, input[0], output[0], true),
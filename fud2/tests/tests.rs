@@ -275,6 +275,12 @@ fn sim_tests() {
     }
 }
 
+#[test]
+fn harness_tests() {
+    let driver = test_driver();
+    request(&driver, &["calyx"], &["dat"], &["harness-simulate"]).test(&driver);
+}
+
 #[test]
 fn cider_tests() {
     let driver = test_driver();
@@ -116,6 +116,11 @@ pub enum InterpreterError {
         a2: AssignedValue,
     },
 
+    /// An active assignment with an undefined source would overwrite a port
+    /// which already has a value
+    #[error("the assignment `{assignment}` drives an undefined value onto a port which already has the value {value}")]
+    UndefiningAssignment { assignment: String, value: String },
+
     /// A currently defunct error type for cross branch conflicts
     #[error(
         "par assignments not disjoint: {parent_id}.{port_id}
@@ -179,6 +184,54 @@ pub enum InterpreterError {
     /// A wrapper for serialization errors
     #[error(transparent)]
     SerializationError(#[from] crate::serialization::SerializationError),
+
+    /// The entrypoint component has no port with the given name
+    #[error("the entrypoint component has no port named \"{0}\"")]
+    UnknownPort(String),
+
+    /// The port with the given name is an output of the entrypoint component
+    /// and cannot be driven
+    #[error("\"{0}\" is an output port of the entrypoint component and cannot be driven")]
+    NotAnInput(String),
+
+    /// There is no memory or register with the given name
+    #[error("no memory or register named \"{0}\"")]
+    UnknownMemory(String),
+
+    /// An index outside of the bounds of a memory was accessed by the
+    /// embedding API
+    #[error("index {index} is out of bounds for memory \"{name}\" with {size} entries")]
+    MemoryIndexOutOfBounds {
+        name: String,
+        index: usize,
+        size: usize,
+    },
+
+    /// A value of the wrong width was given to a port or memory
+    #[error("cannot assign a {given}-bit value to \"{name}\", which is {expected} bits wide")]
+    WidthMismatch {
+        name: String,
+        expected: u64,
+        given: u64,
+    },
+
+    /// An invalid argument was passed through the C API
+    #[error("invalid argument - {0}")]
+    InvalidArgument(String),
+
+    /// The program uses a feature which the compiled simulation does not
+    /// support
+    #[error("the program cannot be simulated in compiled mode: {0}")]
+    UnsupportedCompiledProgram(String),
+
+    /// The compiled simulation could not be built or failed while running
+    #[error("compiled simulation failed: {0}")]
+    CompiledSimulationFailed(String),
+
+    /// A snapshot could not be read or does not belong to the simulated
+    /// program
+    #[error("invalid snapshot: {0}")]
+    InvalidSnapshot(String),
 }
 
 // this is silly but needed to make the program print something sensible when returning
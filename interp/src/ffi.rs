@@ -0,0 +1,497 @@
+//! A C-compatible API for running Cider in-process, for example from Python
+//! through `ctypes`. The crate is built as a `cdylib` exporting the functions
+//! in this module.
+//!
+//! A simulator is created with [`cider_sim_new`] and released with
+//! [`cider_sim_free`]. Functions that return an `int32_t` status return
+//! [`CIDER_OK`] on success and [`CIDER_ERROR`] on failure, in which case
+//! [`cider_last_error`] describes what went wrong. Values are exchanged as
+//! little-endian byte buffers that hold at least `ceil(width / 8)` bytes.
+
+use crate::{
+    errors::{InterpreterError, InterpreterResult},
+    flatten::{
+        setup_simulation,
+        structures::{
+            context::Context,
+            environment::{Environment, Simulator},
+        },
+    },
+    serialization::{
+        converter::{convert_from_data_dump, convert_to_data_dump},
+        json_data::JsonData,
+    },
+    values::Value,
+};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    ptr,
+    rc::Rc,
+    slice,
+};
+
+/// The call succeeded
+pub const CIDER_OK: i32 = 0;
+/// The call failed, see [`cider_last_error`]
+pub const CIDER_ERROR: i32 = -1;
+/// The requested port does not currently have a value
+pub const CIDER_UNDEFINED: i32 = 1;
+
+/// An opaque handle to a simulator
+pub struct CiderSim(Simulator<Rc<Context>>);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(msg: String) {
+    let msg = CString::new(msg.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|err| *err.borrow_mut() = Some(msg));
+}
+
+fn invalid(msg: String) -> crate::errors::BoxedInterpreterError {
+    InterpreterError::InvalidArgument(msg).into()
+}
+
+/// Runs the given function and returns its result. If the function fails or
+/// panics, the error is recorded for [`cider_last_error`] and `default` is
+/// returned instead, since unwinding into C is not allowed.
+fn call<T>(default: T, func: impl FnOnce() -> InterpreterResult<T>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(Ok(val)) => val,
+        Ok(Err(err)) => {
+            set_last_error(err.to_string());
+            default
+        }
+        Err(payload) => {
+            let msg = payload
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            set_last_error(format!("the simulator panicked: {msg}"));
+            default
+        }
+    }
+}
+
+fn status(func: impl FnOnce() -> InterpreterResult<()>) -> i32 {
+    call(CIDER_ERROR, || func().map(|_| CIDER_OK))
+}
+
+unsafe fn to_str<'a>(
+    ptr: *const c_char,
+    what: &str,
+) -> InterpreterResult<&'a str> {
+    if ptr.is_null() {
+        return Err(invalid(format!("{what} is null")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| invalid(format!("{what} is not valid UTF-8")))
+}
+
+unsafe fn to_sim<'a>(
+    sim: *mut CiderSim,
+) -> InterpreterResult<&'a mut Simulator<Rc<Context>>> {
+    sim.as_mut()
+        .map(|sim| &mut sim.0)
+        .ok_or_else(|| invalid("the simulator is null".to_string()))
+}
+
+unsafe fn to_bytes<'a>(bytes: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(bytes, len)
+    }
+}
+
+unsafe fn to_bytes_mut<'a>(bytes: *mut u8, len: usize) -> &'a mut [u8] {
+    if len == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(bytes, len)
+    }
+}
+
+/// Reads a value of the given width from a little-endian byte buffer. The
+/// buffer may be longer or shorter than the value as long as every bit beyond
+/// the width is zero.
+fn read_value(bytes: &[u8], width: u64) -> InterpreterResult<Value> {
+    if width == 0 {
+        return Err(invalid("values must be at least one bit wide".into()));
+    }
+    let needed = width.div_ceil(8) as usize;
+    let (value, rest) = bytes.split_at(needed.min(bytes.len()));
+    let mut value = value.to_vec();
+    value.resize(needed, 0);
+
+    let padding = (needed as u64 * 8 - width) as u32;
+    if value.last().unwrap().leading_zeros() < padding
+        || rest.iter().any(|byte| *byte != 0)
+    {
+        return Err(invalid(format!(
+            "the given value does not fit in {width} bits"
+        )));
+    }
+    Ok(Value::from_bytes_le(&value, width as usize))
+}
+
+/// Writes the value to a little-endian byte buffer, zeroing any bytes
+/// beyond its width.
+fn write_value(val: &Value, buf: &mut [u8]) -> InterpreterResult<()> {
+    let needed = val.width().div_ceil(8) as usize;
+    if buf.len() < needed {
+        return Err(invalid(format!(
+            "a buffer of {} bytes cannot hold a {}-bit value",
+            buf.len(),
+            val.width()
+        )));
+    }
+    let bytes = val.to_bytes();
+    buf.fill(0);
+    buf[..bytes.len()].copy_from_slice(&bytes);
+    Ok(())
+}
+
+/// Returns a description of the last error that occurred on this thread, or
+/// null if no error has occurred. The string is owned by the library and
+/// remains valid until the next failing call.
+#[no_mangle]
+pub extern "C" fn cider_last_error() -> *const c_char {
+    LAST_ERROR.with(|err| {
+        err.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Loads the Calyx program in `file` and constructs a simulator for it,
+/// returning null on failure. `lib_path` is the root of the Calyx repository
+/// used to resolve imports. If `data_json` is not null, it holds memory
+/// contents in the JSON format accepted by `cider-data-converter`, which are
+/// used to initialize the memories of the entrypoint component.
+///
+/// If `embedded` is false, the simulator holds the `go` port of the
+/// entrypoint component high and runs it like the `cider` binary. Otherwise
+/// the caller drives every input, as in [`Simulator::new_embedded`].
+///
+/// # Safety
+/// `file` and `lib_path` must be valid nul-terminated strings and
+/// `data_json` must be null or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_new(
+    file: *const c_char,
+    lib_path: *const c_char,
+    data_json: *const c_char,
+    skip_verification: bool,
+    embedded: bool,
+) -> *mut CiderSim {
+    call(ptr::null_mut(), || {
+        let file = Path::new(to_str(file, "the file name")?).to_path_buf();
+        let lib_path = Path::new(to_str(lib_path, "the library path")?);
+        let data = if data_json.is_null() {
+            None
+        } else {
+            let json: JsonData =
+                serde_json::from_str(to_str(data_json, "the memory data")?)
+                    .map_err(|err| {
+                        invalid(format!("malformed memory data: {err}"))
+                    })?;
+            Some(convert_to_data_dump(&json, false))
+        };
+
+        let ctx = Rc::new(setup_simulation(
+            &Some(file),
+            lib_path,
+            skip_verification,
+        )?);
+        let sim = if embedded {
+            Simulator::new_embedded(ctx, data)
+        } else {
+            Simulator::new(Environment::new(ctx, data))
+        };
+        Ok(Box::into_raw(Box::new(CiderSim(sim))))
+    })
+}
+
+/// Releases a simulator constructed by [`cider_sim_new`].
+///
+/// # Safety
+/// `sim` must be null or a simulator returned by [`cider_sim_new`] that has
+/// not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_free(sim: *mut CiderSim) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
+
+/// Drives the input port of the entrypoint component with the value in the
+/// given buffer. See [`Simulator::set_input`].
+///
+/// # Safety
+/// `sim` must be a valid simulator, `port` a valid nul-terminated string and
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_set_input(
+    sim: *mut CiderSim,
+    port: *const c_char,
+    bytes: *const u8,
+    len: usize,
+) -> i32 {
+    status(|| {
+        let sim = to_sim(sim)?;
+        let port = to_str(port, "the port name")?;
+        let val = read_value(to_bytes(bytes, len), sim.port_width(port)?)?;
+        sim.set_input(port, val)
+    })
+}
+
+/// Stops driving the input port of the entrypoint component.
+///
+/// # Safety
+/// `sim` must be a valid simulator and `port` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_release_input(
+    sim: *mut CiderSim,
+    port: *const c_char,
+) -> i32 {
+    status(|| to_sim(sim)?.release_input(to_str(port, "the port name")?))
+}
+
+/// Propagates the inputs through the combinational logic without advancing
+/// the clock.
+///
+/// # Safety
+/// `sim` must be a valid simulator.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_eval(sim: *mut CiderSim) -> i32 {
+    status(|| to_sim(sim)?.eval())
+}
+
+/// Advances the clock by one cycle.
+///
+/// # Safety
+/// `sim` must be a valid simulator.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_step(sim: *mut CiderSim) -> i32 {
+    status(|| to_sim(sim)?.tick())
+}
+
+/// Runs the simulation until the entrypoint component asserts its `done`
+/// port. An embedded simulator only finishes if the caller drives `go`.
+///
+/// # Safety
+/// `sim` must be a valid simulator.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_run(sim: *mut CiderSim) -> i32 {
+    status(|| to_sim(sim)?.run_program())
+}
+
+/// Returns 1 if the entrypoint component asserts its `done` port, 0 if it
+/// does not and [`CIDER_ERROR`] on failure.
+///
+/// # Safety
+/// `sim` must be a valid simulator.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_is_done(sim: *mut CiderSim) -> i32 {
+    call(CIDER_ERROR, || Ok(to_sim(sim)?.is_done() as i32))
+}
+
+/// Returns the width of the port of the entrypoint component, or
+/// [`CIDER_ERROR`] on failure.
+///
+/// # Safety
+/// `sim` must be a valid simulator and `port` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_port_width(
+    sim: *mut CiderSim,
+    port: *const c_char,
+) -> i64 {
+    call(CIDER_ERROR as i64, || {
+        Ok(to_sim(sim)?.port_width(to_str(port, "the port name")?)? as i64)
+    })
+}
+
+/// Writes the value of the port of the entrypoint component to the given
+/// buffer. Returns [`CIDER_UNDEFINED`] and leaves the buffer untouched if the
+/// port does not have a value.
+///
+/// # Safety
+/// `sim` must be a valid simulator, `port` a valid nul-terminated string and
+/// `buf` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_get_port(
+    sim: *mut CiderSim,
+    port: *const c_char,
+    buf: *mut u8,
+    len: usize,
+) -> i32 {
+    call(CIDER_ERROR, || {
+        match to_sim(sim)?.get_port(to_str(port, "the port name")?)? {
+            Some(val) => {
+                write_value(&val, to_bytes_mut(buf, len))?;
+                Ok(CIDER_OK)
+            }
+            None => Ok(CIDER_UNDEFINED),
+        }
+    })
+}
+
+/// Returns the width of the entries of the memory or register, or
+/// [`CIDER_ERROR`] on failure.
+///
+/// # Safety
+/// `sim` must be a valid simulator and `name` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_memory_width(
+    sim: *mut CiderSim,
+    name: *const c_char,
+) -> i64 {
+    call(CIDER_ERROR as i64, || {
+        let contents =
+            to_sim(sim)?.peek_memory(to_str(name, "the memory name")?)?;
+        Ok(contents[0].width() as i64)
+    })
+}
+
+/// Returns the number of entries of the memory or register, or
+/// [`CIDER_ERROR`] on failure.
+///
+/// # Safety
+/// `sim` must be a valid simulator and `name` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_memory_size(
+    sim: *mut CiderSim,
+    name: *const c_char,
+) -> i64 {
+    call(CIDER_ERROR as i64, || {
+        let contents =
+            to_sim(sim)?.peek_memory(to_str(name, "the memory name")?)?;
+        Ok(contents.len() as i64)
+    })
+}
+
+/// Writes the entry at the given row-major index of the memory or register
+/// to the given buffer. See [`Simulator::peek_memory`].
+///
+/// # Safety
+/// `sim` must be a valid simulator, `name` a valid nul-terminated string and
+/// `buf` must point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_peek_memory(
+    sim: *mut CiderSim,
+    name: *const c_char,
+    index: usize,
+    buf: *mut u8,
+    len: usize,
+) -> i32 {
+    status(|| {
+        let name = to_str(name, "the memory name")?;
+        let contents = to_sim(sim)?.peek_memory(name)?;
+        let val = contents.get(index).ok_or_else(|| {
+            InterpreterError::MemoryIndexOutOfBounds {
+                name: name.to_string(),
+                index,
+                size: contents.len(),
+            }
+        })?;
+        write_value(val, to_bytes_mut(buf, len))
+    })
+}
+
+/// Overwrites the entry at the given row-major index of the memory or
+/// register with the value in the given buffer. See
+/// [`Simulator::poke_memory`].
+///
+/// # Safety
+/// `sim` must be a valid simulator, `name` a valid nul-terminated string and
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_poke_memory(
+    sim: *mut CiderSim,
+    name: *const c_char,
+    index: usize,
+    bytes: *const u8,
+    len: usize,
+) -> i32 {
+    status(|| {
+        let sim = to_sim(sim)?;
+        let name = to_str(name, "the memory name")?;
+        let width = sim.peek_memory(name)?[0].width();
+        sim.poke_memory(name, index, read_value(to_bytes(bytes, len), width)?)
+    })
+}
+
+/// Serializes the memories of the entrypoint component into a data dump in
+/// the format written by the `cider` binary and stores its length in
+/// `out_len`. Returns null on failure. The buffer must be released with
+/// [`cider_bytes_free`].
+///
+/// # Safety
+/// `sim` must be a valid simulator and `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_dump(
+    sim: *mut CiderSim,
+    dump_registers: bool,
+    all_memories: bool,
+    out_len: *mut usize,
+) -> *mut u8 {
+    call(ptr::null_mut(), || {
+        let mut bytes = vec![];
+        to_sim(sim)?
+            .dump_memories(dump_registers, all_memories)
+            .serialize(&mut bytes)?;
+        let bytes = bytes.into_boxed_slice();
+        *out_len = bytes.len();
+        Ok(Box::into_raw(bytes) as *mut u8)
+    })
+}
+
+/// Releases a buffer returned by [`cider_sim_dump`].
+///
+/// # Safety
+/// `bytes` must be null or a buffer returned by [`cider_sim_dump`] along with
+/// its length.
+#[no_mangle]
+pub unsafe extern "C" fn cider_bytes_free(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+    }
+}
+
+/// Returns the memories of the entrypoint component as a JSON string in the
+/// format printed by `cider-data-converter --to json`, or null on failure.
+/// The string must be released with [`cider_string_free`].
+///
+/// # Safety
+/// `sim` must be a valid simulator.
+#[no_mangle]
+pub unsafe extern "C" fn cider_sim_dump_json(
+    sim: *mut CiderSim,
+    dump_registers: bool,
+    all_memories: bool,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let dump = to_sim(sim)?.dump_memories(dump_registers, all_memories);
+        let json = serde_json::to_string(&convert_from_data_dump(&dump, false))
+            .map_err(|err| invalid(err.to_string()))?;
+        Ok(CString::new(json).unwrap().into_raw())
+    })
+}
+
+/// Releases a string returned by [`cider_sim_dump_json`].
+///
+/// # Safety
+/// `string` must be null or a string returned by [`cider_sim_dump_json`].
+#[no_mangle]
+pub unsafe extern "C" fn cider_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
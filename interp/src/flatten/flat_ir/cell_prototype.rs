@@ -1,8 +1,10 @@
 use calyx_ir::{self as cir, BoolAttr};
+use calyx_utils::{CalyxResult, Error};
 use smallvec::SmallVec;
 
 use crate::{
-    flatten::primitives::utils::get_params, serialization::Dimensions,
+    flatten::primitives::{float::FloatFormat, utils::get_params},
+    serialization::Dimensions,
 };
 
 use super::prelude::ComponentIdx;
@@ -147,6 +149,47 @@ pub enum FXType {
     Sqrt,
 }
 
+/// An enum for encoding IEEE-754 floating point primitive operator types
+#[derive(Debug, Clone)]
+pub enum FloatType {
+    /// Addition (`std_float_add`)
+    Add,
+    /// Subtraction (`std_float_sub`)
+    Sub,
+    /// Multiplication (`std_float_mult`)
+    Mult,
+    /// Division (`std_float_div`)
+    Div,
+    /// Square root (`std_float_sqrt`)
+    Sqrt,
+    /// Less than (`std_float_lt`)
+    Lt,
+    /// Less than or equal (`std_float_le`)
+    Le,
+    /// Greater than (`std_float_gt`)
+    Gt,
+    /// Greater than or equal (`std_float_ge`)
+    Ge,
+    /// Equality (`std_float_eq`)
+    Eq,
+    /// Inequality (`std_float_neq`)
+    Neq,
+}
+
+/// An enum for encoding conversions between integers and IEEE-754 floating
+/// point values
+#[derive(Debug, Clone)]
+pub enum FloatConversionType {
+    /// Float to unsigned integer (`std_float_to_int`)
+    ToInt,
+    /// Float to signed integer (`std_float_to_sint`)
+    ToSignedInt,
+    /// Unsigned integer to float (`std_int_to_float`)
+    FromInt,
+    /// Signed integer to float (`std_sint_to_float`)
+    FromSignedInt,
+}
+
 /// An enum for encoding memory primitives operator types
 #[derive(Debug, Clone)]
 pub enum MemType {
@@ -336,6 +379,28 @@ pub enum CellPrototype {
         /// The width of the fractional part
         frac_width: ParamWidth,
     },
+    /// This cell is an IEEE-754 floating point primitive. See [`FloatType`]
+    /// for the list of primitives.
+    Float {
+        /// Floating point operator
+        op: FloatType,
+        /// The width of the exponent
+        exp_width: ParamWidth,
+        /// The width of the mantissa, without the implicit leading bit
+        mant_width: ParamWidth,
+    },
+    /// This cell converts between integers and IEEE-754 floating point
+    /// values. See [`FloatConversionType`] for the list of primitives.
+    FloatConversion {
+        /// Conversion operator
+        op: FloatConversionType,
+        /// The width of the integer
+        int_width: ParamWidth,
+        /// The width of the exponent
+        exp_width: ParamWidth,
+        /// The width of the mantissa, without the implicit leading bit
+        mant_width: ParamWidth,
+    },
     /// This cell is a memory primitive. Either a combinational or sequential memory.
     Memory {
         /// The type of memory
@@ -370,9 +435,9 @@ impl CellPrototype {
         }
     }
 
-    /// Constructs a prototype for the given cell
-    #[must_use]
-    pub fn construct_prototype(cell: &cir::Cell) -> Self {
+    /// Constructs a prototype for the given cell. Fails if the parameters of
+    /// the cell are not supported by the interpreter.
+    pub fn construct_prototype(cell: &cir::Cell) -> CalyxResult<Self> {
         if let cir::CellType::Primitive {
             name,
             param_binding,
@@ -382,7 +447,7 @@ impl CellPrototype {
             let name: &str = name.as_ref();
             let params: &SmallVec<_> = param_binding;
 
-            match name {
+            Ok(match name {
                 "std_reg" => {
                     get_params![params; width: "WIDTH"];
 
@@ -531,6 +596,81 @@ impl CellPrototype {
                     }
                 }
 
+                n @ ("std_float_add" | "std_float_sub" | "std_float_mult"
+                | "std_float_div" | "std_float_sqrt" | "std_float_lt"
+                | "std_float_le" | "std_float_gt" | "std_float_ge"
+                | "std_float_eq" | "std_float_neq") => {
+                    get_params![params;
+                        width: "WIDTH",
+                        exp_width: "EXP_WIDTH",
+                        mant_width: "MANT_WIDTH"
+                    ];
+                    let (exp_width, mant_width) =
+                        float_widths(cell, width, exp_width, mant_width)?;
+
+                    Self::Float {
+                        op: match n {
+                            "std_float_add" => FloatType::Add,
+                            "std_float_sub" => FloatType::Sub,
+                            "std_float_mult" => FloatType::Mult,
+                            "std_float_div" => FloatType::Div,
+                            "std_float_sqrt" => FloatType::Sqrt,
+                            "std_float_lt" => FloatType::Lt,
+                            "std_float_le" => FloatType::Le,
+                            "std_float_gt" => FloatType::Gt,
+                            "std_float_ge" => FloatType::Ge,
+                            "std_float_eq" => FloatType::Eq,
+                            _ => FloatType::Neq,
+                        },
+                        exp_width,
+                        mant_width,
+                    }
+                }
+
+                n @ ("std_float_to_int" | "std_float_to_sint") => {
+                    get_params![params;
+                        width: "WIDTH",
+                        exp_width: "EXP_WIDTH",
+                        mant_width: "MANT_WIDTH",
+                        out_width: "OUT_WIDTH"
+                    ];
+                    let (exp_width, mant_width) =
+                        float_widths(cell, width, exp_width, mant_width)?;
+
+                    Self::FloatConversion {
+                        op: if n == "std_float_to_int" {
+                            FloatConversionType::ToInt
+                        } else {
+                            FloatConversionType::ToSignedInt
+                        },
+                        int_width: int_width(cell, "OUT_WIDTH", out_width)?,
+                        exp_width,
+                        mant_width,
+                    }
+                }
+
+                n @ ("std_int_to_float" | "std_sint_to_float") => {
+                    get_params![params;
+                        in_width: "IN_WIDTH",
+                        width: "WIDTH",
+                        exp_width: "EXP_WIDTH",
+                        mant_width: "MANT_WIDTH"
+                    ];
+                    let (exp_width, mant_width) =
+                        float_widths(cell, width, exp_width, mant_width)?;
+
+                    Self::FloatConversion {
+                        op: if n == "std_int_to_float" {
+                            FloatConversionType::FromInt
+                        } else {
+                            FloatConversionType::FromSignedInt
+                        },
+                        int_width: int_width(cell, "IN_WIDTH", in_width)?,
+                        exp_width,
+                        mant_width,
+                    }
+                }
+
                 n @ ("std_lsh" | "std_rsh" | "std_slsh" | "std_srsh") => {
                     get_params![params; width: "WIDTH"];
 
@@ -812,7 +952,7 @@ impl CellPrototype {
                     name.to_string(),
                     param_binding.clone(),
                 ),
-            }
+            })
         } else {
             unreachable!("construct_primitive called on non-primitive cell");
         }
@@ -840,3 +980,50 @@ impl CellPrototype {
         )
     }
 }
+
+/// Checks the parameters of a floating point primitive and returns its
+/// exponent and mantissa widths
+fn float_widths(
+    cell: &cir::Cell,
+    width: u64,
+    exp_width: u64,
+    mant_width: u64,
+) -> CalyxResult<(u32, u32)> {
+    let widths = u32::try_from(exp_width)
+        .ok()
+        .zip(u32::try_from(mant_width).ok())
+        .filter(|(exp_width, mant_width)| {
+            FloatFormat::new(*exp_width, *mant_width).is_some()
+        });
+    let Some((exp_width, mant_width)) = widths else {
+        return Err(Error::malformed_structure(format!(
+            "Cell `{}' has an unsupported float format: `EXP_WIDTH' must be between 2 and {} and `MANT_WIDTH' must be at least 1",
+            cell.name(),
+            FloatFormat::MAX_EXP_WIDTH
+        ))
+        .with_pos(&cell.attributes));
+    };
+    if width != 1 + exp_width as u64 + mant_width as u64 {
+        return Err(Error::malformed_structure(format!(
+            "Cell `{}' has a `WIDTH' of {width}, but a float with {exp_width} exponent and {mant_width} mantissa bits is {} bits wide",
+            cell.name(),
+            1 + exp_width as u64 + mant_width as u64
+        ))
+        .with_pos(&cell.attributes));
+    }
+    Ok((exp_width, mant_width))
+}
+
+/// Checks the width of the integer of a float conversion primitive
+fn int_width(cell: &cir::Cell, param: &str, width: u64) -> CalyxResult<u32> {
+    u32::try_from(width)
+        .ok()
+        .filter(|width| *width >= 1)
+        .ok_or_else(|| {
+            Error::malformed_structure(format!(
+                "Cell `{}' has an `{param}' of {width}, but integers need at least one bit",
+                cell.name()
+            ))
+            .with_pos(&cell.attributes)
+        })
+}
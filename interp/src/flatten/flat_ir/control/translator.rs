@@ -1,5 +1,6 @@
 use ahash::{HashMap, HashMapExt};
 use calyx_ir::{self as cir, NumAttr, RRC};
+use calyx_utils::{CalyxResult, WithPos};
 use itertools::Itertools;
 
 use crate::{
@@ -34,7 +35,7 @@ pub struct GroupMapper {
     groups: HashMap<*const cir::Group, GroupIdx>,
 }
 
-pub fn translate(orig_ctx: &cir::Context) -> Context {
+pub fn translate(orig_ctx: &cir::Context) -> CalyxResult<Context> {
     let mut ctx = Context::new();
 
     let mut component_id_map = ComponentMapper::new();
@@ -44,14 +45,14 @@ pub fn translate(orig_ctx: &cir::Context) -> Context {
     // iteration over the components in a post-order so this is a hack instead
 
     for comp in CompTraversal::new(&orig_ctx.components).iter() {
-        translate_component(comp, &mut ctx, &mut component_id_map);
+        translate_component(comp, &mut ctx, &mut component_id_map)?;
     }
 
     ctx.entry_point = *component_id_map
         .get(&orig_ctx.entrypoint().name)
         .expect("Unable to find entrypoint");
 
-    ctx
+    Ok(ctx)
 }
 
 #[must_use]
@@ -109,6 +110,7 @@ fn translate_assignment(
         dst: map[&assign.dst.as_raw()],
         src: map[&assign.src.as_raw()],
         guard: translate_guard(&assign.guard, interp_ctx, map),
+        pos: assign.attributes.copy_span().into_option(),
     }
 }
 #[must_use]
@@ -124,7 +126,7 @@ fn translate_component(
     comp: &cir::Component,
     ctx: &mut Context,
     component_id_map: &mut ComponentMapper,
-) -> ComponentIdx {
+) -> CalyxResult<ComponentIdx> {
     let mut auxillary_component_info = AuxillaryComponentInfo::new_with_name(
         ctx.secondary.string_table.insert(comp.name),
     );
@@ -134,7 +136,7 @@ fn translate_component(
         ctx,
         &mut auxillary_component_info,
         component_id_map,
-    );
+    )?;
 
     // Translate the groups
     let mut group_map = HashMap::with_capacity(comp.groups.len());
@@ -229,6 +231,7 @@ fn translate_component(
                     dst: *dst,
                     src: *src,
                     guard: ctx.primary.guards.push(Guard::True),
+                    pos: None,
                 });
             }
 
@@ -270,7 +273,7 @@ fn translate_component(
         .insert(ctrl_ref, auxillary_component_info);
 
     component_id_map.insert(comp.name, ctrl_ref);
-    ctrl_ref
+    Ok(ctrl_ref)
 }
 
 fn insert_port(
@@ -302,7 +305,7 @@ fn insert_cell(
     layout: &mut Layout,
     comp_id: ComponentIdx,
     comp_id_map: &ComponentMapper,
-) {
+) -> CalyxResult<()> {
     let cell_ref = cell.borrow();
     let id = secondary_ctx.string_table.insert(cell_ref.name());
 
@@ -320,7 +323,7 @@ fn insert_cell(
             id,
             range,
             comp_id,
-            create_cell_prototype(cell, comp_id_map),
+            create_cell_prototype(cell, comp_id_map)?,
         );
         let cell_offset = aux.cell_offset_map.insert(cell_def);
         layout.cell_map.insert(cell.as_raw(), cell_offset.into());
@@ -341,11 +344,12 @@ fn insert_cell(
             id,
             range,
             comp_id,
-            create_cell_prototype(cell, comp_id_map),
+            create_cell_prototype(cell, comp_id_map)?,
         );
         let cell_offset = aux.ref_cell_offset_map.insert(ref_cell_def);
         layout.cell_map.insert(cell.as_raw(), cell_offset.into());
     }
+    Ok(())
 }
 
 #[derive(Debug, Default)]
@@ -359,7 +363,7 @@ fn compute_local_layout(
     ctx: &mut Context,
     aux: &mut AuxillaryComponentInfo,
     component_id_map: &ComponentMapper,
-) -> Layout {
+) -> CalyxResult<Layout> {
     let comp_id = ctx.primary.components.peek_next_idx();
 
     let port_def_base = ctx.secondary.local_port_defs.peek_next_idx();
@@ -421,7 +425,7 @@ fn compute_local_layout(
                 &mut layout,
                 comp_id,
                 component_id_map,
-            )
+            )?
         }
         // CASE 3 - Subcomponent
         else {
@@ -440,7 +444,7 @@ fn compute_local_layout(
             &mut layout,
             comp_id,
             component_id_map,
-        );
+        )?;
 
         // Advance the offsets to appropriately layout the next comp-cell
         let cell_ref = cell.borrow();
@@ -474,27 +478,27 @@ fn compute_local_layout(
         ctx.secondary.ref_cell_defs.peek_next_idx(),
     );
 
-    layout
+    Ok(layout)
 }
 
 fn create_cell_prototype(
     cell: &RRC<cir::Cell>,
     comp_id_map: &ComponentMapper,
-) -> CellPrototype {
+) -> CalyxResult<CellPrototype> {
     let borrow = cell.borrow();
     match &borrow.prototype {
         cir::CellType::Primitive { .. } => {
             CellPrototype::construct_prototype(&borrow)
         }
         cir::CellType::Component { name } => {
-            CellPrototype::Component(comp_id_map[name])
+            Ok(CellPrototype::Component(comp_id_map[name]))
         }
 
-        cir::CellType::Constant { val, width } => CellPrototype::Constant {
+        cir::CellType::Constant { val, width } => Ok(CellPrototype::Constant {
             value: *val,
             width: (*width).try_into().unwrap(),
             c_type: ConstantType::Literal,
-        },
+        }),
         cir::CellType::ThisComponent => unreachable!(
             "the flattening should not have this cell type, this is an error"
         ),
@@ -1,3 +1,5 @@
+use calyx_utils::GPosIdx;
+
 use crate::flatten::{
     flat_ir::prelude::*,
     structures::{index_trait::IndexRange, indexed_map::IndexedMap},
@@ -20,6 +22,9 @@ pub struct Assignment {
     pub src: PortRef,
     /// The assignment's guard
     pub guard: GuardIdx,
+    /// The position of the assignment in the source program, if it has one.
+    /// Assignments created by the compiler or by Cider itself do not.
+    pub pos: Option<GPosIdx>,
 }
 
 /// A group in the program. Analogue of [calyx_ir::Group]
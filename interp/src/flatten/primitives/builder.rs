@@ -1,12 +1,12 @@
 use ahash::HashSet;
 
-use super::{combinational::*, stateful::*, Primitive};
+use super::{combinational::*, float::FloatFormat, stateful::*, Primitive};
 use crate::{
     flatten::{
         flat_ir::{
             cell_prototype::{
-                CellPrototype, DoubleWidthType, FXType, MemType,
-                SingleWidthType, TripleWidthType,
+                CellPrototype, DoubleWidthType, FXType, FloatConversionType,
+                FloatType, MemType, SingleWidthType, TripleWidthType,
             },
             prelude::{CellInfo, GlobalPortIdx},
         },
@@ -23,7 +23,7 @@ pub fn build_primitive(
     ctx: &Context,
     dump: &Option<DataDump>,
     memories_initialized: &mut HashSet<String>,
-) -> Box<dyn Primitive> {
+) -> Box<dyn Primitive + Send + Sync> {
     match &prim.prototype {
         CellPrototype::Constant {
             value: val,
@@ -134,6 +134,68 @@ pub fn build_primitive(
                 Some(*frac_width),
             )),
         },
+        CellPrototype::Float {
+            op,
+            exp_width,
+            mant_width,
+        } => {
+            let format = FloatFormat::new(*exp_width, *mant_width)
+                .expect("float formats are checked by the cell prototype");
+            match op {
+                FloatType::Add
+                | FloatType::Sub
+                | FloatType::Mult
+                | FloatType::Div => Box::new(FloatBinaryPipe::new(
+                    base_port,
+                    op.clone(),
+                    format,
+                )),
+                FloatType::Sqrt => Box::new(FloatSqrt::new(base_port, format)),
+                FloatType::Lt
+                | FloatType::Le
+                | FloatType::Gt
+                | FloatType::Ge
+                | FloatType::Eq
+                | FloatType::Neq => Box::new(StdFloatCompare::new(
+                    base_port,
+                    op.clone(),
+                    format,
+                )),
+            }
+        }
+        CellPrototype::FloatConversion {
+            op,
+            int_width,
+            exp_width,
+            mant_width,
+        } => match op {
+            FloatConversionType::ToInt => Box::new(StdFloatToInt::new(
+                base_port,
+                *exp_width,
+                *mant_width,
+                *int_width,
+            )),
+            FloatConversionType::ToSignedInt => Box::new(StdFloatToSint::new(
+                base_port,
+                *exp_width,
+                *mant_width,
+                *int_width,
+            )),
+            FloatConversionType::FromInt => Box::new(StdIntToFloat::new(
+                base_port,
+                *int_width,
+                *exp_width,
+                *mant_width,
+            )),
+            FloatConversionType::FromSignedInt => {
+                Box::new(StdSintToFloat::new(
+                    base_port,
+                    *int_width,
+                    *exp_width,
+                    *mant_width,
+                ))
+            }
+        },
         CellPrototype::DoubleWidth { op, width2, .. } => match op {
             DoubleWidthType::Slice => {
                 Box::new(StdSlice::new(base_port, *width2))
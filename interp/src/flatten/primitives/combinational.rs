@@ -4,10 +4,14 @@ use bitvec::vec::BitVec;
 
 use crate::{
     flatten::{
-        flat_ir::prelude::{AssignedValue, GlobalPortIdx, PortValue},
+        flat_ir::{
+            cell_prototype::FloatType,
+            prelude::{AssignedValue, GlobalPortIdx, PortValue},
+        },
         primitives::{
-            all_defined, comb_primitive, declare_ports, ports,
-            prim_trait::UpdateStatus, utils::floored_division, Primitive,
+            all_defined, comb_primitive, declare_ports, float::FloatFormat,
+            ports, prim_trait::UpdateStatus, utils::floored_division,
+            Primitive,
         },
         structures::environment::PortMap,
     },
@@ -81,6 +85,18 @@ impl Primitive for StdMux {
         }
     }
 
+    fn exec_comb_precise(&self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base; cond: Self::COND, tru: Self::TRU, fal: Self::FAL, out: Self::OUT];
+
+        // an undefined condition does not matter if both inputs agree
+        match (port_map[tru].val(), port_map[fal].val()) {
+            (Some(t), Some(f)) if port_map[cond].is_undef() && t == f => {
+                port_map.insert_val(out, AssignedValue::cell_value(t.clone()))
+            }
+            _ => self.exec_comb(port_map),
+        }
+    }
+
     fn has_stateful(&self) -> bool {
         false
     }
@@ -271,18 +287,90 @@ comb_primitive!(StdSrsh(left [0], right [1]) -> (out [2]) {
     Ok(Some(result))
 });
 // ===================== Logial Operations ======================
-comb_primitive!(StdAnd(left [0], right [1]) -> (out [2]) {
-    all_defined!(left, right);
+/// Evaluates a bitwise operation with ports `left`, `right` and `out` at the
+/// given base. If `controlling` is given, an input whose bits all have that
+/// value determines the output on its own, so the other input may be
+/// undefined.
+fn exec_bitwise(
+    base: GlobalPortIdx,
+    port_map: &mut PortMap,
+    op: fn(&Value, &Value) -> Value,
+    controlling: Option<bool>,
+) -> UpdateResult {
+    ports![&base; left: 0, right: 1, out: 2];
+
+    let output = match (port_map[left].val(), port_map[right].val()) {
+        (Some(left), Some(right)) => Some(op(left, right)),
+        (Some(val), None) | (None, Some(val)) => controlling
+            .filter(|&bit| val.iter().all(|b| b == bit))
+            .map(|_| val.clone()),
+        (None, None) => None,
+    };
+
+    match output {
+        Some(val) => port_map.insert_val(out, AssignedValue::cell_value(val)),
+        None => {
+            port_map.write_undef(out)?;
+            Ok(UpdateStatus::Unchanged)
+        }
+    }
+}
 
-    let result: Value = (left.clone_bit_vec() & right.clone_bit_vec()).into();
-    Ok(Some(result))
-});
-comb_primitive!(StdOr(left [0], right [1]) -> (out [2]) {
-    all_defined!(left, right);
+pub struct StdAnd {
+    base_port: GlobalPortIdx,
+}
 
-    let result: Value = (left.clone_bit_vec() | right.clone_bit_vec()).into();
-    Ok(Some(result))
-});
+impl StdAnd {
+    pub fn new(base_port: GlobalPortIdx) -> Self {
+        Self { base_port }
+    }
+
+    fn and(left: &Value, right: &Value) -> Value {
+        (left.clone_bit_vec() & right.clone_bit_vec()).into()
+    }
+}
+
+impl Primitive for StdAnd {
+    fn exec_comb(&self, port_map: &mut PortMap) -> UpdateResult {
+        exec_bitwise(self.base_port, port_map, Self::and, None)
+    }
+
+    fn exec_comb_precise(&self, port_map: &mut PortMap) -> UpdateResult {
+        exec_bitwise(self.base_port, port_map, Self::and, Some(false))
+    }
+
+    fn has_stateful(&self) -> bool {
+        false
+    }
+}
+
+pub struct StdOr {
+    base_port: GlobalPortIdx,
+}
+
+impl StdOr {
+    pub fn new(base_port: GlobalPortIdx) -> Self {
+        Self { base_port }
+    }
+
+    fn or(left: &Value, right: &Value) -> Value {
+        (left.clone_bit_vec() | right.clone_bit_vec()).into()
+    }
+}
+
+impl Primitive for StdOr {
+    fn exec_comb(&self, port_map: &mut PortMap) -> UpdateResult {
+        exec_bitwise(self.base_port, port_map, Self::or, None)
+    }
+
+    fn exec_comb_precise(&self, port_map: &mut PortMap) -> UpdateResult {
+        exec_bitwise(self.base_port, port_map, Self::or, Some(true))
+    }
+
+    fn has_stateful(&self) -> bool {
+        false
+    }
+}
 comb_primitive!(StdXor(left [0], right [1]) -> (out [2]) {
     all_defined!(left, right);
 
@@ -572,3 +660,93 @@ impl Primitive for StdUndef {
         Ok(UpdateStatus::Unchanged)
     }
 }
+
+/// The IEEE-754 comparison primitives. Every comparison with a NaN is false
+/// except for `std_float_neq`.
+pub struct StdFloatCompare {
+    base: GlobalPortIdx,
+    op: FloatType,
+    format: FloatFormat,
+}
+
+impl StdFloatCompare {
+    declare_ports![LEFT: 0, RIGHT: 1, OUT: 2];
+    pub fn new(
+        base: GlobalPortIdx,
+        op: FloatType,
+        format: FloatFormat,
+    ) -> Self {
+        Self { base, op, format }
+    }
+}
+
+impl Primitive for StdFloatCompare {
+    fn exec_comb(&self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base; left: Self::LEFT, right: Self::RIGHT, out: Self::OUT];
+
+        let (Some(left), Some(right)) =
+            (port_map[left].val(), port_map[right].val())
+        else {
+            port_map.write_undef(out)?;
+            return Ok(UpdateStatus::Unchanged);
+        };
+
+        let ordering = self
+            .format
+            .compare(&left.as_unsigned(), &right.as_unsigned());
+        let result = match self.op {
+            FloatType::Lt => ordering.is_some_and(|o| o.is_lt()),
+            FloatType::Le => ordering.is_some_and(|o| o.is_le()),
+            FloatType::Gt => ordering.is_some_and(|o| o.is_gt()),
+            FloatType::Ge => ordering.is_some_and(|o| o.is_ge()),
+            FloatType::Eq => ordering.is_some_and(|o| o.is_eq()),
+            FloatType::Neq => !ordering.is_some_and(|o| o.is_eq()),
+            _ => unreachable!("{:?} is not a comparison", self.op),
+        };
+
+        port_map.insert_val(
+            out,
+            AssignedValue::cell_value(if result {
+                Value::bit_high()
+            } else {
+                Value::bit_low()
+            }),
+        )
+    }
+
+    fn has_stateful(&self) -> bool {
+        false
+    }
+}
+
+comb_primitive!(StdFloatToInt[EXP_WIDTH, MANT_WIDTH, OUT_WIDTH](input [0]) -> (out [1]) {
+    all_defined!(input);
+    let format = FloatFormat::new(EXP_WIDTH, MANT_WIDTH)
+        .expect("float formats are checked by the cell prototype");
+
+    Ok(Some(Value::from(format.to_int(&input.as_unsigned(), OUT_WIDTH, false), OUT_WIDTH)))
+});
+
+comb_primitive!(StdFloatToSint[EXP_WIDTH, MANT_WIDTH, OUT_WIDTH](input [0]) -> (out [1]) {
+    all_defined!(input);
+    let format = FloatFormat::new(EXP_WIDTH, MANT_WIDTH)
+        .expect("float formats are checked by the cell prototype");
+
+    Ok(Some(Value::from(format.to_int(&input.as_unsigned(), OUT_WIDTH, true), OUT_WIDTH)))
+});
+
+comb_primitive!(StdIntToFloat[IN_WIDTH, EXP_WIDTH, MANT_WIDTH](input [0]) -> (out [1]) {
+    all_defined!(input);
+    let format = FloatFormat::new(EXP_WIDTH, MANT_WIDTH)
+        .expect("float formats are checked by the cell prototype");
+
+    Ok(Some(Value::from(format.from_int(&input.as_unsigned(), IN_WIDTH, false), format.width())))
+});
+
+comb_primitive!(StdSintToFloat[IN_WIDTH, EXP_WIDTH, MANT_WIDTH](input [0]) -> (out [1]) {
+    all_defined!(input);
+    let format = FloatFormat::new(EXP_WIDTH, MANT_WIDTH)
+        .expect("float formats are checked by the cell prototype");
+
+    Ok(Some(Value::from(format.from_int(&input.as_unsigned(), IN_WIDTH, true), format.width())))
+});
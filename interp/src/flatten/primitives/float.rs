@@ -0,0 +1,586 @@
+//! Bit-exact models of IEEE-754 binary floating point arithmetic for arbitrary
+//! exponent and mantissa widths. Values are passed around as their bit
+//! patterns. All operations round to nearest, ties to even, support subnormal
+//! numbers, and produce the canonical quiet NaN whenever the result is NaN.
+//! These are the same semantics as the RTL in `primitives/float.sv`.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+use std::cmp::Ordering;
+
+/// An IEEE-754 binary floating point format. The mantissa width does not
+/// include the implicit leading bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatFormat {
+    exp_width: u32,
+    mant_width: u32,
+}
+
+/// A floating point value unpacked from its bit pattern.
+enum Unpacked {
+    NaN,
+    Inf {
+        sign: bool,
+    },
+    /// The value `(-1)^sign * sig * 2^exp`. Zero has a `sig` of zero.
+    Finite {
+        sign: bool,
+        sig: BigUint,
+        exp: i64,
+    },
+}
+
+fn mask(width: u64) -> BigUint {
+    (BigUint::one() << width) - 1_u32
+}
+
+impl FloatFormat {
+    /// The widest supported exponent. The models keep exponents in `i64`s and
+    /// shift significands by exponent differences, so much wider exponents
+    /// would overflow or exhaust memory.
+    pub const MAX_EXP_WIDTH: u32 = 32;
+
+    /// Returns `None` unless the format has between two and
+    /// [`Self::MAX_EXP_WIDTH`] exponent bits and at least one mantissa bit.
+    pub fn new(exp_width: u32, mant_width: u32) -> Option<Self> {
+        ((2..=Self::MAX_EXP_WIDTH).contains(&exp_width) && mant_width >= 1)
+            .then_some(Self {
+                exp_width,
+                mant_width,
+            })
+    }
+
+    /// The width of the bit pattern
+    pub fn width(&self) -> u32 {
+        1 + self.exp_width + self.mant_width
+    }
+
+    fn bias(&self) -> i64 {
+        (1 << (self.exp_width - 1)) - 1
+    }
+
+    /// The exponent of the smallest normal number
+    fn min_exp(&self) -> i64 {
+        1 - self.bias()
+    }
+
+    /// The biased exponent of infinities and NaNs
+    fn max_biased_exp(&self) -> i64 {
+        (1 << self.exp_width) - 1
+    }
+
+    fn pack(&self, sign: bool, biased_exp: i64, mant: BigUint) -> BigUint {
+        let sign = if sign {
+            BigUint::one()
+        } else {
+            BigUint::zero()
+        };
+        (sign << (self.exp_width + self.mant_width))
+            | (BigUint::from(biased_exp as u64) << self.mant_width)
+            | mant
+    }
+
+    /// The canonical quiet NaN
+    pub fn nan(&self) -> BigUint {
+        self.pack(
+            false,
+            self.max_biased_exp(),
+            BigUint::one() << (self.mant_width - 1),
+        )
+    }
+
+    fn inf(&self, sign: bool) -> BigUint {
+        self.pack(sign, self.max_biased_exp(), BigUint::zero())
+    }
+
+    fn zero(&self, sign: bool) -> BigUint {
+        self.pack(sign, 0, BigUint::zero())
+    }
+
+    fn unpack(&self, bits: &BigUint) -> Unpacked {
+        let mant_width = self.mant_width as u64;
+        let mant = bits & mask(mant_width);
+        let biased_exp = ((bits >> mant_width) & mask(self.exp_width as u64))
+            .iter_u64_digits()
+            .next()
+            .unwrap_or_default() as i64;
+        let sign = bits.bit(mant_width + self.exp_width as u64);
+
+        if biased_exp == self.max_biased_exp() {
+            if mant.is_zero() {
+                Unpacked::Inf { sign }
+            } else {
+                Unpacked::NaN
+            }
+        } else if biased_exp == 0 {
+            Unpacked::Finite {
+                sign,
+                sig: mant,
+                exp: self.min_exp() - mant_width as i64,
+            }
+        } else {
+            Unpacked::Finite {
+                sign,
+                sig: mant | (BigUint::one() << mant_width),
+                exp: biased_exp - self.bias() - mant_width as i64,
+            }
+        }
+    }
+
+    /// Rounds the exact value `(-1)^sign * sig * 2^exp` to the nearest
+    /// representable value, ties to even.
+    fn round(&self, sign: bool, sig: BigUint, exp: i64) -> BigUint {
+        if sig.is_zero() {
+            return self.zero(sign);
+        }
+        let mant_width = self.mant_width as i64;
+
+        // The exponent of the leading one and the exponent of the least
+        // significant bit of the result
+        let lead_exp = sig.bits() as i64 - 1 + exp;
+        let mut quantum = lead_exp.max(self.min_exp()) - mant_width;
+
+        let shift = quantum - exp;
+        let mut keep = if shift <= 0 {
+            sig << (-shift) as u64
+        } else {
+            let shift = shift as u64;
+            let keep = &sig >> shift;
+            let round_bit = sig.bit(shift - 1);
+            let sticky = !(&sig & mask(shift - 1)).is_zero();
+            if round_bit && (sticky || keep.bit(0)) {
+                keep + 1_u32
+            } else {
+                keep
+            }
+        };
+
+        // Rounding up can carry into a new leading bit
+        if keep.bits() as i64 > mant_width + 1 {
+            keep >>= 1;
+            quantum += 1;
+        }
+
+        if keep.bit(mant_width as u64) {
+            let biased_exp = quantum + mant_width + self.bias();
+            if biased_exp >= self.max_biased_exp() {
+                self.inf(sign)
+            } else {
+                self.pack(sign, biased_exp, keep & mask(mant_width as u64))
+            }
+        } else {
+            // subnormal
+            self.pack(sign, 0, keep)
+        }
+    }
+
+    pub fn add(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        match (self.unpack(left), self.unpack(right)) {
+            (Unpacked::NaN, _) | (_, Unpacked::NaN) => self.nan(),
+            (Unpacked::Inf { sign: l }, Unpacked::Inf { sign: r }) => {
+                if l == r {
+                    self.inf(l)
+                } else {
+                    self.nan()
+                }
+            }
+            (Unpacked::Inf { sign }, _) | (_, Unpacked::Inf { sign }) => {
+                self.inf(sign)
+            }
+            (
+                Unpacked::Finite {
+                    sign: l_sign,
+                    sig: l_sig,
+                    exp: l_exp,
+                },
+                Unpacked::Finite {
+                    sign: r_sign,
+                    sig: r_sig,
+                    exp: r_exp,
+                },
+            ) => {
+                let exp = l_exp.min(r_exp);
+                let signed = |sign: bool, sig: BigUint, sig_exp: i64| {
+                    BigInt::from_biguint(
+                        if sign { Sign::Minus } else { Sign::Plus },
+                        sig << (sig_exp - exp) as u64,
+                    )
+                };
+                let sum =
+                    signed(l_sign, l_sig, l_exp) + signed(r_sign, r_sig, r_exp);
+                // An exact zero is only negative if both operands are
+                let sign = if sum.is_zero() {
+                    l_sign && r_sign
+                } else {
+                    sum.sign() == Sign::Minus
+                };
+                self.round(sign, sum.magnitude().clone(), exp)
+            }
+        }
+    }
+
+    fn negate(&self, bits: &BigUint) -> BigUint {
+        bits ^ (BigUint::one() << (self.exp_width + self.mant_width))
+    }
+
+    pub fn sub(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        self.add(left, &self.negate(right))
+    }
+
+    pub fn mult(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        match (self.unpack(left), self.unpack(right)) {
+            (Unpacked::NaN, _) | (_, Unpacked::NaN) => self.nan(),
+            (Unpacked::Inf { sign: l }, Unpacked::Inf { sign: r }) => {
+                self.inf(l ^ r)
+            }
+            (
+                Unpacked::Inf { sign: l },
+                Unpacked::Finite { sign: r, sig, .. },
+            )
+            | (
+                Unpacked::Finite { sign: l, sig, .. },
+                Unpacked::Inf { sign: r },
+            ) => {
+                if sig.is_zero() {
+                    self.nan()
+                } else {
+                    self.inf(l ^ r)
+                }
+            }
+            (
+                Unpacked::Finite {
+                    sign: l_sign,
+                    sig: l_sig,
+                    exp: l_exp,
+                },
+                Unpacked::Finite {
+                    sign: r_sign,
+                    sig: r_sig,
+                    exp: r_exp,
+                },
+            ) => self.round(l_sign ^ r_sign, l_sig * r_sig, l_exp + r_exp),
+        }
+    }
+
+    pub fn div(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        match (self.unpack(left), self.unpack(right)) {
+            (Unpacked::NaN, _) | (_, Unpacked::NaN) => self.nan(),
+            (Unpacked::Inf { .. }, Unpacked::Inf { .. }) => self.nan(),
+            (Unpacked::Inf { sign: l }, Unpacked::Finite { sign: r, .. }) => {
+                self.inf(l ^ r)
+            }
+            (Unpacked::Finite { sign: l, .. }, Unpacked::Inf { sign: r }) => {
+                self.zero(l ^ r)
+            }
+            (
+                Unpacked::Finite {
+                    sign: l_sign,
+                    sig: l_sig,
+                    exp: l_exp,
+                },
+                Unpacked::Finite {
+                    sign: r_sign,
+                    sig: r_sig,
+                    exp: r_exp,
+                },
+            ) => {
+                let sign = l_sign ^ r_sign;
+                match (l_sig.is_zero(), r_sig.is_zero()) {
+                    (true, true) => self.nan(),
+                    (false, true) => self.inf(sign),
+                    (true, false) => self.zero(sign),
+                    (false, false) => {
+                        // Scale the dividend so that the quotient has at least
+                        // two bits more than the mantissa. The remainder is
+                        // folded into a sticky bit below them.
+                        let scale = r_sig.bits() + self.mant_width as u64 + 3;
+                        let dividend = l_sig << scale;
+                        let quotient = &dividend / &r_sig;
+                        let sticky = !(dividend % &r_sig).is_zero();
+                        self.round(
+                            sign,
+                            (quotient << 1_u32) | BigUint::from(sticky as u8),
+                            l_exp - r_exp - scale as i64 - 1,
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn sqrt(&self, input: &BigUint) -> BigUint {
+        match self.unpack(input) {
+            Unpacked::NaN => self.nan(),
+            Unpacked::Inf { sign } => {
+                if sign {
+                    self.nan()
+                } else {
+                    self.inf(false)
+                }
+            }
+            Unpacked::Finite { sign, sig, .. } if sig.is_zero() => {
+                self.zero(sign)
+            }
+            Unpacked::Finite { sign: true, .. } => self.nan(),
+            Unpacked::Finite {
+                sign: false,
+                mut sig,
+                mut exp,
+            } => {
+                if exp % 2 != 0 {
+                    sig <<= 1;
+                    exp -= 1;
+                }
+                // As in division, compute at least two more bits than the
+                // mantissa and fold the remainder into a sticky bit.
+                let scale = self.mant_width as u64 + 3;
+                let radicand = sig << (2 * scale);
+                let root = radicand.sqrt();
+                let sticky = &root * &root != radicand;
+                self.round(
+                    false,
+                    (root << 1_u32) | BigUint::from(sticky as u8),
+                    exp / 2 - scale as i64 - 1,
+                )
+            }
+        }
+    }
+
+    /// Compares the two values. Returns `None` if either of them is NaN.
+    pub fn compare(&self, left: &BigUint, right: &BigUint) -> Option<Ordering> {
+        let value = |unpacked| match unpacked {
+            Unpacked::NaN => None,
+            Unpacked::Inf { sign } => Some((sign, None)),
+            Unpacked::Finite { sign, sig, exp } => {
+                Some((sign, Some((sig, exp))))
+            }
+        };
+        let (l_sign, l_val) = value(self.unpack(left))?;
+        let (r_sign, r_val) = value(self.unpack(right))?;
+
+        // Map infinities to 2^(emax + 1), which is larger than any finite value
+        let as_int = |sign: bool, val: Option<(BigUint, i64)>, exp: i64| {
+            let magnitude = match val {
+                Some((sig, sig_exp)) => sig << (sig_exp - exp) as u64,
+                None => BigUint::one() << (self.bias() + 1 - exp) as u64,
+            };
+            BigInt::from_biguint(
+                if sign { Sign::Minus } else { Sign::Plus },
+                magnitude,
+            )
+        };
+        let exp = self.min_exp() - self.mant_width as i64;
+        Some(as_int(l_sign, l_val, exp).cmp(&as_int(r_sign, r_val, exp)))
+    }
+
+    /// Converts the value to an integer of the given width, rounding toward
+    /// zero. Out of range values saturate and NaN is converted to zero.
+    pub fn to_int(&self, input: &BigUint, width: u32, signed: bool) -> BigUint {
+        let (min, max): (BigInt, BigInt) = if signed {
+            (
+                -(BigInt::one() << (width - 1)),
+                (BigInt::one() << (width - 1)) - 1,
+            )
+        } else {
+            (BigInt::zero(), (BigInt::one() << width) - 1)
+        };
+
+        let value = match self.unpack(input) {
+            Unpacked::NaN => BigInt::zero(),
+            Unpacked::Inf { sign } => {
+                if sign {
+                    min.clone()
+                } else {
+                    max.clone()
+                }
+            }
+            Unpacked::Finite { sign, sig, exp } => {
+                let magnitude = if exp >= 0 {
+                    sig << exp as u64
+                } else {
+                    sig >> (-exp) as u64
+                };
+                BigInt::from_biguint(
+                    if sign { Sign::Minus } else { Sign::Plus },
+                    magnitude,
+                )
+            }
+        };
+        let value = value.clamp(min, max);
+
+        // two's complement encoding
+        let modulus = BigInt::one() << width;
+        ((value % &modulus + &modulus) % modulus)
+            .magnitude()
+            .clone()
+    }
+
+    /// Converts the integer of the given width to the nearest floating point
+    /// value, ties to even.
+    pub fn from_int(
+        &self,
+        input: &BigUint,
+        width: u32,
+        signed: bool,
+    ) -> BigUint {
+        if signed && input.bit(width as u64 - 1) {
+            let magnitude = (BigUint::one() << width) - input;
+            self.round(true, magnitude, 0)
+        } else {
+            self.round(false, input.clone(), 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SINGLE: FloatFormat = FloatFormat {
+        exp_width: 8,
+        mant_width: 23,
+    };
+    const DOUBLE: FloatFormat = FloatFormat {
+        exp_width: 11,
+        mant_width: 52,
+    };
+
+    fn f32_bits(f: f32) -> BigUint {
+        BigUint::from(f.to_bits())
+    }
+
+    fn f64_bits(f: f64) -> BigUint {
+        BigUint::from(f.to_bits())
+    }
+
+    /// Compares against the native result. All NaNs are treated as equal
+    /// since the native operations propagate payloads.
+    fn check_f32(result: BigUint, expected: f32) {
+        if expected.is_nan() {
+            assert_eq!(result, SINGLE.nan());
+        } else {
+            assert_eq!(result, f32_bits(expected), "expected {expected}");
+        }
+    }
+
+    fn check_f64(result: BigUint, expected: f64) {
+        if expected.is_nan() {
+            assert_eq!(result, DOUBLE.nan());
+        } else {
+            assert_eq!(result, f64_bits(expected), "expected {expected}");
+        }
+    }
+
+    /// Bit patterns with a bias towards special values and subnormals
+    fn arb_f32() -> impl Strategy<Value = f32> {
+        prop_oneof![
+            any::<u32>().prop_map(f32::from_bits),
+            (any::<bool>(), 0_u32..8).prop_map(|(sign, mant)| {
+                f32::from_bits(((sign as u32) << 31) | mant)
+            }),
+            Just(f32::INFINITY),
+            Just(f32::NEG_INFINITY),
+            Just(f32::MAX),
+            Just(f32::MIN_POSITIVE),
+            Just(0.0),
+            Just(-0.0),
+            Just(1.0),
+        ]
+    }
+
+    fn arb_f64() -> impl Strategy<Value = f64> {
+        prop_oneof![
+            any::<u64>().prop_map(f64::from_bits),
+            (any::<bool>(), 0_u64..8).prop_map(|(sign, mant)| {
+                f64::from_bits(((sign as u64) << 63) | mant)
+            }),
+            Just(f64::INFINITY),
+            Just(f64::NEG_INFINITY),
+            Just(f64::MAX),
+            Just(0.0),
+            Just(-0.0),
+        ]
+    }
+
+    #[test]
+    fn test_special_values() {
+        let half = FloatFormat::new(5, 10).unwrap();
+        assert_eq!(half.width(), 16);
+        assert_eq!(half.nan(), BigUint::from(0x7e00_u32));
+        // 1.0 + 1.0 = 2.0
+        assert_eq!(
+            half.add(&BigUint::from(0x3c00_u32), &BigUint::from(0x3c00_u32)),
+            BigUint::from(0x4000_u32)
+        );
+        // 65504 * 2 overflows to infinity
+        assert_eq!(
+            half.mult(&BigUint::from(0x7bff_u32), &BigUint::from(0x4000_u32)),
+            BigUint::from(0x7c00_u32)
+        );
+        // the smallest subnormal halved rounds to zero (ties to even)
+        assert_eq!(
+            half.mult(&BigUint::from(0x0001_u32), &BigUint::from(0x3800_u32)),
+            BigUint::from(0x0000_u32)
+        );
+        check_f32(SINGLE.sqrt(&f32_bits(-0.0)), -0.0);
+    }
+
+    proptest! {
+        #[test]
+        fn single_arithmetic(l in arb_f32(), r in arb_f32()) {
+            check_f32(SINGLE.add(&f32_bits(l), &f32_bits(r)), l + r);
+            check_f32(SINGLE.sub(&f32_bits(l), &f32_bits(r)), l - r);
+            check_f32(SINGLE.mult(&f32_bits(l), &f32_bits(r)), l * r);
+            check_f32(SINGLE.div(&f32_bits(l), &f32_bits(r)), l / r);
+            check_f32(SINGLE.sqrt(&f32_bits(l)), l.sqrt());
+            prop_assert_eq!(
+                SINGLE.compare(&f32_bits(l), &f32_bits(r)),
+                l.partial_cmp(&r)
+            );
+        }
+
+        #[test]
+        fn double_arithmetic(l in arb_f64(), r in arb_f64()) {
+            check_f64(DOUBLE.add(&f64_bits(l), &f64_bits(r)), l + r);
+            check_f64(DOUBLE.sub(&f64_bits(l), &f64_bits(r)), l - r);
+            check_f64(DOUBLE.mult(&f64_bits(l), &f64_bits(r)), l * r);
+            check_f64(DOUBLE.div(&f64_bits(l), &f64_bits(r)), l / r);
+            check_f64(DOUBLE.sqrt(&f64_bits(l)), l.sqrt());
+            prop_assert_eq!(
+                DOUBLE.compare(&f64_bits(l), &f64_bits(r)),
+                l.partial_cmp(&r)
+            );
+        }
+
+        #[test]
+        fn conversions(
+            f in arb_f32(),
+            d in arb_f64(),
+            i in any::<i32>(),
+            u in any::<u64>(),
+        ) {
+            prop_assert_eq!(
+                SINGLE.to_int(&f32_bits(f), 32, true),
+                BigUint::from(f as i32 as u32)
+            );
+            prop_assert_eq!(
+                SINGLE.to_int(&f32_bits(f), 16, false),
+                BigUint::from(f as u16)
+            );
+            prop_assert_eq!(
+                DOUBLE.to_int(&f64_bits(d), 64, true),
+                BigUint::from(d as i64 as u64)
+            );
+            check_f32(
+                SINGLE.from_int(&BigUint::from(i as u32), 32, true),
+                i as f32,
+            );
+            check_f32(SINGLE.from_int(&BigUint::from(u), 64, false), u as f32);
+            check_f64(
+                DOUBLE.from_int(&BigUint::from(u), 64, true),
+                u as i64 as f64,
+            );
+        }
+    }
+}
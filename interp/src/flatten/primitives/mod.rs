@@ -1,6 +1,7 @@
 pub mod btor2_prim;
 mod builder;
 pub mod combinational;
+pub mod float;
 pub(crate) mod macros;
 pub mod prim_trait;
 pub mod stateful;
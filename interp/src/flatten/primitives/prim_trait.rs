@@ -1,10 +1,14 @@
 use crate::{
-    errors::InterpreterResult,
-    flatten::{flat_ir::base::GlobalPortIdx, structures::environment::PortMap},
+    errors::{InterpreterError, InterpreterResult},
+    flatten::{
+        flat_ir::base::{GlobalPortIdx, PortValue},
+        structures::environment::PortMap,
+    },
     serialization::PrintCode,
     serialization::Serializable,
     values::Value,
 };
+use serde::{Deserialize, Serialize};
 
 pub struct AssignResult {
     pub destination: GlobalPortIdx,
@@ -98,6 +102,143 @@ impl std::ops::BitOrAssign for UpdateStatus {
 
 pub type UpdateResult = InterpreterResult<UpdateStatus>;
 
+/// The role of an input port that a primitive latches on the next clock edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LatchedInput {
+    /// A value which is stored, such as the input of a register
+    Data,
+    /// A memory address
+    Address,
+}
+
+/// Whether a memory access reads or writes the memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// An access of a memory in the current cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryAccess {
+    pub kind: AccessKind,
+    /// The address in every dimension of the memory
+    pub address: Vec<usize>,
+    /// The input ports which make up the access: the enable signals, then the
+    /// address ports and, for writes, the data
+    pub ports: Vec<GlobalPortIdx>,
+}
+
+/// A value stored in a [`PrimitiveState`] along with its width
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredValue {
+    width: u32,
+    bytes: Vec<u8>,
+}
+
+impl From<&Value> for StoredValue {
+    fn from(value: &Value) -> Self {
+        Self {
+            width: value.width() as u32,
+            bytes: value.to_bytes(),
+        }
+    }
+}
+
+impl From<&StoredValue> for Value {
+    fn from(value: &StoredValue) -> Self {
+        Value::from_bytes_le(&value.bytes, value.width as usize)
+    }
+}
+
+/// The internal state of a primitive which is saved in a snapshot of the
+/// simulation, see [`Primitive::save_state`]. The state is a sequence of flags
+/// and a sequence of possibly undefined values which are read back in the
+/// order they were pushed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrimitiveState {
+    flags: Vec<bool>,
+    values: Vec<Option<StoredValue>>,
+}
+
+impl PrimitiveState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty() && self.values.is_empty()
+    }
+
+    pub fn push_flag(&mut self, flag: bool) {
+        self.flags.push(flag)
+    }
+
+    pub fn push_value(&mut self, value: &PortValue) {
+        self.values.push(value.val().map(StoredValue::from))
+    }
+
+    /// Returns a reader which yields the flags and values in the order they
+    /// were pushed
+    pub fn reader(&self) -> StateReader<'_> {
+        StateReader {
+            state: self,
+            next_flag: 0,
+            next_value: 0,
+        }
+    }
+}
+
+/// Reads the contents of a [`PrimitiveState`] back in order
+pub struct StateReader<'a> {
+    state: &'a PrimitiveState,
+    next_flag: usize,
+    next_value: usize,
+}
+
+impl StateReader<'_> {
+    fn malformed() -> InterpreterError {
+        InterpreterError::InvalidSnapshot(
+            "the saved state of a primitive does not match its type".into(),
+        )
+    }
+
+    pub fn flag(&mut self) -> InterpreterResult<bool> {
+        let flag = *self
+            .state
+            .flags
+            .get(self.next_flag)
+            .ok_or_else(Self::malformed)?;
+        self.next_flag += 1;
+        Ok(flag)
+    }
+
+    /// Reads the next value, which is restored as the output of a cell
+    pub fn value(&mut self) -> InterpreterResult<PortValue> {
+        let value = self
+            .state
+            .values
+            .get(self.next_value)
+            .ok_or_else(Self::malformed)?;
+        self.next_value += 1;
+        Ok(value
+            .as_ref()
+            .map_or(PortValue::new_undef(), |v| PortValue::new_cell(v.into())))
+    }
+
+    /// Checks that the whole state has been read
+    pub fn finish(self) -> InterpreterResult<()> {
+        if self.next_flag == self.state.flags.len()
+            && self.next_value == self.state.values.len()
+        {
+            Ok(())
+        } else {
+            Err(Self::malformed().into())
+        }
+    }
+}
+
 pub trait Primitive {
     fn exec_comb(&self, _port_map: &mut PortMap) -> UpdateResult {
         Ok(UpdateStatus::Unchanged)
@@ -107,6 +248,49 @@ pub trait Primitive {
         Ok(UpdateStatus::Unchanged)
     }
 
+    /// Like [`Primitive::exec_comb`], but only leaves an output undefined if
+    /// its value actually depends on an undefined input, e.g. the output of an
+    /// `and` with one input of zero is zero. Used by the precise X-propagation
+    /// of `--x-checks`. By default, every undefined input makes the outputs
+    /// undefined.
+    fn exec_comb_precise(&self, port_map: &mut PortMap) -> UpdateResult {
+        self.exec_comb(port_map)
+    }
+
+    /// The inputs that the primitive latches on the next clock edge given the
+    /// current values of its enable signals, e.g. the input of a register
+    /// whose `write_en` is high. An undefined value on one of these ports is
+    /// reported by `--x-checks`.
+    fn latched_inputs(
+        &self,
+        _port_map: &PortMap,
+    ) -> Vec<(GlobalPortIdx, LatchedInput)> {
+        vec![]
+    }
+
+    /// The access of the memory in the current cycle given the values of its
+    /// enable signals and address ports, if the primitive is a memory which
+    /// is being accessed. Used by the memory trace of Cider.
+    fn memory_access(&self, _port_map: &PortMap) -> Option<MemoryAccess> {
+        None
+    }
+
+    /// Saves the internal state of the primitive which is neither visible on
+    /// its ports nor part of its [`Primitive::memory_contents`], such as the
+    /// stages of a pipeline or whether its done signal is high. Used to save
+    /// snapshots of a simulation.
+    fn save_state(&self) -> PrimitiveState {
+        PrimitiveState::new()
+    }
+
+    /// Restores the state saved by [`Primitive::save_state`]
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        state.reader().finish()
+    }
+
     fn has_comb(&self) -> bool {
         true
     }
@@ -128,6 +312,18 @@ pub trait Primitive {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         None
     }
+
+    /// The contents of this primitive in row-major order, if it is a memory or
+    /// a register.
+    fn memory_contents(&self) -> Option<&[Value]> {
+        None
+    }
+
+    /// Mutable access to the contents returned by
+    /// [`Primitive::memory_contents`].
+    fn memory_contents_mut(&mut self) -> Option<&mut [Value]> {
+        None
+    }
 }
 
 /// An empty primitive implementation used for testing. It does not do anything
@@ -1,8 +1,11 @@
 use crate::{
+    errors::InterpreterResult,
     flatten::{
-        flat_ir::prelude::*,
+        flat_ir::{cell_prototype::FloatType, prelude::*},
         primitives::{
-            declare_ports, ports,
+            declare_ports,
+            float::FloatFormat,
+            ports,
             prim_trait::*,
             utils::{floored_division, int_sqrt, ShiftBuffer},
         },
@@ -108,6 +111,25 @@ impl<const DEPTH: usize> Primitive for StdMultPipe<DEPTH> {
                 | done_signal,
         )
     }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.current_output);
+        self.pipeline.save(&mut state);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.current_output = reader.value()?;
+        self.pipeline.restore(&mut reader)?;
+        reader.finish()
+    }
 }
 
 pub struct StdDivPipe<const DEPTH: usize, const SIGNED: bool> {
@@ -234,6 +256,27 @@ impl<const DEPTH: usize, const SIGNED: bool> Primitive
 
         Ok(quot_changed | rem_changed | done_signal)
     }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.output_quotient);
+        state.push_value(&self.output_remainder);
+        self.pipeline.save(&mut state);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.output_quotient = reader.value()?;
+        self.output_remainder = reader.value()?;
+        self.pipeline.restore(&mut reader)?;
+        reader.finish()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -313,6 +356,23 @@ impl<const IS_FIXED_POINT: bool> Primitive for Sqrt<IS_FIXED_POINT> {
 
         Ok(out_changed | done_signal)
     }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.output);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.output = reader.value()?;
+        reader.finish()
+    }
 }
 
 pub struct FxpMultPipe<const DEPTH: usize> {
@@ -425,6 +485,25 @@ impl<const DEPTH: usize> Primitive for FxpMultPipe<DEPTH> {
                 | done_signal,
         )
     }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.current_output);
+        self.pipeline.save(&mut state);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.current_output = reader.value()?;
+        self.pipeline.restore(&mut reader)?;
+        reader.finish()
+    }
 }
 
 pub struct FxpDivPipe<const DEPTH: usize, const SIGNED: bool> {
@@ -565,4 +644,217 @@ impl<const DEPTH: usize, const SIGNED: bool> Primitive
 
         Ok(quot_changed | rem_changed | done_signal)
     }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.output_quotient);
+        state.push_value(&self.output_remainder);
+        self.pipeline.save(&mut state);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.output_quotient = reader.value()?;
+        self.output_remainder = reader.value()?;
+        self.pipeline.restore(&mut reader)?;
+        reader.finish()
+    }
+}
+
+/// The sequential IEEE-754 arithmetic primitives (`std_float_add`,
+/// `std_float_sub`, `std_float_mult` and `std_float_div`). The result is
+/// available one cycle after `go` is asserted.
+pub struct FloatBinaryPipe {
+    base_port: GlobalPortIdx,
+    op: FloatType,
+    format: FloatFormat,
+    output: PortValue,
+    done_is_high: bool,
+}
+
+impl FloatBinaryPipe {
+    declare_ports![_CLK: 0, RESET: 1, GO: 2, LEFT: 3, RIGHT: 4, OUT: 5, DONE: 6];
+    pub fn new(
+        base_port: GlobalPortIdx,
+        op: FloatType,
+        format: FloatFormat,
+    ) -> Self {
+        Self {
+            base_port,
+            op,
+            format,
+            output: PortValue::new_cell(Value::zeroes(format.width())),
+            done_is_high: false,
+        }
+    }
+}
+
+impl Primitive for FloatBinaryPipe {
+    fn exec_comb(&self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base_port; out: Self::OUT, done: Self::DONE];
+
+        let done_changed = port_map.set_done(done, self.done_is_high)?;
+        let out_changed =
+            port_map.write_exact_unchecked(out, self.output.clone());
+
+        Ok(out_changed | done_changed)
+    }
+
+    fn exec_cycle(&mut self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base_port;
+            reset: Self::RESET,
+            go: Self::GO,
+            left: Self::LEFT,
+            right: Self::RIGHT,
+            out: Self::OUT,
+            done: Self::DONE
+        ];
+
+        if port_map[reset].as_bool().unwrap_or_default() {
+            self.done_is_high = false;
+            self.output =
+                PortValue::new_cell(Value::zeroes(self.format.width()));
+        } else if port_map[go].as_bool().unwrap_or_default() {
+            self.output =
+                match (port_map[left].as_option(), port_map[right].as_option())
+                {
+                    (Some(left), Some(right)) => {
+                        let (left, right) = (
+                            left.val().as_unsigned(),
+                            right.val().as_unsigned(),
+                        );
+                        let result = match self.op {
+                            FloatType::Add => self.format.add(&left, &right),
+                            FloatType::Sub => self.format.sub(&left, &right),
+                            FloatType::Mult => self.format.mult(&left, &right),
+                            FloatType::Div => self.format.div(&left, &right),
+                            _ => unreachable!(
+                                "{:?} is not a sequential binary operator",
+                                self.op
+                            ),
+                        };
+                        PortValue::new_cell(Value::from(
+                            result,
+                            self.format.width(),
+                        ))
+                    }
+                    _ => PortValue::new_undef(),
+                };
+            self.done_is_high = true;
+        } else {
+            self.done_is_high = false;
+        }
+
+        let done_signal = port_map.set_done(done, self.done_is_high)?;
+        let out_changed =
+            port_map.write_exact_unchecked(out, self.output.clone());
+
+        Ok(out_changed | done_signal)
+    }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.output);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.output = reader.value()?;
+        reader.finish()
+    }
+}
+
+/// The IEEE-754 square root primitive (`std_float_sqrt`). The result is
+/// available one cycle after `go` is asserted.
+pub struct FloatSqrt {
+    base_port: GlobalPortIdx,
+    format: FloatFormat,
+    output: PortValue,
+    done_is_high: bool,
+}
+
+impl FloatSqrt {
+    declare_ports!(_CLK: 0, RESET: 1, GO: 2, IN: 3, OUT: 4, DONE: 5);
+    pub fn new(base_port: GlobalPortIdx, format: FloatFormat) -> Self {
+        Self {
+            base_port,
+            format,
+            output: PortValue::new_cell(Value::zeroes(format.width())),
+            done_is_high: false,
+        }
+    }
+}
+
+impl Primitive for FloatSqrt {
+    fn exec_comb(&self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base_port; out: Self::OUT, done: Self::DONE];
+
+        let done_changed = port_map.set_done(done, self.done_is_high)?;
+        let out_changed =
+            port_map.write_exact_unchecked(out, self.output.clone());
+
+        Ok(out_changed | done_changed)
+    }
+
+    fn exec_cycle(&mut self, port_map: &mut PortMap) -> UpdateResult {
+        ports![&self.base_port;
+            reset: Self::RESET,
+            go: Self::GO,
+            in_val: Self::IN,
+            out: Self::OUT,
+            done: Self::DONE
+        ];
+
+        if port_map[reset].as_bool().unwrap_or_default() {
+            self.done_is_high = false;
+            self.output =
+                PortValue::new_cell(Value::zeroes(self.format.width()));
+        } else if port_map[go].as_bool().unwrap_or_default() {
+            self.output = match port_map[in_val].as_option() {
+                Some(input) => PortValue::new_cell(Value::from(
+                    self.format.sqrt(&input.val().as_unsigned()),
+                    self.format.width(),
+                )),
+                None => PortValue::new_undef(),
+            };
+            self.done_is_high = true;
+        } else {
+            self.done_is_high = false;
+        }
+
+        let done_signal = port_map.set_done(done, self.done_is_high)?;
+        let out_changed =
+            port_map.write_exact_unchecked(out, self.output.clone());
+
+        Ok(out_changed | done_signal)
+    }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.output);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.output = reader.value()?;
+        reader.finish()
+    }
 }
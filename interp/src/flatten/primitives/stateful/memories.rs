@@ -1,12 +1,15 @@
 use itertools::Itertools;
 
 use crate::{
-    errors::InterpreterError,
+    errors::{InterpreterError, InterpreterResult},
     flatten::{
         flat_ir::prelude::{AssignedValue, GlobalPortIdx, PortValue},
         primitives::{
             declare_ports, make_getters, ports,
-            prim_trait::{UpdateResult, UpdateStatus},
+            prim_trait::{
+                AccessKind, LatchedInput, MemoryAccess, PrimitiveState,
+                UpdateResult, UpdateStatus,
+            },
             Primitive,
         },
         structures::{environment::PortMap, index_trait::IndexRef},
@@ -97,6 +100,39 @@ impl Primitive for StdReg {
         Ok(out_signal | done_signal)
     }
 
+    fn latched_inputs(
+        &self,
+        port_map: &PortMap,
+    ) -> Vec<(GlobalPortIdx, LatchedInput)> {
+        ports![&self.base_port;
+            input: Self::IN,
+            write_en: Self::WRITE_EN,
+            reset: Self::RESET
+        ];
+        if !port_map[reset].as_bool().unwrap_or_default()
+            && port_map[write_en].as_bool().unwrap_or_default()
+        {
+            vec![(input, LatchedInput::Data)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        reader.finish()
+    }
+
     fn serialize(&self, code: Option<PrintCode>) -> Serializable {
         Serializable::Val(Entry::from_val_code(
             &self.internal_state,
@@ -111,6 +147,14 @@ impl Primitive for StdReg {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         Some(self.internal_state.clone().to_bytes())
     }
+
+    fn memory_contents(&self) -> Option<&[Value]> {
+        Some(std::slice::from_ref(&self.internal_state))
+    }
+
+    fn memory_contents_mut(&mut self) -> Option<&mut [Value]> {
+        Some(std::slice::from_mut(&mut self.internal_state))
+    }
 }
 
 pub struct MemDx<const SEQ: bool> {
@@ -189,6 +233,37 @@ impl<const SEQ: bool> MemDx<SEQ> {
         }
     }
 
+    /// The address ports of the memory, one for every dimension
+    pub fn addr_ports(&self, base_port: GlobalPortIdx) -> Vec<GlobalPortIdx> {
+        let first = if SEQ {
+            Self::SEQ_ADDR0
+        } else {
+            Self::COMB_ADDR0
+        };
+        let dimensions = match self.shape {
+            Shape::D1(_) => 1,
+            Shape::D2(_, _) => 2,
+            Shape::D3(_, _, _) => 3,
+            Shape::D4(_, _, _, _) => 4,
+        };
+        (first..first + dimensions)
+            .map(|offset| (base_port.index() + offset).into())
+            .collect()
+    }
+
+    /// The address in every dimension, or `None` if any part of it is
+    /// undefined
+    pub fn address(
+        &self,
+        port_map: &PortMap,
+        base_port: GlobalPortIdx,
+    ) -> Option<Vec<usize>> {
+        self.addr_ports(base_port)
+            .into_iter()
+            .map(|port| port_map[port].as_usize())
+            .collect()
+    }
+
     pub fn non_address_base(&self) -> usize {
         if SEQ {
             match self.shape {
@@ -370,6 +445,68 @@ impl Primitive for CombMem {
         }
     }
 
+    fn latched_inputs(
+        &self,
+        port_map: &PortMap,
+    ) -> Vec<(GlobalPortIdx, LatchedInput)> {
+        let reset = port_map[self.reset_port()].as_bool().unwrap_or_default();
+        let write_en = port_map[self.write_en()].as_bool().unwrap_or_default();
+        if reset || !write_en {
+            return vec![];
+        }
+
+        self.addresser
+            .addr_ports(self.base_port)
+            .into_iter()
+            .map(|port| (port, LatchedInput::Address))
+            .chain([(self.write_data(), LatchedInput::Data)])
+            .collect()
+    }
+
+    fn memory_access(&self, port_map: &PortMap) -> Option<MemoryAccess> {
+        let reset = port_map[self.reset_port()].as_bool().unwrap_or_default();
+        if reset {
+            return None;
+        }
+        let address = self.addresser.address(port_map, self.base_port)?;
+        let addr_ports = self.addresser.addr_ports(self.base_port);
+
+        // the memory is read combinationally whenever its address is defined
+        if port_map[self.write_en()].as_bool().unwrap_or_default() {
+            let ports = [self.write_en()]
+                .into_iter()
+                .chain(addr_ports)
+                .chain([self.write_data()])
+                .collect();
+            Some(MemoryAccess {
+                kind: AccessKind::Write,
+                address,
+                ports,
+            })
+        } else {
+            Some(MemoryAccess {
+                kind: AccessKind::Read,
+                address,
+                ports: addr_ports,
+            })
+        }
+    }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        reader.finish()
+    }
+
     fn serialize(&self, code: Option<PrintCode>) -> Serializable {
         let code = code.unwrap_or_default();
 
@@ -389,6 +526,14 @@ impl Primitive for CombMem {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         Some(self.dump_data())
     }
+
+    fn memory_contents(&self) -> Option<&[Value]> {
+        Some(&self.internal_state)
+    }
+
+    fn memory_contents_mut(&mut self) -> Option<&mut [Value]> {
+        Some(&mut self.internal_state)
+    }
 }
 
 pub struct SeqMem {
@@ -575,6 +720,80 @@ impl Primitive for SeqMem {
         true
     }
 
+    fn latched_inputs(
+        &self,
+        port_map: &PortMap,
+    ) -> Vec<(GlobalPortIdx, LatchedInput)> {
+        let reset = port_map[self.reset()].as_bool().unwrap_or_default();
+        let write_en =
+            port_map[self.write_enable()].as_bool().unwrap_or_default();
+        let content_en = port_map[self.content_enable()]
+            .as_bool()
+            .unwrap_or_default();
+        if reset || !content_en {
+            return vec![];
+        }
+
+        let mut inputs = self
+            .addresser
+            .addr_ports(self.base_port)
+            .into_iter()
+            .map(|port| (port, LatchedInput::Address))
+            .collect::<Vec<_>>();
+        if write_en {
+            inputs.push((self.write_data(), LatchedInput::Data));
+        }
+        inputs
+    }
+
+    fn memory_access(&self, port_map: &PortMap) -> Option<MemoryAccess> {
+        let reset = port_map[self.reset()].as_bool().unwrap_or_default();
+        let content_en = port_map[self.content_enable()]
+            .as_bool()
+            .unwrap_or_default();
+        if reset || !content_en {
+            return None;
+        }
+        let address = self.addresser.address(port_map, self.base_port)?;
+        let write_en =
+            port_map[self.write_enable()].as_bool().unwrap_or_default();
+
+        let mut ports = vec![self.content_enable()];
+        if write_en {
+            ports.push(self.write_enable());
+        }
+        ports.extend(self.addresser.addr_ports(self.base_port));
+        if write_en {
+            ports.push(self.write_data());
+        }
+        Some(MemoryAccess {
+            kind: if write_en {
+                AccessKind::Write
+            } else {
+                AccessKind::Read
+            },
+            address,
+            ports,
+        })
+    }
+
+    fn save_state(&self) -> PrimitiveState {
+        let mut state = PrimitiveState::new();
+        state.push_flag(self.done_is_high);
+        state.push_value(&self.read_out);
+        state
+    }
+
+    fn restore_state(
+        &mut self,
+        state: &PrimitiveState,
+    ) -> InterpreterResult<()> {
+        let mut reader = state.reader();
+        self.done_is_high = reader.flag()?;
+        self.read_out = reader.value()?;
+        reader.finish()
+    }
+
     fn serialize(&self, code: Option<PrintCode>) -> Serializable {
         let code = code.unwrap_or_default();
 
@@ -594,6 +813,14 @@ impl Primitive for SeqMem {
     fn dump_memory_state(&self) -> Option<Vec<u8>> {
         Some(self.dump_data())
     }
+
+    fn memory_contents(&self) -> Option<&[Value]> {
+        Some(&self.internal_state)
+    }
+
+    fn memory_contents_mut(&mut self) -> Option<&mut [Value]> {
+        Some(&mut self.internal_state)
+    }
 }
 // type aliases, this is kinda stupid and should probably be changed. or maybe
 // it's fine, I really don't know.
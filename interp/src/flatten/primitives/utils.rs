@@ -1,3 +1,5 @@
+use super::prim_trait::{PrimitiveState, StateReader};
+use crate::{errors::InterpreterResult, flatten::flat_ir::base::PortValue};
 use num_bigint::{BigInt, BigUint, Sign};
 use std::collections::VecDeque;
 
@@ -70,6 +72,34 @@ impl<T, const N: usize> ShiftBuffer<T, N> {
     }
 }
 
+impl<const N: usize> ShiftBuffer<(PortValue, PortValue), N> {
+    /// Saves the occupied slots of a pipeline of operand pairs
+    pub fn save(&self, state: &mut PrimitiveState) {
+        for slot in self.buffer.iter() {
+            state.push_flag(slot.is_some());
+            if let Some((left, right)) = slot {
+                state.push_value(left);
+                state.push_value(right);
+            }
+        }
+    }
+
+    /// Restores the slots saved by [`ShiftBuffer::save`]
+    pub fn restore(
+        &mut self,
+        reader: &mut StateReader,
+    ) -> InterpreterResult<()> {
+        for slot in self.buffer.iter_mut() {
+            *slot = if reader.flag()? {
+                Some((reader.value()?, reader.value()?))
+            } else {
+                None
+            };
+        }
+        Ok(())
+    }
+}
+
 /// An internal macro which is used to extract parameter values from an
 /// association list input. Structured as a declaration list
 macro_rules! get_params {
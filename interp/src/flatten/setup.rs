@@ -46,7 +46,7 @@ fn do_setup(
     };
 
     // general setup
-    Ok((crate::flatten::flat_ir::translate(&ctx), mapping))
+    Ok((crate::flatten::flat_ir::translate(&ctx)?, mapping))
 }
 
 /// This function sets up the simulation context for the given program. This is
@@ -1,4 +1,6 @@
-use crate::flatten::flat_ir::prelude::{GlobalCellIdx, LocalPortOffset};
+use crate::flatten::flat_ir::prelude::{
+    GlobalCellIdx, GroupIdx, LocalPortOffset,
+};
 
 use super::env::AssignmentRange;
 
@@ -14,6 +16,8 @@ pub struct ScheduledAssignments {
     pub active_cell: GlobalCellIdx,
     pub assignments: AssignmentRange,
     pub interface_ports: Option<GroupInterfacePorts>,
+    /// The group the assignments belong to, if they come from a group enable
+    pub group: Option<GroupIdx>,
 }
 
 impl ScheduledAssignments {
@@ -21,11 +25,13 @@ impl ScheduledAssignments {
         active_cell: GlobalCellIdx,
         assignments: AssignmentRange,
         interface_ports: Option<GroupInterfacePorts>,
+        group: Option<GroupIdx>,
     ) -> Self {
         Self {
             active_cell,
             assignments,
             interface_ports,
+            group,
         }
     }
 }
@@ -0,0 +1,1048 @@
+//! Compiled simulation of fully lowered programs.
+//!
+//! Instead of interpreting the flat IR, the program is translated into a Rust
+//! program which is specialized to its assignments and primitives. The
+//! generated program is compiled with `rustc` (or the compiler named by the
+//! `RUSTC` environment variable) and run as a child process. The contents of
+//! every register and memory are sent to it on stdin and read back from its
+//! stdout once the entrypoint component is done, so the memories dumped
+//! afterwards match the ones produced by the interpreter.
+//!
+//! The generated code mirrors the interpreter step for step: every cycle the
+//! ports are undefined, the continuous assignments and combinational
+//! primitives are evaluated until a fixed point is reached and then every
+//! stateful primitive is clocked. Only programs without control, i.e. those
+//! lowered with `-p all`, can be compiled. Ports may be at most 64 bits wide
+//! and the square root, fixed-point and floating-point primitives are not
+//! supported.
+
+use super::env::{CellLedger, ComponentLedger, Environment};
+use crate::{
+    errors::{BoxedInterpreterError, InterpreterError, InterpreterResult},
+    flatten::{
+        flat_ir::{
+            cell_prototype::{
+                CellPrototype, DoubleWidthType, MemType, MemoryDimensions,
+                SingleWidthType, TripleWidthType,
+            },
+            prelude::{GlobalCellIdx, GlobalPortIdx, GuardIdx, PortRef},
+            wires::guards::Guard,
+        },
+        structures::{context::Context, index_trait::IndexRef},
+    },
+    values::Value,
+};
+use calyx_ir::PortComp;
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    path::Path,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The support code shared by every generated program.
+const PRELUDE: &str = r#"#![allow(unused)]
+use std::io::{Read, Write};
+
+const CELL: u32 = 0;
+const IMPLICIT: u32 = 1;
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{msg}");
+    std::process::exit(1)
+}
+
+#[inline(always)]
+fn sext(x: u64, w: u32) -> i64 {
+    if w >= 64 { x as i64 } else { ((x << (64 - w)) as i64) >> (64 - w) }
+}
+
+#[inline(always)]
+fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    Some(a? && b?)
+}
+
+#[inline(always)]
+fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    Some(a? || b?)
+}
+
+#[inline(always)]
+fn div(a: u64, b: u64, name: &str) -> u64 {
+    if b == 0 { fail(&format!("division by zero in \"{name}\"")) }
+    a / b
+}
+
+#[inline(always)]
+fn urem(a: u64, b: u64, name: &str) -> u64 {
+    if b == 0 { fail(&format!("division by zero in \"{name}\"")) }
+    a % b
+}
+
+#[inline(always)]
+fn sdiv(a: i64, b: i64, name: &str) -> i64 {
+    if b == 0 { fail(&format!("division by zero in \"{name}\"")) }
+    a.wrapping_div(b)
+}
+
+#[inline(always)]
+fn smod(a: i64, b: i64, name: &str) -> i64 {
+    if b == 0 { fail(&format!("division by zero in \"{name}\"")) }
+    let r = a.wrapping_rem(b);
+    if r != 0 && (r < 0) != (b < 0) { r + b } else { r }
+}
+
+/// The value, definedness and winning writer of every port. Assignments win
+/// with their index offset by two.
+struct Ports {
+    v: Vec<u64>,
+    d: Vec<bool>,
+    w: Vec<u32>,
+}
+
+impl Ports {
+    fn new(n: usize) -> Self {
+        Self { v: vec![0; n], d: vec![false; n], w: vec![CELL; n] }
+    }
+
+    #[inline(always)]
+    fn get(&self, i: usize) -> Option<u64> {
+        if self.d[i] { Some(self.v[i]) } else { None }
+    }
+
+    #[inline(always)]
+    fn bit(&self, i: usize) -> Option<bool> {
+        self.get(i).map(|x| x != 0)
+    }
+
+    #[inline(always)]
+    fn high(&self, i: usize) -> bool {
+        self.bit(i).unwrap_or(false)
+    }
+
+    #[inline(always)]
+    fn cmp(&self, a: usize, b: usize, f: impl Fn(u64, u64) -> bool) -> Option<bool> {
+        Some(f(self.get(a)?, self.get(b)?))
+    }
+
+    #[inline(always)]
+    fn implicit(&mut self, i: usize, x: u64) {
+        self.v[i] = x;
+        self.d[i] = true;
+        self.w[i] = IMPLICIT;
+    }
+
+    /// Writes a value, raising an error if a different writer already drives
+    /// the port.
+    #[inline(always)]
+    fn insert(&mut self, i: usize, x: u64, winner: u32) -> bool {
+        if self.d[i] {
+            if self.w[i] != winner {
+                fail(&format!("conflicting assignments to port \"{}\"", name(i)))
+            } else if self.v[i] == x {
+                return false;
+            }
+        }
+        self.v[i] = x;
+        self.d[i] = true;
+        self.w[i] = winner;
+        true
+    }
+
+    /// Overwrites the port with the output of a combinational primitive.
+    #[inline(always)]
+    fn put(&mut self, i: usize, x: u64) -> bool {
+        if self.d[i] && self.v[i] == x {
+            return false;
+        }
+        self.v[i] = x;
+        self.d[i] = true;
+        self.w[i] = CELL;
+        true
+    }
+
+    /// Overwrites the port with a possibly undefined value.
+    #[inline(always)]
+    fn exact(&mut self, i: usize, x: Option<u64>) -> bool {
+        match x {
+            Some(x) if self.d[i] && self.w[i] == CELL && self.v[i] == x => false,
+            Some(x) => {
+                self.v[i] = x;
+                self.d[i] = true;
+                self.w[i] = CELL;
+                true
+            }
+            None if self.d[i] => {
+                self.d[i] = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[inline(always)]
+    fn konst(&mut self, i: usize, x: u64) -> bool {
+        !self.d[i] && self.put(i, x)
+    }
+
+    #[inline(always)]
+    fn unop(&mut self, i: usize, o: usize, f: impl Fn(u64) -> u64) -> bool {
+        match self.get(i) {
+            Some(a) => self.put(o, f(a)),
+            None => false,
+        }
+    }
+
+    #[inline(always)]
+    fn binop(&mut self, l: usize, r: usize, o: usize, f: impl Fn(u64, u64) -> u64) -> bool {
+        match (self.get(l), self.get(r)) {
+            (Some(a), Some(b)) => self.put(o, f(a, b)),
+            _ => false,
+        }
+    }
+
+    #[inline(always)]
+    fn mux(&mut self, c: usize, t: usize, f: usize, o: usize) -> bool {
+        match self.bit(c).and_then(|c| self.get(if c { t } else { f })) {
+            Some(x) => self.insert(o, x, CELL),
+            None => false,
+        }
+    }
+
+    /// Computes the row-major address given by the address ports.
+    #[inline(always)]
+    fn addr(&self, ports: &[usize], dims: &[usize]) -> Option<usize> {
+        let mut addr = 0;
+        for (port, dim) in ports.iter().zip(dims) {
+            addr = addr * dim + self.get(*port)? as usize;
+        }
+        Some(addr)
+    }
+}
+
+struct Reg {
+    val: u64,
+    done: bool,
+}
+
+impl Reg {
+    #[inline(always)]
+    fn comb(&self, p: &mut Ports, b: usize) -> bool {
+        p.insert(b + 4, self.val, CELL) | p.insert(b + 5, self.done as u64, CELL)
+    }
+
+    #[inline(always)]
+    fn cycle(&mut self, p: &Ports, b: usize, name: &str) {
+        if p.high(b + 3) {
+            self.val = 0;
+        } else if p.high(b + 1) {
+            self.val = p.get(b).unwrap_or_else(|| undefined_write(name));
+            self.done = true;
+        } else {
+            self.done = false;
+        }
+    }
+}
+
+fn undefined_write(name: &str) -> ! {
+    fail(&format!("Attempted to write an undefined value to register or memory named \"{name}\""))
+}
+
+fn out_of_bounds(name: &str, addr: usize) -> ! {
+    fail(&format!("address {addr} is out of bounds for memory \"{name}\""))
+}
+
+struct Mem {
+    data: Vec<u64>,
+    done: bool,
+    read: Option<u64>,
+}
+
+impl Mem {
+    #[inline(always)]
+    fn comb_comb(&self, p: &mut Ports, addr: Option<usize>, b: usize) -> bool {
+        let read = match addr {
+            Some(a) if a < self.data.len() => p.insert(b + 4, self.data[a], CELL),
+            _ => false,
+        };
+        read | p.insert(b + 5, self.done as u64, CELL)
+    }
+
+    #[inline(always)]
+    fn comb_cycle(&mut self, p: &Ports, addr: Option<usize>, b: usize, name: &str) {
+        if p.high(b + 1) && !p.high(b + 3) {
+            let a = addr.unwrap_or_else(|| fail(&format!(
+                "Attempted to write an undefined memory address in memory named \"{name}\""
+            )));
+            let x = p.get(b).unwrap_or_else(|| undefined_write(name));
+            *self.data.get_mut(a).unwrap_or_else(|| out_of_bounds(name, a)) = x;
+            self.done = true;
+        } else {
+            self.done = false;
+        }
+        if let Some(a) = addr {
+            if a >= self.data.len() {
+                out_of_bounds(name, a)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn seq_comb(&self, p: &mut Ports, b: usize) -> bool {
+        let done = p.insert(b + 4, self.done as u64, CELL);
+        match self.read {
+            Some(x) if !p.d[b + 3] => done | p.insert(b + 3, x, CELL),
+            _ => done,
+        }
+    }
+
+    #[inline(always)]
+    fn seq_cycle(&mut self, p: &Ports, addr: Option<usize>, reset: usize, b: usize, name: &str) {
+        if p.high(reset) {
+            self.done = false;
+            self.read = Some(0);
+        } else if p.high(b) && p.high(b + 1) {
+            self.done = true;
+            self.read = None;
+            let a = addr.unwrap_or_else(|| fail(&format!(
+                "Attempted to write an undefined memory address in memory named \"{name}\""
+            )));
+            let x = p.get(b + 2).unwrap_or_else(|| undefined_write(name));
+            *self.data.get_mut(a).unwrap_or_else(|| out_of_bounds(name, a)) = x;
+        } else if p.high(b) {
+            self.done = true;
+            let a = addr.unwrap_or_else(|| fail(&format!(
+                "Attempted to read an undefined memory address from memory named \"{name}\""
+            )));
+            self.read = Some(*self.data.get(a).unwrap_or_else(|| out_of_bounds(name, a)));
+        } else {
+            self.done = false;
+        }
+    }
+}
+
+/// A two stage pipeline for the multipliers and dividers.
+struct Pipe {
+    buf: [Option<(Option<u64>, Option<u64>)>; 2],
+    out: Option<u64>,
+    rem: Option<u64>,
+    done: bool,
+}
+
+impl Pipe {
+    fn new() -> Self {
+        Self { buf: [None, None], out: Some(0), rem: Some(0), done: false }
+    }
+
+    #[inline(always)]
+    fn shift(&mut self, p: &Ports, b: usize) -> Option<(Option<u64>, Option<u64>)> {
+        let out = self.buf[1].take();
+        self.buf[1] = self.buf[0].take();
+        self.buf[0] = Some((p.get(b + 3), p.get(b + 4)));
+        out
+    }
+
+    #[inline(always)]
+    fn mult_comb(&self, p: &mut Ports, b: usize) -> bool {
+        p.exact(b + 5, self.out) | p.insert(b + 6, self.done as u64, CELL)
+    }
+
+    #[inline(always)]
+    fn mult_cycle(&mut self, p: &Ports, b: usize, mask: u64) {
+        if p.high(b + 1) {
+            self.out = Some(0);
+            self.done = false;
+            self.buf = [None, None];
+        } else if p.high(b + 2) {
+            if let Some((l, r)) = self.shift(p, b) {
+                self.out = l.zip(r).map(|(l, r)| l.wrapping_mul(r) & mask);
+                self.done = true;
+            } else {
+                self.out = Some(0);
+                self.done = false;
+            }
+        } else {
+            self.buf = [None, None];
+            self.done = false;
+        }
+    }
+
+    #[inline(always)]
+    fn div_comb(&self, p: &mut Ports, b: usize) -> bool {
+        p.exact(b + 5, self.out)
+            | p.exact(b + 6, self.rem)
+            | p.insert(b + 7, self.done as u64, CELL)
+    }
+
+    #[inline(always)]
+    fn div_cycle(&mut self, p: &Ports, b: usize, signed: bool, w: u32, mask: u64, name: &str) {
+        if p.high(b + 1) {
+            self.out = Some(0);
+            self.done = false;
+            self.buf = [None, None];
+        } else if p.high(b + 2) {
+            if let Some((l, r)) = self.shift(p, b) {
+                let res = l.zip(r).map(|(l, r)| {
+                    if signed {
+                        let (l, r) = (sext(l, w), sext(r, w));
+                        (sdiv(l, r, name) as u64 & mask, smod(l, r, name) as u64 & mask)
+                    } else {
+                        (div(l, r, name), l % r)
+                    }
+                });
+                self.out = res.map(|x| x.0);
+                self.rem = res.map(|x| x.1);
+                self.done = true;
+            } else {
+                self.out = Some(0);
+                self.rem = Some(0);
+                self.done = false;
+            }
+        } else {
+            self.buf = [None, None];
+            self.done = false;
+        }
+    }
+}
+"#;
+
+/// Gives every compiled program its own build directory.
+static BUILD_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn unsupported(msg: String) -> BoxedInterpreterError {
+    InterpreterError::UnsupportedCompiledProgram(msg).into()
+}
+
+fn failed(msg: String) -> BoxedInterpreterError {
+    InterpreterError::CompiledSimulationFailed(msg).into()
+}
+
+fn mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+fn dimension_sizes(dims: &MemoryDimensions) -> Vec<u32> {
+    match dims {
+        MemoryDimensions::D1 { d0_size, .. } => vec![*d0_size],
+        MemoryDimensions::D2 {
+            d0_size, d1_size, ..
+        } => vec![*d0_size, *d1_size],
+        MemoryDimensions::D3 {
+            d0_size,
+            d1_size,
+            d2_size,
+            ..
+        } => vec![*d0_size, *d1_size, *d2_size],
+        MemoryDimensions::D4 {
+            d0_size,
+            d1_size,
+            d2_size,
+            d3_size,
+            ..
+        } => vec![*d0_size, *d1_size, *d2_size, *d3_size],
+    }
+}
+
+fn global_port(
+    ledger: &ComponentLedger,
+    port: &PortRef,
+) -> InterpreterResult<usize> {
+    match port {
+        PortRef::Local(local) => Ok((&ledger.index_bases + local).index()),
+        PortRef::Ref(_) => {
+            Err(unsupported("ref cells are not supported".to_string()))
+        }
+    }
+}
+
+fn guard_expr(
+    ctx: &Context,
+    ledger: &ComponentLedger,
+    guard: GuardIdx,
+) -> InterpreterResult<String> {
+    Ok(match &ctx.primary[guard] {
+        Guard::True => "Some(true)".to_string(),
+        Guard::Or(a, b) => format!(
+            "or({}, {})",
+            guard_expr(ctx, ledger, *a)?,
+            guard_expr(ctx, ledger, *b)?
+        ),
+        Guard::And(a, b) => format!(
+            "and({}, {})",
+            guard_expr(ctx, ledger, *a)?,
+            guard_expr(ctx, ledger, *b)?
+        ),
+        Guard::Not(g) => {
+            format!("{}.map(|x| !x)", guard_expr(ctx, ledger, *g)?)
+        }
+        Guard::Comp(comp, a, b) => {
+            let op = match comp {
+                PortComp::Eq => "==",
+                PortComp::Neq => "!=",
+                PortComp::Gt => ">",
+                PortComp::Lt => "<",
+                PortComp::Geq => ">=",
+                PortComp::Leq => "<=",
+            };
+            format!(
+                "s.p.cmp({}, {}, |a, b| a {op} b)",
+                global_port(ledger, a)?,
+                global_port(ledger, b)?
+            )
+        }
+        Guard::Port(p) => format!("s.p.bit({})", global_port(ledger, p)?),
+    })
+}
+
+/// The code generated for a single primitive.
+#[derive(Default)]
+struct PrimitiveCode {
+    /// The state held by the primitive and its initializer
+    field: Option<(&'static str, String)>,
+    comb: Option<String>,
+    cycle: Option<String>,
+}
+
+/// A program translated into a standalone Rust program.
+pub(super) struct CompiledProgram {
+    source: String,
+    /// The registers and memories whose contents are passed to and from the
+    /// compiled program, in the order in which they are serialized.
+    state_cells: Vec<GlobalCellIdx>,
+}
+
+impl CompiledProgram {
+    /// Translates the program held by the environment. The pinned ports are
+    /// held at the given values for the entire simulation.
+    pub(super) fn generate<C: AsRef<Context> + Clone>(
+        env: &Environment<C>,
+        drive_root_go: bool,
+        pinned: &[(GlobalPortIdx, Value)],
+    ) -> InterpreterResult<Self> {
+        let ctx = env.ctx();
+        let mut primitives = vec![];
+
+        for (cell, ledger) in env.cells.iter() {
+            let CellLedger::Component(ledger) = ledger else {
+                continue;
+            };
+            if ctx.primary[ledger.comp_id].control.is_some() {
+                return Err(unsupported(format!(
+                    "\"{}\" has a control program, lower the program with `-p all` first",
+                    env.get_full_name(cell)
+                )));
+            }
+
+            let comp = &ctx.secondary[ledger.comp_id];
+            for (offset, def) in comp.port_offset_map.iter() {
+                let width = ctx.secondary[*def].width;
+                if width > 64 {
+                    return Err(unsupported(format!(
+                        "port \"{}\" is {width} bits wide but at most 64 bits are supported",
+                        env.get_full_name(&ledger.index_bases + offset)
+                    )));
+                }
+            }
+            for (offset, def) in comp.cell_offset_map.iter() {
+                let info = &ctx.secondary[*def];
+                if !info.prototype.is_component() {
+                    let base = &ledger.index_bases + info.ports.start();
+                    primitives.push((
+                        &ledger.index_bases + offset,
+                        &info.prototype,
+                        base.index(),
+                    ));
+                }
+            }
+        }
+        primitives.sort_by_key(|(cell, ..)| cell.index());
+
+        let mut assigns = String::new();
+        let mut port_names = vec![];
+        for continuous in env.pc.continuous_assigns() {
+            let ledger = env.cells[continuous.comp].as_comp().unwrap();
+            for idx in continuous.assigns.iter() {
+                let assign = &ctx.primary[idx];
+                let dst = global_port(ledger, &assign.dst)?;
+                let src = global_port(ledger, &assign.src)?;
+                port_names.push(dst);
+
+                let write = format!(
+                    "if let Some(x) = s.p.get({src}) {{ ch |= s.p.insert({dst}, x, {}); }}",
+                    idx.index() + 2
+                );
+                if matches!(ctx.primary[assign.guard], Guard::True) {
+                    writeln!(assigns, "        {write}").unwrap();
+                } else {
+                    writeln!(
+                        assigns,
+                        "        if {}.unwrap_or(false) {{ {write} }}",
+                        guard_expr(ctx, ledger, assign.guard)?
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        port_names.sort_unstable();
+        port_names.dedup();
+
+        let (mut fields, mut init, mut comb, mut cycle, mut save) = (
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        );
+        let mut state_cells = vec![];
+        for (cell, prototype, base) in primitives {
+            let code = Self::primitive(env, cell, prototype, base)?;
+            let n = cell.index();
+            if let Some((ty, initializer)) = code.field {
+                writeln!(fields, "    c{n}: {ty},").unwrap();
+                writeln!(init, "            c{n}: {initializer},").unwrap();
+                match ty {
+                    "Reg" => {
+                        writeln!(save, "        out.push(self.c{n}.val);")
+                            .unwrap();
+                        state_cells.push(cell);
+                    }
+                    "Mem" => {
+                        writeln!(save, "        out.extend(&self.c{n}.data);")
+                            .unwrap();
+                        state_cells.push(cell);
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(code) = code.comb {
+                writeln!(comb, "        {code}").unwrap();
+            }
+            if let Some(code) = code.cycle {
+                writeln!(cycle, "        {code}").unwrap();
+            }
+        }
+
+        let root = env.cells[Environment::<C>::get_root()].as_comp().unwrap();
+        let root_go =
+            (&root.index_bases + ctx.primary[root.comp_id].go).index();
+        let root_done = env.get_root_done().index();
+
+        let mut inputs = String::new();
+        if drive_root_go {
+            writeln!(inputs, "        s.p.implicit({root_go}, 1);").unwrap();
+        }
+        for (port, val) in pinned {
+            writeln!(
+                inputs,
+                "        s.p.implicit({}, {});",
+                port.index(),
+                val.as_u64()
+            )
+            .unwrap();
+        }
+
+        let mut names = String::new();
+        for port in port_names {
+            writeln!(
+                names,
+                "        {port} => {:?},",
+                env.get_full_name(GlobalPortIdx::new(port))
+            )
+            .unwrap();
+        }
+
+        let ports = env.ports.len();
+        let mut source = PRELUDE.to_string();
+        write!(
+            source,
+            r#"
+fn name(i: usize) -> &'static str {{
+    match i {{
+{names}        _ => "<unknown>",
+    }}
+}}
+
+struct State {{
+    p: Ports,
+{fields}}}
+
+impl State {{
+    fn load(w: &mut impl Iterator<Item = u64>) -> Self {{
+        Self {{
+            p: Ports::new({ports}),
+{init}        }}
+    }}
+
+    fn save(&self, out: &mut Vec<u64>) {{
+{save}    }}
+
+    fn converge(&mut self) {{
+        let s = self;
+        s.p.d.fill(false);
+{inputs}        loop {{
+            let mut ch = false;
+{assigns}{comb}            if !ch {{
+                break;
+            }}
+        }}
+    }}
+
+    fn cycle(&mut self) {{
+        let s = self;
+{cycle}    }}
+}}
+
+fn main() {{
+    let mut input = Vec::new();
+    std::io::stdin().read_to_end(&mut input).unwrap();
+    let mut words = input
+        .chunks_exact(8)
+        .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+    let mut s = State::load(&mut words);
+    while !s.p.high({root_done}) {{
+        s.converge();
+        s.cycle();
+    }}
+    let mut out = Vec::new();
+    s.save(&mut out);
+    let bytes: Vec<u8> = out.iter().flat_map(|x| x.to_le_bytes()).collect();
+    std::io::stdout().write_all(&bytes).unwrap();
+}}
+"#
+        )
+        .unwrap();
+
+        Ok(Self {
+            source,
+            state_cells,
+        })
+    }
+
+    /// Generates the code simulating a single primitive. `base` is the index
+    /// of its first port.
+    fn primitive<C: AsRef<Context> + Clone>(
+        env: &Environment<C>,
+        cell: GlobalCellIdx,
+        prototype: &CellPrototype,
+        base: usize,
+    ) -> InterpreterResult<PrimitiveCode> {
+        let name = format!("{:?}", env.get_full_name(cell));
+        let unop = |f: String| PrimitiveCode {
+            comb: Some(format!(
+                "ch |= s.p.unop({base}, {}, |a| {f});",
+                base + 1
+            )),
+            ..Default::default()
+        };
+        let binop = |f: String| PrimitiveCode {
+            comb: Some(format!(
+                "ch |= s.p.binop({base}, {}, {}, |a, b| {f});",
+                base + 1,
+                base + 2
+            )),
+            ..Default::default()
+        };
+
+        let code = match prototype {
+            CellPrototype::Constant { value, width, .. } => PrimitiveCode {
+                comb: Some(format!(
+                    "ch |= s.p.konst({base}, {});",
+                    value & mask(*width)
+                )),
+                ..Default::default()
+            },
+            CellPrototype::SingleWidth { op, width } => {
+                let (w, m) = (*width, mask(*width));
+                match op {
+                    SingleWidthType::Reg => PrimitiveCode {
+                        field: Some((
+                            "Reg",
+                            "Reg { val: w.next().unwrap(), done: false }"
+                                .to_string(),
+                        )),
+                        comb: Some(format!(
+                            "ch |= s.c{}.comb(&mut s.p, {base});",
+                            cell.index()
+                        )),
+                        cycle: Some(format!(
+                            "s.c{}.cycle(&s.p, {base}, {name});",
+                            cell.index()
+                        )),
+                    },
+                    SingleWidthType::Not => unop(format!("!a & {m}")),
+                    SingleWidthType::Wire => unop("a".to_string()),
+                    SingleWidthType::And => binop("a & b".to_string()),
+                    SingleWidthType::Or => binop("a | b".to_string()),
+                    SingleWidthType::Xor => binop("a ^ b".to_string()),
+                    SingleWidthType::Add | SingleWidthType::SignedAdd => {
+                        binop(format!("a.wrapping_add(b) & {m}"))
+                    }
+                    SingleWidthType::Sub | SingleWidthType::SignedSub => {
+                        binop(format!("a.wrapping_sub(b) & {m}"))
+                    }
+                    SingleWidthType::Gt => binop("(a > b) as u64".to_string()),
+                    SingleWidthType::Lt => binop("(a < b) as u64".to_string()),
+                    SingleWidthType::Eq => {
+                        binop("(a == b) as u64".to_string())
+                    }
+                    SingleWidthType::Neq => {
+                        binop("(a != b) as u64".to_string())
+                    }
+                    SingleWidthType::Ge => {
+                        binop("(a >= b) as u64".to_string())
+                    }
+                    SingleWidthType::Le => {
+                        binop("(a <= b) as u64".to_string())
+                    }
+                    SingleWidthType::SignedGt => {
+                        binop(format!("(sext(a, {w}) > sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::SignedLt => {
+                        binop(format!("(sext(a, {w}) < sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::SignedEq => {
+                        binop(format!("(sext(a, {w}) == sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::SignedNeq => {
+                        binop(format!("(sext(a, {w}) != sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::SignedGe => {
+                        binop(format!("(sext(a, {w}) >= sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::SignedLe => {
+                        binop(format!("(sext(a, {w}) <= sext(b, {w})) as u64"))
+                    }
+                    SingleWidthType::Lsh | SingleWidthType::SignedLsh => binop(
+                        format!("if b >= {w} {{ 0 }} else {{ (a << b) & {m} }}"),
+                    ),
+                    SingleWidthType::Rsh => {
+                        binop(format!("if b >= {w} {{ 0 }} else {{ a >> b }}"))
+                    }
+                    SingleWidthType::SignedRsh => binop(format!(
+                        "(sext(a, {w}) >> b.min(63)) as u64 & {m}"
+                    )),
+                    SingleWidthType::Mux => PrimitiveCode {
+                        comb: Some(format!(
+                            "ch |= s.p.mux({base}, {}, {}, {});",
+                            base + 1,
+                            base + 2,
+                            base + 3
+                        )),
+                        ..Default::default()
+                    },
+                    SingleWidthType::MultPipe
+                    | SingleWidthType::SignedMultPipe => PrimitiveCode {
+                        field: Some(("Pipe", "Pipe::new()".to_string())),
+                        comb: Some(format!(
+                            "ch |= s.c{}.mult_comb(&mut s.p, {base});",
+                            cell.index()
+                        )),
+                        cycle: Some(format!(
+                            "s.c{}.mult_cycle(&s.p, {base}, {m});",
+                            cell.index()
+                        )),
+                    },
+                    SingleWidthType::DivPipe
+                    | SingleWidthType::SignedDivPipe => PrimitiveCode {
+                        field: Some(("Pipe", "Pipe::new()".to_string())),
+                        comb: Some(format!(
+                            "ch |= s.c{}.div_comb(&mut s.p, {base});",
+                            cell.index()
+                        )),
+                        cycle: Some(format!(
+                            "s.c{}.div_cycle(&s.p, {base}, {}, {w}, {m}, {name});",
+                            cell.index(),
+                            matches!(op, SingleWidthType::SignedDivPipe)
+                        )),
+                    },
+                    SingleWidthType::UnsynMult => {
+                        binop(format!("a.wrapping_mul(b) & {m}"))
+                    }
+                    SingleWidthType::UnsynDiv => {
+                        binop(format!("div(a, b, {name})"))
+                    }
+                    SingleWidthType::UnsynMod => {
+                        binop(format!("urem(a, b, {name})"))
+                    }
+                    SingleWidthType::UnsynSMult => binop(format!(
+                        "(sext(a, {w}).wrapping_mul(sext(b, {w})) as u64) & {m}"
+                    )),
+                    SingleWidthType::UnsynSDiv => binop(format!(
+                        "sdiv(sext(a, {w}), sext(b, {w}), {name}) as u64 & {m}"
+                    )),
+                    SingleWidthType::UnsynSMod => binop(format!(
+                        "smod(sext(a, {w}), sext(b, {w}), {name}) as u64 & {m}"
+                    )),
+                    SingleWidthType::Undef => PrimitiveCode::default(),
+                    SingleWidthType::Sqrt => {
+                        return Err(unsupported(format!(
+                            "the square root primitive \"{}\" is not supported",
+                            env.get_full_name(cell)
+                        )))
+                    }
+                }
+            }
+            CellPrototype::DoubleWidth { op, width2, .. } => match op {
+                DoubleWidthType::Slice => {
+                    unop(format!("a & {}", mask(*width2)))
+                }
+                DoubleWidthType::Pad => unop("a".to_string()),
+            },
+            CellPrototype::TripleWidth {
+                op,
+                width1,
+                width2: _,
+                width3,
+            } => match op {
+                // the interpreter places the left operand in the low bits
+                TripleWidthType::Cat if *width1 >= 64 => binop("a".to_string()),
+                TripleWidthType::Cat => binop(format!("a | (b << {width1})")),
+                TripleWidthType::BitSlice => {
+                    unop(format!("(a >> {width1}) & {}", mask(*width3)))
+                }
+            },
+            CellPrototype::Memory { mem_type, dims, .. } => {
+                let sizes = dimension_sizes(dims);
+                let size: usize = sizes.iter().map(|x| *x as usize).product();
+                let first_addr = match mem_type {
+                    MemType::Std => base,
+                    MemType::Seq => base + 2,
+                };
+                let rest = first_addr + sizes.len();
+                let addr_ports = (first_addr..rest)
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let sizes = sizes
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let addr = format!("s.p.addr(&[{addr_ports}], &[{sizes}])");
+                let n = cell.index();
+
+                let (comb, cycle) = match mem_type {
+                    MemType::Std => (
+                        format!("{{ let a = {addr}; ch |= s.c{n}.comb_comb(&mut s.p, a, {rest}); }}"),
+                        format!("{{ let a = {addr}; s.c{n}.comb_cycle(&s.p, a, {rest}, {name}); }}"),
+                    ),
+                    MemType::Seq => (
+                        format!("ch |= s.c{n}.seq_comb(&mut s.p, {rest});"),
+                        format!("{{ let a = {addr}; s.c{n}.seq_cycle(&s.p, a, {}, {rest}, {name}); }}", base + 1),
+                    ),
+                };
+                PrimitiveCode {
+                    field: Some((
+                        "Mem",
+                        format!("Mem {{ data: (0..{size}).map(|_| w.next().unwrap()).collect(), done: false, read: None }}"),
+                    )),
+                    comb: Some(comb),
+                    cycle: Some(cycle),
+                }
+            }
+            CellPrototype::FixedPoint { .. }
+            | CellPrototype::Float { .. }
+            | CellPrototype::FloatConversion { .. }
+            | CellPrototype::Unknown(..)
+            | CellPrototype::Component(_) => {
+                return Err(unsupported(format!(
+                    "the primitive \"{}\" is not supported",
+                    env.get_full_name(cell)
+                )))
+            }
+        };
+        Ok(code)
+    }
+
+    /// Compiles and runs the program, then copies the final contents of the
+    /// registers and memories back into the environment.
+    pub(super) fn run<C: AsRef<Context> + Clone>(
+        &self,
+        env: &mut Environment<C>,
+    ) -> InterpreterResult<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "cider-compiled-{}-{}",
+            std::process::id(),
+            BUILD_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = self.build_and_run(&dir, env);
+        let _ = std::fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn build_and_run<C: AsRef<Context> + Clone>(
+        &self,
+        dir: &Path,
+        env: &mut Environment<C>,
+    ) -> InterpreterResult<()> {
+        std::fs::create_dir_all(dir)?;
+        let source = dir.join("sim.rs");
+        let binary = dir.join(format!("sim{}", std::env::consts::EXE_SUFFIX));
+        std::fs::write(&source, &self.source)?;
+
+        let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+        let output = Command::new(&rustc)
+            .args(["--edition", "2021", "-O", "-C", "debuginfo=0", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .output()
+            .map_err(|e| failed(format!("could not run {rustc:?}: {e}")))?;
+        if !output.status.success() {
+            return Err(failed(format!(
+                "the generated program did not compile:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let mut input = vec![];
+        for cell in &self.state_cells {
+            let CellLedger::Primitive { cell_dyn } = &env.cells[*cell] else {
+                unreachable!("registers and memories are primitives")
+            };
+            for val in cell_dyn.memory_contents().unwrap() {
+                input.extend(val.as_u64().to_le_bytes());
+            }
+        }
+
+        let mut child = Command::new(&binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        // the program may exit before reading its input if it fails, in which
+        // case its error is more useful than the broken pipe
+        let written = stdin.write_all(&input);
+        drop(stdin);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(failed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        written?;
+
+        let mut words = output
+            .stdout
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+        for cell in &self.state_cells {
+            let CellLedger::Primitive { cell_dyn } = &mut env.cells[*cell]
+            else {
+                unreachable!("registers and memories are primitives")
+            };
+            for val in cell_dyn.memory_contents_mut().unwrap() {
+                let word = words.next().ok_or_else(|| {
+                    failed("the compiled program exited early".to_string())
+                })?;
+                *val = Value::from(word, val.width() as u32);
+            }
+        }
+        Ok(())
+    }
+}
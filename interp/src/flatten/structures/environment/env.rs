@@ -3,8 +3,13 @@ use super::{
         context::Context, index_trait::IndexRange, indexed_map::IndexedMap,
     },
     assignments::{GroupInterfacePorts, ScheduledAssignments},
+    compiled::CompiledProgram,
+    mem_trace::{self, MemoryTrace},
+    parallel::ParallelConvergence,
     program_counter::{PcMaps, ProgramCounter, WithEntry},
+    snapshot::Snapshot,
     traverser::{Path, TraversalError},
+    x_checks::{self, XChecks, XPessimism, XReport},
 };
 use crate::{
     errors::{BoxedInterpreterError, InterpreterError, InterpreterResult},
@@ -163,8 +168,9 @@ impl ComponentLedger {
 /// primitive or information about a calyx component instance
 pub(crate) enum CellLedger {
     Primitive {
-        // wish there was a better option with this one
-        cell_dyn: Box<dyn Primitive>,
+        // wish there was a better option with this one. Primitives are shared
+        // between threads when independent par arms are evaluated in parallel
+        cell_dyn: Box<dyn Primitive + Send + Sync>,
     },
     Component(ComponentLedger),
 }
@@ -229,7 +235,7 @@ impl Debug for CellLedger {
 }
 
 #[derive(Debug, Clone)]
-struct PinnedPorts {
+pub(super) struct PinnedPorts {
     map: HashMap<GlobalPortIdx, Value>,
 }
 
@@ -251,12 +257,16 @@ impl PinnedPorts {
     pub fn remove(&mut self, port: GlobalPortIdx) {
         self.map.remove(&port);
     }
+
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
 }
 
 #[derive(Debug)]
 pub struct Environment<C: AsRef<Context> + Clone> {
     /// A map from global port IDs to their current values.
-    ports: PortMap,
+    pub(super) ports: PortMap,
     /// A map from global cell IDs to their current state and execution info.
     pub(super) cells: CellMap,
     /// A map from global ref cell IDs to the cell they reference, if any.
@@ -267,14 +277,15 @@ pub struct Environment<C: AsRef<Context> + Clone> {
     /// The program counter for the whole program execution.
     pub(super) pc: ProgramCounter,
 
-    pinned_ports: PinnedPorts,
+    /// Values of ports which are held constant, such as driven inputs
+    pub(super) pinned_ports: PinnedPorts,
 
     /// The immutable context. This is retained for ease of use.
     /// This value should have a cheap clone implementation, such as &Context
     /// or RC<Context>.
     pub(super) ctx: C,
 
-    memory_header: Option<Vec<MemoryDeclaration>>,
+    pub(super) memory_header: Option<Vec<MemoryDeclaration>>,
 }
 
 impl<C: AsRef<Context> + Clone> Environment<C> {
@@ -1123,6 +1134,26 @@ impl<C: AsRef<Context> + Clone> Environment<C> {
         &ledger.index_bases + found
     }
 
+    /// Returns the port of the entrypoint component with the given name, its
+    /// definition, and whether it is an input port.
+    fn find_root_port<S: AsRef<str>>(
+        &self,
+        port: S,
+    ) -> Option<(GlobalPortIdx, &PortDefinitionInfo, bool)> {
+        let ctx = self.ctx.as_ref();
+        let ledger = self.cells[Self::get_root()].as_comp().unwrap();
+        let comp = &ctx.secondary[ledger.comp_id];
+
+        comp.inputs()
+            .map(|offset| (offset, true))
+            .chain(comp.outputs().map(|offset| (offset, false)))
+            .find_map(|(offset, is_input)| {
+                let def = &ctx.secondary[comp.port_offset_map[offset]];
+                (ctx.lookup_name(def.name) == port.as_ref())
+                    .then(|| (&ledger.index_bases + offset, def, is_input))
+            })
+    }
+
     /// Pins the port with the given name to the given value. This may only be
     /// used for input ports on the entrypoint component (excluding the go port)
     /// and will panic if used otherwise. Intended for external use. Unrelated
@@ -1187,6 +1218,302 @@ impl<C: AsRef<Context> + Clone> Environment<C> {
     }
 }
 
+/// The parts of the environment which are needed to evaluate scheduled
+/// assignments against a port map. Unlike the environment itself, this can be
+/// shared between threads.
+#[derive(Clone, Copy)]
+pub(super) struct AssignmentEvaluator<'a> {
+    pub(super) ctx: &'a Context,
+    pub(super) cells: &'a CellMap,
+    pub(super) ref_ports: &'a RefPortMap,
+    /// How undefined values propagate through guards and primitives
+    pub(super) pessimism: XPessimism,
+}
+
+/// An error raised while converging assignments along with the primitive which
+/// raised it, if any
+pub(super) type ConvergenceError =
+    (Option<GlobalCellIdx>, BoxedInterpreterError);
+
+impl AssignmentEvaluator<'_> {
+    /// Resolves the given port of the given component to its global index.
+    /// Returns `None` for ref ports which are not bound to a cell.
+    #[inline]
+    pub(super) fn try_global_port_idx(
+        &self,
+        port: &PortRef,
+        comp: GlobalCellIdx,
+    ) -> Option<GlobalPortIdx> {
+        let ledger = self.cells[comp].unwrap_comp();
+        match ledger.convert_to_global_port(port) {
+            GlobalPortRef::Port(p) => Some(p),
+            GlobalPortRef::Ref(r) => self.ref_ports[r],
+        }
+    }
+
+    #[inline]
+    pub(super) fn get_global_port_idx(
+        &self,
+        port: &PortRef,
+        comp: GlobalCellIdx,
+    ) -> GlobalPortIdx {
+        // TODO Griffin: Please make sure this error message is correct with
+        // respect to the compiler
+        self.try_global_port_idx(port, comp).expect("A ref port is being queried without a supplied ref-cell. This is an error?")
+    }
+
+    /// Returns the go port of the given component instance
+    #[inline]
+    pub(super) fn get_comp_go(&self, comp: GlobalCellIdx) -> GlobalPortIdx {
+        let ledger = self.cells[comp].unwrap_comp();
+        &ledger.index_bases + self.ctx.primary[ledger.comp_id].go
+    }
+
+    /// Returns the go and done ports of the given scheduled assignments, if
+    /// they belong to a group
+    #[inline]
+    pub(super) fn get_interface_ports(
+        &self,
+        scheduled: &ScheduledAssignments,
+    ) -> Option<(GlobalPortIdx, GlobalPortIdx)> {
+        let ledger = self.cells[scheduled.active_cell].unwrap_comp();
+        scheduled
+            .interface_ports
+            .as_ref()
+            .map(|x| (&ledger.index_bases + x.go, &ledger.index_bases + x.done))
+    }
+
+    /// Calls `f` with every assignment of the bundle that the convergence
+    /// evaluates, i.e. skipping the assignments of groups or components which
+    /// are not running and those of groups which are already done, along with
+    /// its destination port and whether that port is a done signal.
+    pub(super) fn for_each_active_assignment<F>(
+        &self,
+        ports: &PortMap,
+        assigns_bundle: &[ScheduledAssignments],
+        mut f: F,
+    ) where
+        F: FnMut(&ScheduledAssignments, AssignmentIdx, GlobalPortIdx, bool),
+    {
+        for scheduled in assigns_bundle {
+            let active_cell = scheduled.active_cell;
+            let ledger = self.cells[active_cell].unwrap_comp();
+            let interface = self.get_interface_ports(scheduled);
+            let comp_go = self.get_comp_go(active_cell);
+            let comp_done =
+                &ledger.index_bases + self.ctx.primary[ledger.comp_id].done;
+
+            if let Some((go, _)) = interface {
+                if !(ports[go].as_bool().unwrap_or_default()
+                    && ports[comp_go].as_bool().unwrap_or_default())
+                {
+                    continue;
+                }
+            }
+
+            for assign_idx in scheduled.assignments {
+                let assign = &self.ctx.primary[assign_idx];
+                let dest = self.get_global_port_idx(&assign.dst, active_cell);
+                let is_done = dest == comp_done
+                    || interface.is_some_and(|(_, done)| done == dest);
+                if let Some((_, done)) = interface {
+                    if dest != done && ports[done].as_bool().unwrap_or(true) {
+                        continue;
+                    }
+                }
+                f(scheduled, assign_idx, dest, is_done);
+            }
+        }
+    }
+
+    /// Evaluates the guard, returning `None` if its value is undefined
+    pub(super) fn evaluate_guard(
+        &self,
+        ports: &PortMap,
+        guard: GuardIdx,
+        comp: GlobalCellIdx,
+    ) -> Option<bool> {
+        let precise = self.pessimism == XPessimism::Precise;
+        let guard = &self.ctx.primary[guard];
+        match guard {
+            Guard::True => Some(true),
+            Guard::Or(a, b) => {
+                let g1 = self.evaluate_guard(ports, *a, comp);
+                let g2 = self.evaluate_guard(ports, *b, comp);
+                match (g1, g2) {
+                    (Some(g1), Some(g2)) => Some(g1 || g2),
+                    (Some(true), None) | (None, Some(true)) if precise => {
+                        Some(true)
+                    }
+                    _ => None,
+                }
+            }
+            Guard::And(a, b) => {
+                let g1 = self.evaluate_guard(ports, *a, comp);
+                let g2 = self.evaluate_guard(ports, *b, comp);
+                match (g1, g2) {
+                    (Some(g1), Some(g2)) => Some(g1 && g2),
+                    (Some(false), None) | (None, Some(false)) if precise => {
+                        Some(false)
+                    }
+                    _ => None,
+                }
+            }
+            Guard::Not(n) => Some(!self.evaluate_guard(ports, *n, comp)?),
+            Guard::Comp(c, a, b) => {
+                let a_val = ports[self.get_global_port_idx(a, comp)].val()?;
+                let b_val = ports[self.get_global_port_idx(b, comp)].val()?;
+                match c {
+                    calyx_ir::PortComp::Eq => a_val == b_val,
+                    calyx_ir::PortComp::Neq => a_val != b_val,
+                    calyx_ir::PortComp::Gt => a_val > b_val,
+                    calyx_ir::PortComp::Lt => a_val < b_val,
+                    calyx_ir::PortComp::Geq => a_val >= b_val,
+                    calyx_ir::PortComp::Leq => a_val <= b_val,
+                }
+                .into()
+            }
+            Guard::Port(p) => {
+                ports[self.get_global_port_idx(p, comp)].as_bool()
+            }
+        }
+    }
+
+    /// Evaluates the given assignments and the combinational logic of the given
+    /// primitives until the ports stop changing.
+    pub(super) fn converge(
+        &self,
+        ports: &mut PortMap,
+        assigns_bundle: &[&ScheduledAssignments],
+        primitives: &[GlobalCellIdx],
+    ) -> Result<(), ConvergenceError> {
+        let mut has_changed = true;
+
+        // TODO griffin: rewrite this so that someone can actually read it
+        let done_ports: Vec<_> = assigns_bundle
+            .iter()
+            .filter_map(|x| self.get_interface_ports(x).map(|(_, done)| done))
+            .collect();
+
+        while has_changed {
+            has_changed = false;
+
+            // evaluate all the assignments and make updates
+            for scheduled in assigns_bundle.iter() {
+                let active_cell = scheduled.active_cell;
+                let ledger = self.cells[active_cell].unwrap_comp();
+                let interface = self.get_interface_ports(scheduled);
+                let go = interface.map(|(go, _)| go);
+                let done = interface.map(|(_, done)| done);
+
+                let comp_go = self.get_comp_go(active_cell);
+
+                for assign_idx in scheduled.assignments {
+                    let assign = &self.ctx.primary[assign_idx];
+
+                    // TODO griffin: Come back to this unwrap default later
+                    // since we may want to do something different if the guard
+                    // does not have a defined value
+                    if self
+                        .evaluate_guard(ports, assign.guard, active_cell)
+                        .unwrap_or_default()
+                    // the go for the group is high
+                    && go
+                        .as_ref()
+                        // the group must have its go signal high and the go
+                        // signal of the component must also be high
+                        .map(|g| ports[*g].as_bool().unwrap_or_default() && ports[comp_go].as_bool().unwrap_or_default())
+                        // if there is no go signal, then we want to run the
+                        // assignment
+                        .unwrap_or(true)
+                    {
+                        let val = &ports[self
+                            .get_global_port_idx(&assign.src, active_cell)];
+                        let dest =
+                            self.get_global_port_idx(&assign.dst, active_cell);
+
+                        if let Some(done) = done {
+                            if dest != done {
+                                let done_val = &ports[done];
+
+                                if done_val.as_bool().unwrap_or(true) {
+                                    // skip this assignment when we are done or
+                                    // or the done signal is undefined
+                                    continue;
+                                }
+                            }
+                        }
+
+                        if let Some(v) = val.as_option() {
+                            let changed = ports
+                                .insert_val(
+                                    dest,
+                                    AssignedValue::new(
+                                        v.val().clone(),
+                                        assign_idx,
+                                    ),
+                                )
+                                .map_err(|e| (None, e))?;
+
+                            has_changed |= changed.as_bool();
+                        } else if ports[dest].is_def() {
+                            return Err((
+                                None,
+                                InterpreterError::UndefiningAssignment {
+                                    assignment: self
+                                        .ctx
+                                        .printer()
+                                        .print_assignment(
+                                            ledger.comp_id,
+                                            assign_idx,
+                                        ),
+                                    value: ports[dest].to_string(),
+                                }
+                                .into(),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            // Run all the primitives
+            let changed: bool = primitives
+                .iter()
+                .map(|&x| {
+                    let primitive = self.cells[x].unwrap_primitive();
+                    match self.pessimism {
+                        XPessimism::Pessimistic => primitive.exec_comb(ports),
+                        XPessimism::Precise => {
+                            primitive.exec_comb_precise(ports)
+                        }
+                    }
+                    .map_err(|e| (Some(x), e))
+                })
+                .fold_ok(UpdateStatus::Unchanged, |has_changed, update| {
+                    has_changed | update
+                })?
+                .as_bool();
+
+            has_changed |= changed;
+
+            // check for undefined done ports. If any remain after we've
+            // converged then they should be set to zero and we should continue
+            // convergence
+            if !has_changed {
+                for &done_port in &done_ports {
+                    if ports[done_port].is_undef() {
+                        ports[done_port] =
+                            PortValue::new_implicit(Value::bit_low());
+                        has_changed = true;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// A wrapper struct for the environment that provides the functions used to
 /// simulate the actual program.
 ///
@@ -1194,11 +1521,29 @@ impl<C: AsRef<Context> + Clone> Environment<C> {
 /// the environment to avoid confusion
 pub struct Simulator<C: AsRef<Context> + Clone> {
     env: Environment<C>,
+    /// Whether the simulator holds the go port of the entrypoint component
+    /// high. Otherwise it is driven by the caller like every other input.
+    drive_root_go: bool,
+    /// How the active assignments are converged
+    parallel: ParallelConvergence,
+    /// The state of the X checks, if they are enabled
+    x_checks: Option<XChecks>,
+    /// The memory accesses recorded so far, if tracing is enabled
+    memory_trace: Option<MemoryTrace>,
+    /// The number of clock cycles simulated so far
+    cycle: u64,
 }
 
 impl<C: AsRef<Context> + Clone> Simulator<C> {
     pub fn new(env: Environment<C>) -> Self {
-        let mut output = Self { env };
+        let mut output = Self {
+            env,
+            drive_root_go: true,
+            parallel: ParallelConvergence::new(),
+            x_checks: None,
+            memory_trace: None,
+            cycle: 0,
+        };
         output.set_root_go_high();
         output
     }
@@ -1220,6 +1565,71 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         self.env
     }
 
+    /// Sets the number of threads used to converge the active assignments.
+    /// With more than one thread, the active assignments are split into
+    /// partitions whose ports do not interact, such as the arms of a `par`
+    /// block over distinct component instances, and the partitions are
+    /// converged in parallel. The results are identical to those of the
+    /// sequential evaluation, which is the default.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.parallel.set_threads(threads)
+    }
+
+    /// Enables the X checks, which report undefined values that reach a
+    /// guard, a done signal, a memory address or a value latched by a register
+    /// or memory. Every problem is logged as a warning the first time it
+    /// occurs and can be retrieved with [`Simulator::x_reports`]. The
+    /// pessimism determines how undefined values propagate through guards and
+    /// primitives while the checks are enabled.
+    pub fn enable_x_checks(&mut self, pessimism: XPessimism) {
+        self.x_checks = Some(XChecks::new(pessimism));
+    }
+
+    /// The problems found by the X checks so far, in the order they occurred
+    pub fn x_reports(&self) -> &[XReport] {
+        self.x_checks
+            .as_ref()
+            .map(|checks| checks.reports.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Enables the memory trace, which records every read and write of a
+    /// memory primitive along with the cycle, the address and the group
+    /// driving the access. See [`Simulator::memory_trace`].
+    pub fn enable_memory_trace(&mut self) {
+        self.memory_trace = Some(MemoryTrace::new());
+    }
+
+    /// The memory accesses recorded so far, if the trace is enabled
+    pub fn memory_trace(&self) -> Option<&MemoryTrace> {
+        self.memory_trace.as_ref()
+    }
+
+    /// The number of clock cycles simulated so far
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// Captures the complete state of the simulation, including the internal
+    /// state of primitives, the contents of memories and the program counter.
+    /// The snapshot can be written to a file and restored into a simulator of
+    /// the same program with [`Simulator::restore_snapshot`].
+    pub fn save_snapshot(&self) -> Snapshot {
+        Snapshot::capture(&self.env, self.cycle, self.drive_root_go)
+    }
+
+    /// Overwrites the state of the simulation with the given snapshot. Fails
+    /// if the snapshot was taken from a different program.
+    pub fn restore_snapshot(
+        &mut self,
+        snapshot: &Snapshot,
+    ) -> InterpreterResult<()> {
+        let (cycle, drive_root_go) = snapshot.apply(&mut self.env)?;
+        self.cycle = cycle;
+        self.drive_root_go = drive_root_go;
+        Ok(())
+    }
+
     pub fn build_simulator(
         ctx: C,
         data_file: &Option<std::path::PathBuf>,
@@ -1291,6 +1701,192 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
     }
 }
 
+// ============================== embedding API ===============================
+/// Functions for driving the simulation from a testbench one clock cycle at a
+/// time, like an RTL simulator. A simulator constructed with
+/// [`Simulator::new_embedded`] does not run the go/done protocol of the
+/// entrypoint component itself. Instead the caller drives all of its inputs,
+/// including `go` and `reset`, so it can also simulate components that are
+/// only used through their continuous assignments.
+impl<C: AsRef<Context> + Clone> Simulator<C> {
+    /// Constructs a simulator for the given context whose inputs are driven
+    /// by the caller. The memories of the entrypoint component are initialized
+    /// from the data dump, if one is given. All inputs start out undefined.
+    pub fn new_embedded(ctx: C, data: Option<DataDump>) -> Self {
+        Self {
+            env: Environment::new(ctx, data),
+            drive_root_go: false,
+            parallel: ParallelConvergence::new(),
+            x_checks: None,
+            memory_trace: None,
+            cycle: 0,
+        }
+    }
+
+    /// Drives the input port of the entrypoint component with the given name
+    /// with the given value until it is set again or released. The new value
+    /// is visible after the next call to [`Simulator::eval`] or
+    /// [`Simulator::tick`].
+    pub fn set_input<S: AsRef<str>>(
+        &mut self,
+        port: S,
+        val: Value,
+    ) -> InterpreterResult<()> {
+        let name = port.as_ref();
+        let (idx, def, is_input) = self
+            .env
+            .find_root_port(name)
+            .ok_or_else(|| InterpreterError::UnknownPort(name.to_string()))?;
+
+        if !is_input {
+            return Err(InterpreterError::NotAnInput(name.to_string()).into());
+        }
+        if val.width() != def.width as u64 {
+            return Err(InterpreterError::WidthMismatch {
+                name: name.to_string(),
+                expected: def.width as u64,
+                given: val.width(),
+            }
+            .into());
+        }
+
+        self.env.pinned_ports.insert(idx, val);
+        Ok(())
+    }
+
+    /// Stops driving the input port of the entrypoint component with the given
+    /// name, leaving it undefined.
+    pub fn release_input<S: AsRef<str>>(
+        &mut self,
+        port: S,
+    ) -> InterpreterResult<()> {
+        let name = port.as_ref();
+        match self.env.find_root_port(name) {
+            Some((idx, _, true)) => {
+                self.env.pinned_ports.remove(idx);
+                Ok(())
+            }
+            Some((_, _, false)) => {
+                Err(InterpreterError::NotAnInput(name.to_string()).into())
+            }
+            None => Err(InterpreterError::UnknownPort(name.to_string()).into()),
+        }
+    }
+
+    /// Propagates the current inputs and state through the combinational
+    /// logic without advancing the clock.
+    pub fn eval(&mut self) -> InterpreterResult<()> {
+        self.converge()
+    }
+
+    /// Advances the clock by one cycle and then propagates the new state
+    /// through the combinational logic.
+    pub fn tick(&mut self) -> InterpreterResult<()> {
+        self.step()?;
+        self.converge()
+    }
+
+    /// Returns the value of the port of the entrypoint component with the
+    /// given name as of the last call to [`Simulator::eval`] or
+    /// [`Simulator::tick`], or `None` if the port is undefined.
+    pub fn get_port<S: AsRef<str>>(
+        &self,
+        port: S,
+    ) -> InterpreterResult<Option<Value>> {
+        let name = port.as_ref();
+        let (idx, _, _) = self
+            .env
+            .find_root_port(name)
+            .ok_or_else(|| InterpreterError::UnknownPort(name.to_string()))?;
+
+        Ok(self.env.ports[idx].as_option().map(|x| x.val().clone()))
+    }
+
+    /// Returns the width of the port of the entrypoint component with the
+    /// given name.
+    pub fn port_width<S: AsRef<str>>(&self, port: S) -> InterpreterResult<u64> {
+        let name = port.as_ref();
+        let (_, def, _) = self
+            .env
+            .find_root_port(name)
+            .ok_or_else(|| InterpreterError::UnknownPort(name.to_string()))?;
+
+        Ok(def.width as u64)
+    }
+
+    /// Finds the memory or register with the given name.
+    fn find_memory(&self, name: &str) -> InterpreterResult<GlobalCellIdx> {
+        let unknown = || InterpreterError::UnknownMemory(name.to_string());
+        let path = name.split('.').map(|x| x.to_string()).collect_vec();
+
+        let cell = self
+            .env
+            .traverse_name_vec(&path)
+            .ok()
+            .and_then(|path| path.resolve_path(&self.env).ok())
+            .and_then(|resolved| resolved.as_cell().copied())
+            .ok_or_else(unknown)?;
+
+        match self.env.cells[cell].as_primitive() {
+            Some(prim) if prim.memory_contents().is_some() => Ok(cell),
+            _ => Err(unknown().into()),
+        }
+    }
+
+    /// Returns the contents of the memory or register with the given name in
+    /// row-major order. Cells inside of sub-components are named by their path
+    /// from the entrypoint component, separated by dots, e.g. `pe0.acc`.
+    pub fn peek_memory<S: AsRef<str>>(
+        &self,
+        name: S,
+    ) -> InterpreterResult<&[Value]> {
+        let cell = self.find_memory(name.as_ref())?;
+        Ok(self.env.cells[cell]
+            .unwrap_primitive()
+            .memory_contents()
+            .unwrap())
+    }
+
+    /// Overwrites the entry at the given row-major index of the memory or
+    /// register with the given name. Like the inputs, the new value is visible
+    /// after the next call to [`Simulator::eval`] or [`Simulator::tick`].
+    pub fn poke_memory<S: AsRef<str>>(
+        &mut self,
+        name: S,
+        index: usize,
+        val: Value,
+    ) -> InterpreterResult<()> {
+        let name = name.as_ref();
+        let cell = self.find_memory(name)?;
+
+        let CellLedger::Primitive { cell_dyn } = &mut self.env.cells[cell]
+        else {
+            unreachable!("memories are primitives")
+        };
+        let contents = cell_dyn.memory_contents_mut().unwrap();
+
+        let size = contents.len();
+        let entry = contents.get_mut(index).ok_or_else(|| {
+            InterpreterError::MemoryIndexOutOfBounds {
+                name: name.to_string(),
+                index,
+                size,
+            }
+        })?;
+        if entry.width() != val.width() {
+            return Err(InterpreterError::WidthMismatch {
+                name: name.to_string(),
+                expected: entry.width(),
+                given: val.width(),
+            }
+            .into());
+        }
+
+        *entry = val;
+        Ok(())
+    }
+}
+
 // =========================== simulation functions ===========================
 impl<C: AsRef<Context> + Clone> Simulator<C> {
     #[inline]
@@ -1333,16 +1929,6 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         self.lookup_global_cell_id(ledger.convert_to_global_cell(cell))
     }
 
-    #[inline]
-    fn get_value(
-        &self,
-        port: &PortRef,
-        parent_comp: GlobalCellIdx,
-    ) -> &PortValue {
-        let port_idx = self.get_global_port_idx(port, parent_comp);
-        &self.env.ports[port_idx]
-    }
-
     pub(crate) fn get_root_component(&self) -> &ComponentLedger {
         self.env.cells[Environment::<C>::get_root()]
             .as_comp()
@@ -1377,6 +1963,7 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                                 go: group.go,
                                 done: group.done,
                             }),
+                            Some(e.group()),
                         ))
                     }
 
@@ -1384,6 +1971,7 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                         node.comp,
                         i.assignments,
                         None,
+                        None,
                     )),
 
                     ControlNode::Empty(_) => None,
@@ -1395,16 +1983,14 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
                     | ControlNode::Par(_) => None,
                 }
             })
-            .chain(
-                self.env.pc.continuous_assigns().iter().map(|x| {
-                    ScheduledAssignments::new(x.comp, x.assigns, None)
-                }),
-            )
+            .chain(self.env.pc.continuous_assigns().iter().map(|x| {
+                ScheduledAssignments::new(x.comp, x.assigns, None, None)
+            }))
             .chain(self.env.pc.with_map().iter().map(
                 |(ctrl_pt, with_entry)| {
                     let assigns =
                         self.ctx().primary[with_entry.group].assignments;
-                    ScheduledAssignments::new(ctrl_pt.comp, assigns, None)
+                    ScheduledAssignments::new(ctrl_pt.comp, assigns, None, None)
                 },
             ))
             .collect()
@@ -1523,7 +2109,9 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
     //
     pub fn converge(&mut self) -> InterpreterResult<()> {
         self.undef_all_ports();
-        self.set_root_go_high();
+        if self.drive_root_go {
+            self.set_root_go_high();
+        }
         // set the pinned values
         for (port, val) in self.env.pinned_ports.iter() {
             self.env.ports[*port] = PortValue::new_implicit(val.clone());
@@ -1621,7 +2209,15 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
     }
 
     pub fn step(&mut self) -> InterpreterResult<()> {
-        self.converge()?;
+        let converged = self.converge();
+        // the accesses are also recorded if the convergence fails since two
+        // groups driving the same memory port at once cause a conflict error
+        if self.memory_trace.is_some() {
+            let assigns_bundle = self.get_assignments(self.env.pc.node_slice());
+            self.record_memory_accesses(&assigns_bundle);
+        }
+        converged?;
+        self.cycle += 1;
 
         let out: Result<(), (GlobalCellIdx, BoxedInterpreterError)> = {
             let mut result = Ok(());
@@ -1890,52 +2486,26 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         Ok(())
     }
 
-    fn evaluate_guard(
-        &self,
-        guard: GuardIdx,
-        comp: GlobalCellIdx,
-    ) -> Option<bool> {
-        let guard = &self.ctx().primary[guard];
-        match guard {
-            Guard::True => Some(true),
-            Guard::Or(a, b) => {
-                let g1 = self.evaluate_guard(*a, comp)?;
-                let g2 = self.evaluate_guard(*b, comp)?;
-                Some(g1 || g2)
-            }
-            Guard::And(a, b) => {
-                let g1 = self.evaluate_guard(*a, comp)?;
-                let g2 = self.evaluate_guard(*b, comp)?;
-                Some(g1 && g2)
-            }
-            Guard::Not(n) => Some(!self.evaluate_guard(*n, comp)?),
-            Guard::Comp(c, a, b) => {
-                let comp_v = self.env.cells[comp].unwrap_comp();
-
-                let a = self
-                    .lookup_global_port_id(comp_v.convert_to_global_port(a));
-                let b = self
-                    .lookup_global_port_id(comp_v.convert_to_global_port(b));
-
-                let a_val = self.env.ports[a].val()?;
-                let b_val = self.env.ports[b].val()?;
-                match c {
-                    calyx_ir::PortComp::Eq => a_val == b_val,
-                    calyx_ir::PortComp::Neq => a_val != b_val,
-                    calyx_ir::PortComp::Gt => a_val > b_val,
-                    calyx_ir::PortComp::Lt => a_val < b_val,
-                    calyx_ir::PortComp::Geq => a_val >= b_val,
-                    calyx_ir::PortComp::Leq => a_val <= b_val,
-                }
-                .into()
-            }
-            Guard::Port(p) => {
-                let comp_v = self.env.cells[comp].unwrap_comp();
-                let p_idx = self
-                    .lookup_global_port_id(comp_v.convert_to_global_port(p));
-                self.env.ports[p_idx].as_bool()
-            }
-        }
+    /// Evaluate the entire program by compiling it to native code rather than
+    /// interpreting it. This is only possible for programs that have been
+    /// fully lowered and must be called before the simulation is stepped,
+    /// since only the contents of registers and memories are carried over to
+    /// the compiled program. See [`compiled`](super::compiled) for details.
+    pub fn run_program_compiled(&mut self) -> InterpreterResult<()> {
+        let pinned = self
+            .env
+            .pinned_ports
+            .iter()
+            .map(|(port, val)| (*port, val.clone()))
+            .collect_vec();
+        let program =
+            CompiledProgram::generate(&self.env, self.drive_root_go, &pinned)?;
+        program.run(&mut self.env)?;
+
+        // leave the program in the same state as `run_program` would
+        let done = self.env.get_root_done();
+        self.env.ports[done] = PortValue::new_implicit(Value::bit_high());
+        Ok(())
     }
 
     fn undef_all_ports(&mut self) {
@@ -1948,130 +2518,114 @@ impl<C: AsRef<Context> + Clone> Simulator<C> {
         &mut self,
         assigns_bundle: &[ScheduledAssignments],
     ) -> InterpreterResult<()> {
-        let mut has_changed = true;
-
-        // TODO griffin: rewrite this so that someone can actually read it
-        let done_ports: Vec<_> = assigns_bundle
-            .iter()
-            .filter_map(|x| {
-                x.interface_ports.as_ref().map(|y| {
-                    &self.env.cells[x.active_cell]
-                        .as_comp()
-                        .unwrap()
-                        .index_bases
-                        + y.done
-                })
-            })
-            .collect();
-
-        while has_changed {
-            has_changed = false;
-
-            // evaluate all the assignments and make updates
-            for ScheduledAssignments {
-                active_cell,
-                assignments,
-                interface_ports,
-            } in assigns_bundle.iter()
-            {
-                let ledger = self.env.cells[*active_cell].as_comp().unwrap();
-                let go = interface_ports
-                    .as_ref()
-                    .map(|x| &ledger.index_bases + x.go);
-                let done = interface_ports
-                    .as_ref()
-                    .map(|x| &ledger.index_bases + x.done);
-
-                let comp_go = self.env.get_comp_go(*active_cell);
-
-                for assign_idx in assignments {
-                    let assign = &self.env.ctx.as_ref().primary[assign_idx];
-
-                    // TODO griffin: Come back to this unwrap default later
-                    // since we may want to do something different if the guard
-                    // does not have a defined value
-                    if self
-                        .evaluate_guard(assign.guard, *active_cell)
-                        .unwrap_or_default()
-                    // the go for the group is high
-                    && go
-                        .as_ref()
-                        // the group must have its go signal high and the go
-                        // signal of the component must also be high
-                        .map(|g| self.env.ports[*g].as_bool().unwrap_or_default() && self.env.ports[comp_go].as_bool().unwrap_or_default())
-                        // if there is no go signal, then we want to run the
-                        // assignment
-                        .unwrap_or(true)
-                    {
-                        let val = self.get_value(&assign.src, *active_cell);
-                        let dest =
-                            self.get_global_port_idx(&assign.dst, *active_cell);
-
-                        if let Some(done) = done {
-                            if dest != done {
-                                let done_val = &self.env.ports[done];
+        let evaluator = AssignmentEvaluator {
+            ctx: self.env.ctx.as_ref(),
+            cells: &self.env.cells,
+            ref_ports: &self.env.ref_ports,
+            pessimism: self
+                .x_checks
+                .as_ref()
+                .map(|checks| checks.pessimism)
+                .unwrap_or_default(),
+        };
 
-                                if done_val.as_bool().unwrap_or(true) {
-                                    // skip this assignment when we are done or
-                                    // or the done signal is undefined
-                                    continue;
-                                }
-                            }
-                        }
+        self.parallel
+            .converge(evaluator, &mut self.env.ports, assigns_bundle)
+            .map_err(|(cell, err)| match cell {
+                Some(cell) => self.env.make_nice_error(cell, err),
+                None => err,
+            })?;
+
+        if self.x_checks.is_some() {
+            let findings = x_checks::find_undefined(
+                &evaluator,
+                &self.env.ports,
+                assigns_bundle,
+            );
+            self.report_undefined(findings);
+        }
+        Ok(())
+    }
 
-                        if let Some(v) = val.as_option() {
-                            let changed = self.env.ports.insert_val(
-                                dest,
-                                AssignedValue::new(v.val().clone(), assign_idx),
-                            )?;
+    /// Names and logs the findings of the X checks which have not been
+    /// reported before
+    fn report_undefined(&mut self, findings: Vec<x_checks::XFinding>) {
+        let Some(checks) = &mut self.x_checks else {
+            return;
+        };
+        for finding in findings {
+            if !checks.is_new(&finding) {
+                continue;
+            }
 
-                            has_changed |= changed.as_bool();
-                        } else if self.env.ports[dest].is_def() {
-                            todo!("Raise an error here since this assignment is undefining things: {}. Port currently has value: {}", self.env.ctx.as_ref().printer().print_assignment(ledger.comp_id, assign_idx), &self.env.ports[dest])
-                        }
-                    }
+            let ctx = self.env.ctx.as_ref();
+            let (assignment, position) = match finding.assignment {
+                Some((cell, assign_idx)) => {
+                    let comp = self.env.cells[cell].unwrap_comp().comp_id;
+                    let position = ctx.primary[assign_idx].pos.map(|pos| {
+                        let (file, (line, _)) = pos.get_line_num();
+                        format!("{file}:{line}")
+                    });
+                    (
+                        Some(ctx.printer().print_assignment(comp, assign_idx)),
+                        position,
+                    )
                 }
-            }
+                None => (None, None),
+            };
 
-            // Run all the primitives
-            let changed: bool = self
-                .env
-                .cells
-                .range()
-                .iter()
-                .filter_map(|x| match &mut self.env.cells[x] {
-                    CellLedger::Primitive { cell_dyn } => Some(
-                        cell_dyn
-                            .exec_comb(&mut self.env.ports)
-                            .map_err(|e| (x, e)),
-                    ),
-                    CellLedger::Component(_) => None,
-                })
-                .fold_ok(UpdateStatus::Unchanged, |has_changed, update| {
-                    has_changed | update
-                })
-                .map_err(|(cell_idx, err)| {
-                    self.env.make_nice_error(cell_idx, err)
-                })?
-                .as_bool();
+            let report = XReport {
+                cycle: self.cycle,
+                sink: finding.sink,
+                port: finding.port.get_full_name(&self.env),
+                assignment,
+                position,
+            };
+            warn!(logging::root(), "{report}");
+            checks.reports.push(report);
+        }
+    }
 
-            has_changed |= changed;
+    /// Records the memory accesses of the current cycle in the memory trace
+    fn record_memory_accesses(
+        &mut self,
+        assigns_bundle: &[ScheduledAssignments],
+    ) {
+        let evaluator = AssignmentEvaluator {
+            ctx: self.env.ctx.as_ref(),
+            cells: &self.env.cells,
+            ref_ports: &self.env.ref_ports,
+            pessimism: self
+                .x_checks
+                .as_ref()
+                .map(|checks| checks.pessimism)
+                .unwrap_or_default(),
+        };
+        let found = mem_trace::find_accesses(
+            &evaluator,
+            &self.env.ports,
+            assigns_bundle,
+        );
 
-            // check for undefined done ports. If any remain after we've
-            // converged then they should be set to zero and we should continue
-            // convergence
-            if !has_changed {
-                for &done_port in &done_ports {
-                    if self.env.ports[done_port].is_undef() {
-                        self.env.ports[done_port] =
-                            PortValue::new_implicit(Value::bit_low());
-                        has_changed = true;
-                    }
-                }
-            }
+        let Some(trace) = &mut self.memory_trace else {
+            return;
+        };
+        let env = &self.env;
+        for access in found {
+            trace.record(
+                self.cycle,
+                access,
+                |memory| memory.get_full_name(env),
+                |cell, group| {
+                    let name = env.ctx().primary[group].name();
+                    format!(
+                        "{}.{}",
+                        cell.get_full_name(env),
+                        env.ctx().lookup_name(name)
+                    )
+                },
+            );
         }
-
-        Ok(())
     }
 
     /// Dump the current state of the environment as a DataDump
@@ -0,0 +1,234 @@
+//! Tracing of memory accesses.
+//!
+//! With the memory trace enabled, Cider records every read and write of a
+//! `comb_mem` or `seq_mem` primitive along with the cycle, the address and the
+//! group whose assignments drive the access. The trace summarizes the accesses
+//! as a histogram of the addresses of every memory and lists the cycles in
+//! which the single port of a memory is used by more than one group, which
+//! helps to check banking and partitioning choices.
+use super::{
+    assignments::ScheduledAssignments,
+    env::{AssignmentEvaluator, CellLedger},
+    PortMap,
+};
+use crate::flatten::{
+    flat_ir::prelude::{GlobalCellIdx, GlobalPortIdx, GroupIdx},
+    primitives::prim_trait::{AccessKind, MemoryAccess},
+};
+use ahash::{HashMap, HashMapExt};
+use itertools::Itertools;
+use serde::Serialize;
+use std::{collections::BTreeMap, io::Write};
+
+/// A single read or write of a memory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccessRecord {
+    /// The clock cycle of the access, starting from zero
+    pub cycle: u64,
+    /// The full name of the memory
+    pub memory: String,
+    pub kind: AccessKind,
+    /// The address in every dimension of the memory
+    pub address: Vec<usize>,
+    /// The full name of the group which drives the access, or `None` if it is
+    /// driven by continuous assignments or an invoke
+    pub group: Option<String>,
+}
+
+/// A cycle in which several groups use the port of a memory at once
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortConflict {
+    pub cycle: u64,
+    pub memory: String,
+    /// The full names of the groups which drive the ports of the memory
+    pub groups: Vec<String>,
+}
+
+/// The number of accesses of a single address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressCount {
+    pub address: Vec<usize>,
+    pub reads: u64,
+    pub writes: u64,
+}
+
+/// The memory accesses recorded by a simulator
+#[derive(Debug, Default)]
+pub struct MemoryTrace {
+    accesses: Vec<AccessRecord>,
+    conflicts: Vec<PortConflict>,
+    /// Cached full names of memories and groups
+    memory_names: HashMap<GlobalCellIdx, String>,
+    group_names: HashMap<(GlobalCellIdx, GroupIdx), String>,
+}
+
+impl MemoryTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every access in the order it occurred
+    pub fn accesses(&self) -> &[AccessRecord] {
+        &self.accesses
+    }
+
+    /// Every port conflict in the order it occurred
+    pub fn conflicts(&self) -> &[PortConflict] {
+        &self.conflicts
+    }
+
+    /// The number of reads and writes of every accessed address of every
+    /// memory, keyed by the name of the memory and sorted by address
+    pub fn histograms(&self) -> BTreeMap<String, Vec<AddressCount>> {
+        let mut counts: BTreeMap<&str, BTreeMap<&[usize], (u64, u64)>> =
+            BTreeMap::new();
+        for access in &self.accesses {
+            let (reads, writes) = counts
+                .entry(&access.memory)
+                .or_default()
+                .entry(&access.address)
+                .or_default();
+            match access.kind {
+                AccessKind::Read => *reads += 1,
+                AccessKind::Write => *writes += 1,
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|(memory, addresses)| {
+                let addresses = addresses
+                    .into_iter()
+                    .map(|(address, (reads, writes))| AddressCount {
+                        address: address.to_vec(),
+                        reads,
+                        writes,
+                    })
+                    .collect();
+                (memory.to_string(), addresses)
+            })
+            .collect()
+    }
+
+    /// Writes the accesses, histograms and conflicts as a JSON object
+    pub fn write_json<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        #[derive(Serialize)]
+        struct Output<'a> {
+            accesses: &'a [AccessRecord],
+            histograms: BTreeMap<String, Vec<AddressCount>>,
+            conflicts: &'a [PortConflict],
+        }
+
+        let output = Output {
+            accesses: &self.accesses,
+            histograms: self.histograms(),
+            conflicts: &self.conflicts,
+        };
+        serde_json::to_writer_pretty(writer, &output)?;
+        Ok(())
+    }
+
+    /// Records an access, naming the memory and groups with the given
+    /// functions if they have not been named before
+    pub(super) fn record<M, G>(
+        &mut self,
+        cycle: u64,
+        found: FoundAccess,
+        name_memory: M,
+        name_group: G,
+    ) where
+        M: FnOnce(GlobalCellIdx) -> String,
+        G: Fn(GlobalCellIdx, GroupIdx) -> String,
+    {
+        let memory = self
+            .memory_names
+            .entry(found.memory)
+            .or_insert_with(|| name_memory(found.memory))
+            .clone();
+        let groups = found
+            .groups
+            .iter()
+            .map(|&(cell, group)| {
+                self.group_names
+                    .entry((cell, group))
+                    .or_insert_with(|| name_group(cell, group))
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+
+        if groups.len() > 1 {
+            self.conflicts.push(PortConflict {
+                cycle,
+                memory: memory.clone(),
+                groups: groups.clone(),
+            });
+        }
+        self.accesses.push(AccessRecord {
+            cycle,
+            memory,
+            kind: found.access.kind,
+            address: found.access.address,
+            group: groups.into_iter().next(),
+        });
+    }
+}
+
+/// A memory access found in the port map, before it is given names
+pub(super) struct FoundAccess {
+    pub memory: GlobalCellIdx,
+    pub access: MemoryAccess,
+    /// The groups whose active assignments drive the ports of the access, in
+    /// the order of the ports
+    pub groups: Vec<(GlobalCellIdx, GroupIdx)>,
+}
+
+/// Finds the accesses of all memories in the converged port map
+pub(super) fn find_accesses(
+    evaluator: &AssignmentEvaluator,
+    ports: &PortMap,
+    assigns_bundle: &[ScheduledAssignments],
+) -> Vec<FoundAccess> {
+    // the groups which drive a value onto a port
+    let mut drivers: HashMap<GlobalPortIdx, Vec<(GlobalCellIdx, GroupIdx)>> =
+        HashMap::new();
+    evaluator.for_each_active_assignment(
+        ports,
+        assigns_bundle,
+        |scheduled, assign_idx, dest, _| {
+            let Some(group) = scheduled.group else {
+                return;
+            };
+            let assign = &evaluator.ctx.primary[assign_idx];
+            let active_cell = scheduled.active_cell;
+            if evaluator.evaluate_guard(ports, assign.guard, active_cell)
+                == Some(true)
+            {
+                drivers.entry(dest).or_default().push((active_cell, group));
+            }
+        },
+    );
+
+    evaluator
+        .cells
+        .iter()
+        .filter_map(|(memory, cell)| {
+            let CellLedger::Primitive { cell_dyn } = cell else {
+                return None;
+            };
+            let access = cell_dyn.memory_access(ports)?;
+            let groups = access
+                .ports
+                .iter()
+                .filter_map(|port| drivers.get(port))
+                .flatten()
+                .copied()
+                .unique()
+                .collect();
+            Some(FoundAccess {
+                memory,
+                access,
+                groups,
+            })
+        })
+        .collect()
+}
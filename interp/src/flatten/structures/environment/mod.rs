@@ -1,7 +1,15 @@
 mod assignments;
+mod compiled;
 mod env;
+mod mem_trace;
+mod parallel;
 mod program_counter;
+mod snapshot;
 mod traverser;
+mod x_checks;
 
 pub use env::{Environment, PortMap, Simulator};
+pub use mem_trace::{AccessRecord, AddressCount, MemoryTrace, PortConflict};
+pub use snapshot::Snapshot;
 pub use traverser::{Path, PathError, PathResolution};
+pub use x_checks::{XPessimism, XReport, XSink};
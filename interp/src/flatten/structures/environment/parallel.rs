@@ -0,0 +1,405 @@
+//! Convergence of the active assignments, optionally split across threads.
+//!
+//! Scheduled assignments only interact with each other through the ports that
+//! are written during convergence, i.e. the destinations of assignments, the
+//! done ports of groups and the ports of the primitives driven by them.
+//! Assignments which do not share any such port, usually the arms of a `par`
+//! block over distinct component instances, can therefore be converged
+//! independently. Each partition is converged on a private copy of the ports it
+//! touches and the results are copied back afterwards, so the outcome is the
+//! same as that of the sequential evaluation.
+
+use super::{
+    assignments::ScheduledAssignments,
+    env::{AssignmentEvaluator, ConvergenceError, PortMap},
+};
+use crate::flatten::{
+    flat_ir::{
+        prelude::{GlobalCellIdx, GlobalPortIdx, GuardIdx, PortValue},
+        wires::guards::Guard,
+    },
+    structures::indexed_map::IndexedMap,
+};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use itertools::Itertools;
+use std::collections::hash_map::Entry;
+
+/// Something through which scheduled assignments may interact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Port(GlobalPortIdx),
+    /// All the ports of a primitive, since its combinational logic may connect
+    /// any of them
+    Primitive(GlobalCellIdx),
+}
+
+/// The ports which are read and written by a set of scheduled assignments
+#[derive(Debug, Default)]
+struct Footprint {
+    reads: Vec<GlobalPortIdx>,
+    writes: Vec<GlobalPortIdx>,
+}
+
+/// Scheduled assignments and primitives which can be converged independently
+/// of everything else
+#[derive(Debug, Default)]
+struct Partition {
+    /// Indices into the scheduled assignments
+    bundles: Vec<usize>,
+    primitives: Vec<GlobalCellIdx>,
+    /// Every port read or written by the partition
+    ports: Vec<GlobalPortIdx>,
+}
+
+/// Which ports belong to which primitive
+#[derive(Debug)]
+struct PortLayout {
+    owners: IndexedMap<GlobalPortIdx, Option<GlobalCellIdx>>,
+    primitive_ports: HashMap<GlobalCellIdx, Vec<GlobalPortIdx>>,
+}
+
+impl PortLayout {
+    fn new(evaluator: AssignmentEvaluator, ports: &PortMap) -> Self {
+        let mut owners = IndexedMap::with_capacity(ports.len());
+        for _ in 0..ports.len() {
+            owners.push(None);
+        }
+        let mut primitive_ports: HashMap<_, Vec<_>> = HashMap::new();
+
+        for (_, cell) in evaluator.cells.iter() {
+            let Some(ledger) = cell.as_comp() else {
+                continue;
+            };
+            let comp = &evaluator.ctx.secondary[ledger.comp_id];
+
+            for (cell_off, def_idx) in comp.cell_offset_map.iter() {
+                let cell = &ledger.index_bases + cell_off;
+                if evaluator.cells[cell].as_primitive().is_none() {
+                    continue;
+                }
+
+                for port in evaluator.ctx.secondary[*def_idx].ports.iter() {
+                    let port = &ledger.index_bases + port;
+                    owners[port] = Some(cell);
+                    primitive_ports.entry(cell).or_default().push(port);
+                }
+            }
+        }
+
+        Self {
+            owners,
+            primitive_ports,
+        }
+    }
+
+    fn node(&self, port: GlobalPortIdx) -> Node {
+        match self.owners[port] {
+            Some(cell) => Node::Primitive(cell),
+            None => Node::Port(port),
+        }
+    }
+
+    /// Splits the scheduled assignments into partitions which do not interact.
+    /// Returns `None` if there is only a single partition or the ports of the
+    /// assignments cannot be determined up front.
+    fn partition(
+        &self,
+        evaluator: AssignmentEvaluator,
+        assigns_bundle: &[ScheduledAssignments],
+    ) -> Option<Vec<Partition>> {
+        let footprints = assigns_bundle
+            .iter()
+            .map(|x| footprint(evaluator, x))
+            .collect::<Option<Vec<_>>>()?;
+
+        // ports which are only read have the same value throughout
+        // convergence, so they do not connect anything. The same holds for
+        // primitives none of whose ports are written, which are converged as
+        // part of every partition that reads them instead.
+        let mut written = HashSet::new();
+        for footprint in footprints.iter() {
+            written.extend(footprint.writes.iter().map(|&x| self.node(x)));
+        }
+
+        let mut parents = (0..assigns_bundle.len()).collect_vec();
+        let mut first_use: HashMap<Node, usize> = HashMap::new();
+        for (idx, footprint) in footprints.iter().enumerate() {
+            for &port in footprint.reads.iter().chain(&footprint.writes) {
+                let node = self.node(port);
+                if !written.contains(&node) {
+                    continue;
+                }
+                match first_use.entry(node) {
+                    Entry::Occupied(entry) => {
+                        union(&mut parents, idx, *entry.get())
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(idx);
+                    }
+                }
+            }
+        }
+
+        let mut partitions: Vec<Partition> = vec![];
+        let mut partition_of = HashMap::new();
+        for (idx, footprint) in footprints.into_iter().enumerate() {
+            let root = find(&mut parents, idx);
+            let partition = *partition_of.entry(root).or_insert_with(|| {
+                partitions.push(Partition::default());
+                partitions.len() - 1
+            });
+            let partition = &mut partitions[partition];
+            partition.bundles.push(idx);
+            for port in footprint.reads.into_iter().chain(footprint.writes) {
+                match self.node(port) {
+                    Node::Primitive(cell) => partition.primitives.push(cell),
+                    Node::Port(port) => partition.ports.push(port),
+                }
+            }
+        }
+
+        if partitions.len() < 2 {
+            return None;
+        }
+
+        for partition in partitions.iter_mut() {
+            // run the primitives in the same order as the sequential mode
+            partition.primitives.sort();
+            partition.primitives.dedup();
+            for cell in partition.primitives.iter() {
+                partition.ports.extend(&self.primitive_ports[cell]);
+            }
+        }
+
+        Some(partitions)
+    }
+}
+
+/// Collects the ports used by the given scheduled assignments. Returns `None`
+/// if a ref port is not bound to a cell.
+fn footprint(
+    evaluator: AssignmentEvaluator,
+    scheduled: &ScheduledAssignments,
+) -> Option<Footprint> {
+    let comp = scheduled.active_cell;
+    let mut footprint = Footprint::default();
+
+    if let Some((go, done)) = evaluator.get_interface_ports(scheduled) {
+        footprint.reads.push(go);
+        footprint.reads.push(evaluator.get_comp_go(comp));
+        footprint.writes.push(done);
+    }
+
+    for assign_idx in scheduled.assignments.iter() {
+        let assign = &evaluator.ctx.primary[assign_idx];
+        footprint
+            .reads
+            .push(evaluator.try_global_port_idx(&assign.src, comp)?);
+        footprint
+            .writes
+            .push(evaluator.try_global_port_idx(&assign.dst, comp)?);
+        guard_ports(evaluator, assign.guard, comp, &mut footprint.reads)?;
+    }
+
+    Some(footprint)
+}
+
+fn guard_ports(
+    evaluator: AssignmentEvaluator,
+    guard: GuardIdx,
+    comp: GlobalCellIdx,
+    ports: &mut Vec<GlobalPortIdx>,
+) -> Option<()> {
+    match &evaluator.ctx.primary[guard] {
+        Guard::True => {}
+        Guard::Or(a, b) | Guard::And(a, b) => {
+            guard_ports(evaluator, *a, comp, ports)?;
+            guard_ports(evaluator, *b, comp, ports)?;
+        }
+        Guard::Not(n) => guard_ports(evaluator, *n, comp, ports)?,
+        Guard::Comp(_, a, b) => {
+            ports.push(evaluator.try_global_port_idx(a, comp)?);
+            ports.push(evaluator.try_global_port_idx(b, comp)?);
+        }
+        Guard::Port(p) => ports.push(evaluator.try_global_port_idx(p, comp)?),
+    }
+    Some(())
+}
+
+fn find(parents: &mut [usize], mut idx: usize) -> usize {
+    while parents[idx] != idx {
+        parents[idx] = parents[parents[idx]];
+        idx = parents[idx];
+    }
+    idx
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    // the smallest index is always the root so partitions are ordered by
+    // their first scheduled assignments
+    parents[a.max(b)] = a.min(b);
+}
+
+/// Converges the active assignments of a simulation, in parallel if it has
+/// been given more than one thread
+#[derive(Debug)]
+pub(super) struct ParallelConvergence {
+    threads: usize,
+    /// Every primitive cell in order. Computed on first use
+    primitives: Option<Vec<GlobalCellIdx>>,
+    /// Computed the first time convergence runs in parallel
+    layout: Option<PortLayout>,
+    /// A private port map for every worker thread
+    workers: Vec<PortMap>,
+}
+
+impl ParallelConvergence {
+    pub(super) fn new() -> Self {
+        Self {
+            threads: 1,
+            primitives: None,
+            layout: None,
+            workers: vec![],
+        }
+    }
+
+    pub(super) fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+        self.workers.truncate(self.threads);
+    }
+
+    pub(super) fn converge(
+        &mut self,
+        evaluator: AssignmentEvaluator,
+        ports: &mut PortMap,
+        assigns_bundle: &[ScheduledAssignments],
+    ) -> Result<(), ConvergenceError> {
+        let Self {
+            threads,
+            primitives,
+            layout,
+            workers,
+        } = self;
+
+        let primitives = primitives.get_or_insert_with(|| {
+            evaluator
+                .cells
+                .iter()
+                .filter_map(|(idx, cell)| cell.as_primitive().map(|_| idx))
+                .collect()
+        });
+
+        if *threads > 1 && assigns_bundle.len() > 1 {
+            let layout =
+                layout.get_or_insert_with(|| PortLayout::new(evaluator, ports));
+
+            if let Some(partitions) =
+                layout.partition(evaluator, assigns_bundle)
+            {
+                let worker_count = (*threads).min(partitions.len());
+                while workers.len() < worker_count {
+                    let mut map = PortMap::with_capacity(ports.len());
+                    for _ in 0..ports.len() {
+                        map.push(PortValue::new_undef());
+                    }
+                    workers.push(map);
+                }
+
+                converge_partitions(
+                    evaluator,
+                    ports,
+                    assigns_bundle,
+                    &partitions,
+                    &mut workers[..worker_count],
+                )?;
+
+                // the remaining primitives are not connected to any active
+                // assignment but may still drive their outputs
+                let touched: HashSet<_> = partitions
+                    .iter()
+                    .flat_map(|x| x.primitives.iter().copied())
+                    .collect();
+                let rest = primitives
+                    .iter()
+                    .copied()
+                    .filter(|x| !touched.contains(x))
+                    .collect_vec();
+                return evaluator.converge(ports, &[], &rest);
+            }
+        }
+
+        let assigns_bundle = assigns_bundle.iter().collect_vec();
+        evaluator.converge(ports, &assigns_bundle, primitives)
+    }
+}
+
+/// Converges every partition on one of the workers and copies the results back
+/// into the given port map. If several partitions fail, the error of the first
+/// one is returned.
+fn converge_partitions(
+    evaluator: AssignmentEvaluator,
+    ports: &mut PortMap,
+    assigns_bundle: &[ScheduledAssignments],
+    partitions: &[Partition],
+    workers: &mut [PortMap],
+) -> Result<(), ConvergenceError> {
+    let worker_count = workers.len();
+    let shared: &PortMap = ports;
+
+    let failures = std::thread::scope(|scope| {
+        let handles = workers
+            .iter_mut()
+            .enumerate()
+            .map(|(worker, map)| {
+                scope.spawn(move || {
+                    for (idx, partition) in partitions
+                        .iter()
+                        .enumerate()
+                        .skip(worker)
+                        .step_by(worker_count)
+                    {
+                        for &port in partition.ports.iter() {
+                            map[port] = shared[port].clone();
+                        }
+                        let bundles = partition
+                            .bundles
+                            .iter()
+                            .map(|&x| &assigns_bundle[x])
+                            .collect_vec();
+
+                        if let Err(err) = evaluator.converge(
+                            map,
+                            &bundles,
+                            &partition.primitives,
+                        ) {
+                            return Some((idx, err));
+                        }
+                    }
+                    None
+                })
+            })
+            .collect_vec();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect_vec()
+    });
+
+    for (idx, partition) in partitions.iter().enumerate() {
+        let map = &workers[idx % worker_count];
+        for &port in partition.ports.iter() {
+            ports[port] = map[port].clone();
+        }
+    }
+
+    match failures.into_iter().min_by_key(|(idx, _)| *idx) {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
@@ -465,6 +465,10 @@ impl ProgramCounter {
         &self.with_map
     }
 
+    pub(crate) fn repeat_map(&self) -> &HashMap<ControlPoint, u64> {
+        &self.repeat_map
+    }
+
     pub fn set_finshed_comp(&mut self, comp: GlobalCellIdx) {
         self.just_finished_comps.push(comp)
     }
@@ -476,6 +480,10 @@ impl ProgramCounter {
     pub fn clear_finished_comps(&mut self) {
         self.just_finished_comps.clear()
     }
+
+    pub(crate) fn set_finished_comps(&mut self, comps: Vec<GlobalCellIdx>) {
+        self.just_finished_comps = comps
+    }
 }
 
 impl<'a> IntoIterator for &'a ProgramCounter {
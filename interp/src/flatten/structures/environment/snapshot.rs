@@ -0,0 +1,415 @@
+//! Snapshots of the complete state of a simulation.
+//!
+//! A snapshot contains the values of all ports, the bindings of ref cells and
+//! ports, the pinned inputs, the program counter, the cycle count and the
+//! internal state of every primitive, such as the stages of a pipelined
+//! multiplier. The contents of memories and registers are stored as a
+//! [`DataDump`] which names every cell by its full path. Restoring a snapshot
+//! into a simulator of the same program continues the simulation exactly where
+//! it was saved.
+//!
+//! The file format is a magic number, the length of the CBOR encoded state as
+//! a little-endian `u32`, the state itself and finally the serialized
+//! [`DataDump`].
+use super::{
+    env::{CellLedger, Environment},
+    program_counter::{ChildCount, ControlPoint, WithEntry},
+};
+use crate::{
+    errors::{InterpreterError, InterpreterResult},
+    flatten::{
+        flat_ir::{
+            base::{AssignmentWinner, PortValue},
+            prelude::{
+                AssignedValue, AssignmentIdx, CombGroupIdx, ControlIdx,
+                GlobalCellIdx, GlobalPortIdx,
+            },
+        },
+        primitives::prim_trait::{PrimitiveState, StoredValue},
+        structures::{context::Context, index_trait::IndexRef},
+    },
+    serialization::{DataDump, Dimensions, MemoryDeclaration},
+    values::Value,
+};
+use ahash::HashMap;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum StoredWinner {
+    Cell,
+    Implicit,
+    Assign(usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredPort {
+    value: StoredValue,
+    winner: StoredWinner,
+}
+
+impl From<&AssignedValue> for StoredPort {
+    fn from(value: &AssignedValue) -> Self {
+        let winner = match value.winner() {
+            AssignmentWinner::Cell => StoredWinner::Cell,
+            AssignmentWinner::Implicit => StoredWinner::Implicit,
+            AssignmentWinner::Assign(idx) => StoredWinner::Assign(idx.index()),
+        };
+        Self {
+            value: value.val().into(),
+            winner,
+        }
+    }
+}
+
+impl From<&StoredPort> for AssignedValue {
+    fn from(port: &StoredPort) -> Self {
+        let value: Value = (&port.value).into();
+        match port.winner {
+            StoredWinner::Cell => AssignedValue::cell_value(value),
+            StoredWinner::Implicit => AssignedValue::implicit_value(value),
+            StoredWinner::Assign(idx) => {
+                AssignedValue::new(value, AssignmentIdx::new(idx))
+            }
+        }
+    }
+}
+
+/// A [`ControlPoint`] as the indices of its component instance and control
+/// node
+type StoredControlPoint = (usize, usize);
+
+fn store_point(point: &ControlPoint) -> StoredControlPoint {
+    (point.comp.index(), point.control_node_idx.index())
+}
+
+fn load_point(point: &StoredControlPoint) -> ControlPoint {
+    ControlPoint::new(GlobalCellIdx::new(point.0), ControlIdx::new(point.1))
+}
+
+/// Everything but the contents of memories and registers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SimulatorState {
+    /// The name of the entrypoint component and the number of ports, cells,
+    /// ref cells and ref ports, which identify the program
+    entrypoint: String,
+    port_count: usize,
+    cell_count: usize,
+    ref_cell_count: usize,
+    ref_port_count: usize,
+
+    cycle: u64,
+    drive_root_go: bool,
+    ports: Vec<Option<StoredPort>>,
+    ref_cells: Vec<Option<usize>>,
+    ref_ports: Vec<Option<usize>>,
+    pinned_ports: Vec<(usize, StoredValue)>,
+
+    control_points: Vec<StoredControlPoint>,
+    par_map: Vec<(StoredControlPoint, ChildCount)>,
+    /// The active comb groups and whether the body has been entered
+    with_map: Vec<(StoredControlPoint, usize, bool)>,
+    repeat_map: Vec<(StoredControlPoint, u64)>,
+    finished_comps: Vec<usize>,
+
+    /// The state of every primitive which has any
+    primitives: Vec<(usize, PrimitiveState)>,
+    memory_header: Option<Vec<MemoryDeclaration>>,
+}
+
+/// A snapshot of the complete state of a simulation, see
+/// [`Simulator::save_snapshot`](super::Simulator::save_snapshot)
+#[derive(Debug)]
+pub struct Snapshot {
+    state: SimulatorState,
+    memories: DataDump,
+}
+
+impl Snapshot {
+    /// Magic number to identify a snapshot file
+    const MAGIC_NUMBER: [u8; 4] = [216, 194, 228, 83];
+
+    /// The number of cycles which had been simulated when the snapshot was
+    /// taken
+    pub fn cycle(&self) -> u64 {
+        self.state.cycle
+    }
+
+    pub fn serialize(&self, writer: &mut dyn Write) -> InterpreterResult<()> {
+        let mut state = Vec::new();
+        ciborium::ser::into_writer(&self.state, &mut state).map_err(|err| {
+            InterpreterError::InvalidSnapshot(err.to_string())
+        })?;
+        let len: u32 = state
+            .len()
+            .try_into()
+            .expect("Snapshot state length cannot fit in u32");
+
+        writer.write_all(&Self::MAGIC_NUMBER)?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(&state)?;
+        self.memories.serialize(writer)?;
+        Ok(())
+    }
+
+    pub fn deserialize(reader: &mut dyn Read) -> InterpreterResult<Self> {
+        let invalid = |reason: &str| {
+            InterpreterError::InvalidSnapshot(reason.to_string())
+        };
+
+        let mut magic_number = [0u8; 4];
+        reader
+            .read_exact(&mut magic_number)
+            .map_err(|_| invalid("the file is not a snapshot"))?;
+        if magic_number != Self::MAGIC_NUMBER {
+            return Err(invalid("the file is not a snapshot").into());
+        }
+
+        let mut len = [0u8; 4];
+        reader
+            .read_exact(&mut len)
+            .map_err(|_| invalid("the file is truncated"))?;
+        let mut state = vec![0u8; u32::from_le_bytes(len) as usize];
+        reader
+            .read_exact(&mut state)
+            .map_err(|_| invalid("the file is truncated"))?;
+        let state = ciborium::from_reader(state.as_slice()).map_err(|err| {
+            InterpreterError::InvalidSnapshot(err.to_string())
+        })?;
+
+        let memories = DataDump::deserialize(reader)?;
+        Ok(Self { state, memories })
+    }
+
+    /// Captures the state of the given environment
+    pub(super) fn capture<C: AsRef<Context> + Clone>(
+        env: &Environment<C>,
+        cycle: u64,
+        drive_root_go: bool,
+    ) -> Self {
+        let ctx = env.ctx();
+        let entrypoint = ctx.resolve_id(ctx.secondary[ctx.entry_point].name);
+        let pc = &env.pc;
+
+        let state = SimulatorState {
+            entrypoint: entrypoint.clone(),
+            port_count: env.ports.len(),
+            cell_count: env.cells.len(),
+            ref_cell_count: env.ref_cells.len(),
+            ref_port_count: env.ref_ports.len(),
+            cycle,
+            drive_root_go,
+            ports: env
+                .ports
+                .iter()
+                .map(|(_, port)| port.as_option().map(StoredPort::from))
+                .collect(),
+            ref_cells: env
+                .ref_cells
+                .iter()
+                .map(|(_, cell)| cell.map(|c| c.index()))
+                .collect(),
+            ref_ports: env
+                .ref_ports
+                .iter()
+                .map(|(_, port)| port.map(|p| p.index()))
+                .collect(),
+            pinned_ports: env
+                .pinned_ports
+                .iter()
+                .map(|(port, val)| (port.index(), val.into()))
+                .collect(),
+            control_points: pc.iter().map(store_point).collect(),
+            par_map: pc
+                ._par_map()
+                .iter()
+                .map(|(point, count)| (store_point(point), *count))
+                .collect(),
+            with_map: pc
+                .with_map()
+                .iter()
+                .map(|(point, entry)| {
+                    (store_point(point), entry.group.index(), entry.entered)
+                })
+                .collect(),
+            repeat_map: pc
+                .repeat_map()
+                .iter()
+                .map(|(point, count)| (store_point(point), *count))
+                .collect(),
+            finished_comps: pc
+                .finished_comps()
+                .iter()
+                .map(|comp| comp.index())
+                .collect(),
+            primitives: env
+                .cells
+                .iter()
+                .filter_map(|(idx, cell)| {
+                    let state = cell.as_primitive()?.save_state();
+                    (!state.is_empty()).then(|| (idx.index(), state))
+                })
+                .collect(),
+            memory_header: env.memory_header.clone(),
+        };
+
+        let mut memories =
+            DataDump::new_empty_with_top_level(entrypoint.clone());
+        for (idx, contents) in memory_cells(env) {
+            let width = contents.first().map_or(0, |v| v.width() as u32);
+            memories.push_memory(
+                MemoryDeclaration::new_bitnum(
+                    env.get_full_name(idx),
+                    width,
+                    Dimensions::D1(contents.len()),
+                    false,
+                ),
+                contents.iter().flat_map(|v| v.to_bytes()),
+            );
+        }
+
+        Self { state, memories }
+    }
+
+    /// Overwrites the state of the given environment with the snapshot and
+    /// returns the cycle count and whether the simulator drives the go port
+    /// of the entrypoint
+    pub(super) fn apply<C: AsRef<Context> + Clone>(
+        &self,
+        env: &mut Environment<C>,
+    ) -> InterpreterResult<(u64, bool)> {
+        let invalid =
+            |reason: String| InterpreterError::InvalidSnapshot(reason).into();
+        let state = &self.state;
+
+        let ctx = env.ctx();
+        let entrypoint = ctx.resolve_id(ctx.secondary[ctx.entry_point].name);
+        if state.entrypoint != *entrypoint
+            || state.port_count != env.ports.len()
+            || state.cell_count != env.cells.len()
+            || state.ref_cell_count != env.ref_cells.len()
+            || state.ref_port_count != env.ref_ports.len()
+        {
+            return Err(invalid(format!(
+                "it was taken from a different program with entrypoint `{}'",
+                state.entrypoint
+            )));
+        }
+
+        // the memories are checked first so that a malformed snapshot does not
+        // leave the environment half restored
+        let cells = memory_cells(env)
+            .map(|(idx, _)| (env.get_full_name(idx), idx))
+            .collect::<HashMap<_, _>>();
+        let mut memories =
+            Vec::with_capacity(self.memories.header.memories.len());
+        for declaration in &self.memories.header.memories {
+            let idx = *cells.get(&declaration.name).ok_or_else(|| {
+                invalid(format!("unknown memory `{}'", declaration.name))
+            })?;
+            let data = self.memories.get_data(&declaration.name).unwrap();
+            let width = declaration.width();
+            let values = data
+                .chunks_exact(width.div_ceil(8) as usize)
+                .map(|bytes| Value::from_bytes_le(bytes, width as usize))
+                .collect::<Vec<_>>();
+            memories.push((idx, declaration, values));
+        }
+
+        for (idx, declaration, values) in memories {
+            let CellLedger::Primitive { cell_dyn } = &mut env.cells[idx] else {
+                unreachable!("memories are primitives")
+            };
+            let contents = cell_dyn.memory_contents_mut().unwrap();
+            if contents.len() != values.len()
+                || contents.first().map(|v| v.width())
+                    != values.first().map(|v| v.width())
+            {
+                return Err(invalid(format!(
+                    "the shape of memory `{}' does not match",
+                    declaration.name
+                )));
+            }
+            contents.clone_from_slice(&values);
+        }
+
+        for (idx, prim_state) in &state.primitives {
+            let idx = GlobalCellIdx::new(*idx);
+            let name = env.get_full_name(idx);
+            match &mut env.cells[idx] {
+                CellLedger::Primitive { cell_dyn } => {
+                    cell_dyn.restore_state(prim_state).map_err(|_| {
+                        invalid(format!("the state of `{name}' is malformed"))
+                    })?
+                }
+                CellLedger::Component(_) => {
+                    return Err(invalid(format!("`{name}' is not a primitive")))
+                }
+            }
+        }
+
+        for ((_, port), stored) in env.ports.iter_mut().zip(&state.ports) {
+            *port = PortValue::new(stored.as_ref().map(AssignedValue::from));
+        }
+        for ((_, cell), stored) in
+            env.ref_cells.iter_mut().zip(&state.ref_cells)
+        {
+            *cell = stored.map(GlobalCellIdx::new);
+        }
+        for ((_, port), stored) in
+            env.ref_ports.iter_mut().zip(&state.ref_ports)
+        {
+            *port = stored.map(GlobalPortIdx::new);
+        }
+        env.pinned_ports.clear();
+        for (port, val) in &state.pinned_ports {
+            env.pinned_ports
+                .insert(GlobalPortIdx::new(*port), val.into());
+        }
+
+        let control_points =
+            state.control_points.iter().map(load_point).collect();
+        let par_map = state
+            .par_map
+            .iter()
+            .map(|(point, count)| (load_point(point), *count))
+            .collect();
+        let with_map = state
+            .with_map
+            .iter()
+            .map(|(point, group, entered)| {
+                let mut entry = WithEntry::new(CombGroupIdx::new(*group));
+                if *entered {
+                    entry.set_entered();
+                }
+                (load_point(point), entry)
+            })
+            .collect();
+        let repeat_map = state
+            .repeat_map
+            .iter()
+            .map(|(point, count)| (load_point(point), *count))
+            .collect();
+        env.pc
+            .restore_fields((control_points, par_map, with_map, repeat_map));
+        env.pc.set_finished_comps(
+            state
+                .finished_comps
+                .iter()
+                .map(|comp| GlobalCellIdx::new(*comp))
+                .collect(),
+        );
+        env.memory_header = state.memory_header.clone();
+
+        Ok((state.cycle, state.drive_root_go))
+    }
+}
+
+/// The memories and registers of the environment along with their contents
+fn memory_cells<C: AsRef<Context> + Clone>(
+    env: &Environment<C>,
+) -> impl Iterator<Item = (GlobalCellIdx, &[crate::values::Value])> + '_ {
+    env.cells.iter().filter_map(|(idx, cell)| {
+        Some((idx, cell.as_primitive()?.memory_contents()?))
+    })
+}
@@ -0,0 +1,212 @@
+//! Checks for undefined values (X) which reach places where they are likely
+//! to cause trouble once the program is synthesized.
+//!
+//! Cider models X as an undefined port value. Most undefined values are
+//! harmless, e.g. the inputs of a cell that no active assignment drives, but
+//! an undefined value that is latched by a register or memory, used as a
+//! memory address, used in a guard or driven onto a done signal means that
+//! the hardware may behave differently from the simulation. With X checks
+//! enabled, these cases are reported after every convergence along with the
+//! assignment responsible and its position in the source program.
+use super::{
+    assignments::ScheduledAssignments,
+    env::{AssignmentEvaluator, CellLedger},
+    PortMap,
+};
+use crate::flatten::{
+    flat_ir::prelude::{AssignmentIdx, GlobalCellIdx, GlobalPortIdx},
+    primitives::prim_trait::LatchedInput,
+};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use std::{fmt::Display, str::FromStr};
+
+/// How pessimistically undefined values propagate through primitives and
+/// guards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum XPessimism {
+    /// Any undefined input makes the output undefined. This is how Cider
+    /// evaluates programs without X checks.
+    #[default]
+    Pessimistic,
+    /// An output is only undefined if its value depends on an undefined input.
+    /// For example, `0 & X` is `0`, `1 | X` is `1` and a `std_mux` whose
+    /// inputs agree produces their value even if its condition is undefined.
+    /// This is closer to what synthesized hardware does, but can hide some
+    /// problems.
+    Precise,
+}
+
+impl FromStr for XPessimism {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pessimistic" => Ok(XPessimism::Pessimistic),
+            "precise" => Ok(XPessimism::Precise),
+            _ => Err(format!(
+                "unknown X pessimism \"{s}\". Expected \"pessimistic\" or \"precise\""
+            )),
+        }
+    }
+}
+
+/// Where an undefined value was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XSink {
+    /// The guard of an active assignment
+    Guard,
+    /// The done signal of a group or component
+    Done,
+    /// A value latched by a register or memory, e.g. the input of a register
+    /// whose `write_en` is high
+    Input,
+    /// The address of a memory which is being accessed
+    Address,
+}
+
+impl From<LatchedInput> for XSink {
+    fn from(input: LatchedInput) -> Self {
+        match input {
+            LatchedInput::Data => XSink::Input,
+            LatchedInput::Address => XSink::Address,
+        }
+    }
+}
+
+/// An undefined value found by the X checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XReport {
+    /// The clock cycle in which the value was found, starting from zero
+    pub cycle: u64,
+    pub sink: XSink,
+    /// The full name of the port the undefined value reaches
+    pub port: String,
+    /// The assignment whose guard is undefined or which drives the undefined
+    /// value, if any
+    pub assignment: Option<String>,
+    /// The position of the assignment in the source program as
+    /// `file:line`, if it is known
+    pub position: Option<String>,
+}
+
+impl Display for XReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cycle {}: undefined value reaches ", self.cycle)?;
+        match self.sink {
+            XSink::Guard => write!(f, "the guard of an assignment to")?,
+            XSink::Done => write!(f, "the done signal")?,
+            XSink::Input => write!(f, "the latched input")?,
+            XSink::Address => write!(f, "the address")?,
+        }
+        write!(f, " {}", self.port)?;
+
+        match (&self.assignment, self.sink) {
+            (Some(assign), _) => write!(f, " in `{assign}`")?,
+            (None, XSink::Input | XSink::Address) => {
+                write!(f, ", which no active assignment drives")?
+            }
+            (None, _) => {}
+        }
+        if let Some(position) = &self.position {
+            write!(f, " at {position}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An undefined value found in the port map, before it is given names
+pub(super) struct XFinding {
+    pub sink: XSink,
+    pub port: GlobalPortIdx,
+    /// The assignment responsible and the component instance it belongs to
+    pub assignment: Option<(GlobalCellIdx, AssignmentIdx)>,
+}
+
+/// The state of the X checks of a simulator
+pub(super) struct XChecks {
+    pub pessimism: XPessimism,
+    /// Findings which have already been reported. Every problem is only
+    /// reported the first time it occurs.
+    seen: HashSet<(XSink, GlobalPortIdx, Option<AssignmentIdx>)>,
+    pub reports: Vec<XReport>,
+}
+
+impl XChecks {
+    pub fn new(pessimism: XPessimism) -> Self {
+        Self {
+            pessimism,
+            seen: HashSet::new(),
+            reports: vec![],
+        }
+    }
+
+    /// Returns true if the finding has not been reported before
+    pub fn is_new(&mut self, finding: &XFinding) -> bool {
+        self.seen.insert((
+            finding.sink,
+            finding.port,
+            finding.assignment.map(|(_, assign)| assign),
+        ))
+    }
+}
+
+/// Finds the undefined values in the converged port map which reach a guard or
+/// done signal of the given assignments or a latched input of a primitive
+pub(super) fn find_undefined(
+    evaluator: &AssignmentEvaluator,
+    ports: &PortMap,
+    assigns_bundle: &[ScheduledAssignments],
+) -> Vec<XFinding> {
+    let mut findings = vec![];
+    // the assignments which drive an undefined value onto a port
+    let mut drivers = HashMap::new();
+
+    evaluator.for_each_active_assignment(
+        ports,
+        assigns_bundle,
+        |scheduled, assign_idx, dest, is_done| {
+            let active_cell = scheduled.active_cell;
+            let assign = &evaluator.ctx.primary[assign_idx];
+            let guard =
+                evaluator.evaluate_guard(ports, assign.guard, active_cell);
+            let src = evaluator.get_global_port_idx(&assign.src, active_cell);
+            let assignment = Some((active_cell, assign_idx));
+            match guard {
+                None => findings.push(XFinding {
+                    sink: if is_done { XSink::Done } else { XSink::Guard },
+                    port: dest,
+                    assignment,
+                }),
+                Some(true) if ports[src].is_undef() => {
+                    if is_done {
+                        findings.push(XFinding {
+                            sink: XSink::Done,
+                            port: dest,
+                            assignment,
+                        })
+                    } else {
+                        drivers.insert(dest, (active_cell, assign_idx));
+                    }
+                }
+                Some(_) => {}
+            }
+        },
+    );
+
+    for (_, cell) in evaluator.cells.iter() {
+        let CellLedger::Primitive { cell_dyn } = cell else {
+            continue;
+        };
+        for (port, input) in cell_dyn.latched_inputs(ports) {
+            if ports[port].is_undef() {
+                findings.push(XFinding {
+                    sink: input.into(),
+                    port,
+                    assignment: drivers.get(&port).copied(),
+                })
+            }
+        }
+    }
+
+    findings
+}
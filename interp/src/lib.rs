@@ -2,6 +2,7 @@ mod as_raw;
 pub mod configuration;
 pub mod debugger;
 pub mod errors;
+pub mod ffi;
 pub mod logging;
 mod macros;
 pub mod serialization;
@@ -7,11 +7,15 @@ use interp::{
     configuration,
     debugger::{Debugger, DebuggerInfo, DebuggerReturnStatus},
     errors::InterpreterResult,
-    flatten::structures::environment::Simulator,
+    flatten::structures::{
+        context::Context,
+        environment::{Simulator, Snapshot, XPessimism},
+    },
 };
 
 use slog::warn;
 use std::{
+    fs::File,
     io::stdout,
     path::{Path, PathBuf},
 };
@@ -68,6 +72,44 @@ pub struct Opts {
     #[argh(switch, long = "all-memories")]
     dump_all_memories: bool,
 
+    /// simulate the program by compiling it to native code with rustc. The
+    /// program must be fully lowered, e.g. with `-p all`
+    #[argh(switch, long = "compiled")]
+    compiled: bool,
+
+    /// number of threads used to converge independent par arms in parallel.
+    /// Defaults to one, which evaluates everything sequentially
+    #[argh(option, long = "threads", default = "1")]
+    threads: usize,
+
+    /// report undefined values which reach a guard, a done signal, a memory
+    /// address or the input of a register or memory that is being written
+    #[argh(switch, long = "x-checks")]
+    x_checks: bool,
+
+    /// how undefined values propagate through guards and primitives with
+    /// `--x-checks`: "pessimistic" (the default) or "precise"
+    #[argh(option, long = "x-pessimism", default = "XPessimism::Pessimistic")]
+    x_pessimism: XPessimism,
+
+    /// record every read and write of a memory and write the accesses, the
+    /// per-memory address histograms and the port conflicts to this JSON file
+    #[argh(option, long = "trace-memories")]
+    trace_memories: Option<PathBuf>,
+
+    /// save the complete state of the simulation to this file, either once
+    /// `--snapshot-cycle` cycles have been simulated or at the end
+    #[argh(option, long = "save-snapshot")]
+    save_snapshot: Option<PathBuf>,
+
+    /// the cycle at which `--save-snapshot` saves the state of the simulation
+    #[argh(option, long = "snapshot-cycle")]
+    snapshot_cycle: Option<u64>,
+
+    /// resume the simulation from a snapshot saved with `--save-snapshot`
+    #[argh(option, long = "restore-snapshot")]
+    restore_snapshot: Option<PathBuf>,
+
     #[argh(subcommand)]
     mode: Option<Command>,
 }
@@ -120,8 +162,60 @@ fn main() -> InterpreterResult<()> {
     match &command {
         Command::Interpret(_) => {
             let mut sim = Simulator::build_simulator(&i_ctx, &opts.data_file)?;
+            sim.set_threads(opts.threads);
+            if opts.x_checks {
+                if opts.compiled {
+                    warn!(log, "X checks are not supported by the compiled simulation and will be skipped")
+                }
+                sim.enable_x_checks(opts.x_pessimism);
+            }
+            if opts.trace_memories.is_some() {
+                if opts.compiled {
+                    warn!(log, "Memory tracing is not supported by the compiled simulation and will be skipped")
+                }
+                sim.enable_memory_trace();
+            }
 
-            sim.run_program()?;
+            let uses_snapshots =
+                opts.save_snapshot.is_some() || opts.restore_snapshot.is_some();
+            if uses_snapshots && opts.compiled {
+                warn!(log, "Snapshots are not supported by the compiled simulation and will be skipped")
+            } else if opts.snapshot_cycle.is_some()
+                && opts.save_snapshot.is_none()
+            {
+                warn!(
+                    log,
+                    "--snapshot-cycle has no effect without --save-snapshot"
+                )
+            }
+
+            let result = if opts.compiled {
+                sim.run_program_compiled()
+            } else {
+                run_with_snapshots(
+                    &mut sim,
+                    opts.restore_snapshot.as_deref(),
+                    opts.save_snapshot.as_deref(),
+                    opts.snapshot_cycle,
+                )
+            };
+
+            // the trace is written even if the simulation fails since it may
+            // help to find the cause
+            if let (Some(path), Some(trace)) =
+                (&opts.trace_memories, sim.memory_trace())
+            {
+                trace.write_json(File::create(path)?)?;
+                if !trace.conflicts().is_empty() {
+                    warn!(
+                        log,
+                        "Found {} memory port conflicts. See {}",
+                        trace.conflicts().len(),
+                        path.display()
+                    )
+                }
+            }
+            result?;
 
             let output = sim
                 .dump_memories(config.dump_registers, config.dump_all_memories);
@@ -146,3 +240,28 @@ fn main() -> InterpreterResult<()> {
         }
     }
 }
+
+/// Runs the program after restoring the snapshot at `restore`, if any, and
+/// saves a snapshot to `save` once `save_cycle` cycles have been simulated or
+/// the program is done
+fn run_with_snapshots(
+    sim: &mut Simulator<&Context>,
+    restore: Option<&Path>,
+    save: Option<&Path>,
+    save_cycle: Option<u64>,
+) -> InterpreterResult<()> {
+    if let Some(path) = restore {
+        let snapshot = Snapshot::deserialize(&mut File::open(path)?)?;
+        sim.restore_snapshot(&snapshot)?;
+    }
+
+    if let Some(path) = save {
+        let cycle = save_cycle.unwrap_or(u64::MAX);
+        while !sim.is_done() && sim.cycle() < cycle {
+            sim.step()?;
+        }
+        sim.save_snapshot().serialize(&mut File::create(path)?)?;
+    }
+
+    sim.run_program()
+}
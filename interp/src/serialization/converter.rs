@@ -1,12 +1,13 @@
+use half::f16;
 use itertools::Itertools;
-use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_bigint::BigInt;
 use num_rational::BigRational;
-use num_traits::{sign::Signed, Num, ToPrimitive};
+use num_traits::{sign::Signed, ToPrimitive};
 use serde_json::Number;
 use std::{collections::HashMap, iter::repeat, str::FromStr};
 
 use super::json_data::*;
-use interp::serialization::*;
+use crate::serialization::{DataDump, Dimensions, MemoryDeclaration};
 
 fn msb(width: u32) -> u8 {
     let rem = width % 8;
@@ -66,52 +67,11 @@ pub fn convert_to_data_dump(json: &JsonData, round_float: bool) -> DataDump {
         let width = dec.width();
         let signed = dec.signed();
 
-        let data: Box<dyn Iterator<Item = u8>> = match &data_vec {
-            DataVec::Id1(v1) => Box::new(v1.iter().flat_map(|val| {
-                // chopping off the upper bits
-                unroll_bigint(val, width, signed)
-            })),
-            DataVec::Id2(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter().flat_map(|val| unroll_bigint(val, width, signed))
-            })),
-            DataVec::Id3(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter().flat_map(|v3| {
-                    v3.iter().flat_map(|val| unroll_bigint(val, width, signed))
-                })
-            })),
-            DataVec::Id4(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter().flat_map(|v3| {
-                    v3.iter().flat_map(|v4| {
-                        v4.iter()
-                            .flat_map(|val| unroll_bigint(val, width, signed))
-                    })
-                })
-            })),
-            DataVec::Fd1(v1) => Box::new(
-                v1.iter()
-                    .flat_map(|val| unroll_float(*val, &format, round_float)),
-            ),
-            DataVec::Fd2(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter()
-                    .flat_map(|val| unroll_float(*val, &format, round_float))
-            })),
-            DataVec::Fd3(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter().flat_map(|v3| {
-                    v3.iter().flat_map(|val| {
-                        unroll_float(*val, &format, round_float)
-                    })
-                })
-            })),
-            DataVec::Fd4(v1) => Box::new(v1.iter().flat_map(|v2| {
-                v2.iter().flat_map(|v3| {
-                    v3.iter().flat_map(|v4| {
-                        v4.iter().flat_map(|val| {
-                            unroll_float(*val, &format, round_float)
-                        })
-                    })
-                })
-            })),
-        };
+        let data = data_vec.values.iter().flat_map(|val| match val {
+            ParsedNumber::Int(val) => unroll_bigint(val, width, signed),
+            ParsedNumber::Fixed(val) => unroll_fixed(val, &format, round_float),
+            ParsedNumber::Float(val) => unroll_ieee(*val, width),
+        });
 
         data_dump.push_memory(dec, data)
     }
@@ -120,111 +80,63 @@ pub fn convert_to_data_dump(json: &JsonData, round_float: bool) -> DataDump {
 }
 
 #[inline]
-fn unroll_bigint(
-    val: &BigInt,
-    width: u32,
-    signed: bool,
-) -> std::iter::Take<std::vec::IntoIter<u8>> {
-    sign_extend_vec(val.to_signed_bytes_le(), width, signed)
-        .into_iter()
-        .take(width.div_ceil(8) as usize)
-}
-
-/// This is so so so stupid. unfortunately, using the `BigRational` type's
-/// to_f64 method results in some rounding behavior which creates very confusing
-/// errors (demanding more precision) so this is a workaround.
-fn float_to_rational(float: f64) -> BigRational {
-    let string = format!("{:.}", float);
-    let string = string.split('.').collect_vec();
-
-    if string.len() == 1 {
-        return BigRational::from_integer(
-            BigInt::from_str_radix(string[0], 10).unwrap(),
-        );
-    }
-
-    let is_neg = string[0].starts_with('-');
-
-    let int = BigInt::from_str_radix(
-        string[0].strip_prefix('-').unwrap_or(string[0]),
-        10,
-    )
-    .unwrap();
-    let frac = BigInt::from_str_radix(string[1], 10).unwrap();
-    let denom = BigInt::from(10).pow(string[1].len() as u32);
-
-    let result = BigRational::from_integer(int) + BigRational::new(frac, denom);
-    if is_neg {
-        -result
-    } else {
-        result
-    }
+fn unroll_bigint(val: &BigInt, width: u32, signed: bool) -> Vec<u8> {
+    // chopping off the upper bits
+    let mut bytes = sign_extend_vec(val.to_signed_bytes_le(), width, signed);
+    bytes.truncate(width.div_ceil(8) as usize);
+    bytes
 }
 
-fn unroll_float(
-    val: f64,
-    format: &interp::serialization::FormatInfo,
+fn unroll_fixed(
+    val: &BigRational,
+    format: &crate::serialization::FormatInfo,
     round_float: bool,
-) -> impl Iterator<Item = u8> {
-    if let &interp::serialization::FormatInfo::Fixed {
+) -> Vec<u8> {
+    let &crate::serialization::FormatInfo::Fixed {
         signed,
         int_width,
         frac_width,
     } = format
-    {
-        let rational = float_to_rational(val);
-
-        let frac_part = rational.fract().abs();
-        let frac_log = log2_exact(&frac_part.denom().to_biguint().unwrap());
-
-        let number = if frac_log.is_none() && round_float {
-            let w = BigInt::from(1) << frac_width;
-            let new = (val * w.to_f64().unwrap()).round();
-            new.to_bigint().unwrap()
-        } else if frac_log.is_none() {
-            panic!("Number {val} cannot be represented as a fixed-point number. If you want to approximate the number, set the `round_float` flag to true.");
-        } else {
-            let int_part = rational.to_integer();
-
-            let frac_log = frac_log.unwrap_or_else(|| panic!("unable to round the given value to a value representable with {frac_width} fractional bits"));
-            if frac_log > frac_width {
-                panic!("cannot represent value with {frac_width} fractional bits, requires at least {frac_log} bits");
-            }
-
-            let mut int_log =
-                log2_round_down(&int_part.abs().to_biguint().unwrap());
-            if (BigInt::from(1) << int_log) <= int_part.abs() {
-                int_log += 1;
-            }
-            if signed {
-                int_log += 1;
-            }
-
-            if int_log > int_width {
-                let signed_str = if signed { "signed " } else { "" };
-
-                panic!("cannot represent {signed_str}value of {val} with {int_width} integer bits, requires at least {int_log} bits");
-            }
-
-            rational.numer() << (frac_width - frac_log)
-        };
-
-        let bit_count = number.bits() + if signed { 1 } else { 0 };
-
-        if bit_count > (frac_width + int_width) as u64 {
-            let difference = bit_count - frac_width as u64;
-            panic!("The approximation of the number {val} cannot be represented with {frac_width} fractional bits and {int_width} integer bits. Requires at least {difference} integer bits.");
-        }
+    else {
+        panic!("Called unroll_fixed on a non-fixed point type");
+    };
+    // only used for error messages
+    let approx = val.to_f64().unwrap_or(f64::NAN);
+
+    let scaled = val * BigRational::from_integer(BigInt::from(1) << frac_width);
+    let number = if scaled.is_integer() {
+        scaled.to_integer()
+    } else if round_float {
+        scaled.round().to_integer()
+    } else {
+        panic!("Number {approx} cannot be represented as a fixed-point number with {frac_width} fractional bits. If you want to approximate the number, set the `round_float` flag to true.");
+    };
 
-        sign_extend_vec(
-            number.to_signed_bytes_le(),
-            frac_width + int_width,
-            signed,
+    let width = int_width + frac_width;
+    let (min, max) = if signed {
+        (
+            -(BigInt::from(1) << (width - 1)),
+            BigInt::from(1) << (width - 1),
         )
-        .into_iter()
-        .take((frac_width + int_width).div_ceil(8) as usize)
     } else {
-        panic!("Called unroll_float on a non-fixed point type");
+        (BigInt::ZERO, BigInt::from(1) << width)
+    };
+    if number < min || number >= max {
+        let signed_str = if signed { "signed " } else { "" };
+        panic!("cannot represent {signed_str}value of {approx} with {int_width} integer bits and {frac_width} fractional bits");
+    }
+
+    unroll_bigint(&number, width, signed)
+}
+
+/// The little-endian encoding of the given value, which must be exactly
+/// representable in the IEEE-754 format of the given width.
+fn unroll_ieee(val: f64, width: u32) -> Vec<u8> {
+    match width {
+        16 => f16::from_f64(val).to_le_bytes().to_vec(),
+        32 => (val as f32).to_le_bytes().to_vec(),
+        64 => val.to_le_bytes().to_vec(),
+        _ => panic!("IEEE-754 floats must be 16, 32 or 64 bits wide"),
     }
 }
 
@@ -258,28 +170,71 @@ fn parse_bytes_fixed(
     BigRational::new(int.clone(), BigInt::from(1) << frac_width)
 }
 
+fn parse_bytes_ieee(bytes: &[u8], width: u32) -> f64 {
+    match width {
+        16 => f16::from_le_bytes(bytes.try_into().unwrap()).to_f64(),
+        32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        _ => panic!("IEEE-754 floats must be 16, 32 or 64 bits wide"),
+    }
+}
+
+/// The exact decimal representation of a fixed-point value. This always
+/// exists since the denominator is a power of two.
+fn fixed_to_decimal(val: &BigRational) -> String {
+    let frac_digits = (val.denom().bits() - 1) as usize;
+    debug_assert_eq!(*val.denom(), BigInt::from(1) << frac_digits);
+
+    // n / 2^k = (n * 5^k) / 10^k
+    let digits = (val.numer().abs() * BigInt::from(5).pow(frac_digits as u32))
+        .to_str_radix(10);
+    let digits = format!("{digits:0>width$}", width = frac_digits + 1);
+    let (int, frac) = digits.split_at(digits.len() - frac_digits);
+    let frac = if frac.is_empty() { "0" } else { frac };
+
+    let sign = if val.is_negative() { "-" } else { "" };
+    format!("{sign}{int}.{frac}")
+}
+
+/// The shortest decimal representation that parses back to the same value in
+/// the IEEE-754 format of the given width.
+fn float_to_decimal(val: f64, width: u32) -> String {
+    if width == 64 {
+        format!("{val:?}")
+    } else {
+        format!("{:?}", val as f32)
+    }
+}
+
 fn format_data(declaration: &MemoryDeclaration, data: &[u8]) -> ParseVec {
     let width = declaration.width();
 
     let chunk_stream =
         data.chunks_exact(width.div_ceil(8) as usize).map(|chunk| {
             match declaration.format {
-                interp::serialization::FormatInfo::Bitnum {
+                crate::serialization::FormatInfo::Bitnum {
                     signed, ..
                 } => {
                     let int = parse_bytes(chunk, width, signed);
                     Number::from_str(&int.to_str_radix(10)).unwrap()
                 }
-                interp::serialization::FormatInfo::Fixed {
+                crate::serialization::FormatInfo::Fixed {
                     signed,
                     int_width,
                     frac_width,
                 } => {
                     let int =
                         parse_bytes_fixed(chunk, int_width, frac_width, signed);
-                    let float = int.to_f64().unwrap();
-
-                    Number::from_f64(float).unwrap()
+                    Number::from_str(&fixed_to_decimal(&int)).unwrap()
+                }
+                crate::serialization::FormatInfo::IEEFloat {
+                    width, ..
+                } => {
+                    let float = parse_bytes_ieee(chunk, width);
+                    Number::from_str(&float_to_decimal(float, width))
+                        .unwrap_or_else(|_| {
+                            panic!("Memory `{}' contains {float}, which cannot be represented in JSON", declaration.name)
+                        })
                 }
             }
         });
@@ -345,69 +300,56 @@ pub fn convert_from_data_dump(
     }
 }
 
-/// This is catastrophically stupid.
-fn log2_round_down(x: &BigUint) -> u32 {
-    if *x == BigUint::ZERO {
-        return 0;
-    }
-
-    let mut count = 0_u32;
-    while *x > BigUint::from(2_u32).pow(count) {
-        count += 1;
-    }
-
-    if BigUint::from(2_u32).pow(count) == *x {
-        count
-    } else {
-        count - 1
-    }
-}
-
-fn log2_exact(x: &BigUint) -> Option<u32> {
-    let log_round_down = log2_round_down(x);
-    if *x == BigUint::from(2_u32).pow(log_round_down) {
-        Some(log_round_down)
-    } else {
-        None
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use proptest::prelude::*;
 
     #[test]
-    fn test_unroll_float() {
-        let float = -0.5;
-
+    fn test_unroll_fixed() {
         let signed = true;
         let int_width = 16;
         let frac_width = 16;
 
-        let format = interp::serialization::FormatInfo::Fixed {
+        let format = crate::serialization::FormatInfo::Fixed {
             signed,
             int_width,
             frac_width,
         };
 
-        let result = unroll_float(float, &format, true);
-        let result = result.collect_vec();
-        BigInt::from_signed_bytes_le(&result);
+        let val = BigRational::new(BigInt::from(-1), BigInt::from(2));
+        let result = unroll_fixed(&val, &format, false);
         let parsed_res =
             parse_bytes_fixed(&result, int_width, frac_width, signed);
-        println!(
-            " exact {}\n approx {}",
-            parsed_res,
-            parsed_res.to_f64().unwrap()
-        );
 
-        assert_eq!(parsed_res.to_f64().unwrap(), float)
+        assert_eq!(parsed_res, val);
+        assert_eq!(fixed_to_decimal(&parsed_res), "-0.5");
+    }
+
+    #[test]
+    fn test_fixed_to_decimal() {
+        let rat = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+
+        assert_eq!(fixed_to_decimal(&rat(4, 1)), "4.0");
+        assert_eq!(fixed_to_decimal(&rat(-3, 2)), "-1.5");
+        assert_eq!(fixed_to_decimal(&rat(1, 1024)), "0.0009765625");
+        assert_eq!(fixed_to_decimal(&rat(-13, 8)), "-1.625");
+    }
+
+    #[test]
+    fn test_unroll_ieee() {
+        for (val, width) in [(1.5, 16), (-0.1f32 as f64, 32), (1e-300, 64)] {
+            let bytes = unroll_ieee(val, width);
+            assert_eq!(bytes.len(), width as usize / 8);
+            assert_eq!(parse_bytes_ieee(&bytes, width), val);
+        }
+        assert_eq!(unroll_ieee(1.0, 16), vec![0x00, 0x3c]);
+        assert_eq!(float_to_decimal(-0.1f32 as f64, 32), "-0.1");
     }
 
     prop_compose! {
-        fn arb_format_info_bitnum()(width in 1_u32..=128, signed in any::<bool>()) -> crate::json_data::FormatInfo {
-            crate::json_data::FormatInfo {
+        fn arb_format_info_bitnum()(width in 1_u32..=128, signed in any::<bool>()) -> crate::serialization::json_data::FormatInfo {
+            crate::serialization::json_data::FormatInfo {
                 width: Some(width),
                 is_signed: signed,
                 numeric_type: NumericType::Bitnum,
@@ -418,8 +360,8 @@ mod tests {
     }
 
     prop_compose! {
-        fn arb_format_info_fixed()(int_width in 1_u32..=128, frac_width in 1_u32..=128, signed in any::<bool>()) -> crate::json_data::FormatInfo {
-            crate::json_data::FormatInfo {
+        fn arb_format_info_fixed()(int_width in 1_u32..=128, frac_width in 1_u32..=128, signed in any::<bool>()) -> crate::serialization::json_data::FormatInfo {
+            crate::serialization::json_data::FormatInfo {
                 width: None,
                 is_signed: signed,
                 numeric_type: NumericType::Fixed,
@@ -429,9 +371,26 @@ mod tests {
         }
     }
 
+    prop_compose! {
+        fn arb_format_info_float()(width in prop_oneof![Just(16_u32), Just(32), Just(64)]) -> crate::serialization::json_data::FormatInfo {
+            crate::serialization::json_data::FormatInfo {
+                width: Some(width),
+                is_signed: true,
+                numeric_type: NumericType::IEEE754Float,
+                int_width: None,
+                frac_width: None,
+            }
+        }
+    }
+
     fn format_info_generator(
-    ) -> impl Strategy<Value = crate::json_data::FormatInfo> {
-        prop_oneof![arb_format_info_bitnum(), arb_format_info_fixed()]
+    ) -> impl Strategy<Value = crate::serialization::json_data::FormatInfo>
+    {
+        prop_oneof![
+            arb_format_info_bitnum(),
+            arb_format_info_fixed(),
+            arb_format_info_float()
+        ]
     }
 
     fn dim_generator() -> impl Strategy<Value = Dimensions> {
@@ -464,11 +423,18 @@ mod tests {
     }
 
     prop_compose! {
-        fn arb_data(format: crate::json_data::FormatInfo, dimensions: Dimensions, signed: bool)(data in prop::collection::vec(arb_bigint(format.get_width(), signed), dimensions.size())) -> ParseVec {
+        fn arb_data(format: crate::serialization::json_data::FormatInfo, dimensions: Dimensions, signed: bool)(data in prop::collection::vec(arb_bigint(format.get_width(), signed), dimensions.size())) -> ParseVec {
             let data = data.into_iter().map(|x| {
-                if format.is_fixedpt() {
+                if format.numeric_type == NumericType::IEEE754Float {
+                    let width = format.get_width();
+                    let bytes = unroll_bigint(&x, width, format.is_signed);
+                    let float = parse_bytes_ieee(&bytes, width);
+                    // NaN and infinity have no JSON representation
+                    let float = if float.is_finite() { float } else { 0.0 };
+                    Number::from_str(&float_to_decimal(float, width)).unwrap()
+                } else if format.is_fixedpt() {
                     let rat = BigRational::new(x.clone(), BigInt::from(1) << format.frac_width().unwrap());
-                    Number::from_f64(rat.to_f64().unwrap()).unwrap()
+                    Number::from_str(&fixed_to_decimal(&rat)).unwrap()
                 } else {
                     Number::from_str(&x.to_str_radix(10)).unwrap()
                 }
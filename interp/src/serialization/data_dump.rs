@@ -55,6 +55,12 @@ pub enum FormatInfo {
         int_width: u32,
         frac_width: u32,
     },
+    /// An IEEE-754 binary floating point number. The width must be 16, 32 or
+    /// 64 for the half, single and double precision formats respectively.
+    IEEFloat {
+        signed: bool,
+        width: u32,
+    },
 }
 
 impl FormatInfo {
@@ -62,6 +68,7 @@ impl FormatInfo {
         match self {
             FormatInfo::Bitnum { signed, .. } => *signed,
             FormatInfo::Fixed { signed, .. } => *signed,
+            FormatInfo::IEEFloat { signed, .. } => *signed,
         }
     }
 
@@ -73,6 +80,7 @@ impl FormatInfo {
                 frac_width,
                 ..
             } => *int_width + *frac_width,
+            FormatInfo::IEEFloat { width, .. } => *width,
         }
     }
 }
@@ -118,6 +126,22 @@ impl MemoryDeclaration {
         }
     }
 
+    pub fn new_float(name: String, dimensions: Dimensions, width: u32) -> Self {
+        assert!(
+            matches!(width, 16 | 32 | 64),
+            "IEEE-754 floats must be 16, 32 or 64 bits wide"
+        );
+
+        Self {
+            name,
+            dimensions,
+            format: FormatInfo::IEEFloat {
+                signed: true,
+                width,
+            },
+        }
+    }
+
     pub fn new(
         name: String,
         dimensions: Dimensions,
@@ -0,0 +1,494 @@
+use std::{collections::HashMap, num::ParseFloatError, str::FromStr};
+
+use super::Dimensions;
+use half::f16;
+use num_bigint::{BigInt, ParseBigIntError};
+use num_rational::BigRational;
+use serde::{self, Deserialize, Serialize};
+use serde_json::Number;
+use thiserror::Error;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NumericType {
+    Bitnum,
+    #[serde(alias = "fixed_point")]
+    Fixed,
+    #[serde(rename = "ieee754_float", alias = "float")]
+    IEEE754Float,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormatInfo {
+    pub numeric_type: NumericType,
+    pub is_signed: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub int_width: Option<u32>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frac_width: Option<u32>,
+}
+
+impl FormatInfo {
+    pub fn get_width(&self) -> u32 {
+        if let Some(w) = self.width {
+            w
+        } else if self.int_width.is_some() && self.frac_width.is_some() {
+            self.int_width.unwrap() + self.frac_width.unwrap()
+        } else {
+            panic!("Either width or int_width and frac_width must be set")
+        }
+    }
+
+    pub fn is_fixedpt(&self) -> bool {
+        self.numeric_type != NumericType::IEEE754Float
+            && (self.int_width.is_some() && self.frac_width.is_some()
+                || self.width.is_some() && self.frac_width.is_some()
+                || self.width.is_some() && self.int_width.is_some())
+    }
+
+    pub fn int_width(&self) -> Option<u32> {
+        if self.int_width.is_some() {
+            self.int_width
+        } else if self.width.is_some() && self.frac_width.is_some() {
+            Some(self.width.unwrap() - self.frac_width.unwrap())
+        } else {
+            None
+        }
+    }
+
+    pub fn frac_width(&self) -> Option<u32> {
+        if self.frac_width.is_some() {
+            self.frac_width
+        } else if self.int_width.is_some() && self.width.is_some() {
+            Some(self.width.unwrap() - self.int_width.unwrap())
+        } else {
+            None
+        }
+    }
+
+    pub fn as_data_dump_format(&self) -> crate::serialization::FormatInfo {
+        match &self.numeric_type {
+            NumericType::Bitnum => crate::serialization::FormatInfo::Bitnum {
+                signed: self.is_signed,
+                width: self.width.unwrap(),
+            },
+            NumericType::Fixed => {
+                let (int_width, frac_width) = if self.int_width.is_some()
+                    && self.frac_width.is_some()
+                {
+                    (self.int_width.unwrap(), self.frac_width.unwrap())
+                } else if self.width.is_some() && self.frac_width.is_some() {
+                    (
+                        self.width.unwrap() - self.frac_width.unwrap(),
+                        self.frac_width.unwrap(),
+                    )
+                } else if self.width.is_some() && self.int_width.is_some() {
+                    (
+                        self.int_width.unwrap(),
+                        self.width.unwrap() - self.int_width.unwrap(),
+                    )
+                } else {
+                    panic!(
+                        "Either width or int_width and frac_width must be set"
+                    )
+                };
+
+                crate::serialization::FormatInfo::Fixed {
+                    signed: self.is_signed,
+                    int_width,
+                    frac_width,
+                }
+            }
+            NumericType::IEEE754Float => {
+                let width = self.get_width();
+                assert!(
+                    matches!(width, 16 | 32 | 64),
+                    "IEEE-754 floats must be 16, 32 or 64 bits wide, not {width}"
+                );
+                crate::serialization::FormatInfo::IEEFloat {
+                    signed: self.is_signed,
+                    width,
+                }
+            }
+        }
+    }
+}
+
+impl From<&crate::serialization::FormatInfo> for FormatInfo {
+    fn from(format: &crate::serialization::FormatInfo) -> Self {
+        match *format {
+            crate::serialization::FormatInfo::Bitnum { signed, width } => {
+                FormatInfo {
+                    numeric_type: NumericType::Bitnum,
+                    is_signed: signed,
+                    width: Some(width),
+                    int_width: None,
+                    frac_width: None,
+                }
+            }
+            crate::serialization::FormatInfo::Fixed {
+                signed,
+                int_width,
+                frac_width,
+            } => FormatInfo {
+                numeric_type: NumericType::Fixed,
+                is_signed: signed,
+                width: Some(int_width + frac_width),
+                int_width: Some(int_width),
+                frac_width: None,
+            },
+            crate::serialization::FormatInfo::IEEFloat { signed, width } => {
+                FormatInfo {
+                    numeric_type: NumericType::IEEE754Float,
+                    is_signed: signed,
+                    width: Some(width),
+                    int_width: None,
+                    frac_width: None,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParsedNumber {
+    /// The value of a bitnum
+    Int(BigInt),
+    /// The exact value of a fixed-point number
+    Fixed(BigRational),
+    /// The value of an IEEE-754 float
+    Float(f64),
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not parse number as integer: {0}")]
+    ParseInt(#[from] ParseBigIntError),
+    #[error("Could not parse number as float: {0}")]
+    ParseFloat(#[from] ParseFloatError),
+    #[error("Could not parse number as a decimal: {0}")]
+    ParseRational(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct QuoteWrappedNumber(Number);
+
+impl Serialize for QuoteWrappedNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.0.is_f64() {
+            serializer.serialize_str(&self.0.to_string())
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum PrintVec {
+    D1(Vec<QuoteWrappedNumber>),
+    D2(Vec<Vec<QuoteWrappedNumber>>),
+    D3(Vec<Vec<Vec<QuoteWrappedNumber>>>),
+    D4(Vec<Vec<Vec<Vec<QuoteWrappedNumber>>>>),
+}
+
+impl From<ParseVec> for PrintVec {
+    fn from(v: ParseVec) -> Self {
+        match v {
+            ParseVec::D1(v) => {
+                PrintVec::D1(v.into_iter().map(QuoteWrappedNumber).collect())
+            }
+            ParseVec::D2(v) => PrintVec::D2(
+                v.into_iter()
+                    .map(|v| v.into_iter().map(QuoteWrappedNumber).collect())
+                    .collect(),
+            ),
+            ParseVec::D3(v) => PrintVec::D3(
+                v.into_iter()
+                    .map(|v| {
+                        v.into_iter()
+                            .map(|v| {
+                                v.into_iter().map(QuoteWrappedNumber).collect()
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            ParseVec::D4(v) => PrintVec::D4(
+                v.into_iter()
+                    .map(|v| {
+                        v.into_iter()
+                            .map(|v| {
+                                v.into_iter()
+                                    .map(|v| {
+                                        v.into_iter()
+                                            .map(QuoteWrappedNumber)
+                                            .collect()
+                                    })
+                                    .collect()
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ParseVec {
+    D1(Vec<Number>),
+    D2(Vec<Vec<Number>>),
+    D3(Vec<Vec<Vec<Number>>>),
+    D4(Vec<Vec<Vec<Vec<Number>>>>),
+}
+
+impl From<Vec<Vec<Vec<Vec<Number>>>>> for ParseVec {
+    fn from(v: Vec<Vec<Vec<Vec<Number>>>>) -> Self {
+        Self::D4(v)
+    }
+}
+
+impl From<Vec<Vec<Vec<Number>>>> for ParseVec {
+    fn from(v: Vec<Vec<Vec<Number>>>) -> Self {
+        Self::D3(v)
+    }
+}
+
+impl From<Vec<Vec<Number>>> for ParseVec {
+    fn from(v: Vec<Vec<Number>>) -> Self {
+        Self::D2(v)
+    }
+}
+
+impl From<Vec<Number>> for ParseVec {
+    fn from(v: Vec<Number>) -> Self {
+        Self::D1(v)
+    }
+}
+
+impl ParseVec {
+    fn parse_int(val: &Number) -> Result<BigInt, ParseError> {
+        Ok(BigInt::from_str(&val.to_string())?)
+    }
+
+    /// Parses the exact decimal value of the number, including any exponent,
+    /// so that no precision is lost for wide fixed-point formats.
+    fn parse_rational(val: &Number) -> Result<BigRational, ParseError> {
+        let string = val.to_string();
+        let malformed = || ParseError::ParseRational(string.clone());
+
+        let (mantissa, exponent) = match string.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                (mantissa, exponent.parse::<i32>().map_err(|_| malformed())?)
+            }
+            None => (string.as_str(), 0),
+        };
+        let (is_neg, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => (true, mantissa),
+            None => (false, mantissa),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int.is_empty() && frac.is_empty() {
+            return Err(malformed());
+        }
+
+        let digits = BigInt::from_str(&format!("{int}{frac}"))
+            .map_err(|_| malformed())?;
+        let exponent = exponent - frac.len() as i32;
+        let scale = BigInt::from(10).pow(exponent.unsigned_abs());
+        let result = if exponent < 0 {
+            BigRational::new(digits, scale)
+        } else {
+            BigRational::from_integer(digits * scale)
+        };
+
+        Ok(if is_neg { -result } else { result })
+    }
+
+    /// Parses the number as a float of the given width. The result is exactly
+    /// representable in the target format.
+    fn parse_float(val: &Number, width: u32) -> Result<f64, ParseError> {
+        let string = val.to_string();
+        Ok(match width {
+            16 => f16::from_f32(f32::from_str(&string)?).to_f64(),
+            32 => f32::from_str(&string)? as f64,
+            _ => f64::from_str(&string)?,
+        })
+    }
+
+    /// The dimensions of the data. Will panic if the vectors do not all have
+    /// the same length within a given dimension.
+    pub fn dimensions(&self) -> Dimensions {
+        let dims = match self {
+            ParseVec::D1(v) => Dimensions::D1(v.len()),
+            ParseVec::D2(v) => Dimensions::D2(v.len(), v[0].len()),
+            ParseVec::D3(v) => {
+                Dimensions::D3(v.len(), v[0].len(), v[0][0].len())
+            }
+            ParseVec::D4(v) => Dimensions::D4(
+                v.len(),
+                v[0].len(),
+                v[0][0].len(),
+                v[0][0][0].len(),
+            ),
+        };
+
+        // Check that sizes are the same across each dimension
+        assert_eq!(
+            self.iter().count(),
+            dims.size(),
+            "memory data must have the same length within each dimension"
+        );
+        dims
+    }
+
+    /// Iterates over the numbers in row-major order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &Number> + '_> {
+        match self {
+            ParseVec::D1(v) => Box::new(v.iter()),
+            ParseVec::D2(v) => Box::new(v.iter().flatten()),
+            ParseVec::D3(v) => Box::new(v.iter().flatten().flatten()),
+            ParseVec::D4(v) => Box::new(v.iter().flatten().flatten().flatten()),
+        }
+    }
+
+    /// Mutably iterates over the numbers in row-major order.
+    pub fn iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut Number> + '_> {
+        match self {
+            ParseVec::D1(v) => Box::new(v.iter_mut()),
+            ParseVec::D2(v) => Box::new(v.iter_mut().flatten()),
+            ParseVec::D3(v) => Box::new(v.iter_mut().flatten().flatten()),
+            ParseVec::D4(v) => {
+                Box::new(v.iter_mut().flatten().flatten().flatten())
+            }
+        }
+    }
+
+    pub fn parse(&self, format: &FormatInfo) -> Result<DataVec, ParseError> {
+        let values = self
+            .iter()
+            .map(|val| match format.numeric_type {
+                NumericType::IEEE754Float => {
+                    Self::parse_float(val, format.get_width())
+                        .map(ParsedNumber::Float)
+                }
+                _ if format.is_fixedpt() => {
+                    Self::parse_rational(val).map(ParsedNumber::Fixed)
+                }
+                _ => Self::parse_int(val).map(ParsedNumber::Int),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(DataVec {
+            dimensions: self.dimensions(),
+            values,
+        })
+    }
+}
+
+/// The parsed values of a memory, flattened in row-major order.
+#[derive(Debug, PartialEq)]
+pub struct DataVec {
+    pub dimensions: Dimensions,
+    pub values: Vec<ParsedNumber>,
+}
+
+impl DataVec {
+    /// Returns the number of elements in the memory.
+    pub fn size(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions.clone()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonDataEntry {
+    pub data: ParseVec,
+    pub format: FormatInfo,
+}
+
+// using a hashmap here means that the serialization is non-deterministic but
+// for now that's probably fine
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonData(pub HashMap<String, JsonDataEntry>);
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+/// A structure meant to mimic the old style of data dump printing.
+pub enum JsonPrintDump {
+    Normal(HashMap<String, ParseVec>),
+    Quoted(HashMap<String, PrintVec>),
+}
+
+impl JsonPrintDump {
+    #[must_use]
+    pub fn as_normal(&self) -> Option<&HashMap<String, ParseVec>> {
+        if let Self::Normal(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<HashMap<String, PrintVec>> for JsonPrintDump {
+    fn from(v: HashMap<String, PrintVec>) -> Self {
+        Self::Quoted(v)
+    }
+}
+
+impl From<HashMap<String, ParseVec>> for JsonPrintDump {
+    fn from(v: HashMap<String, ParseVec>) -> Self {
+        Self::Normal(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_data() {
+        let data = r#"
+{
+  "in": {
+    "data": [[
+      4.0
+    ]],
+    "format": {
+      "numeric_type": "bitnum",
+      "is_signed": false,
+      "width": 32
+    }
+  },
+  "out": {
+    "data": [
+      6
+    ],
+    "format": {
+      "numeric_type": "bitnum",
+      "is_signed": false,
+      "width": 32
+    }
+  }
+}"#;
+
+        let json_data: JsonData = serde_json::from_str(data).unwrap();
+        println!("{:?}", json_data);
+        println!("{}", serde_json::to_string_pretty(&json_data).unwrap());
+    }
+}
@@ -1,5 +1,7 @@
+pub mod converter;
 mod data_dump;
 mod formatting;
+pub mod json_data;
 
 pub use data_dump::*;
 pub use formatting::*;
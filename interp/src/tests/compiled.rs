@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod compiled_test {
+    use crate::{
+        errors::InterpreterError, flatten::structures::environment::Simulator,
+        tests::build_context,
+    };
+
+    /// A program with control, which has to be lowered first.
+    const STORE: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+component main() -> () {
+  cells {
+    @external mem = comb_mem_d1(32, 4, 3);
+  }
+  wires {
+    group store {
+      mem.addr0 = 3'd2;
+      mem.write_data = 32'd42;
+      mem.write_en = 1'd1;
+      store[done] = mem.done;
+    }
+  }
+  control { store; }
+}
+"#;
+
+    #[test]
+    fn rejects_control() {
+        let ctx = build_context(STORE, true);
+        let mut sim = Simulator::build_simulator(&ctx, &None).unwrap();
+        let err = sim.run_program_compiled().unwrap_err();
+        assert!(matches!(
+            *err,
+            InterpreterError::UnsupportedCompiledProgram(_)
+        ));
+    }
+}
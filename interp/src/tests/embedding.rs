@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod embedding_test {
+    use crate::{
+        errors::InterpreterError,
+        flatten::structures::{context::Context, environment::Simulator},
+        tests::build_context,
+        values::Value,
+    };
+
+    /// A component without a done protocol accumulating its inputs into a
+    /// register every cycle. Since it never assigns to its done port, it has to
+    /// be built without running the well-formedness checks.
+    const ACCUMULATOR: &str = r#"
+import "primitives/core.futil";
+component main(a: 8, b: 8) -> (sum: 8, total: 8) {
+  cells {
+    add = std_add(8);
+    acc_add = std_add(8);
+    acc = std_reg(8);
+  }
+  wires {
+    add.left = a;
+    add.right = b;
+    sum = add.out;
+    acc_add.left = acc.out;
+    acc_add.right = add.out;
+    acc.in = acc_add.out;
+    acc.write_en = 1'd1;
+    total = acc.out;
+  }
+  control {}
+}
+"#;
+
+    /// A component using the go/done protocol that stores its input into a
+    /// memory.
+    const STORE: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+component main(x: 32) -> () {
+  cells {
+    mem = comb_mem_d1(32, 4, 3);
+  }
+  wires {
+    group store {
+      mem.addr0 = 3'd2;
+      mem.write_data = x;
+      mem.write_en = 1'd1;
+      store[done] = mem.done;
+    }
+  }
+  control { store; }
+}
+"#;
+
+    fn get(sim: &Simulator<&Context>, port: &str) -> u64 {
+        sim.get_port(port).unwrap().unwrap().as_u64()
+    }
+
+    #[test]
+    fn continuous_assignments() {
+        let ctx = build_context(ACCUMULATOR, false);
+        let mut sim = Simulator::new_embedded(&ctx, None);
+
+        sim.eval().unwrap();
+        assert!(sim.get_port("sum").unwrap().is_none());
+
+        sim.set_input("a", Value::from(3, 8)).unwrap();
+        sim.set_input("b", Value::from(4, 8)).unwrap();
+        sim.eval().unwrap();
+        assert_eq!(get(&sim, "sum"), 7);
+        assert_eq!(get(&sim, "total"), 0);
+
+        sim.tick().unwrap();
+        assert_eq!(get(&sim, "total"), 7);
+        sim.set_input("b", Value::from(1, 8)).unwrap();
+        sim.tick().unwrap();
+        assert_eq!(get(&sim, "total"), 11);
+        assert_eq!(sim.peek_memory("acc").unwrap()[0].as_u64(), 11);
+
+        sim.poke_memory("acc", 0, Value::from(100, 8)).unwrap();
+        sim.eval().unwrap();
+        assert_eq!(get(&sim, "total"), 100);
+
+        sim.release_input("a").unwrap();
+        sim.eval().unwrap();
+        assert!(sim.get_port("sum").unwrap().is_none());
+    }
+
+    #[test]
+    fn go_done_protocol() {
+        let ctx = build_context(STORE, true);
+        let mut sim = Simulator::new_embedded(&ctx, None);
+
+        sim.set_input("x", Value::from(42, 32)).unwrap();
+        sim.set_input("go", Value::bit_low()).unwrap();
+        sim.tick().unwrap();
+        assert_eq!(sim.peek_memory("mem").unwrap()[2].as_u64(), 0);
+
+        sim.set_input("go", Value::bit_high()).unwrap();
+        let mut cycles = 0;
+        while get(&sim, "done") == 0 {
+            sim.tick().unwrap();
+            cycles += 1;
+            assert!(cycles < 10, "component never finished");
+        }
+        let contents = sim
+            .peek_memory("mem")
+            .unwrap()
+            .iter()
+            .map(|x| x.as_u64())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, vec![0, 0, 42, 0]);
+    }
+
+    #[test]
+    fn invalid_accesses() {
+        let ctx = build_context(STORE, true);
+        let mut sim = Simulator::new_embedded(&ctx, None);
+
+        let err = sim.set_input("y", Value::from(1, 32)).unwrap_err();
+        assert!(matches!(*err, InterpreterError::UnknownPort(_)));
+        let err = sim.set_input("done", Value::bit_high()).unwrap_err();
+        assert!(matches!(*err, InterpreterError::NotAnInput(_)));
+        let err = sim.set_input("x", Value::from(1, 8)).unwrap_err();
+        assert!(matches!(
+            *err,
+            InterpreterError::WidthMismatch {
+                expected: 32,
+                given: 8,
+                ..
+            }
+        ));
+
+        let err = sim.peek_memory("memory").unwrap_err();
+        assert!(matches!(*err, InterpreterError::UnknownMemory(_)));
+        let err = sim.poke_memory("mem", 4, Value::from(1, 32)).unwrap_err();
+        assert!(matches!(
+            *err,
+            InterpreterError::MemoryIndexOutOfBounds {
+                index: 4,
+                size: 4,
+                ..
+            }
+        ));
+        let err = sim.poke_memory("mem", 0, Value::from(1, 8)).unwrap_err();
+        assert!(matches!(*err, InterpreterError::WidthMismatch { .. }));
+    }
+}
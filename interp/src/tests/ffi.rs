@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod ffi_test {
+    use crate::{
+        ffi::*,
+        tests::{lib_path, ProgramFile},
+    };
+    use std::ffi::{CStr, CString};
+
+    /// Adds the two values in `in` and writes the result to `out`.
+    const ADD: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+component main(wide: 70) -> (wide_out: 70) {
+  cells {
+    @external in = comb_mem_d1(32, 2, 1);
+    @external out = comb_mem_d1(32, 1, 1);
+    a = std_reg(32);
+    add = std_add(32);
+  }
+  wires {
+    wide_out = wide;
+    group read {
+      in.addr0 = 1'd0;
+      a.in = in.read_data;
+      a.write_en = 1'd1;
+      read[done] = a.done;
+    }
+    group write {
+      in.addr0 = 1'd1;
+      add.left = a.out;
+      add.right = in.read_data;
+      out.addr0 = 1'd0;
+      out.write_data = add.out;
+      out.write_en = 1'd1;
+      write[done] = out.done;
+    }
+  }
+  control { seq { read; write; } }
+}
+"#;
+
+    const DATA: &str = r#"{
+  "in": {
+    "data": [20, 22],
+    "format": { "numeric_type": "bitnum", "is_signed": false, "width": 32 }
+  },
+  "out": {
+    "data": [0],
+    "format": { "numeric_type": "bitnum", "is_signed": false, "width": 32 }
+  }
+}"#;
+
+    fn c(string: &str) -> CString {
+        CString::new(string).unwrap()
+    }
+
+    fn new_sim(embedded: bool) -> *mut CiderSim {
+        let file = ProgramFile::new(ADD);
+        let sim = unsafe {
+            cider_sim_new(
+                c(file.path().to_str().unwrap()).as_ptr(),
+                c(lib_path().to_str().unwrap()).as_ptr(),
+                c(DATA).as_ptr(),
+                false,
+                embedded,
+            )
+        };
+        assert!(!sim.is_null(), "{}", last_error());
+        sim
+    }
+
+    fn last_error() -> String {
+        let err = cider_last_error();
+        assert!(!err.is_null());
+        unsafe { CStr::from_ptr(err) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn run_to_completion() {
+        let sim = new_sim(false);
+        unsafe {
+            assert_eq!(cider_sim_is_done(sim), 0);
+            assert_eq!(cider_sim_run(sim), CIDER_OK);
+            assert_eq!(cider_sim_is_done(sim), 1);
+
+            let name = c("out");
+            assert_eq!(cider_sim_memory_width(sim, name.as_ptr()), 32);
+            assert_eq!(cider_sim_memory_size(sim, name.as_ptr()), 1);
+            let mut buf = [0_u8; 4];
+            assert_eq!(
+                cider_sim_peek_memory(
+                    sim,
+                    name.as_ptr(),
+                    0,
+                    buf.as_mut_ptr(),
+                    4
+                ),
+                CIDER_OK
+            );
+            assert_eq!(u32::from_le_bytes(buf), 42);
+
+            let json = cider_sim_dump_json(sim, false, false);
+            let parsed: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(json).to_str().unwrap())
+                    .unwrap();
+            cider_string_free(json);
+            assert_eq!(
+                parsed,
+                serde_json::json!({ "in": [20, 22], "out": [42] })
+            );
+
+            let mut len = 0;
+            let dump = cider_sim_dump(sim, false, false, &mut len);
+            let mut bytes = std::slice::from_raw_parts(dump, len);
+            let read_back =
+                crate::serialization::DataDump::deserialize(&mut bytes)
+                    .unwrap();
+            cider_bytes_free(dump, len);
+            assert_eq!(read_back.get_data("out"), Some(&[42, 0, 0, 0][..]));
+
+            cider_sim_free(sim);
+        }
+    }
+
+    #[test]
+    fn embedded_ports() {
+        let sim = new_sim(true);
+        unsafe {
+            let wide = c("wide");
+            let wide_out = c("wide_out");
+            assert_eq!(cider_sim_port_width(sim, wide.as_ptr()), 70);
+
+            let mut buf = [0_u8; 9];
+            assert_eq!(cider_sim_eval(sim), CIDER_OK);
+            assert_eq!(
+                cider_sim_get_port(sim, wide_out.as_ptr(), buf.as_mut_ptr(), 9),
+                CIDER_UNDEFINED
+            );
+
+            let val = (1_u128 << 69) | 5;
+            let bytes = val.to_le_bytes();
+            assert_eq!(
+                cider_sim_set_input(sim, wide.as_ptr(), bytes.as_ptr(), 16),
+                CIDER_OK
+            );
+            assert_eq!(cider_sim_step(sim), CIDER_OK);
+            assert_eq!(
+                cider_sim_get_port(sim, wide_out.as_ptr(), buf.as_mut_ptr(), 9),
+                CIDER_OK
+            );
+            let mut read = [0_u8; 16];
+            read[..9].copy_from_slice(&buf);
+            assert_eq!(u128::from_le_bytes(read), val);
+
+            // the value does not fit in the port
+            let bytes = (1_u128 << 70).to_le_bytes();
+            assert_eq!(
+                cider_sim_set_input(sim, wide.as_ptr(), bytes.as_ptr(), 16),
+                CIDER_ERROR
+            );
+            assert!(last_error().contains("does not fit in 70 bits"));
+
+            // the buffer is too small for the value
+            assert_eq!(
+                cider_sim_get_port(sim, wide_out.as_ptr(), buf.as_mut_ptr(), 8),
+                CIDER_ERROR
+            );
+
+            let name = c("in");
+            let bytes = 7_u32.to_le_bytes();
+            assert_eq!(
+                cider_sim_poke_memory(sim, name.as_ptr(), 1, bytes.as_ptr(), 4),
+                CIDER_OK
+            );
+            let mut buf = [0_u8; 4];
+            cider_sim_peek_memory(sim, name.as_ptr(), 1, buf.as_mut_ptr(), 4);
+            assert_eq!(u32::from_le_bytes(buf), 7);
+            assert_eq!(
+                cider_sim_peek_memory(
+                    sim,
+                    name.as_ptr(),
+                    2,
+                    buf.as_mut_ptr(),
+                    4
+                ),
+                CIDER_ERROR
+            );
+
+            cider_sim_free(sim);
+        }
+    }
+
+    /// A component with an input port that has no bits
+    const EMPTY_PORT: &str = r#"
+import "primitives/core.futil";
+component main(empty: 0) -> () {
+  cells {}
+  wires { done = 1'd1; }
+  control {}
+}
+"#;
+
+    #[test]
+    fn zero_width_port() {
+        let file = ProgramFile::new(EMPTY_PORT);
+        unsafe {
+            let sim = cider_sim_new(
+                c(file.path().to_str().unwrap()).as_ptr(),
+                c(lib_path().to_str().unwrap()).as_ptr(),
+                std::ptr::null(),
+                false,
+                true,
+            );
+            assert!(!sim.is_null(), "{}", last_error());
+            let empty = c("empty");
+            assert_eq!(cider_sim_port_width(sim, empty.as_ptr()), 0);
+            let bytes = [0_u8; 1];
+            assert_eq!(
+                cider_sim_set_input(sim, empty.as_ptr(), bytes.as_ptr(), 1),
+                CIDER_ERROR
+            );
+            assert!(last_error().contains("at least one bit wide"));
+            cider_sim_free(sim);
+        }
+    }
+
+    #[test]
+    fn invalid_program() {
+        let sim = unsafe {
+            cider_sim_new(
+                c("/nonexistent/program.futil").as_ptr(),
+                c(".").as_ptr(),
+                std::ptr::null(),
+                false,
+                false,
+            )
+        };
+        assert!(sim.is_null());
+        assert!(!last_error().is_empty());
+    }
+}
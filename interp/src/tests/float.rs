@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod float_test {
+    use crate::{
+        errors::{InterpreterError, InterpreterResult},
+        flatten::{setup_simulation, structures::context::Context},
+        tests::{lib_path, ProgramFile},
+    };
+
+    /// A program with a single instance of the float primitive `cell`
+    fn program(cell: &str) -> String {
+        format!(
+            r#"
+import "primitives/core.futil";
+import "primitives/float.futil";
+component main() -> () {{
+  cells {{
+    f = {cell};
+  }}
+  wires {{}}
+  control {{}}
+}}
+"#
+        )
+    }
+
+    fn setup(cell: &str) -> InterpreterResult<Context> {
+        let file = ProgramFile::new(&program(cell));
+        setup_simulation(&Some(file.path().to_path_buf()), &lib_path(), true)
+    }
+
+    /// The message of the error reported when setting up `cell`
+    fn setup_error(cell: &str) -> String {
+        let err = setup(cell).err().expect("the prototype should be rejected");
+        match &*err {
+            InterpreterError::CompilerError(err) => err.message(),
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_formats() {
+        for cell in [
+            "std_float_add(16, 5, 10)",
+            "std_float_lt(64, 11, 52)",
+            "std_float_to_sint(32, 8, 23, 8)",
+            "std_int_to_float(1, 32, 8, 23)",
+        ] {
+            assert!(setup(cell).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_width() {
+        assert!(setup_error("std_float_mult(32, 8, 24)")
+            .contains("has a `WIDTH' of 32"));
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        for cell in [
+            "std_float_add(66, 64, 1)",
+            "std_float_sqrt(33, 32, 0)",
+            "std_float_eq(4, 1, 2)",
+        ] {
+            assert!(setup_error(cell).contains("unsupported float format"));
+        }
+    }
+
+    #[test]
+    fn rejects_empty_integers() {
+        assert!(setup_error("std_float_to_int(32, 8, 23, 0)")
+            .contains("`OUT_WIDTH' of 0"));
+        assert!(setup_error("std_sint_to_float(0, 32, 8, 23)")
+            .contains("`IN_WIDTH' of 0"));
+    }
+}
@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod mem_trace_test {
+    use crate::{
+        errors::InterpreterError,
+        flatten::{
+            primitives::prim_trait::AccessKind,
+            structures::{
+                context::Context,
+                environment::{AddressCount, Simulator},
+            },
+        },
+        tests::build_context,
+    };
+
+    /// Writes two banks in parallel, reads them back in parallel and stores
+    /// one of the values in a sequential memory.
+    const BANKS: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+import "primitives/memories/seq.futil";
+component main() -> () {
+  cells {
+    @external b0 = comb_mem_d1(32, 2, 1);
+    @external b1 = comb_mem_d1(32, 2, 1);
+    @external s = seq_mem_d2(32, 2, 2, 1, 1);
+    r0 = std_reg(32);
+    r1 = std_reg(32);
+  }
+  wires {
+    group w0 {
+      b0.addr0 = 1'd1;
+      b0.write_data = 32'd7;
+      b0.write_en = 1'd1;
+      w0[done] = b0.done;
+    }
+    group w1 {
+      b1.addr0 = 1'd0;
+      b1.write_data = 32'd9;
+      b1.write_en = 1'd1;
+      w1[done] = b1.done;
+    }
+    group rd0 {
+      b0.addr0 = 1'd1;
+      r0.in = b0.read_data;
+      r0.write_en = 1'd1;
+      rd0[done] = r0.done;
+    }
+    group rd1 {
+      b1.addr0 = 1'd0;
+      r1.in = b1.read_data;
+      r1.write_en = 1'd1;
+      rd1[done] = r1.done;
+    }
+    group sw {
+      s.addr0 = 1'd1;
+      s.addr1 = 1'd0;
+      s.content_en = 1'd1;
+      s.write_en = 1'd1;
+      s.write_data = r0.out;
+      sw[done] = s.done;
+    }
+  }
+  control { seq { par { w0; w1; } par { rd0; rd1; } sw; } }
+}
+"#;
+
+    /// Like `BANKS`, but both parallel reads use the first bank.
+    const CONFLICT: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+import "primitives/memories/seq.futil";
+component main() -> () {
+  cells {
+    @external b0 = comb_mem_d1(32, 2, 1);
+    @external b1 = comb_mem_d1(32, 2, 1);
+    @external s = seq_mem_d2(32, 2, 2, 1, 1);
+    r0 = std_reg(32);
+    r1 = std_reg(32);
+  }
+  wires {
+    group w0 {
+      b0.addr0 = 1'd1;
+      b0.write_data = 32'd7;
+      b0.write_en = 1'd1;
+      w0[done] = b0.done;
+    }
+    group w1 {
+      b1.addr0 = 1'd0;
+      b1.write_data = 32'd9;
+      b1.write_en = 1'd1;
+      w1[done] = b1.done;
+    }
+    group rd0 {
+      b0.addr0 = 1'd1;
+      r0.in = b0.read_data;
+      r0.write_en = 1'd1;
+      rd0[done] = r0.done;
+    }
+    group rd1 {
+      b0.addr0 = 1'd1;
+      r1.in = b0.read_data;
+      r1.write_en = 1'd1;
+      rd1[done] = r1.done;
+    }
+    group sw {
+      s.addr0 = 1'd1;
+      s.addr1 = 1'd0;
+      s.content_en = 1'd1;
+      s.write_en = 1'd1;
+      s.write_data = r0.out;
+      sw[done] = s.done;
+    }
+  }
+  control { seq { par { w0; w1; } par { rd0; rd1; } sw; } }
+}
+"#;
+
+    fn simulator(ctx: &Context) -> Simulator<&Context> {
+        let mut sim = Simulator::build_simulator(ctx, &None).unwrap();
+        sim.enable_memory_trace();
+        sim
+    }
+
+    #[test]
+    fn records_accesses() {
+        let ctx = build_context(BANKS, true);
+        let mut sim = simulator(&ctx);
+        sim.run_program().unwrap();
+
+        let trace = sim.memory_trace().unwrap();
+        let accesses = trace
+            .accesses()
+            .iter()
+            .map(|access| {
+                (
+                    access.memory.as_str(),
+                    access.kind,
+                    access.address.clone(),
+                    access.group.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            accesses,
+            vec![
+                ("main.b0", AccessKind::Write, vec![1], Some("main.w0")),
+                ("main.b1", AccessKind::Write, vec![0], Some("main.w1")),
+                ("main.b0", AccessKind::Read, vec![1], Some("main.rd0")),
+                ("main.b1", AccessKind::Read, vec![0], Some("main.rd1")),
+                ("main.s", AccessKind::Write, vec![1, 0], Some("main.sw")),
+            ]
+        );
+        // the parallel accesses of the banks happen in the same cycle
+        let cycles = trace
+            .accesses()
+            .iter()
+            .map(|access| access.cycle)
+            .collect::<Vec<_>>();
+        assert_eq!(cycles[0], cycles[1]);
+        assert_eq!(cycles[2], cycles[3]);
+        assert!(cycles[4] > cycles[3] && cycles[4] < sim.cycle());
+
+        assert!(trace.conflicts().is_empty());
+        assert_eq!(
+            trace.histograms()["main.b0"],
+            vec![AddressCount {
+                address: vec![1],
+                reads: 1,
+                writes: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn records_conflicts() {
+        let ctx = build_context(CONFLICT, true);
+        let mut sim = simulator(&ctx);
+
+        let err = sim.run_program().unwrap_err();
+        assert!(matches!(
+            *err,
+            InterpreterError::FlatConflictingAssignments { .. }
+        ));
+
+        let conflicts = sim.memory_trace().unwrap().conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].memory, "main.b0");
+        assert_eq!(conflicts[0].groups, vec!["main.rd0", "main.rd1"]);
+    }
+
+    #[test]
+    fn writes_json() {
+        let ctx = build_context(BANKS, true);
+        let mut sim = simulator(&ctx);
+        sim.run_program().unwrap();
+
+        let mut buf = vec![];
+        sim.memory_trace().unwrap().write_json(&mut buf).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(json["accesses"].as_array().unwrap().len(), 5);
+        assert_eq!(json["accesses"][4]["kind"], "write");
+        assert_eq!(json["histograms"]["main.s"][0]["address"][1], 0);
+        assert_eq!(json["conflicts"].as_array().unwrap().len(), 0);
+    }
+}
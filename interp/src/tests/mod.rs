@@ -1 +1,62 @@
+mod compiled;
+mod embedding;
+mod ffi;
+mod float;
+mod mem_trace;
+mod parallel;
+mod snapshot;
 mod values;
+mod x_checks;
+
+#[cfg(test)]
+use crate::flatten::{setup_simulation, structures::context::Context};
+#[cfg(test)]
+use std::path::{Path, PathBuf};
+
+/// A Calyx program written to a fresh temporary file, which is removed again
+/// when this is dropped.
+#[cfg(test)]
+pub(crate) struct ProgramFile(PathBuf);
+
+#[cfg(test)]
+impl ProgramFile {
+    pub fn new(program: &str) -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "cider-test-{}-{}.futil",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, program).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for ProgramFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// The root of the repository, which the primitive imports are resolved
+/// against.
+#[cfg(test)]
+pub(crate) fn lib_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Builds the simulation context for `program`. The well-formedness checks
+/// are only run when `verify` is set.
+#[cfg(test)]
+pub(crate) fn build_context(program: &str, verify: bool) -> Context {
+    let file = ProgramFile::new(program);
+    setup_simulation(&Some(file.path().to_path_buf()), &lib_path(), !verify)
+        .unwrap()
+}
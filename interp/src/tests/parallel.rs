@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod parallel_test {
+    use crate::{
+        errors::InterpreterError,
+        flatten::structures::{context::Context, environment::Simulator},
+        tests::build_context,
+    };
+
+    /// Two independent par arms, one of which drives a register with two
+    /// different values at once.
+    const CONFLICT: &str = r#"
+import "primitives/core.futil";
+component main() -> () {
+  cells {
+    x = std_reg(32);
+    y = std_reg(32);
+  }
+  wires {
+    group bad {
+      x.in = x.out == 32'd0 ? 32'd1;
+      x.in = 32'd2;
+      x.write_en = 1'd1;
+      bad[done] = x.done;
+    }
+    group good {
+      y.in = 32'd3;
+      y.write_en = 1'd1;
+      good[done] = y.done;
+    }
+  }
+  control { par { good; bad; } }
+}
+"#;
+
+    fn simulator(ctx: &Context, threads: usize) -> Simulator<&Context> {
+        let mut sim = Simulator::build_simulator(ctx, &None).unwrap();
+        sim.set_threads(threads);
+        sim
+    }
+
+    #[test]
+    fn reports_conflicts() {
+        let ctx = build_context(CONFLICT, true);
+
+        let sequential = simulator(&ctx, 1).run_program().unwrap_err();
+        let parallel = simulator(&ctx, 4).run_program().unwrap_err();
+
+        assert!(matches!(
+            *parallel,
+            InterpreterError::FlatConflictingAssignments { .. }
+        ));
+        assert_eq!(parallel.to_string(), sequential.to_string());
+    }
+}
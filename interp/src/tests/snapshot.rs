@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod snapshot_test {
+    use crate::{
+        errors::InterpreterError,
+        flatten::structures::{
+            context::Context,
+            environment::{Simulator, Snapshot},
+        },
+        tests::build_context,
+    };
+
+    /// Multiplies the loop index with a pipelined multiplier and stores the
+    /// products in a sequential memory.
+    const PRODUCTS: &str = r#"
+import "primitives/core.futil";
+import "primitives/binary_operators.futil";
+import "primitives/memories/seq.futil";
+component main() -> () {
+  cells {
+    @external mem = seq_mem_d1(32, 4, 3);
+    i = std_reg(3);
+    add = std_add(3);
+    lt = std_lt(3);
+    pad = std_pad(3, 32);
+    mul = std_mult_pipe(32);
+  }
+  wires {
+    comb group cond {
+      lt.left = i.out;
+      lt.right = 3'd4;
+    }
+    group do_mul {
+      pad.in = i.out;
+      mul.left = pad.out;
+      mul.right = 32'd7;
+      mul.go = !mul.done ? 1'd1;
+      do_mul[done] = mul.done;
+    }
+    group write {
+      mem.addr0 = i.out;
+      mem.write_data = mul.out;
+      mem.write_en = 1'd1;
+      mem.content_en = 1'd1;
+      write[done] = mem.done;
+    }
+    group incr {
+      add.left = i.out;
+      add.right = 3'd1;
+      i.in = add.out;
+      i.write_en = 1'd1;
+      incr[done] = i.done;
+    }
+  }
+  control { while lt.out with cond { seq { do_mul; write; incr; } } }
+}
+"#;
+
+    /// A different program with the same entrypoint
+    const OTHER: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/seq.futil";
+component main() -> () {
+  cells {
+    @external mem = seq_mem_d1(32, 4, 3);
+  }
+  wires {
+    group write {
+      mem.addr0 = 3'd0;
+      mem.write_data = 32'd1;
+      mem.write_en = 1'd1;
+      mem.content_en = 1'd1;
+      write[done] = mem.done;
+    }
+  }
+  control { write; }
+}
+"#;
+
+    /// Saves a snapshot and reads it back from its serialized form
+    fn round_trip(sim: &Simulator<&Context>) -> Snapshot {
+        let mut buf = vec![];
+        sim.save_snapshot().serialize(&mut buf).unwrap();
+        Snapshot::deserialize(&mut buf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn resumes_from_every_cycle() {
+        let ctx = build_context(PRODUCTS, true);
+        let mut reference = Simulator::build_simulator(&ctx, &None).unwrap();
+        reference.run_program().unwrap();
+        let expected = reference.dump_memories(true, true);
+        let products = expected.get_data("mem").unwrap();
+        assert_eq!(products[12..16], 21u32.to_le_bytes());
+
+        for cycle in 0..reference.cycle() {
+            let mut sim = Simulator::build_simulator(&ctx, &None).unwrap();
+            while sim.cycle() < cycle {
+                sim.step().unwrap();
+            }
+            let snapshot = round_trip(&sim);
+            assert_eq!(snapshot.cycle(), cycle);
+
+            let mut resumed = Simulator::build_simulator(&ctx, &None).unwrap();
+            resumed.restore_snapshot(&snapshot).unwrap();
+            resumed.run_program().unwrap();
+            assert_eq!(resumed.cycle(), reference.cycle());
+            assert_eq!(resumed.dump_memories(true, true), expected);
+        }
+    }
+
+    #[test]
+    fn rejects_other_programs() {
+        let ctx = build_context(PRODUCTS, true);
+        let mut sim = Simulator::build_simulator(&ctx, &None).unwrap();
+        sim.step().unwrap();
+        let snapshot = round_trip(&sim);
+
+        let other_ctx = build_context(OTHER, true);
+        let mut other = Simulator::build_simulator(&other_ctx, &None).unwrap();
+        let err = other.restore_snapshot(&snapshot).unwrap_err();
+        assert!(matches!(*err, InterpreterError::InvalidSnapshot(_)));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        let err = Snapshot::deserialize(&mut b"Z not a snapshot".as_slice())
+            .unwrap_err();
+        assert!(matches!(*err, InterpreterError::InvalidSnapshot(_)));
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod x_checks_test {
+    use crate::{
+        errors::InterpreterError,
+        flatten::structures::{
+            context::Context,
+            environment::{Simulator, XPessimism, XSink},
+        },
+        tests::build_context,
+    };
+
+    /// Drives a register write enable with a comparison of undriven ports and
+    /// selects the data written to a memory with a guard that is undefined
+    /// unless `0 & X` is known to be `0`.
+    const GUARDS: &str = r#"
+import "primitives/core.futil";
+import "primitives/memories/comb.futil";
+component main() -> () {
+  cells {
+    x = std_reg(32);
+    y = std_reg(32);
+    lt = std_lt(32);
+    a = std_and(1);
+    u = undef(1);
+    @external mem = comb_mem_d1(32, 1, 1);
+  }
+  wires {
+    group g {
+      x.in = 32'd1;
+      x.write_en = 1'd1;
+      y.in = 32'd2;
+      y.write_en = lt.out ? 1'd1;
+      g[done] = x.done;
+    }
+    group h {
+      a.left = 1'd0;
+      a.right = u.out;
+      mem.addr0 = 1'd0;
+      mem.write_data = a.out ? 32'd5;
+      mem.write_data = !a.out ? x.out;
+      mem.write_en = 1'd1;
+      h[done] = mem.done;
+    }
+  }
+  control { seq { g; h; } }
+}
+"#;
+
+    fn simulator(ctx: &Context, pessimism: XPessimism) -> Simulator<&Context> {
+        let mut sim = Simulator::build_simulator(ctx, &None).unwrap();
+        sim.enable_x_checks(pessimism);
+        sim
+    }
+
+    #[test]
+    fn pessimistic_reports() {
+        let ctx = build_context(GUARDS, true);
+        let mut sim = simulator(&ctx, XPessimism::Pessimistic);
+
+        let err = sim.run_program().unwrap_err();
+        assert!(matches!(*err, InterpreterError::UndefinedWrite(_)));
+
+        let reports = sim
+            .x_reports()
+            .iter()
+            .map(|report| (report.cycle, report.sink, report.port.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reports,
+            vec![
+                (1, XSink::Guard, "main.y.write_en"),
+                (3, XSink::Guard, "main.mem.write_data"),
+                (3, XSink::Guard, "main.mem.write_data"),
+                (3, XSink::Input, "main.mem.write_data"),
+            ]
+        );
+
+        let first = &sim.x_reports()[0];
+        assert_eq!(
+            first.assignment.as_deref(),
+            Some("y.write_en = lt.out ? 1'd1;")
+        );
+        assert!(first.position.as_ref().unwrap().ends_with(":18"));
+    }
+
+    #[test]
+    fn precise_reports() {
+        let ctx = build_context(GUARDS, true);
+        let mut sim = simulator(&ctx, XPessimism::Precise);
+
+        sim.run_program().unwrap();
+        let reports = sim
+            .x_reports()
+            .iter()
+            .map(|report| (report.cycle, report.sink, report.port.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(reports, vec![(1, XSink::Guard, "main.y.write_en")]);
+        assert_eq!(sim.peek_memory("mem").unwrap()[0].as_u64(), 1);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let ctx = build_context(GUARDS, true);
+        let mut sim = Simulator::build_simulator(&ctx, &None).unwrap();
+
+        assert!(sim.run_program().is_err());
+        assert!(sim.x_reports().is_empty());
+    }
+}
@@ -0,0 +1,715 @@
+/**
+ * IEEE-754 binary floating point primitives.
+ *
+ * Values are stored as bit patterns of WIDTH = 1 + EXP_WIDTH + MANT_WIDTH
+ * bits, where MANT_WIDTH does not include the implicit leading bit. All
+ * operators round to nearest, ties to even, support subnormal numbers and
+ * produce the canonical quiet NaN whenever the result is NaN. Cider's models
+ * of these primitives in `interp/src/flatten/primitives/float.rs` are
+ * bit-exact with this implementation.
+ *
+ * The arithmetic operators compute the exact result combinationally and
+ * register it, so it is available one cycle after `go` is asserted.
+ */
+
+/// Splits a floating point value into its fields. Finite values are equal to
+/// (-1)^sign * sig * 2^exp.
+module std_float_unpack #(
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [EXP_WIDTH+MANT_WIDTH:0] in,
+    output logic                          sign,
+    output logic                          is_nan,
+    output logic                          is_inf,
+    output logic [MANT_WIDTH:0]           sig,
+    output logic signed [31:0]            exp
+);
+  localparam signed [31:0] BIAS = (1 << (EXP_WIDTH - 1)) - 1;
+
+  logic [EXP_WIDTH-1:0] biased_exp;
+  logic [MANT_WIDTH-1:0] mant;
+
+  assign {sign, biased_exp, mant} = in;
+  assign is_nan = &biased_exp && |mant;
+  assign is_inf = &biased_exp && !(|mant);
+  assign sig = {|biased_exp, mant};
+  // Subnormal numbers have the same exponent as the smallest normal number
+  assign exp = (biased_exp == 0 ? 32'sd1 : {{(32-EXP_WIDTH){1'b0}}, biased_exp})
+    - BIAS - MANT_WIDTH;
+endmodule
+
+/// Rounds the exact value (-1)^sign * sig * 2^exp to the nearest
+/// representable value, ties to even. Overflows produce infinity.
+module std_float_round #(
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23,
+    parameter SIG_WIDTH = 24
+) (
+    input  logic                          sign,
+    input  logic signed [31:0]            exp,
+    input  logic [SIG_WIDTH-1:0]          sig,
+    output logic [EXP_WIDTH+MANT_WIDTH:0] out
+);
+  localparam signed [31:0] BIAS = (1 << (EXP_WIDTH - 1)) - 1;
+  localparam signed [31:0] MIN_EXP = 1 - BIAS;
+  localparam signed [31:0] MAX_BIASED_EXP = (1 << EXP_WIDTH) - 1;
+  // The mantissa with its leading bit and a bit for the carry of rounding
+  localparam KEEP_WIDTH = MANT_WIDTH + 2;
+
+  logic signed [31:0] lead, quantum, shift, biased_exp;
+  logic [KEEP_WIDTH+SIG_WIDTH-1:0] wide;
+  logic [KEEP_WIDTH-1:0] keep;
+  logic round_bit, sticky;
+
+  always_comb begin
+    lead = 0;
+    for (int i = 0; i < SIG_WIDTH; i++)
+      if (sig[i]) lead = i;
+
+    // The exponent of the least significant bit of the result
+    quantum = (lead + exp > MIN_EXP ? lead + exp : MIN_EXP) - MANT_WIDTH;
+    shift = quantum - exp;
+
+    wide = {{KEEP_WIDTH{1'b0}}, sig};
+    round_bit = 0;
+    sticky = 0;
+    if (shift <= 0) begin
+      keep = wide << -shift;
+    end else begin
+      keep = wide >> shift;
+      for (int i = 0; i < SIG_WIDTH; i++) begin
+        if (i == shift - 1) round_bit = sig[i];
+        if (i < shift - 1) sticky = sticky | sig[i];
+      end
+      if (round_bit && (sticky || keep[0])) keep = keep + 1;
+    end
+
+    // Rounding up can carry into a new leading bit
+    if (keep[MANT_WIDTH+1]) begin
+      keep = keep >> 1;
+      quantum = quantum + 1;
+    end
+    biased_exp = quantum + MANT_WIDTH + BIAS;
+
+    if (sig == 0)
+      out = {sign, {(EXP_WIDTH+MANT_WIDTH){1'b0}}};
+    else if (!keep[MANT_WIDTH])
+      out = {sign, {EXP_WIDTH{1'b0}}, keep[MANT_WIDTH-1:0]};
+    else if (biased_exp >= MAX_BIASED_EXP)
+      out = {sign, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}};
+    else
+      out = {sign, biased_exp[EXP_WIDTH-1:0], keep[MANT_WIDTH-1:0]};
+  end
+endmodule
+
+/// Combinational addition shared by `std_float_add` and `std_float_sub`.
+module std_float_add_comb #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic [WIDTH-1:0] out
+);
+  // Guard, round and sticky bits below the mantissa
+  localparam EXT_WIDTH = MANT_WIDTH + 4;
+  localparam [WIDTH-1:0] NAN =
+    {1'b0, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}} | ({{(WIDTH-1){1'b0}}, 1'b1} << (MANT_WIDTH - 1));
+
+  logic l_sign, l_nan, l_inf, r_sign, r_nan, r_inf;
+  logic [MANT_WIDTH:0] l_sig, r_sig;
+  logic signed [31:0] l_exp, r_exp;
+
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_left (
+    .in(left), .sign(l_sign), .is_nan(l_nan), .is_inf(l_inf), .sig(l_sig), .exp(l_exp)
+  );
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_right (
+    .in(right), .sign(r_sign), .is_nan(r_nan), .is_inf(r_inf), .sig(r_sig), .exp(r_exp)
+  );
+
+  logic big_sign, small_sign, sticky, sum_sign;
+  logic [EXT_WIDTH-1:0] big_sig, small_sig, shifted;
+  logic [EXT_WIDTH:0] sum;
+  logic signed [31:0] big_exp, diff;
+  logic [WIDTH-1:0] rounded;
+
+  always_comb begin
+    // The bit patterns of the magnitudes are ordered like the magnitudes
+    if (right[WIDTH-2:0] > left[WIDTH-2:0]) begin
+      {big_sign, big_sig, big_exp} = {r_sign, r_sig, 3'b0, r_exp};
+      {small_sign, small_sig} = {l_sign, l_sig, 3'b0};
+      diff = r_exp - l_exp;
+    end else begin
+      {big_sign, big_sig, big_exp} = {l_sign, l_sig, 3'b0, l_exp};
+      {small_sign, small_sig} = {r_sign, r_sig, 3'b0};
+      diff = l_exp - r_exp;
+    end
+
+    // Align the smaller operand and fold the bits shifted out into the
+    // sticky bit
+    shifted = small_sig >> diff;
+    sticky = 0;
+    for (int i = 0; i < EXT_WIDTH; i++)
+      if (i < diff) sticky = sticky | small_sig[i];
+    shifted[0] = shifted[0] | sticky;
+
+    if (big_sign == small_sign)
+      sum = big_sig + shifted;
+    else
+      sum = big_sig - shifted;
+    // An exact zero is only negative if both operands are
+    sum_sign = sum == 0 ? big_sign & small_sign : big_sign;
+  end
+
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(EXT_WIDTH + 1)
+  ) round (
+    .sign(sum_sign), .exp(big_exp - 32'sd3), .sig(sum), .out(rounded)
+  );
+
+  always_comb begin
+    if (l_nan || r_nan || (l_inf && r_inf && l_sign != r_sign))
+      out = NAN;
+    else if (l_inf)
+      out = left;
+    else if (r_inf)
+      out = right;
+    else
+      out = rounded;
+  end
+endmodule
+
+module std_float_add #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic             clk,
+    input  logic             reset,
+    input  logic             go,
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic [WIDTH-1:0] out,
+    output logic             done
+);
+  logic [WIDTH-1:0] result;
+
+  std_float_add_comb #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) add (
+    .left(left), .right(right), .out(result)
+  );
+
+  always_ff @(posedge clk) begin
+    if (reset) begin
+      out <= 0;
+      done <= 0;
+    end else if (go) begin
+      out <= result;
+      done <= 1;
+    end else begin
+      done <= 0;
+    end
+  end
+endmodule
+
+module std_float_sub #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic             clk,
+    input  logic             reset,
+    input  logic             go,
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic [WIDTH-1:0] out,
+    output logic             done
+);
+  logic [WIDTH-1:0] result;
+
+  std_float_add_comb #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) add (
+    .left(left), .right({~right[WIDTH-1], right[WIDTH-2:0]}), .out(result)
+  );
+
+  always_ff @(posedge clk) begin
+    if (reset) begin
+      out <= 0;
+      done <= 0;
+    end else if (go) begin
+      out <= result;
+      done <= 1;
+    end else begin
+      done <= 0;
+    end
+  end
+endmodule
+
+module std_float_mult #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic             clk,
+    input  logic             reset,
+    input  logic             go,
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic [WIDTH-1:0] out,
+    output logic             done
+);
+  localparam [WIDTH-1:0] NAN =
+    {1'b0, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}} | ({{(WIDTH-1){1'b0}}, 1'b1} << (MANT_WIDTH - 1));
+
+  logic l_sign, l_nan, l_inf, r_sign, r_nan, r_inf, sign;
+  logic [MANT_WIDTH:0] l_sig, r_sig;
+  logic signed [31:0] l_exp, r_exp;
+  logic [2*MANT_WIDTH+1:0] product;
+  logic [WIDTH-1:0] rounded, result;
+
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_left (
+    .in(left), .sign(l_sign), .is_nan(l_nan), .is_inf(l_inf), .sig(l_sig), .exp(l_exp)
+  );
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_right (
+    .in(right), .sign(r_sign), .is_nan(r_nan), .is_inf(r_inf), .sig(r_sig), .exp(r_exp)
+  );
+
+  assign sign = l_sign ^ r_sign;
+  assign product = l_sig * r_sig;
+
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(2 * MANT_WIDTH + 2)
+  ) round (
+    .sign(sign), .exp(l_exp + r_exp), .sig(product), .out(rounded)
+  );
+
+  always_comb begin
+    if (l_nan || r_nan || (l_inf && r_sig == 0) || (r_inf && l_sig == 0))
+      result = NAN;
+    else if (l_inf || r_inf)
+      result = {sign, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}};
+    else
+      result = rounded;
+  end
+
+  always_ff @(posedge clk) begin
+    if (reset) begin
+      out <= 0;
+      done <= 0;
+    end else if (go) begin
+      out <= result;
+      done <= 1;
+    end else begin
+      done <= 0;
+    end
+  end
+endmodule
+
+module std_float_div #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic             clk,
+    input  logic             reset,
+    input  logic             go,
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic [WIDTH-1:0] out,
+    output logic             done
+);
+  localparam [WIDTH-1:0] NAN =
+    {1'b0, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}} | ({{(WIDTH-1){1'b0}}, 1'b1} << (MANT_WIDTH - 1));
+  // Scale the dividend so that the quotient has at least two bits more than
+  // the mantissa. The remainder is folded into a sticky bit below them.
+  localparam SCALE = 2 * MANT_WIDTH + 4;
+
+  logic l_sign, l_nan, l_inf, r_sign, r_nan, r_inf, sign;
+  logic [MANT_WIDTH:0] l_sig, r_sig;
+  logic signed [31:0] l_exp, r_exp;
+  logic [MANT_WIDTH+SCALE:0] dividend, quotient, remainder;
+  logic [WIDTH-1:0] rounded, result;
+
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_left (
+    .in(left), .sign(l_sign), .is_nan(l_nan), .is_inf(l_inf), .sig(l_sig), .exp(l_exp)
+  );
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack_right (
+    .in(right), .sign(r_sign), .is_nan(r_nan), .is_inf(r_inf), .sig(r_sig), .exp(r_exp)
+  );
+
+  assign sign = l_sign ^ r_sign;
+  assign dividend = {l_sig, {SCALE{1'b0}}};
+  // Avoid dividing by zero, the result is not used in that case
+  assign quotient = dividend / (r_sig == 0 ? 1 : r_sig);
+  assign remainder = dividend % (r_sig == 0 ? 1 : r_sig);
+
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(MANT_WIDTH + SCALE + 2)
+  ) round (
+    .sign(sign),
+    .exp(l_exp - r_exp - SCALE - 1),
+    .sig({quotient, remainder != 0}),
+    .out(rounded)
+  );
+
+  always_comb begin
+    if (l_nan || r_nan || (l_inf && r_inf) || (l_sig == 0 && r_sig == 0))
+      result = NAN;
+    else if (l_inf || (r_sig == 0 && !r_inf))
+      result = {sign, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}};
+    else if (r_inf)
+      result = {sign, {(EXP_WIDTH+MANT_WIDTH){1'b0}}};
+    else
+      result = rounded;
+  end
+
+  always_ff @(posedge clk) begin
+    if (reset) begin
+      out <= 0;
+      done <= 0;
+    end else if (go) begin
+      out <= result;
+      done <= 1;
+    end else begin
+      done <= 0;
+    end
+  end
+endmodule
+
+module std_float_sqrt #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic             clk,
+    input  logic             reset,
+    input  logic             go,
+    input  logic [WIDTH-1:0] in,
+    output logic [WIDTH-1:0] out,
+    output logic             done
+);
+  localparam [WIDTH-1:0] NAN =
+    {1'b0, {EXP_WIDTH{1'b1}}, {MANT_WIDTH{1'b0}}} | ({{(WIDTH-1){1'b0}}, 1'b1} << (MANT_WIDTH - 1));
+  // As in division, compute at least two more bits than the mantissa and fold
+  // the remainder into a sticky bit.
+  localparam SCALE = MANT_WIDTH + 3;
+  // Half the width of the radicand, which must be even
+  localparam HALF = (MANT_WIDTH + 2 + 2 * SCALE + 1) / 2;
+
+  logic sign, is_nan, is_inf, odd;
+  logic [MANT_WIDTH:0] sig;
+  logic signed [31:0] exp, even_exp;
+  logic [2*HALF-1:0] radicand;
+  logic [HALF-1:0] root;
+  logic [HALF+1:0] rem, trial;
+  logic [WIDTH-1:0] rounded, result;
+
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack (
+    .in(in), .sign(sign), .is_nan(is_nan), .is_inf(is_inf), .sig(sig), .exp(exp)
+  );
+
+  always_comb begin
+    // Make the exponent even so that it can be halved
+    odd = exp[0];
+    even_exp = odd ? exp - 1 : exp;
+    radicand = {odd ? {sig, 1'b0} : {1'b0, sig}, {(2*SCALE){1'b0}}};
+
+    // Digit-by-digit integer square root
+    root = 0;
+    rem = 0;
+    for (int i = HALF - 1; i >= 0; i--) begin
+      rem = {rem, radicand[2*i +: 2]};
+      trial = {root, 2'b01};
+      if (rem >= trial) begin
+        rem = rem - trial;
+        root = {root, 1'b1};
+      end else begin
+        root = {root, 1'b0};
+      end
+    end
+  end
+
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(HALF + 1)
+  ) round (
+    .sign(1'b0),
+    .exp((even_exp >>> 1) - SCALE - 1),
+    .sig({root, rem != 0}),
+    .out(rounded)
+  );
+
+  always_comb begin
+    if (is_nan || (sign && sig != 0))
+      result = NAN;
+    else if (is_inf || sig == 0)
+      // Square roots of infinity and of signed zeros are themselves
+      result = in;
+    else
+      result = rounded;
+  end
+
+  always_ff @(posedge clk) begin
+    if (reset) begin
+      out <= 0;
+      done <= 0;
+    end else if (go) begin
+      out <= result;
+      done <= 1;
+    end else begin
+      done <= 0;
+    end
+  end
+endmodule
+
+/// Maps values to integers with the same order. The comparisons are false
+/// when either value is NaN, except for `neq`.
+module std_float_compare #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             unordered,
+    output logic             lt,
+    output logic             eq
+);
+  logic signed [WIDTH-1:0] l_key, r_key;
+
+  // Both zeros map to 0 and infinities to the extremes
+  assign l_key = left[WIDTH-1] ? -$signed({1'b0, left[WIDTH-2:0]}) : $signed({1'b0, left[WIDTH-2:0]});
+  assign r_key = right[WIDTH-1] ? -$signed({1'b0, right[WIDTH-2:0]}) : $signed({1'b0, right[WIDTH-2:0]});
+
+  assign unordered = (&left[WIDTH-2:MANT_WIDTH] && |left[MANT_WIDTH-1:0])
+    || (&right[WIDTH-2:MANT_WIDTH] && |right[MANT_WIDTH-1:0]);
+  assign lt = !unordered && l_key < r_key;
+  assign eq = !unordered && l_key == r_key;
+endmodule
+
+module std_float_lt #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(left), .right(right), .unordered(), .lt(out), .eq()
+  );
+endmodule
+
+module std_float_le #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  logic lt, eq;
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(left), .right(right), .unordered(), .lt(lt), .eq(eq)
+  );
+  assign out = lt || eq;
+endmodule
+
+module std_float_gt #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(right), .right(left), .unordered(), .lt(out), .eq()
+  );
+endmodule
+
+module std_float_ge #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  logic gt, eq;
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(right), .right(left), .unordered(), .lt(gt), .eq(eq)
+  );
+  assign out = gt || eq;
+endmodule
+
+module std_float_eq #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(left), .right(right), .unordered(), .lt(), .eq(out)
+  );
+endmodule
+
+module std_float_neq #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [WIDTH-1:0] left,
+    input  logic [WIDTH-1:0] right,
+    output logic             out
+);
+  logic eq;
+  std_float_compare #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)
+  ) compare (
+    .left(left), .right(right), .unordered(), .lt(), .eq(eq)
+  );
+  assign out = !eq;
+endmodule
+
+/// Conversion to integers shared by `std_float_to_int` and
+/// `std_float_to_sint`. Rounds toward zero and saturates.
+module std_float_to_int_comb #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23,
+    parameter OUT_WIDTH = 32,
+    parameter SIGNED = 0
+) (
+    input  logic [WIDTH-1:0]     in,
+    output logic [OUT_WIDTH-1:0] out
+);
+  localparam MAG_WIDTH = OUT_WIDTH + MANT_WIDTH + 2;
+
+  logic sign, is_nan, is_inf, overflow;
+  logic [MANT_WIDTH:0] sig;
+  logic signed [31:0] exp;
+  logic [MAG_WIDTH-1:0] mag, max_mag;
+
+  std_float_unpack #(.EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH)) unpack (
+    .in(in), .sign(sign), .is_nan(is_nan), .is_inf(is_inf), .sig(sig), .exp(exp)
+  );
+
+  always_comb begin
+    // The magnitude of the value, rounded toward zero
+    mag = {{(OUT_WIDTH+1){1'b0}}, sig};
+    overflow = is_inf;
+    if (exp >= 0) begin
+      if (exp > OUT_WIDTH)
+        overflow = overflow || sig != 0;
+      else
+        mag = mag << exp;
+    end else begin
+      mag = mag >> -exp;
+    end
+
+    // The largest magnitude that can be represented with the sign
+    if (SIGNED)
+      max_mag = ({{(MAG_WIDTH-1){1'b0}}, 1'b1} << (OUT_WIDTH - 1)) - (sign ? 0 : 1);
+    else
+      max_mag = sign ? 0 : ({{(MAG_WIDTH-1){1'b0}}, 1'b1} << OUT_WIDTH) - 1;
+    if (overflow || mag > max_mag)
+      mag = max_mag;
+
+    if (is_nan)
+      out = 0;
+    else if (sign)
+      out = -mag[OUT_WIDTH-1:0];
+    else
+      out = mag[OUT_WIDTH-1:0];
+  end
+endmodule
+
+module std_float_to_int #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23,
+    parameter OUT_WIDTH = 32
+) (
+    input  logic [WIDTH-1:0]     in,
+    output logic [OUT_WIDTH-1:0] out
+);
+  std_float_to_int_comb #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH),
+    .OUT_WIDTH(OUT_WIDTH), .SIGNED(0)
+  ) convert (
+    .in(in), .out(out)
+  );
+endmodule
+
+module std_float_to_sint #(
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23,
+    parameter OUT_WIDTH = 32
+) (
+    input  logic [WIDTH-1:0]     in,
+    output logic [OUT_WIDTH-1:0] out
+);
+  std_float_to_int_comb #(
+    .WIDTH(WIDTH), .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH),
+    .OUT_WIDTH(OUT_WIDTH), .SIGNED(1)
+  ) convert (
+    .in(in), .out(out)
+  );
+endmodule
+
+module std_int_to_float #(
+    parameter IN_WIDTH = 32,
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [IN_WIDTH-1:0] in,
+    output logic [WIDTH-1:0]    out
+);
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(IN_WIDTH)
+  ) round (
+    .sign(1'b0), .exp(32'sd0), .sig(in), .out(out)
+  );
+endmodule
+
+module std_sint_to_float #(
+    parameter IN_WIDTH = 32,
+    parameter WIDTH = 32,
+    parameter EXP_WIDTH = 8,
+    parameter MANT_WIDTH = 23
+) (
+    input  logic [IN_WIDTH-1:0] in,
+    output logic [WIDTH-1:0]    out
+);
+  logic [IN_WIDTH-1:0] mag;
+  assign mag = in[IN_WIDTH-1] ? -in : in;
+
+  std_float_round #(
+    .EXP_WIDTH(EXP_WIDTH), .MANT_WIDTH(MANT_WIDTH), .SIG_WIDTH(IN_WIDTH)
+  ) round (
+    .sign(in[IN_WIDTH-1]), .exp(32'sd0), .sig(mag), .out(out)
+  );
+endmodule
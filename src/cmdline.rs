@@ -4,8 +4,9 @@ use argh::FromArgs;
 use calyx_backend::SexpBackend;
 use calyx_backend::{
     xilinx::{XilinxInterfaceBackend, XilinxXmlBackend},
-    Backend, BackendOpt, FirrtlBackend, MlirBackend, PrimitiveUsesBackend,
-    ResourcesBackend, VerilogBackend,
+    Backend, BackendOpt, FirrtlBackend, JsonBackend, MlirBackend,
+    PrimitiveUsesBackend, ResourcesBackend, TimingBackend,
+    VerilatorHarnessBackend, VerilogBackend, YxiBackend,
 };
 use calyx_ir as ir;
 use calyx_utils::{CalyxResult, Error, OutputFile};
@@ -73,10 +74,24 @@ pub struct Opts {
     #[argh(switch, long = "emit-primitive-extmodules")]
     pub emit_primitive_extmodules: bool,
 
+    /// emit FIRRTL implementations of primitives (only relevant to the
+    /// FIRRTL backend)
+    #[argh(switch, long = "emit-primitive-definitions")]
+    pub emit_primitive_definitions: bool,
+
     /// select a backend
     #[argh(option, short = 'b', default = "BackendOpt::default()")]
     pub backend: BackendOpt,
 
+    /// directory used to cache the generated code for each component
+    /// between invocations (only relevant to the Verilog backend)
+    #[argh(option, long = "cache-dir")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// number of threads used by passes that visit components independently
+    #[argh(option, short = 'j', long = "threads", default = "1")]
+    pub threads: usize,
+
     /// run this pass during execution
     #[argh(option, short = 'p')]
     pub pass: Vec<String>,
@@ -159,6 +174,14 @@ impl Opts {
                     ))
                 }
             }
+            BackendOpt::Timing => {
+                let backend = TimingBackend;
+                backend.run(context, self.output)
+            }
+            BackendOpt::VerilatorHarness => {
+                let backend = VerilatorHarnessBackend;
+                backend.run(context, self.output)
+            }
             BackendOpt::Verilog => {
                 let backend = VerilogBackend;
                 backend.run(context, self.output)
@@ -179,6 +202,14 @@ impl Opts {
                 let backend = PrimitiveUsesBackend;
                 backend.run(context, self.output)
             }
+            BackendOpt::Yxi => {
+                let backend = YxiBackend;
+                backend.run(context, self.output)
+            }
+            BackendOpt::Json => {
+                let backend = JsonBackend;
+                backend.run(context, self.output)
+            }
             BackendOpt::Calyx => {
                 ir::Printer::write_context(
                     &context,
@@ -204,6 +235,13 @@ impl Opts {
             )));
         }
 
+        if opts.cache_dir.is_some() && opts.backend != BackendOpt::Verilog {
+            return Err(Error::misc(format!(
+                "--cache-dir is only supported by the verilog backend, not `-b {}`",
+                opts.backend.to_string()
+            )));
+        }
+
         // argh doesn't allow us to specify a default for this so we fill it
         // in manually.
         if opts.pass.is_empty() {
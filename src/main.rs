@@ -6,12 +6,14 @@
 //! Instead, depend on the crates that this crate depends: [`calyx_frontend`], [`calyx_ir`], [`calyx_opt`].
 
 mod cmdline;
-use calyx_backend::BackendOpt;
-use calyx_frontend as frontend;
+use calyx_backend::{BackendOpt, ComponentCache};
+use calyx_frontend::{self as frontend, interchange::Program};
 use calyx_ir as ir;
 use calyx_opt::pass_manager::{PassManager, PassResult};
+use calyx_utils::{CalyxResult, Error};
 use cmdline::{CompileMode, Opts};
 use itertools::Itertools;
+use std::path::{Path, PathBuf};
 
 fn main() -> PassResult<()> {
     // parse the command line arguments into Opts struct
@@ -56,21 +58,16 @@ fn main() -> PassResult<()> {
     }
 
     // Construct the namespace.
-    let mut ws = frontend::Workspace::construct(&opts.file, &opts.lib_path)?;
+    let mut ws = load_workspace(&opts)?;
 
     let imports = ws.original_imports.drain(..).collect_vec();
 
     // Build the IR representation
-    let mut ctx = ir::from_ast::ast_to_ir(ws)?;
-    // Configuration for the backend
-    ctx.bc = ir::BackendConf {
-        synthesis_mode: opts.enable_synthesis,
-        enable_verification: !opts.disable_verify,
-        flat_assign: !opts.nested_assign,
-        emit_primitive_extmodules: opts.emit_primitive_extmodules,
-    };
-    // Extra options for the passes
-    ctx.extra_opts = opts.extra_opts.drain(..).collect();
+    let mut ctx = build_context(ws, &opts)?;
+
+    if let Some(dir) = opts.cache_dir.take() {
+        return compile_cached(dir, ctx, &pm, opts);
+    }
 
     // Run all passes specified by the command line
     pm.execute_plan(
@@ -102,3 +99,116 @@ fn main() -> PassResult<()> {
         Ok(opts.run_backend(ctx)?)
     }
 }
+
+/// Construct the workspace for the input file. Files with the `.json` (or
+/// `.sexp`) extension contain programs serialized with the interchange schema
+/// and are not parsed as Calyx syntax.
+fn load_workspace(opts: &Opts) -> CalyxResult<frontend::Workspace> {
+    let Some(file) = &opts.file else {
+        return frontend::Workspace::construct(&opts.file, &opts.lib_path);
+    };
+    let program = match file.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let contents = std::fs::read_to_string(file)?;
+            serde_json::from_str::<Program>(&contents).map_err(|err| {
+                Error::misc(format!(
+                    "Failed to read `{}`: {err}",
+                    file.display()
+                ))
+            })?
+        }
+        #[cfg(feature = "serialize")]
+        Some("sexp") => {
+            let contents = std::fs::read_to_string(file)?;
+            serde_sexpr::from_str::<Program>(&contents).map_err(|err| {
+                Error::misc(format!(
+                    "Failed to read `{}`: {err}",
+                    file.display()
+                ))
+            })?
+        }
+        #[cfg(not(feature = "serialize"))]
+        Some("sexp") => {
+            return Err(Error::misc(
+                "Reading `.sexp` programs requires the `serialize` feature to be enabled",
+            ))
+        }
+        _ => return frontend::Workspace::construct(&opts.file, &opts.lib_path),
+    };
+    program.into_workspace(&parent_dir(file), &opts.lib_path)
+}
+
+/// Directory that contains `file`.
+fn parent_dir(file: &Path) -> PathBuf {
+    match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Build the IR representation of the workspace and configure it using the
+/// command line options.
+fn build_context(
+    ws: frontend::Workspace,
+    opts: &Opts,
+) -> CalyxResult<ir::Context> {
+    let mut ctx = ir::from_ast::ast_to_ir(ws)?;
+    // Configuration for the backend
+    ctx.bc = ir::BackendConf {
+        synthesis_mode: opts.enable_synthesis,
+        enable_verification: !opts.disable_verify,
+        flat_assign: !opts.nested_assign,
+        emit_primitive_extmodules: opts.emit_primitive_extmodules,
+        emit_primitive_definitions: opts.emit_primitive_definitions,
+        extern_roots: std::iter::once(opts.lib_path.clone())
+            .chain(opts.file.as_deref().map(parent_dir))
+            .collect(),
+    };
+    // Extra options for the passes
+    ctx.extra_opts = opts.extra_opts.clone();
+    ctx.threads = opts.threads;
+    Ok(ctx)
+}
+
+/// Compile the program with the Verilog backend and only run the passes on
+/// the components whose output is not in the cache stored in `dir`.
+fn compile_cached(
+    dir: PathBuf,
+    mut ctx: ir::Context,
+    pm: &PassManager,
+    mut opts: Opts,
+) -> PassResult<()> {
+    let pipeline = [
+        opts.pass.join(","),
+        opts.disable_pass.join(","),
+        opts.insertions.join(","),
+        opts.extra_opts.join(","),
+        format!(
+            "{} {} {}",
+            opts.enable_synthesis, opts.disable_verify, opts.nested_assign
+        ),
+    ]
+    .join("\n");
+    let mut cache = ComponentCache::new(dir, &pipeline, &ctx)?;
+    loop {
+        cache.prune(&mut ctx);
+        if !cache.is_complete() {
+            pm.execute_plan(
+                &mut ctx,
+                &opts.pass,
+                &opts.disable_pass,
+                &opts.insertions,
+                opts.dump_ir,
+            )?;
+        }
+        // If compiling a component changed the interface its cached parents
+        // were compiled against, the parents need to be compiled again.
+        if cache.validate(&ctx) {
+            break;
+        }
+        let ws = load_workspace(&opts)?;
+        ctx = build_context(ws, &opts)?;
+    }
+    cache.emit(&ctx, &mut opts.output)?;
+    Ok(())
+}
@@ -0,0 +1,247 @@
+//! Conversion between data dumps and directories of hex-encoded `.dat` files
+//! for RTL simulators, using the same layout as fud's `json-dat.py`. Every
+//! memory is stored in a file named after the memory with one value per line,
+//! in row-major order, as an unprefixed hexadecimal number that can be loaded
+//! using `$readmemh`. A `shape.json` file records the format and dimensions of
+//! every memory so that the directory can be converted back.
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use interp::serialization::{DataDump, Dimensions, MemoryDeclaration};
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::json_data::FormatInfo;
+
+/// The name of the file describing the memories in the directory
+pub const SHAPE_FILE: &str = "shape.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MemoryShape {
+    #[serde(flatten)]
+    format: FormatInfo,
+    shape: Vec<usize>,
+}
+
+#[derive(Debug, Error)]
+pub enum DatError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("Malformed {SHAPE_FILE}: {0}")]
+    Shape(#[from] serde_json::Error),
+
+    #[error("Memory `{name}' has unsupported shape {shape:?}. Memories must have between one and four dimensions")]
+    BadShape { name: String, shape: Vec<usize> },
+
+    #[error("Data file `{}' for memory `{name}' is missing. Is the memory marked with @external?", path.display())]
+    MissingFile { name: String, path: PathBuf },
+
+    #[error("{}:{line}: `{value}' is not a valid {width}-bit hexadecimal value", path.display())]
+    BadValue {
+        path: PathBuf,
+        line: usize,
+        value: String,
+        width: u32,
+    },
+
+    #[error("Memory `{name}' has {expected} elements but `{}' contains {found} values", path.display())]
+    WrongCount {
+        name: String,
+        path: PathBuf,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Writes the memories in the data dump to `<dir>/<memory>.<extension>` along
+/// with a `shape.json` file describing them. The directory is created if it
+/// does not exist.
+pub fn write_dat_dir(
+    dump: &DataDump,
+    dir: &Path,
+    extension: &str,
+) -> Result<(), DatError> {
+    fs::create_dir_all(dir)?;
+
+    let mut shapes = BTreeMap::new();
+    for declaration in &dump.header.memories {
+        let data = dump.get_data(&declaration.name).unwrap();
+        let bytes_per_value = declaration.width().div_ceil(8) as usize;
+
+        let mut contents = String::new();
+        for value in data.chunks_exact(bytes_per_value) {
+            contents.push_str(
+                &BigUint::from_bytes_le(value)
+                    .to_str_radix(16)
+                    .to_uppercase(),
+            );
+            contents.push('\n');
+        }
+        fs::write(
+            dir.join(format!("{}.{extension}", declaration.name)),
+            contents,
+        )?;
+
+        let shape = match declaration.dimensions {
+            Dimensions::D1(d0) => vec![d0],
+            Dimensions::D2(d0, d1) => vec![d0, d1],
+            Dimensions::D3(d0, d1, d2) => vec![d0, d1, d2],
+            Dimensions::D4(d0, d1, d2, d3) => vec![d0, d1, d2, d3],
+        };
+        shapes.insert(
+            declaration.name.clone(),
+            MemoryShape {
+                format: (&declaration.format).into(),
+                shape,
+            },
+        );
+    }
+
+    let shape_file = File::create(dir.join(SHAPE_FILE))?;
+    serde_json::to_writer_pretty(shape_file, &shapes)?;
+    Ok(())
+}
+
+/// Reads the memories described by `<dir>/shape.json` from the files
+/// `<dir>/<memory>.<extension>`. Lines starting with `//` are ignored since
+/// some simulators emit comments in the files they write.
+pub fn read_dat_dir(dir: &Path, extension: &str) -> Result<DataDump, DatError> {
+    let shapes: BTreeMap<String, MemoryShape> =
+        serde_json::from_reader(File::open(dir.join(SHAPE_FILE))?)?;
+
+    let mut dump = DataDump::new_empty();
+    for (name, shape) in shapes {
+        let dimensions = match shape.shape[..] {
+            [d0] => Dimensions::D1(d0),
+            [d0, d1] => Dimensions::D2(d0, d1),
+            [d0, d1, d2] => Dimensions::D3(d0, d1, d2),
+            [d0, d1, d2, d3] => Dimensions::D4(d0, d1, d2, d3),
+            _ => {
+                return Err(DatError::BadShape {
+                    name,
+                    shape: shape.shape,
+                })
+            }
+        };
+        let format = shape.format.as_data_dump_format();
+        let width = format.width();
+        let bytes_per_value = width.div_ceil(8) as usize;
+
+        let path = dir.join(format!("{name}.{extension}"));
+        let contents = fs::read_to_string(&path).map_err(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                DatError::MissingFile {
+                    name: name.clone(),
+                    path: path.clone(),
+                }
+            } else {
+                err.into()
+            }
+        })?;
+
+        let mut data = Vec::with_capacity(bytes_per_value * dimensions.size());
+        let mut found = 0;
+        for (line, value) in contents.lines().enumerate() {
+            let value = value.trim();
+            if value.is_empty() || value.starts_with("//") {
+                continue;
+            }
+            let parsed = BigUint::from_str_radix(value, 16)
+                .ok()
+                .filter(|parsed| parsed.bits() <= width as u64)
+                .ok_or_else(|| DatError::BadValue {
+                    path: path.clone(),
+                    line: line + 1,
+                    value: value.to_string(),
+                    width,
+                })?;
+
+            let mut bytes = parsed.to_bytes_le();
+            bytes.resize(bytes_per_value, 0);
+            data.extend(bytes);
+            found += 1;
+        }
+
+        if found != dimensions.size() {
+            return Err(DatError::WrongCount {
+                name,
+                path,
+                expected: dimensions.size(),
+                found,
+            });
+        }
+
+        dump.push_memory(
+            MemoryDeclaration::new(name, dimensions, format),
+            data,
+        );
+    }
+
+    Ok(dump)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{converter::convert_to_data_dump, json_data::JsonData};
+
+    #[test]
+    fn test_dat_roundtrip() {
+        let json: JsonData = serde_json::from_str(
+            r#"{
+  "ints": {
+    "data": [[-1, 2, 3], [4, 5, -6]],
+    "format": { "numeric_type": "bitnum", "is_signed": true, "width": 12 }
+  },
+  "fixed": {
+    "data": [-1.5, 0.25, 3.75],
+    "format": { "numeric_type": "fixed_point", "is_signed": true, "width": 8, "int_width": 4 }
+  },
+  "floats": {
+    "data": [1.0, -0.1, 65504.0],
+    "format": { "numeric_type": "ieee754_float", "is_signed": true, "width": 16 }
+  }
+}"#,
+        )
+        .unwrap();
+        let dump = convert_to_data_dump(&json, true);
+
+        let dir = std::env::temp_dir()
+            .join(format!("cider-dat-roundtrip-{}", std::process::id()));
+        write_dat_dir(&dump, &dir, "dat").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("ints.dat")).unwrap(),
+            "FFF\n2\n3\n4\n5\nFFA\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("floats.dat")).unwrap(),
+            "3C00\nAE66\n7BFF\n"
+        );
+
+        let mut read_back = read_dat_dir(&dir, "dat").unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // the directory does not preserve the order of the memories
+        read_back
+            .header
+            .memories
+            .sort_by(|a, b| a.name.cmp(&b.name));
+        for dec in &dump.header.memories {
+            let other = read_back
+                .header
+                .memories
+                .iter()
+                .find(|x| x.name == dec.name)
+                .unwrap();
+            assert_eq!(dec, other);
+            assert_eq!(dump.get_data(&dec.name), read_back.get_data(&dec.name));
+        }
+    }
+}
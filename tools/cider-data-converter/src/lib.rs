@@ -1,2 +1,4 @@
-pub mod converter;
-pub mod json_data;
+pub mod dat;
+pub mod values;
+
+pub use interp::serialization::{converter, json_data};
@@ -1,5 +1,10 @@
 use argh::FromArgs;
-use cider_data_converter::{converter, json_data::JsonData};
+use cider_data_converter::{
+    converter,
+    dat::{self, DatError},
+    json_data::JsonData,
+    values::{self, ValueError, ValueFormat, ValueSpec},
+};
 use interp::serialization::{self, SerializationError};
 use std::{
     fs::File,
@@ -17,17 +22,28 @@ enum CiderDataConverterError {
     #[error("Failed to read file: {0}")]
     IoError(#[from] std::io::Error),
 
-    #[error("Failed to parse \"to\" argument: {0}")]
-    BadToArgument(String),
+    #[error(
+        "Unknown data format \"{0}\". Expected \"json\", \"cider\" or \"dat\""
+    )]
+    BadFormatArgument(String),
 
     #[error("Unable to guess the conversion target. Please specify the target using the \"--to\" argument")]
     UnknownTarget,
 
+    #[error("The {0} of a dat conversion must be a directory. Please specify it explicitly")]
+    MissingDirectory(&'static str),
+
     #[error(transparent)]
     SerdeError(#[from] serde_json::Error),
 
     #[error(transparent)]
     DataDumpError(#[from] SerializationError),
+
+    #[error(transparent)]
+    DatError(#[from] DatError),
+
+    #[error(transparent)]
+    ValueError(#[from] ValueError),
 }
 
 impl std::fmt::Debug for CiderDataConverterError {
@@ -36,30 +52,51 @@ impl std::fmt::Debug for CiderDataConverterError {
     }
 }
 
-enum Action {
-    ToDataDump,
-    ToJson,
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    DataDump,
+    Dat,
 }
 
-impl FromStr for Action {
+impl FromStr for Format {
     type Err = CiderDataConverterError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "json" => Ok(Action::ToJson),
-            "cider" | "dump" | "data-dump" => Ok(Action::ToDataDump),
-            _ => Err(CiderDataConverterError::BadToArgument(s.to_string())),
+            "json" => Ok(Format::Json),
+            "cider" | "dump" | "data-dump" => Ok(Format::DataDump),
+            "dat" | "hex" => Ok(Format::Dat),
+            _ => Err(CiderDataConverterError::BadFormatArgument(s.to_string())),
+        }
+    }
+}
+
+impl Format {
+    /// Guess the format of a file based on its extension
+    fn guess(path: &Option<PathBuf>) -> Option<Self> {
+        let path = path.as_ref()?;
+        if path.is_dir() {
+            return Some(Format::Dat);
+        }
+        match path.extension()?.to_str()? {
+            JSON_EXTENSION | "json" => Some(Format::Json),
+            CIDER_EXTENSION => Some(Format::DataDump),
+            _ => None,
         }
     }
 }
 
 #[derive(FromArgs)]
-/// Convert json data files to Cider DataDumps and vice-versa
+/// Convert memory data between json data files, Cider DataDumps and
+/// directories of hex-encoded .dat files
 struct Opts {
-    /// the input file to be converted. If not provided, it will read from stdin
+    /// the input file to be converted. If not provided, it will read from stdin.
+    /// Must be a directory when converting from "dat"
     #[argh(positional)]
     input_path: Option<PathBuf>,
-    /// the output file to be written. If not provided, it will write to stdout
+    /// the output file to be written. If not provided, it will write to stdout.
+    /// Must be a directory when converting to "dat"
     #[argh(option, short = 'o')]
     output_path: Option<PathBuf>,
 
@@ -68,78 +105,173 @@ struct Opts {
     #[argh(switch, short = 'r', long = "round-float")]
     round_float: bool,
 
-    /// optional specification of what action to perform. Can be "cider" or
-    /// "json". If not provided, the converter will try to guess based on file names
+    /// optional specification of the input format. Can be "json", "cider" or
+    /// "dat". If not provided, the converter will try to guess based on file
+    /// names and the output format
+    #[argh(option, short = 'f', long = "from")]
+    from: Option<Format>,
+
+    /// optional specification of the output format. Can be "json", "cider" or
+    /// "dat". If not provided, the converter will try to guess based on file
+    /// names and the input format
     #[argh(option, short = 't', long = "to")]
-    action: Option<Action>,
+    to: Option<Format>,
+
+    /// the extension of the memory files in a dat directory. Defaults to "dat"
+    /// when writing and to "out", the extension used by the RTL simulation
+    /// testbench, when reading
+    #[argh(option, long = "dat-extension")]
+    dat_extension: Option<String>,
 
     /// whether to use quotes around floating point numbers in the output. This
     /// exists solely for backwards compatibility with the old display format.
     #[argh(switch, long = "legacy-quotes")]
     use_quotes: bool,
+
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Values(ValuesOpts),
+}
+
+#[derive(FromArgs)]
+/// Convert lists of values with one value per line between their bits, in
+/// binary or hexadecimal, and decimal numbers
+#[argh(subcommand, name = "values")]
+struct ValuesOpts {
+    /// the input file. If not provided, it will read from stdin
+    #[argh(positional)]
+    input_path: Option<PathBuf>,
+    /// the output file. If not provided, it will write to stdout
+    #[argh(option, short = 'o')]
+    output_path: Option<PathBuf>,
+
+    /// the format of the input values. Can be "binary", "hex", "bitnum",
+    /// "fixed" or "float"
+    #[argh(option, short = 'f', long = "from")]
+    from: ValueFormat,
+
+    /// the format of the output values. Can be "binary", "hex", "bitnum",
+    /// "fixed" or "float"
+    #[argh(option, short = 't', long = "to")]
+    to: ValueFormat,
+
+    /// the width of the values in bits. Defaults to 32
+    #[argh(option, short = 'w', default = "32")]
+    width: u32,
+
+    /// the number of fractional bits of fixed-point values
+    #[argh(option, long = "frac-width")]
+    frac_width: Option<u32>,
+
+    /// whether integers and fixed-point values are signed
+    #[argh(switch, short = 's')]
+    signed: bool,
+
+    /// whether to round un-representable fixed-point values rather than
+    /// throwing an error
+    #[argh(switch, short = 'r', long = "round-float")]
+    round_float: bool,
 }
 
 fn main() -> Result<(), CiderDataConverterError> {
-    let mut opts: Opts = argh::from_env();
-
-    let mut input: Box<dyn Read> = opts
-        .input_path
-        .as_ref()
-        .map(|path| File::open(path).map(|x| Box::new(x) as Box<dyn Read>))
-        .unwrap_or(Ok(Box::new(io::stdin())))?;
-
-    let mut output: Box<dyn Write> = opts
-        .output_path
-        .as_ref()
-        .map(|path| File::create(path).map(|x| Box::new(x) as Box<dyn Write>))
-        .unwrap_or(Ok(Box::new(io::stdout())))?;
-
-    // if no action is specified, try to guess based on file extensions
-    if opts.action.is_none()
-        && (opts.input_path.as_ref().is_some_and(|x| {
-            x.extension().map_or(false, |y| y == JSON_EXTENSION)
-        }) || opts.output_path.as_ref().is_some_and(|x| {
-            x.extension().map_or(false, |y| y == CIDER_EXTENSION)
-        }))
-    {
-        opts.action = Some(Action::ToDataDump);
-    } else if opts.action.is_none()
-        && (opts.output_path.as_ref().is_some_and(|x| {
-            x.extension().map_or(false, |x| x == JSON_EXTENSION)
-        }) || opts.input_path.as_ref().is_some_and(|x| {
-            x.extension().map_or(false, |x| x == CIDER_EXTENSION)
-        }))
-    {
-        opts.action = Some(Action::ToJson);
+    let opts: Opts = argh::from_env();
+    if let Some(Command::Values(values)) = opts.command {
+        return convert_values(values);
     }
 
-    if let Some(action) = opts.action {
-        match action {
-            Action::ToDataDump => {
-                let parsed_json: JsonData =
-                    serde_json::from_reader(&mut input)?;
-                converter::convert_to_data_dump(&parsed_json, opts.round_float)
-                    .serialize(&mut output)?;
-            }
-            Action::ToJson => {
-                let data_dump =
-                    serialization::DataDump::deserialize(&mut input)?;
-                let json_data = converter::convert_from_data_dump(
-                    &data_dump,
-                    opts.use_quotes,
-                );
-                writeln!(
-                    &mut output,
-                    "{}",
-                    serde_json::to_string_pretty(&json_data)?
-                )?;
-            }
-        }
-    } else {
+    // if the formats are not specified, try to guess based on file extensions
+    let from = opts.from.or_else(|| Format::guess(&opts.input_path));
+    let to = opts.to.or_else(|| Format::guess(&opts.output_path));
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        (Some(Format::Json), None) => (Format::Json, Format::DataDump),
+        (Some(from), None) => (from, Format::Json),
+        (None, Some(Format::Json)) => (Format::DataDump, Format::Json),
+        (None, Some(to)) => (Format::Json, to),
         // Since we can't guess based on input/output file names and no target
         // was specified, we just error out.
-        return Err(CiderDataConverterError::UnknownTarget);
+        (None, None) => return Err(CiderDataConverterError::UnknownTarget),
+    };
+
+    let dump = match from {
+        Format::Json => {
+            let parsed_json: JsonData =
+                serde_json::from_reader(open_input(&opts.input_path)?)?;
+            converter::convert_to_data_dump(&parsed_json, opts.round_float)
+        }
+        Format::DataDump => serialization::DataDump::deserialize(
+            &mut open_input(&opts.input_path)?,
+        )?,
+        Format::Dat => dat::read_dat_dir(
+            opts.input_path
+                .as_ref()
+                .ok_or(CiderDataConverterError::MissingDirectory("input"))?,
+            opts.dat_extension.as_deref().unwrap_or("out"),
+        )?,
+    };
+
+    match to {
+        Format::Json => {
+            let json_data =
+                converter::convert_from_data_dump(&dump, opts.use_quotes);
+            writeln!(
+                open_output(&opts.output_path)?,
+                "{}",
+                serde_json::to_string_pretty(&json_data)?
+            )?;
+        }
+        Format::DataDump => {
+            dump.serialize(&mut open_output(&opts.output_path)?)?;
+        }
+        Format::Dat => dat::write_dat_dir(
+            &dump,
+            opts.output_path
+                .as_ref()
+                .ok_or(CiderDataConverterError::MissingDirectory("output"))?,
+            opts.dat_extension.as_deref().unwrap_or("dat"),
+        )?,
     }
 
     Ok(())
 }
+
+fn convert_values(opts: ValuesOpts) -> Result<(), CiderDataConverterError> {
+    let mut input = String::new();
+    open_input(&opts.input_path)?.read_to_string(&mut input)?;
+    let spec = ValueSpec {
+        width: opts.width,
+        signed: opts.signed,
+        frac_width: opts.frac_width,
+    };
+    let converted = values::convert_values(
+        &input,
+        opts.from,
+        opts.to,
+        &spec,
+        opts.round_float,
+    )?;
+    let mut output = open_output(&opts.output_path)?;
+    for value in converted {
+        writeln!(output, "{value}")?;
+    }
+    Ok(())
+}
+
+fn open_input(path: &Option<PathBuf>) -> io::Result<Box<dyn Read>> {
+    Ok(match path {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::stdin()),
+    })
+}
+
+fn open_output(path: &Option<PathBuf>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    })
+}
@@ -0,0 +1,407 @@
+//! Conversion of lists of values with one value per line, as used by the
+//! `data-conversion` tool that this replaces. A value is either given by its
+//! bits, in binary or hexadecimal, or as a decimal number in one of the
+//! numeric formats of the JSON data format. Decimal values go through the
+//! same conversions as JSON data files, so converting between the formats
+//! never loses precision.
+use std::{collections::HashMap, str::FromStr};
+
+use interp::serialization::{DataDump, Dimensions, MemoryDeclaration};
+use num_bigint::BigUint;
+use num_traits::Num;
+use serde_json::Number;
+use thiserror::Error;
+
+use crate::{
+    converter,
+    json_data::{FormatInfo, JsonData, JsonDataEntry, NumericType, ParseVec},
+};
+
+/// The name of the memory used to convert decimal values
+const VALUES: &str = "values";
+
+/// How a value is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    /// The bits of the value, most significant first
+    Binary,
+    /// The bits of the value as an unprefixed hexadecimal number
+    Hex,
+    /// A decimal number in the given numeric format
+    Decimal(NumericType),
+}
+
+impl FromStr for ValueFormat {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "binary" => Ok(ValueFormat::Binary),
+            "hex" => Ok(ValueFormat::Hex),
+            "bitnum" | "int" => Ok(ValueFormat::Decimal(NumericType::Bitnum)),
+            "fixed" | "fixed_point" => {
+                Ok(ValueFormat::Decimal(NumericType::Fixed))
+            }
+            "float" | "ieee754_float" => {
+                Ok(ValueFormat::Decimal(NumericType::IEEE754Float))
+            }
+            _ => Err(ValueError::BadFormat(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ValueError {
+    #[error("Unknown value format \"{0}\". Expected \"binary\", \"hex\", \"bitnum\", \"fixed\" or \"float\"")]
+    BadFormat(String),
+
+    #[error(
+        "Floating-point values must have a width of 16, 32 or 64, not {0}"
+    )]
+    BadFloatWidth(u32),
+
+    #[error("Fixed-point values need a number of fractional bits that is at most the width")]
+    MissingFracWidth,
+
+    #[error("line {line}: `{value}' is not a valid {width}-bit {kind} value")]
+    BadValue {
+        line: usize,
+        value: String,
+        width: u32,
+        kind: &'static str,
+    },
+
+    #[error("Values with infinities or NaNs cannot be printed as decimals")]
+    NotFinite,
+}
+
+/// The format of the values being converted
+#[derive(Debug, Clone, Copy)]
+pub struct ValueSpec {
+    pub width: u32,
+    pub signed: bool,
+    /// The number of fractional bits of fixed-point values
+    pub frac_width: Option<u32>,
+}
+
+impl ValueSpec {
+    /// The format of decimal values with the numeric type
+    fn format_info(
+        &self,
+        numeric_type: NumericType,
+    ) -> Result<FormatInfo, ValueError> {
+        let frac_width = match numeric_type {
+            NumericType::Bitnum => None,
+            NumericType::Fixed => Some(
+                self.frac_width
+                    .filter(|frac| *frac <= self.width)
+                    .ok_or(ValueError::MissingFracWidth)?,
+            ),
+            NumericType::IEEE754Float => {
+                if ![16, 32, 64].contains(&self.width) {
+                    return Err(ValueError::BadFloatWidth(self.width));
+                }
+                None
+            }
+        };
+        Ok(FormatInfo {
+            numeric_type,
+            is_signed: self.signed || numeric_type == NumericType::IEEE754Float,
+            width: Some(self.width),
+            int_width: None,
+            frac_width,
+        })
+    }
+}
+
+/// Converts every non-empty line of `input` from one format to another and
+/// returns the converted values.
+pub fn convert_values(
+    input: &str,
+    from: ValueFormat,
+    to: ValueFormat,
+    spec: &ValueSpec,
+    round_float: bool,
+) -> Result<Vec<String>, ValueError> {
+    let lines = input
+        .lines()
+        .enumerate()
+        .map(|(line, value)| (line + 1, value.trim()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect::<Vec<_>>();
+    let bits = read_values(&lines, from, spec, round_float)?;
+    write_values(&bits, to, spec)
+}
+
+/// The bits of every value, as little-endian bytes
+fn read_values(
+    lines: &[(usize, &str)],
+    from: ValueFormat,
+    spec: &ValueSpec,
+    round_float: bool,
+) -> Result<Vec<Vec<u8>>, ValueError> {
+    let bytes_per_value = spec.width.div_ceil(8) as usize;
+    let (radix, kind, prefix) = match from {
+        ValueFormat::Binary => (2, "binary", "0b"),
+        ValueFormat::Hex => (16, "hexadecimal", "0x"),
+        ValueFormat::Decimal(numeric_type) => {
+            return read_decimals(lines, numeric_type, spec, round_float)
+        }
+    };
+    lines
+        .iter()
+        .map(|(line, value)| {
+            // Binary values may be split into fields, i.e. the sign,
+            // exponent and mantissa of a float
+            let digits =
+                value.trim_start_matches(prefix).replace([' ', '_'], "");
+            let parsed = BigUint::from_str_radix(&digits, radix)
+                .ok()
+                .filter(|parsed| parsed.bits() <= spec.width as u64)
+                .ok_or_else(|| ValueError::BadValue {
+                    line: *line,
+                    value: value.to_string(),
+                    width: spec.width,
+                    kind,
+                })?;
+            let mut bytes = parsed.to_bytes_le();
+            bytes.resize(bytes_per_value, 0);
+            Ok(bytes)
+        })
+        .collect()
+}
+
+/// Reads decimal values by converting them like a memory in a JSON data file
+fn read_decimals(
+    lines: &[(usize, &str)],
+    numeric_type: NumericType,
+    spec: &ValueSpec,
+    round_float: bool,
+) -> Result<Vec<Vec<u8>>, ValueError> {
+    let format = spec.format_info(numeric_type)?;
+    let kind = match numeric_type {
+        NumericType::Bitnum => "integer",
+        NumericType::Fixed => "fixed-point",
+        NumericType::IEEE754Float => "floating-point",
+    };
+    let numbers = lines
+        .iter()
+        .map(|(line, value)| {
+            let bad = || ValueError::BadValue {
+                line: *line,
+                value: value.to_string(),
+                width: spec.width,
+                kind,
+            };
+            let number = Number::from_str(value).map_err(|_| bad())?;
+            // Check every value on its own to report the line
+            ParseVec::D1(vec![number.clone()])
+                .parse(&format)
+                .map_err(|_| bad())?;
+            Ok(number)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let json = JsonData(HashMap::from([(
+        VALUES.to_string(),
+        JsonDataEntry {
+            data: ParseVec::D1(numbers),
+            format,
+        },
+    )]));
+    let dump = converter::convert_to_data_dump(&json, round_float);
+    let bytes_per_value = spec.width.div_ceil(8) as usize;
+    Ok(dump
+        .get_data(VALUES)
+        .unwrap()
+        .chunks_exact(bytes_per_value)
+        .map(<[u8]>::to_vec)
+        .collect())
+}
+
+fn write_values(
+    values: &[Vec<u8>],
+    to: ValueFormat,
+    spec: &ValueSpec,
+) -> Result<Vec<String>, ValueError> {
+    let width = spec.width as usize;
+    match to {
+        ValueFormat::Binary => Ok(values
+            .iter()
+            .map(|bytes| format!("{:0width$b}", BigUint::from_bytes_le(bytes)))
+            .collect()),
+        ValueFormat::Hex => Ok(values
+            .iter()
+            .map(|bytes| {
+                BigUint::from_bytes_le(bytes)
+                    .to_str_radix(16)
+                    .to_uppercase()
+            })
+            .collect()),
+        ValueFormat::Decimal(numeric_type) => {
+            write_decimals(values, numeric_type, spec)
+        }
+    }
+}
+
+/// Prints decimal values by converting them like a memory in a data dump
+fn write_decimals(
+    values: &[Vec<u8>],
+    numeric_type: NumericType,
+    spec: &ValueSpec,
+) -> Result<Vec<String>, ValueError> {
+    let format = spec.format_info(numeric_type)?;
+    if values.is_empty() {
+        return Ok(vec![]);
+    }
+    if numeric_type == NumericType::IEEE754Float
+        && values.iter().any(|bytes| !is_finite(bytes, spec.width))
+    {
+        return Err(ValueError::NotFinite);
+    }
+
+    let mut dump = DataDump::new_empty();
+    dump.push_memory(
+        MemoryDeclaration::new(
+            VALUES.to_string(),
+            Dimensions::D1(values.len()),
+            format.as_data_dump_format(),
+        ),
+        values.iter().flatten().copied(),
+    );
+    let printed = converter::convert_from_data_dump(&dump, false);
+    let Some(ParseVec::D1(numbers)) = printed.as_normal().unwrap().get(VALUES)
+    else {
+        unreachable!("values are converted as a one-dimensional memory")
+    };
+    Ok(numbers.iter().map(Number::to_string).collect())
+}
+
+/// Whether the bits of an IEEE-754 float are neither an infinity nor a NaN,
+/// i.e. whether its exponent is not all ones
+fn is_finite(bytes: &[u8], width: u32) -> bool {
+    let (exp_width, mant_width) = match width {
+        16 => (5, 10),
+        32 => (8, 23),
+        _ => (11, 52),
+    };
+    let bits = bytes
+        .iter()
+        .rev()
+        .fold(0u64, |bits, byte| bits << 8 | *byte as u64);
+    let max_exp = (1u64 << exp_width) - 1;
+    (bits >> mant_width) & max_exp != max_exp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(width: u32, signed: bool, frac_width: Option<u32>) -> ValueSpec {
+        ValueSpec {
+            width,
+            signed,
+            frac_width,
+        }
+    }
+
+    fn convert(
+        input: &str,
+        from: &str,
+        to: &str,
+        spec: &ValueSpec,
+    ) -> Result<Vec<String>, ValueError> {
+        convert_values(
+            input,
+            from.parse().unwrap(),
+            to.parse().unwrap(),
+            spec,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_bits() {
+        let spec = spec(12, false, None);
+        assert_eq!(
+            convert("0xfff\n\n2a\n", "hex", "binary", &spec).unwrap(),
+            ["111111111111", "000000101010"]
+        );
+        assert_eq!(
+            convert("0b1_0000 0001", "binary", "hex", &spec).unwrap(),
+            ["101"]
+        );
+        assert!(matches!(
+            convert("1000", "hex", "binary", &spec),
+            Err(ValueError::BadValue { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_float() {
+        let spec = spec(32, false, None);
+        assert_eq!(
+            convert("0.1\n-2.5", "float", "binary", &spec).unwrap(),
+            [
+                "00111101110011001100110011001101",
+                "11000000001000000000000000000000"
+            ]
+        );
+        assert_eq!(
+            convert(
+                "0 01111111 00000000000000000000000",
+                "binary",
+                "float",
+                &spec
+            )
+            .unwrap(),
+            ["1.0"]
+        );
+        assert!(matches!(
+            convert("7F800000", "hex", "float", &spec),
+            Err(ValueError::NotFinite)
+        ));
+        assert!(matches!(
+            convert(
+                "1.0",
+                "float",
+                "hex",
+                &super::tests::spec(24, false, None)
+            ),
+            Err(ValueError::BadFloatWidth(24))
+        ));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let spec = spec(8, true, Some(4));
+        assert_eq!(
+            convert("00010011\n11111000", "binary", "fixed", &spec).unwrap(),
+            ["1.1875", "-0.5"]
+        );
+        assert_eq!(
+            convert("1.1875\n-0.5", "fixed", "hex", &spec).unwrap(),
+            ["13", "F8"]
+        );
+        assert!(matches!(
+            convert("1.0", "fixed", "hex", &super::tests::spec(8, true, None)),
+            Err(ValueError::MissingFracWidth)
+        ));
+    }
+
+    #[test]
+    fn test_bitnum() {
+        let spec = spec(8, true, None);
+        assert_eq!(
+            convert("-1\n5", "bitnum", "hex", &spec).unwrap(),
+            ["FF", "5"]
+        );
+        assert!(matches!(
+            convert("1.5", "bitnum", "hex", &spec),
+            Err(ValueError::BadValue { line: 1, .. })
+        ));
+    }
+}
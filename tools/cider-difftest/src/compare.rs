@@ -0,0 +1,303 @@
+//! Comparison of the output memories of different simulators.
+use crate::{runner::Simulator, DiffError};
+use interp::serialization::{
+    converter::convert_to_data_dump,
+    json_data::{FormatInfo, JsonData, JsonDataEntry, ParseVec},
+    Dimensions,
+};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, fmt::Display};
+
+/// A single value in the output of a simulator
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    /// The little-endian bit pattern of the value in the format of its memory
+    pub bits: Vec<u8>,
+    /// The value as printed by the simulator
+    pub text: String,
+}
+
+/// The first difference between the outputs of the simulators
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub memory: String,
+    /// The index of the first differing element, or `None` if the memory is
+    /// missing from some of the outputs
+    pub index: Option<Vec<usize>>,
+    /// The value of the element in the output of every simulator, or `None`
+    /// if the output does not contain the memory
+    pub values: Vec<(Simulator, Option<String>)>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.index {
+            Some(index) => {
+                let index = index
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join("][");
+                write!(f, "memory `{}' differs at [{index}]", self.memory)?
+            }
+            None => write!(
+                f,
+                "memory `{}' is missing from some outputs",
+                self.memory
+            )?,
+        }
+        for (sim, value) in &self.values {
+            write!(f, "\n  {sim}: {}", value.as_deref().unwrap_or("missing"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The memories in the output of a simulator. The RTL flows wrap them in an
+/// object together with the number of simulated cycles.
+fn output_memories(output: &Value) -> Option<&Map<String, Value>> {
+    let map = output.as_object()?;
+    match (map.get("cycles"), map.get("memories")) {
+        (Some(_), Some(Value::Object(memories))) => Some(memories),
+        _ => Some(map),
+    }
+}
+
+/// Reads the values of a memory from the output of a simulator and converts
+/// them to bit patterns of the given format. This makes the values
+/// independent of how the simulator prints them, e.g. whether it prints the
+/// unsigned or signed value of an integer or how many digits it prints for a
+/// fixed-point number. Returns `None` if the output does not contain the
+/// memory.
+pub fn normalize(
+    simulator: Simulator,
+    output: &Value,
+    memory: &str,
+    format: &FormatInfo,
+    size: usize,
+) -> Result<Option<Vec<Element>>, DiffError> {
+    let malformed = |reason: String| DiffError::MalformedOutput {
+        simulator: simulator.to_string(),
+        memory: memory.to_string(),
+        reason,
+    };
+
+    let Some(value) = output_memories(output).and_then(|m| m.get(memory))
+    else {
+        return Ok(None);
+    };
+    let data: ParseVec = serde_json::from_value(value.clone())
+        .map_err(|err| malformed(err.to_string()))?;
+    let numbers = data.iter().cloned().collect::<Vec<_>>();
+    if numbers.len() != size {
+        return Err(malformed(format!(
+            "it has {} elements but the memory has {size}",
+            numbers.len()
+        )));
+    }
+
+    // the data is flattened so that the shape of the output does not matter
+    let data = ParseVec::D1(numbers);
+    data.parse(format)
+        .map_err(|err| malformed(err.to_string()))?;
+    let texts = data.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let json = JsonData(HashMap::from([(
+        memory.to_string(),
+        JsonDataEntry {
+            data,
+            format: format.clone(),
+        },
+    )]));
+    let dump = convert_to_data_dump(&json, true);
+
+    let bytes_per_value = format.get_width().div_ceil(8) as usize;
+    let elements = dump
+        .get_data(memory)
+        .unwrap()
+        .chunks(bytes_per_value)
+        .zip(texts)
+        .map(|(bits, text)| Element {
+            bits: bits.to_vec(),
+            text,
+        })
+        .collect();
+    Ok(Some(elements))
+}
+
+/// The row-major index of the element at the given offset
+fn unflatten(mut offset: usize, dimensions: &Dimensions) -> Vec<usize> {
+    let sizes = match *dimensions {
+        Dimensions::D1(d0) => vec![d0],
+        Dimensions::D2(d0, d1) => vec![d0, d1],
+        Dimensions::D3(d0, d1, d2) => vec![d0, d1, d2],
+        Dimensions::D4(d0, d1, d2, d3) => vec![d0, d1, d2, d3],
+    };
+    let mut index = sizes
+        .iter()
+        .rev()
+        .map(|size| {
+            let i = offset % size;
+            offset /= size;
+            i
+        })
+        .collect::<Vec<_>>();
+    index.reverse();
+    index
+}
+
+/// Compares the outputs of the simulators for every memory in the input data
+/// and returns the first mismatch. Memories are compared in order of their
+/// names and elements in row-major order.
+pub fn first_mismatch(
+    data: &JsonData,
+    outputs: &[(Simulator, Value)],
+) -> Result<Option<Mismatch>, DiffError> {
+    let mut names = data.0.keys().collect::<Vec<_>>();
+    names.sort();
+
+    for name in names {
+        let entry = &data.0[name];
+        let dimensions = entry.data.dimensions();
+        let memories = outputs
+            .iter()
+            .map(|(sim, output)| {
+                normalize(*sim, output, name, &entry.format, dimensions.size())
+                    .map(|elements| (*sim, elements))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if memories.iter().all(|(_, elements)| elements.is_none()) {
+            continue;
+        }
+        if memories.iter().any(|(_, elements)| elements.is_none()) {
+            return Ok(Some(Mismatch {
+                memory: name.clone(),
+                index: None,
+                values: memories
+                    .into_iter()
+                    .map(|(sim, elements)| {
+                        (sim, elements.map(|_| "present".to_string()))
+                    })
+                    .collect(),
+            }));
+        }
+
+        let memories = memories
+            .into_iter()
+            .map(|(sim, elements)| (sim, elements.unwrap()))
+            .collect::<Vec<_>>();
+        let Some((_, reference)) = memories.first() else {
+            continue;
+        };
+        let differing = (0..dimensions.size()).find(|&i| {
+            memories
+                .iter()
+                .any(|(_, elements)| elements[i].bits != reference[i].bits)
+        });
+        if let Some(i) = differing {
+            return Ok(Some(Mismatch {
+                memory: name.clone(),
+                index: Some(unflatten(i, &dimensions)),
+                values: memories
+                    .iter()
+                    .map(|(sim, elements)| {
+                        (*sim, Some(elements[i].text.clone()))
+                    })
+                    .collect(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn data() -> JsonData {
+        serde_json::from_value(json!({
+            "ints": {
+                "data": [[0, 0], [0, 0]],
+                "format": { "numeric_type": "bitnum", "is_signed": true, "width": 8 }
+            },
+            "fixed": {
+                "data": [0.0, 0.0],
+                "format": {
+                    "numeric_type": "fixed_point",
+                    "is_signed": false,
+                    "width": 8,
+                    "frac_width": 4
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ignores_number_formatting() {
+        let cider = json!({ "ints": [[-1, 2], [3, 4]], "fixed": [1.5, 0.25] });
+        let icarus: Value = serde_json::from_str(
+            r#"{
+  "cycles": 10,
+  "memories": { "ints": [[255, 2], [3, 4]], "fixed": [1.50, 0.2500] }
+}"#,
+        )
+        .unwrap();
+        let verilator = json!({ "ints": [-1, 2, 3, 4], "fixed": [1.5, 0.25] });
+
+        let outputs = [
+            (Simulator::Cider, cider),
+            (Simulator::Icarus, icarus),
+            (Simulator::Verilator, verilator),
+        ];
+        assert_eq!(first_mismatch(&data(), &outputs).unwrap(), None);
+    }
+
+    #[test]
+    fn reports_first_mismatch() {
+        let cider = json!({ "ints": [[1, 2], [3, 4]], "fixed": [1.5, 0.0] });
+        let icarus = json!({ "ints": [[1, 2], [5, 4]], "fixed": [1.0, 0.0] });
+
+        let outputs = [(Simulator::Cider, cider), (Simulator::Icarus, icarus)];
+        let mismatch = first_mismatch(&data(), &outputs).unwrap().unwrap();
+        assert_eq!(mismatch.memory, "fixed");
+        assert_eq!(mismatch.index, Some(vec![0]));
+
+        let outputs = [
+            (
+                Simulator::Cider,
+                json!({ "ints": [[1, 2], [3, 4]], "fixed": [0, 0] }),
+            ),
+            (
+                Simulator::Icarus,
+                json!({ "ints": [[1, 2], [5, 4]], "fixed": [0, 0] }),
+            ),
+        ];
+        let mismatch = first_mismatch(&data(), &outputs).unwrap().unwrap();
+        assert_eq!(mismatch.memory, "ints");
+        assert_eq!(mismatch.index, Some(vec![1, 0]));
+        assert_eq!(
+            mismatch.to_string(),
+            "memory `ints' differs at [1][0]\n  cider: 3\n  icarus: 5"
+        );
+    }
+
+    #[test]
+    fn reports_missing_memories() {
+        let cider = json!({ "ints": [[1, 2], [3, 4]], "fixed": [0, 0] });
+        let icarus = json!({ "ints": [[1, 2], [3, 4]] });
+
+        let outputs = [(Simulator::Cider, cider), (Simulator::Icarus, icarus)];
+        let mismatch = first_mismatch(&data(), &outputs).unwrap().unwrap();
+        assert_eq!(mismatch.memory, "fixed");
+        assert_eq!(mismatch.index, None);
+        assert_eq!(
+            mismatch.values,
+            vec![
+                (Simulator::Cider, Some("present".to_string())),
+                (Simulator::Icarus, None)
+            ]
+        );
+    }
+}
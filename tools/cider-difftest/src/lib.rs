@@ -0,0 +1,43 @@
+//! Differential testing of Cider against RTL simulation.
+//!
+//! A program is run through the simulation flows that `fud2` defines for
+//! Cider, Icarus Verilog and Verilator with the same input data. The output
+//! memories are [normalized](compare::normalize) to the bit patterns of the
+//! formats in the input data so that differences in how the flows print
+//! numbers do not matter, and the [first mismatch](compare::first_mismatch) is
+//! reported. Failing inputs can be [shrunk](shrink::shrink) to make the
+//! mismatch easier to debug.
+pub mod compare;
+pub mod runner;
+pub mod shrink;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DiffError {
+    #[error(
+        "Unknown simulator `{0}'. Expected one of cider, icarus or verilator"
+    )]
+    UnknownSimulator(String),
+
+    #[error("Could not generate data for the program: {0}")]
+    DataGen(String),
+
+    #[error("Simulation with {simulator} failed:\n{stderr}")]
+    Simulation { simulator: String, stderr: String },
+
+    #[error(
+        "Memory `{memory}' in the output of {simulator} is malformed: {reason}"
+    )]
+    MalformedOutput {
+        simulator: String,
+        memory: String,
+        reason: String,
+    },
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,143 @@
+use argh::FromArgs;
+use calyx_frontend as frontend;
+use calyx_ir as ir;
+use cider_difftest::{
+    compare::{first_mismatch, Mismatch},
+    runner::{Runner, Simulator},
+    shrink::shrink,
+    DiffError,
+};
+use data_gen::{constraints::Constraints, Generator};
+use interp::serialization::json_data::JsonData;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+#[derive(FromArgs)]
+/// Runs a Calyx program through Cider and RTL simulation and compares the
+/// output memories
+struct Opts {
+    /// the Calyx program
+    #[argh(positional)]
+    program: PathBuf,
+
+    /// library path, used to read the program when generating data
+    #[argh(option, short = 'l', default = "Path::new(\".\").into()")]
+    lib_path: PathBuf,
+
+    /// JSON data file. Random data is generated if it is not given
+    #[argh(option, short = 'd')]
+    data: Option<PathBuf>,
+
+    /// JSON file with per-memory constraints on the generated data
+    #[argh(option, short = 'c', long = "constraints")]
+    constraints: Option<PathBuf>,
+
+    /// seed for the generated data
+    #[argh(option, short = 's')]
+    seed: Option<u64>,
+
+    /// a simulator to compare: cider, icarus or verilator. Can be given
+    /// multiple times and defaults to all three
+    #[argh(option, long = "sim")]
+    sims: Vec<Simulator>,
+
+    /// the fud2 executable
+    #[argh(option, default = "\"fud2\".into()")]
+    fud2: PathBuf,
+
+    /// set a configuration variable for every fud2 run (key=value)
+    #[argh(option, long = "set")]
+    config: Vec<String>,
+
+    /// shrink the input data of a mismatch
+    #[argh(switch)]
+    shrink: bool,
+
+    /// file to write the input data of a mismatch to. Defaults to stdout
+    #[argh(option, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+/// Generates random data for the memories of the program
+fn generate_data(opts: &Opts) -> Result<JsonData, DiffError> {
+    let constraints: Constraints = match &opts.constraints {
+        Some(path) => serde_json::from_reader(File::open(path)?)?,
+        None => Constraints::new(),
+    };
+
+    let ws = frontend::Workspace::construct(
+        &Some(opts.program.clone()),
+        &opts.lib_path,
+    )
+    .map_err(|err| DiffError::DataGen(format!("{err:?}")))?;
+    let ctx = ir::from_ast::ast_to_ir(ws)
+        .map_err(|err| DiffError::DataGen(format!("{err:?}")))?;
+    let (toplevel, memories) = data_gen::interface_memories(&ctx)
+        .map_err(|err| DiffError::DataGen(format!("{err:?}")))?;
+
+    let dump = Generator::new(opts.seed)
+        .with_random_data(true)
+        .generate(&toplevel, &memories, &constraints)
+        .map_err(|err| DiffError::DataGen(err.to_string()))?;
+    Ok(serde_json::from_value(data_gen::to_json(&dump))?)
+}
+
+/// Runs the simulators and compares their outputs
+fn check(
+    runner: &Runner,
+    sims: &[Simulator],
+    data: &JsonData,
+) -> Result<Option<Mismatch>, DiffError> {
+    let outputs = runner.run(sims, data)?;
+    let outputs = sims.iter().copied().zip(outputs).collect::<Vec<_>>();
+    first_mismatch(data, &outputs)
+}
+
+fn run(opts: Opts) -> Result<bool, DiffError> {
+    let sims = if opts.sims.is_empty() {
+        Simulator::ALL.to_vec()
+    } else {
+        opts.sims.clone()
+    };
+    let mut data = match &opts.data {
+        Some(path) => serde_json::from_reader(File::open(path)?)?,
+        None => generate_data(&opts)?,
+    };
+    let runner =
+        Runner::new(opts.fud2.clone(), &opts.program, opts.config.clone())?;
+
+    let Some(mut mismatch) = check(&runner, &sims, &data)? else {
+        println!("No mismatch between {} simulators", sims.len());
+        return Ok(true);
+    };
+
+    if opts.shrink {
+        // inputs on which a simulator fails do not reproduce the mismatch
+        data = shrink(data, |data| {
+            matches!(check(&runner, &sims, data), Ok(Some(_)))
+        });
+        mismatch = check(&runner, &sims, &data)?
+            .expect("shrunk data no longer causes a mismatch");
+    }
+
+    eprintln!("Mismatch: {mismatch}");
+    match &opts.output {
+        Some(path) => serde_json::to_writer_pretty(File::create(path)?, &data)?,
+        None => println!("{}", serde_json::to_string_pretty(&data)?),
+    }
+    Ok(false)
+}
+
+fn main() -> ExitCode {
+    match run(argh::from_env()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Runs a program through the simulation flows of `fud2`.
+use crate::DiffError;
+use interp::serialization::json_data::JsonData;
+use serde_json::Value;
+use std::{
+    fmt::Display,
+    fs::File,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use tempfile::TempDir;
+
+/// A simulator that `fud2` can produce output memories with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Simulator {
+    Cider,
+    Icarus,
+    Verilator,
+}
+
+impl Simulator {
+    pub const ALL: [Simulator; 3] =
+        [Simulator::Cider, Simulator::Icarus, Simulator::Verilator];
+
+    /// The `fud2` operation that the conversion to `dat` is routed through
+    fn op(&self) -> &'static str {
+        match self {
+            Simulator::Cider => "cider",
+            Simulator::Icarus => "icarus",
+            Simulator::Verilator => "verilator",
+        }
+    }
+}
+
+impl Display for Simulator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.op())
+    }
+}
+
+impl FromStr for Simulator {
+    type Err = DiffError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Simulator::ALL
+            .into_iter()
+            .find(|sim| sim.op() == s)
+            .ok_or_else(|| DiffError::UnknownSimulator(s.to_string()))
+    }
+}
+
+/// Runs a program with the given data through `fud2`
+pub struct Runner {
+    fud2: PathBuf,
+    program: PathBuf,
+    /// Additional `key=value` configuration passed to every `fud2` run
+    config: Vec<String>,
+    /// Holds the data file and the outputs of every run
+    dir: TempDir,
+}
+
+impl Runner {
+    pub fn new(
+        fud2: PathBuf,
+        program: &Path,
+        config: Vec<String>,
+    ) -> Result<Self, DiffError> {
+        Ok(Self {
+            fud2,
+            program: program.canonicalize()?,
+            config,
+            dir: tempfile::tempdir()?,
+        })
+    }
+
+    /// Runs the program with the given data on every simulator and returns
+    /// their outputs in the same order
+    pub fn run(
+        &self,
+        simulators: &[Simulator],
+        data: &JsonData,
+    ) -> Result<Vec<Value>, DiffError> {
+        let data_path = self.dir.path().join("data.json");
+        serde_json::to_writer(File::create(&data_path)?, data)?;
+
+        simulators
+            .iter()
+            .map(|sim| self.run_one(*sim, &data_path))
+            .collect()
+    }
+
+    fn run_one(
+        &self,
+        sim: Simulator,
+        data_path: &Path,
+    ) -> Result<Value, DiffError> {
+        let out_path = self.dir.path().join(format!("{sim}.json"));
+        let mut cmd = Command::new(&self.fud2);
+        cmd.arg(&self.program)
+            .args(["--to", "dat", "--through", sim.op()])
+            .arg("-s")
+            .arg(format!("sim.data={}", data_path.display()))
+            .arg("-o")
+            .arg(&out_path);
+        for config in &self.config {
+            cmd.args(["-s", config]);
+        }
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(DiffError::Simulation {
+                simulator: sim.to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        Ok(serde_json::from_reader(File::open(&out_path)?)?)
+    }
+}
@@ -0,0 +1,126 @@
+//! Shrinking of input data that causes a mismatch.
+use interp::serialization::json_data::{JsonData, NumericType};
+use num_bigint::BigInt;
+use serde_json::Number;
+use std::str::FromStr;
+
+fn is_zero(number: &Number) -> bool {
+    number.as_f64() == Some(0.0)
+}
+
+/// Half of an integer, rounded towards zero
+fn halve(number: &Number) -> Option<Number> {
+    let value: BigInt = BigInt::from_str(&number.to_string()).ok()? / 2;
+    Number::from_str(&value.to_string()).ok()
+}
+
+fn element<'a>(
+    data: &'a mut JsonData,
+    memory: &str,
+    i: usize,
+) -> &'a mut Number {
+    data.0
+        .get_mut(memory)
+        .unwrap()
+        .data
+        .iter_mut()
+        .nth(i)
+        .unwrap()
+}
+
+/// Shrinks the data while `fails` holds for it. Whole memories are zeroed
+/// first, then individual elements, and finally integer elements are halved
+/// until no element can be shrunk any further. The result is a local minimum:
+/// zeroing or halving any single element makes `fails` false.
+pub fn shrink<F>(mut data: JsonData, mut fails: F) -> JsonData
+where
+    F: FnMut(&JsonData) -> bool,
+{
+    let mut names = data.0.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+
+    for name in &names {
+        let values = data.0[name].data.iter().cloned().collect::<Vec<_>>();
+        if values.iter().all(is_zero) {
+            continue;
+        }
+        for value in data.0.get_mut(name).unwrap().data.iter_mut() {
+            *value = Number::from(0);
+        }
+        if !fails(&data) {
+            for (value, old) in
+                data.0.get_mut(name).unwrap().data.iter_mut().zip(values)
+            {
+                *value = old;
+            }
+        }
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for name in &names {
+            let format = &data.0[name].format;
+            let is_int = format.numeric_type == NumericType::Bitnum
+                && !format.is_fixedpt();
+            let size = data.0[name].data.iter().count();
+            for i in 0..size {
+                let old = element(&mut data, name, i).clone();
+                if is_zero(&old) {
+                    continue;
+                }
+
+                let mut candidates = vec![Number::from(0)];
+                if is_int {
+                    candidates
+                        .extend(halve(&old).filter(|half| !is_zero(half)));
+                }
+                for candidate in candidates {
+                    *element(&mut data, name, i) = candidate;
+                    if fails(&data) {
+                        changed = true;
+                        break;
+                    }
+                    *element(&mut data, name, i) = old.clone();
+                }
+            }
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn shrinks_to_local_minimum() {
+        let data: JsonData = serde_json::from_value(json!({
+            "a": {
+                "data": [5, 7, 100],
+                "format": { "numeric_type": "bitnum", "is_signed": false, "width": 8 }
+            },
+            "b": {
+                "data": [1, 2],
+                "format": { "numeric_type": "bitnum", "is_signed": false, "width": 8 }
+            }
+        }))
+        .unwrap();
+
+        // fails as long as the last element of `a' is at least 10
+        let shrunk = shrink(data, |data| {
+            data.0["a"].data.iter().nth(2).unwrap().as_u64().unwrap() >= 10
+        });
+
+        let values = |name: &str| {
+            shrunk.0[name]
+                .data
+                .iter()
+                .map(|n| n.as_u64().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("a"), vec![0, 0, 12]);
+        assert_eq!(values("b"), vec![0, 0]);
+    }
+}
@@ -0,0 +1,65 @@
+//! Per-memory constraints on the generated data.
+//!
+//! Constraints are read from a JSON object mapping memory names to their
+//! constraints, for example:
+//!
+//! ```json
+//! {
+//!   "keys": { "range": [0, 1000], "sorted": "ascending" },
+//!   "weights": {
+//!     "format": {
+//!       "numeric_type": "fixed_point",
+//!       "is_signed": true,
+//!       "width": 32,
+//!       "frac_width": 16
+//!     },
+//!     "distribution": { "normal": { "mean": 0.0, "std_dev": 0.5 } },
+//!     "sparsity": 0.25
+//!   }
+//! }
+//! ```
+use interp::serialization::json_data::FormatInfo;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// How the values of a memory are distributed
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Distribution {
+    /// Every value is zero
+    Zeros,
+    /// Values are drawn uniformly from the range
+    Uniform,
+    /// Values are drawn from a normal distribution and clamped to the range
+    Normal { mean: f64, std_dev: f64 },
+}
+
+/// The order of the values of a memory, in row-major order
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+/// Constraints on the contents of a single memory
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryConstraints {
+    /// The numeric format of the values. Defaults to unsigned integers of the
+    /// width of the memory.
+    pub format: Option<FormatInfo>,
+    /// The inclusive lower and upper bounds of the values. Defaults to every
+    /// value the format can represent.
+    pub range: Option<(f64, f64)>,
+    /// Defaults to `uniform` if a range is given and to the generator's
+    /// default distribution otherwise.
+    pub distribution: Option<Distribution>,
+    /// The probability that a value is zero regardless of the distribution
+    #[serde(default)]
+    pub sparsity: f64,
+    pub sorted: Option<Order>,
+}
+
+/// Constraints on the memories of a program, by memory name
+pub type Constraints = BTreeMap<String, MemoryConstraints>;
@@ -0,0 +1,492 @@
+//! Generation of memory contents for the interface of a program.
+use crate::constraints::{Constraints, Distribution, MemoryConstraints, Order};
+use calyx_ir::utils::Memory;
+use interp::serialization::{
+    json_data::NumericType, DataDump, Dimensions, FormatInfo, MemoryDeclaration,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DataGenError {
+    #[error("Constraints given for `{0}', which is not an @external or ref memory of the toplevel component")]
+    UnknownMemory(String),
+
+    #[error("Memory `{name}' is {width} bits wide. Only memories up to 64 bits wide are supported")]
+    TooWide { name: String, width: u64 },
+
+    #[error("Memory `{0}' has an unsupported format. Only bitnum and fixed_point memories are supported")]
+    UnsupportedFormat(String),
+
+    #[error("The format of memory `{name}' is {format_width} bits wide but the memory is {width} bits wide")]
+    WidthMismatch {
+        name: String,
+        format_width: u32,
+        width: u64,
+    },
+
+    #[error("The range of memory `{0}' does not contain any value its format can represent")]
+    EmptyRange(String),
+
+    #[error(
+        "The range of memory `{0}' contains values its format cannot represent"
+    )]
+    RangeOutOfBounds(String),
+
+    #[error(
+        "Memory `{0}' has to contain zeros but zero is outside of its range"
+    )]
+    ZeroOutOfRange(String),
+
+    #[error("The sparsity of memory `{0}' must be between 0 and 1")]
+    BadSparsity(String),
+
+    #[error("The normal distribution of memory `{0}' must have a finite mean and a non-negative standard deviation")]
+    BadDistribution(String),
+}
+
+/// Generates the contents of memories according to their constraints
+pub struct Generator {
+    rng: StdRng,
+    /// The distribution of memories without a range or distribution constraint
+    default_distribution: Distribution,
+    /// Whether memories without a format constraint hold fixed-point numbers
+    /// with 16 fractional bits rather than unsigned integers
+    default_fixed_point: bool,
+}
+
+impl Generator {
+    /// Creates a generator which fills unconstrained memories with zeros. The
+    /// same seed always produces the same data. Without a seed, the generator
+    /// is seeded from the operating system.
+    pub fn new(seed: Option<u64>) -> Self {
+        Self {
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            default_distribution: Distribution::Zeros,
+            default_fixed_point: false,
+        }
+    }
+
+    /// Fill unconstrained memories with uniformly distributed values instead
+    /// of zeros
+    pub fn with_random_data(mut self, random: bool) -> Self {
+        self.default_distribution = if random {
+            Distribution::Uniform
+        } else {
+            Distribution::Zeros
+        };
+        self
+    }
+
+    /// Use signed fixed-point numbers with 16 fractional bits for memories
+    /// without a format constraint
+    pub fn with_fixed_point(mut self, fixed_point: bool) -> Self {
+        self.default_fixed_point = fixed_point;
+        self
+    }
+
+    /// Generates the contents of the given memories
+    pub fn generate(
+        &mut self,
+        toplevel: &str,
+        memories: &[Memory],
+        constraints: &Constraints,
+    ) -> Result<DataDump, DataGenError> {
+        if let Some(name) = constraints
+            .keys()
+            .find(|name| !memories.iter().any(|mem| &&mem.name == name))
+        {
+            return Err(DataGenError::UnknownMemory(name.clone()));
+        }
+
+        let mut dump = DataDump::new_empty_with_top_level(toplevel.into());
+        for memory in memories {
+            let default = MemoryConstraints::default();
+            let constraints = constraints.get(&memory.name).unwrap_or(&default);
+            let (declaration, data) =
+                self.generate_memory(memory, constraints)?;
+            dump.push_memory(declaration, data);
+        }
+        Ok(dump)
+    }
+
+    fn generate_memory(
+        &mut self,
+        memory: &Memory,
+        constraints: &MemoryConstraints,
+    ) -> Result<(MemoryDeclaration, Vec<u8>), DataGenError> {
+        let name = &memory.name;
+        if memory.data_width > 64 {
+            return Err(DataGenError::TooWide {
+                name: name.clone(),
+                width: memory.data_width,
+            });
+        }
+        let width = memory.data_width as u32;
+        let format = self.format(memory, constraints)?;
+        let (signed, frac_width) = match format {
+            FormatInfo::Bitnum { signed, .. } => (signed, 0),
+            FormatInfo::Fixed {
+                signed, frac_width, ..
+            } => (signed, frac_width),
+            FormatInfo::IEEFloat { .. } => unreachable!(),
+        };
+
+        // values are generated as raw integers, i.e. fixed-point numbers
+        // scaled by 2^frac_width
+        let (min, max) = if signed {
+            (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+        } else {
+            (0, (1i128 << width) - 1)
+        };
+        let scale = 2f64.powi(frac_width as i32);
+        let (low, high) = match constraints.range {
+            Some((low, high)) => {
+                let (low, high) =
+                    ((low * scale).ceil(), (high * scale).floor());
+                if low > high {
+                    return Err(DataGenError::EmptyRange(name.clone()));
+                }
+                if low < min as f64 || high > max as f64 {
+                    return Err(DataGenError::RangeOutOfBounds(name.clone()));
+                }
+                (low as i128, high as i128)
+            }
+            None => (min, max),
+        };
+
+        let distribution = constraints.distribution.unwrap_or(
+            if constraints.range.is_some() {
+                Distribution::Uniform
+            } else {
+                self.default_distribution
+            },
+        );
+        if !(0.0..=1.0).contains(&constraints.sparsity) {
+            return Err(DataGenError::BadSparsity(name.clone()));
+        }
+        let needs_zero =
+            distribution == Distribution::Zeros || constraints.sparsity > 0.0;
+        if needs_zero && !(low..=high).contains(&0) {
+            return Err(DataGenError::ZeroOutOfRange(name.clone()));
+        }
+        if let Distribution::Normal { mean, std_dev } = distribution {
+            if !mean.is_finite() || !std_dev.is_finite() || std_dev < 0.0 {
+                return Err(DataGenError::BadDistribution(name.clone()));
+            }
+        }
+
+        let mut values = (0..memory.total_size)
+            .map(|_| {
+                if constraints.sparsity > 0.0
+                    && self.rng.gen_bool(constraints.sparsity)
+                {
+                    return 0;
+                }
+                match distribution {
+                    Distribution::Zeros => 0,
+                    Distribution::Uniform => self.rng.gen_range(low..=high),
+                    Distribution::Normal { mean, std_dev } => {
+                        let value = mean + std_dev * self.standard_normal();
+                        ((value * scale).round() as i128).clamp(low, high)
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match constraints.sorted {
+            Some(Order::Ascending) => values.sort(),
+            Some(Order::Descending) => values.sort_by(|a, b| b.cmp(a)),
+            None => {}
+        }
+
+        let bytes_per_value = width.div_ceil(8) as usize;
+        let mask = (1u128 << width) - 1;
+        let data = values
+            .into_iter()
+            .flat_map(|value| {
+                ((value as u128) & mask)
+                    .to_le_bytes()
+                    .into_iter()
+                    .take(bytes_per_value)
+            })
+            .collect();
+
+        let dimensions = match memory.dimension_sizes[..] {
+            [d0] => Dimensions::D1(d0 as usize),
+            [d0, d1] => Dimensions::D2(d0 as usize, d1 as usize),
+            [d0, d1, d2] => {
+                Dimensions::D3(d0 as usize, d1 as usize, d2 as usize)
+            }
+            [d0, d1, d2, d3] => Dimensions::D4(
+                d0 as usize,
+                d1 as usize,
+                d2 as usize,
+                d3 as usize,
+            ),
+            _ => unreachable!("memories have between one and four dimensions"),
+        };
+
+        Ok((
+            MemoryDeclaration::new(name.clone(), dimensions, format),
+            data,
+        ))
+    }
+
+    /// Determines the format of a memory from its constraints, checking that
+    /// it matches the width of the memory
+    fn format(
+        &self,
+        memory: &Memory,
+        constraints: &MemoryConstraints,
+    ) -> Result<FormatInfo, DataGenError> {
+        let width = memory.data_width as u32;
+        let Some(format) = &constraints.format else {
+            return Ok(if self.default_fixed_point {
+                FormatInfo::Fixed {
+                    signed: true,
+                    int_width: width.saturating_sub(16),
+                    frac_width: width.min(16),
+                }
+            } else {
+                FormatInfo::Bitnum {
+                    signed: false,
+                    width,
+                }
+            });
+        };
+
+        let format = match format.numeric_type {
+            NumericType::Bitnum => FormatInfo::Bitnum {
+                signed: format.is_signed,
+                width: format.width.unwrap_or(width),
+            },
+            NumericType::Fixed => {
+                let frac_width = format.frac_width().ok_or_else(|| {
+                    DataGenError::UnsupportedFormat(memory.name.clone())
+                })?;
+                FormatInfo::Fixed {
+                    signed: format.is_signed,
+                    int_width: format
+                        .int_width()
+                        .unwrap_or(width.saturating_sub(frac_width)),
+                    frac_width,
+                }
+            }
+            NumericType::IEEE754Float => {
+                return Err(DataGenError::UnsupportedFormat(
+                    memory.name.clone(),
+                ))
+            }
+        };
+
+        if format.width() != width {
+            return Err(DataGenError::WidthMismatch {
+                name: memory.name.clone(),
+                format_width: format.width(),
+                width: memory.data_width,
+            });
+        }
+        Ok(format)
+    }
+
+    /// Samples the standard normal distribution using the Box-Muller transform
+    fn standard_normal(&mut self) -> f64 {
+        // sample from (0, 1] to avoid taking the logarithm of zero
+        let u1 = 1.0 - self.rng.gen::<f64>();
+        let u2 = self.rng.gen::<f64>();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calyx_ir::utils::MemoryType;
+
+    fn memory(name: &str, width: u64, size: u64) -> Memory {
+        Memory {
+            name: name.to_string(),
+            memory_type: MemoryType::Combinational,
+            data_width: width,
+            dimensions: 1,
+            dimension_sizes: vec![size],
+            total_size: size,
+            idx_sizes: vec![64 - size.leading_zeros() as u64],
+        }
+    }
+
+    fn constraints(json: &str) -> Constraints {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn generate(
+        seed: u64,
+        memory: &Memory,
+        json: &str,
+    ) -> Result<DataDump, DataGenError> {
+        Generator::new(Some(seed)).generate(
+            "main",
+            std::slice::from_ref(memory),
+            &constraints(json),
+        )
+    }
+
+    /// The values of the memory, sign-extended if the memory is signed
+    fn values(dump: &DataDump, memory: &Memory, signed: bool) -> Vec<i64> {
+        let width = memory.data_width as u32;
+        dump.get_data(&memory.name)
+            .unwrap()
+            .chunks_exact(width.div_ceil(8) as usize)
+            .map(|bytes| {
+                let value = bytes
+                    .iter()
+                    .rev()
+                    .fold(0u64, |value, byte| value << 8 | *byte as u64);
+                let shift = 64 - width;
+                if signed {
+                    ((value << shift) as i64) >> shift
+                } else {
+                    value as i64
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_errors() {
+        let mem = memory("mem", 8, 4);
+        let error = |mem: &Memory, json: &str| {
+            generate(0, mem, json).map(|_| ()).unwrap_err()
+        };
+
+        assert!(matches!(
+            error(&mem, r#"{ "other": {} }"#),
+            DataGenError::UnknownMemory(name) if name == "other"
+        ));
+        assert!(matches!(
+            error(&memory("wide", 65, 1), "{}"),
+            DataGenError::TooWide { width: 65, .. }
+        ));
+        assert!(matches!(
+            error(
+                &mem,
+                r#"{ "mem": { "format": {
+                    "numeric_type": "ieee754_float", "is_signed": true, "width": 8
+                } } }"#
+            ),
+            DataGenError::UnsupportedFormat(_)
+        ));
+        assert!(matches!(
+            error(
+                &mem,
+                r#"{ "mem": { "format": {
+                    "numeric_type": "bitnum", "is_signed": false, "width": 16
+                } } }"#
+            ),
+            DataGenError::WidthMismatch {
+                format_width: 16,
+                width: 8,
+                ..
+            }
+        ));
+        assert!(matches!(
+            error(&mem, r#"{ "mem": { "range": [0.25, 0.75] } }"#),
+            DataGenError::EmptyRange(_)
+        ));
+        assert!(matches!(
+            error(&mem, r#"{ "mem": { "range": [-1, 10] } }"#),
+            DataGenError::RangeOutOfBounds(_)
+        ));
+        assert!(matches!(
+            error(&mem, r#"{ "mem": { "range": [0, 256] } }"#),
+            DataGenError::RangeOutOfBounds(_)
+        ));
+        assert!(matches!(
+            error(&mem, r#"{ "mem": { "range": [5, 10], "sparsity": 0.5 } }"#),
+            DataGenError::ZeroOutOfRange(_)
+        ));
+        assert!(matches!(
+            error(
+                &mem,
+                r#"{ "mem": { "range": [5, 10], "distribution": "zeros" } }"#
+            ),
+            DataGenError::ZeroOutOfRange(_)
+        ));
+        assert!(matches!(
+            error(&mem, r#"{ "mem": { "sparsity": 1.5 } }"#),
+            DataGenError::BadSparsity(_)
+        ));
+        assert!(matches!(
+            error(
+                &mem,
+                r#"{ "mem": { "distribution": {
+                    "normal": { "mean": 0.0, "std_dev": -1.0 }
+                } } }"#
+            ),
+            DataGenError::BadDistribution(_)
+        ));
+    }
+
+    #[test]
+    fn test_sorted_sparse() {
+        let mem = memory("mem", 8, 64);
+        let json = r#"{ "mem": {
+            "format": { "numeric_type": "bitnum", "is_signed": true, "width": 8 },
+            "range": [-20, 20],
+            "sparsity": 0.5,
+            "sorted": "descending"
+        } }"#;
+        let values = values(&generate(1, &mem, json).unwrap(), &mem, true);
+
+        assert!(values.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(values.iter().all(|value| (-20..=20).contains(value)));
+        // with a sparsity of one half, about half of the values are zeros
+        let zeros = values.iter().filter(|value| **value == 0).count();
+        assert!((16..=48).contains(&zeros), "{zeros} zeros");
+        assert!(values.iter().any(|value| *value < 0));
+        assert!(values.iter().any(|value| *value > 0));
+    }
+
+    #[test]
+    fn test_clamped_normal() {
+        let mem = memory("mem", 16, 256);
+        let json = r#"{ "mem": {
+            "format": {
+                "numeric_type": "fixed_point",
+                "is_signed": true,
+                "width": 16,
+                "frac_width": 8
+            },
+            "range": [-1.0, 2.0],
+            "distribution": { "normal": { "mean": 2.0, "std_dev": 1.0 } }
+        } }"#;
+        let values = values(&generate(2, &mem, json).unwrap(), &mem, true);
+
+        // the range is scaled by 2^8 and half of the values are clamped to
+        // its upper bound
+        assert!(values.iter().all(|value| (-256..=512).contains(value)));
+        let clamped = values.iter().filter(|value| **value == 512).count();
+        assert!((96..=160).contains(&clamped), "{clamped} clamped values");
+        assert!(values.iter().any(|value| *value < 256));
+    }
+
+    #[test]
+    fn test_seed() {
+        let mem = memory("mem", 32, 16);
+        let json = r#"{ "mem": { "range": [0, 1000000] } }"#;
+        let data = |seed| {
+            generate(seed, &mem, json)
+                .unwrap()
+                .get_data("mem")
+                .unwrap()
+                .to_vec()
+        };
+
+        assert_eq!(data(7), data(7));
+        assert_ne!(data(7), data(8));
+    }
+}
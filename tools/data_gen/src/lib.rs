@@ -0,0 +1,44 @@
+//! Generates stimulus for the memories in the interface of a Calyx program.
+//!
+//! The memories are read from the `@external` and `ref` memories of the
+//! toplevel component and filled according to per-memory [constraints]. The
+//! result is a [DataDump] that can be printed as JSON with [to_json] or written
+//! to a directory of `.dat` files for RTL simulation with
+//! [cider_data_converter::dat::write_dat_dir].
+pub mod constraints;
+mod generator;
+
+pub use generator::{DataGenError, Generator};
+
+use calyx_ir::utils::{Memory, ProgramInterface};
+use calyx_utils::CalyxResult;
+use interp::serialization::{converter, json_data::FormatInfo, DataDump};
+use serde_json::{json, Map, Value};
+
+/// Returns the name of the toplevel component of the program and the memories
+/// in its interface
+pub fn interface_memories(
+    ctx: &calyx_ir::Context,
+) -> CalyxResult<(String, Vec<Memory>)> {
+    let interface = ProgramInterface::from_component(ctx.entrypoint())?;
+    Ok((interface.toplevel, interface.memories))
+}
+
+/// Converts the data dump to the JSON data format, including the format of
+/// every memory
+pub fn to_json(dump: &DataDump) -> Value {
+    let data = converter::convert_from_data_dump(dump, false);
+    let data = data.as_normal().unwrap();
+
+    let mut map = Map::new();
+    for declaration in &dump.header.memories {
+        map.insert(
+            declaration.name.clone(),
+            json!({
+                "data": data[&declaration.name],
+                "format": FormatInfo::from(&declaration.format),
+            }),
+        );
+    }
+    map.into()
+}